@@ -0,0 +1,63 @@
+//delaunay_fill.wgsl
+// 这个着色器负责以填充模式绘制Delaunay三角剖分
+// 不使用索引缓冲区绘制，而是从 storage 中读取三角形索引，
+// 每个三角形按 vertex_index / 3 取对应的平涂颜色
+
+struct Pos2 {
+    x: f32,
+    y: f32,
+};
+
+struct CanvasUniforms {
+    canvas_x: f32,
+    canvas_y: f32,
+    canvas_width: f32,
+    canvas_height: f32,
+    translation_x: f32,
+    translation_y: f32,
+    scale: f32,
+    padding1: f32,
+    padding2: f32,
+    padding3: f32,
+}
+
+@group(0) @binding(0)
+var<storage, read> vertices : array<Pos2>;
+
+@group(0) @binding(1)
+var<uniform> uniforms : CanvasUniforms;
+
+// 三角形索引，每3个构成一个三角形
+@group(0) @binding(2)
+var<storage, read> triangle_indices : array<u32>;
+
+// 每个三角形的颜色，打包为 RGBA8
+@group(0) @binding(3)
+var<storage, read> triangle_colors : array<u32>;
+
+struct VSOutput {
+    @builtin(position) pos : vec4<f32>,
+    @location(0) color : vec4<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index : u32) -> VSOutput {
+    let point_pos = vertices[triangle_indices[vertex_index]];
+
+    var out : VSOutput;
+    out.pos = vec4<f32>(get_screen_pos(point_pos, uniforms), 0.0, 1.0);
+    out.color = unpack4x8unorm(triangle_colors[vertex_index / 3u]);
+    return out;
+}
+
+@fragment
+fn fs_main(in : VSOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
+
+fn get_screen_pos(point: Pos2, uniforms: CanvasUniforms) -> vec2<f32> {
+    // 应用平移和缩放，然后将[0,2]范围调整为[-1,1]
+    let x = (point.x * uniforms.scale + uniforms.translation_x - uniforms.canvas_x) / uniforms.canvas_width * 2.0 - 1.0;
+    let y = -((point.y * uniforms.scale + uniforms.translation_y - uniforms.canvas_y) / uniforms.canvas_height * 2.0 - 1.0);
+    return vec2<f32>(x, y);
+}
@@ -24,6 +24,10 @@ var<storage, read> points : array<Pos2>;
 @group(0) @binding(1)
 var<uniform> uniforms : CanvasUniforms; //x: time, y,z,w: 保留
 
+// 逐点颜色，打包为 RGBA8
+@group(0) @binding(2)
+var<storage, read> colors : array<u32>;
+
 struct VSOutput {
     @builtin(position) pos : vec4<f32>,
     @location(0) color : vec4<f32>,
@@ -41,7 +45,7 @@ fn vs_main(@builtin(vertex_index) vertex_index : u32) -> VSOutput {
     
     var out : VSOutput;
     out.pos = get_triangle_pos(vertex_index, screen_pos, uniforms);
-    out.color = vec4<f32>(0.9, 0.5, 0.5, 0.7);
+    out.color = unpack4x8unorm(colors[point_id]);
     return out;
 }
 
@@ -0,0 +1,57 @@
+// Voronoi 单元格填充着色器 - 按逐单元格颜色填充
+
+struct FillVertex {
+    x: f32,
+    y: f32,
+    cell: u32,
+};
+
+struct CanvasUniforms {
+    canvas_x: f32,
+    canvas_y: f32,
+    canvas_width: f32,
+    canvas_height: f32,
+    translation_x: f32,
+    translation_y: f32,
+    scale: f32,
+    padding1: f32,
+    padding2: f32,
+    padding3: f32,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+// 填充三角形顶点，每3个构成一个三角形
+@group(0) @binding(0)
+var<storage, read> fill_vertices: array<FillVertex>;
+
+// 逐单元格颜色，打包为 RGBA8
+@group(0) @binding(1)
+var<storage, read> cell_colors: array<u32>;
+
+@group(0) @binding(2)
+var<uniform> uniforms: CanvasUniforms;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+
+    let vertex = fill_vertices[vertex_index];
+    let x = (vertex.x * uniforms.scale + uniforms.translation_x - uniforms.canvas_x) / uniforms.canvas_width * 2.0 - 1.0;
+    let y = -((vertex.y * uniforms.scale + uniforms.translation_y - uniforms.canvas_y) / uniforms.canvas_height * 2.0 - 1.0);
+    out.position = vec4<f32>(x, y, 0.0, 1.0);
+
+    // 确保索引在范围内
+    let max_cell = arrayLength(&cell_colors) - 1u;
+    out.color = unpack4x8unorm(cell_colors[min(vertex.cell, max_cell)]);
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
@@ -448,6 +448,9 @@ impl TemplateApp {
                 template_name
             );
         });
+
+        // 新地形生成后将视图适配到整张地图
+        self.canvas_widget.request_fit();
     }
 
     /// 生成新的地形（兼容旧代码）
@@ -5,6 +5,7 @@ use sekai::models::map::grid::Grid;
 use sekai::terrain::{TerrainConfig, TerrainGenerator};
 use std::collections::HashSet;
 use std::fs;
+#[allow(clippy::useless_borrows_in_formatting)]
 fn main() {
     let templates = [
         "earth-like",
@@ -23,7 +23,14 @@
 //! - `halfedges[i]` = 半边 i 的对偶半边（twin）
 //! - 三角形 t 的三条半边索引为 `3*t`, `3*t+1`, `3*t+2`
 
-use egui::Pos2;
+use std::collections::{HashMap, HashSet};
+
+use egui::{Pos2, Rect, Vec2};
+#[cfg(not(target_arch = "wasm32"))]
+use rayon::prelude::*;
+
+use crate::delaunay::voronoi::clip_polygon_to_rect;
+use crate::delaunay::{validate_delaunay, Triangle};
 
 // ============================================================================
 // 常量
@@ -60,6 +67,72 @@ pub struct DelaunayMesh {
     pub hull: Vec<u32>,
 }
 
+/// 网格拓扑错误，由 [`DelaunayMesh::validate_topology`] 返回
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MeshError {
+    /// `triangles` 长度不是 3 的倍数，或与 `halfedges` 长度不一致
+    LengthMismatch { triangles: usize, halfedges: usize },
+    /// 三角形引用了不存在的顶点
+    VertexOutOfRange { halfedge: u32, vertex: u32 },
+    /// 对偶半边索引越界
+    TwinOutOfRange { halfedge: u32, twin: u32 },
+    /// `twin(twin(e)) != e`
+    TwinNotSymmetric { halfedge: u32, twin: u32 },
+    /// 对偶半边方向不相反（起点、终点没有互换）
+    TwinNotOpposite { halfedge: u32, twin: u32 },
+    /// `vertex_to_halfedge` 长度与顶点数不一致
+    VertexTableLength { expected: usize, actual: usize },
+    /// `vertex_to_halfedge[vertex]` 指向的半边不存在或不是从该顶点出发
+    VertexHalfedgeMismatch { vertex: u32, halfedge: u32 },
+}
+
+impl std::fmt::Display for MeshError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LengthMismatch {
+                triangles,
+                halfedges,
+            } => write!(
+                f,
+                "triangles ({}) and halfedges ({}) lengths are inconsistent",
+                triangles, halfedges
+            ),
+            Self::VertexOutOfRange { halfedge, vertex } => {
+                write!(
+                    f,
+                    "halfedge {} starts at missing vertex {}",
+                    halfedge, vertex
+                )
+            }
+            Self::TwinOutOfRange { halfedge, twin } => {
+                write!(f, "halfedge {} has out-of-range twin {}", halfedge, twin)
+            }
+            Self::TwinNotSymmetric { halfedge, twin } => write!(
+                f,
+                "twin of halfedge {} is {}, whose twin is not {}",
+                halfedge, twin, halfedge
+            ),
+            Self::TwinNotOpposite { halfedge, twin } => write!(
+                f,
+                "halfedge {} and its twin {} do not run in opposite directions",
+                halfedge, twin
+            ),
+            Self::VertexTableLength { expected, actual } => write!(
+                f,
+                "vertex_to_halfedge has {} entries, expected {}",
+                actual, expected
+            ),
+            Self::VertexHalfedgeMismatch { vertex, halfedge } => write!(
+                f,
+                "vertex_to_halfedge[{}] = {} does not start at that vertex",
+                vertex, halfedge
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MeshError {}
+
 impl Default for DelaunayMesh {
     fn default() -> Self {
         Self::new()
@@ -107,13 +180,64 @@ impl DelaunayMesh {
         let hull: Vec<u32> = triangulation.hull.iter().map(|&i| i as u32).collect();
 
         // 构建顶点到半边的映射
-        let mut vertex_to_halfedge = vec![EMPTY; n_points];
-        for (he_idx, &vertex_idx) in triangles.iter().enumerate() {
-            if vertex_to_halfedge[vertex_idx as usize] == EMPTY {
-                vertex_to_halfedge[vertex_idx as usize] = he_idx as u32;
+        let vertex_to_halfedge = Self::build_vertex_to_halfedge(n_points, &triangles);
+
+        Self {
+            points,
+            halfedges,
+            triangles,
+            vertex_to_halfedge,
+            hull,
+        }
+    }
+
+    /// 从外部提供的三角形索引构建半边网格
+    ///
+    /// 用于约束剖分或编辑后的拓扑，三角形不一定满足 Delaunay 性质，
+    /// 可随后调用 [`DelaunayMesh::flip_nondelaunay_edges`] 修复。
+    /// 所有三角形的顶点顺序需保持一致（同为顺时针或逆时针）。
+    pub fn from_triangles(points: Vec<Pos2>, triangles: &[u32]) -> Self {
+        let triangles: Vec<u32> = triangles[..triangles.len() / 3 * 3].to_vec();
+
+        // 通过 (起点, 终点) 查找对偶半边
+        let mut edge_map: HashMap<(u32, u32), u32> = HashMap::with_capacity(triangles.len());
+        for he in 0..triangles.len() as u32 {
+            let start = triangles[he as usize];
+            let end = triangles[Self::next_halfedge(he) as usize];
+            edge_map.insert((start, end), he);
+        }
+
+        let mut halfedges = vec![EMPTY; triangles.len()];
+        for he in 0..triangles.len() as u32 {
+            let start = triangles[he as usize];
+            let end = triangles[Self::next_halfedge(he) as usize];
+            if let Some(&twin) = edge_map.get(&(end, start)) {
+                halfedges[he as usize] = twin;
+            }
+        }
+
+        // 沿边界半边首尾相接得到凸包顶点
+        let mut hull = Vec::new();
+        if let Some(first) = (0..triangles.len() as u32).find(|&he| halfedges[he as usize] == EMPTY)
+        {
+            let boundary_from: HashMap<u32, u32> = (0..triangles.len() as u32)
+                .filter(|&he| halfedges[he as usize] == EMPTY)
+                .map(|he| (triangles[he as usize], he))
+                .collect();
+
+            let mut he = first;
+            while hull.len() < boundary_from.len() {
+                hull.push(triangles[he as usize]);
+                let end = triangles[Self::next_halfedge(he) as usize];
+                match boundary_from.get(&end) {
+                    Some(&next) if next != first => he = next,
+                    _ => break,
+                }
             }
         }
 
+        let vertex_to_halfedge = Self::build_vertex_to_halfedge(points.len(), &triangles);
+
         Self {
             points,
             halfedges,
@@ -123,6 +247,17 @@ impl DelaunayMesh {
         }
     }
 
+    /// 构建顶点到半边的映射（取每个顶点的第一条出边）
+    fn build_vertex_to_halfedge(n_points: usize, triangles: &[u32]) -> Vec<u32> {
+        let mut vertex_to_halfedge = vec![EMPTY; n_points];
+        for (he_idx, &vertex_idx) in triangles.iter().enumerate() {
+            if vertex_to_halfedge[vertex_idx as usize] == EMPTY {
+                vertex_to_halfedge[vertex_idx as usize] = he_idx as u32;
+            }
+        }
+        vertex_to_halfedge
+    }
+
     // ========================================================================
     // 基本查询
     // ========================================================================
@@ -145,6 +280,16 @@ impl DelaunayMesh {
         self.points.len()
     }
 
+    /// 获取凸包多边形的顶点坐标（与 [`Self::hull`] 同序）
+    pub fn hull_polygon(&self) -> Vec<Pos2> {
+        self.hull.iter().map(|&v| self.points[v as usize]).collect()
+    }
+
+    /// 顶点是否在凸包边界上
+    pub fn is_hull_vertex(&self, v: u32) -> bool {
+        self.hull.contains(&v)
+    }
+
     /// 获取半边的起点索引
     #[inline]
     pub fn halfedge_start(&self, he: u32) -> u32 {
@@ -248,6 +393,79 @@ impl DelaunayMesh {
         neighbors
     }
 
+    /// 查找包含点 `p` 的三角形
+    ///
+    /// 从三角形 0 出发做可见性行走：若 `p` 位于某条边的外侧（与对顶点异侧），
+    /// 就穿过这条边走到相邻三角形，直到 `p` 不在任何边的外侧。
+    /// 需要穿过凸包边界时说明 `p` 在凸包之外，返回 None。落在边或顶点上的点
+    /// 归属于任一相邻三角形。非 Delaunay 网格上行走可能绕圈，超过三角形数量步后
+    /// 退回逐个检查。
+    pub fn locate_triangle(&self, p: Pos2) -> Option<u32> {
+        self.locate_triangle_from(p, 0)
+    }
+
+    /// 从三角形 `start` 出发查找包含点 `p` 的三角形
+    ///
+    /// 与 [`Self::locate_triangle`] 相同，起点靠近 `p` 时（例如取最近顶点的三角形）
+    /// 行走步数很少。`start` 越界时从三角形 0 出发。
+    pub fn locate_triangle_from(&self, p: Pos2, start: u32) -> Option<u32> {
+        let count = self.triangle_count() as u32;
+        if count == 0 {
+            return None;
+        }
+
+        let mut tri = if start < count { start } else { 0 };
+        'walk: for _ in 0..count {
+            let [a, b, c] = self.triangle_points(tri);
+            let orientation = orient(a, b, c);
+            let base = Self::halfedge_of_triangle(tri);
+            for (k, (from, to)) in [(a, b), (b, c), (c, a)].into_iter().enumerate() {
+                if orient(from, to, p) * orientation < 0.0 {
+                    let twin = self.halfedges[(base + k as u32) as usize];
+                    if twin == EMPTY {
+                        return None;
+                    }
+                    tri = Self::triangle_of_halfedge(twin);
+                    continue 'walk;
+                }
+            }
+            return Some(tri);
+        }
+
+        (0..count).find(|&t| {
+            let [a, b, c] = self.triangle_points(t);
+            let orientation = orient(a, b, c);
+            [(a, b), (b, c), (c, a)]
+                .iter()
+                .all(|&(from, to)| orient(from, to, p) * orientation >= 0.0)
+        })
+    }
+
+    /// 在点 `p` 处按重心坐标插值顶点值
+    ///
+    /// `values[v]` 为顶点 v 的值。先用 [`Self::locate_triangle`] 找到包含 `p` 的三角形，
+    /// 再按重心坐标混合三个顶点的值，结果在网格内连续变化。
+    /// `p` 在凸包之外、三角形退化或 `values` 缺少顶点的值时返回 None。
+    pub fn interpolate(&self, p: Pos2, values: &[f32]) -> Option<f32> {
+        let tri = self.locate_triangle(p)?;
+        let [ia, ib, ic] = self.triangle_vertices(tri);
+        let [a, b, c] = self.triangle_points(tri);
+
+        let area = orient(a, b, c);
+        if area.abs() <= f32::EPSILON {
+            return None;
+        }
+        let wa = orient(b, c, p) / area;
+        let wb = orient(c, a, p) / area;
+        let wc = 1.0 - wa - wb;
+
+        Some(
+            wa * values.get(ia as usize)?
+                + wb * values.get(ib as usize)?
+                + wc * values.get(ic as usize)?,
+        )
+    }
+
     // ========================================================================
     // 顶点周围遍历
     // ========================================================================
@@ -298,12 +516,12 @@ impl DelaunayMesh {
             current = Self::next_halfedge(twin_start);
 
             while current != start {
+                edges.insert(0, current); // 插入到开头保持顺序
                 let twin = self.twin(current);
                 if twin == EMPTY {
                     break;
                 }
                 current = Self::next_halfedge(twin);
-                edges.insert(0, current); // 插入到开头保持顺序
             }
         }
 
@@ -342,6 +560,365 @@ impl DelaunayMesh {
         (vertices, is_closed)
     }
 
+    /// 提取每个顶点的 Delaunay 邻居（即 Voronoi 单元格的邻接关系）
+    ///
+    /// 沿 [`DelaunayMesh::edges_around_vertex`] 收集各出边的终点；凸包顶点的扇形不闭合，
+    /// 还需补上最后一条入边的起点。每个列表排序去重，与 `World` 中的邻接表一致。
+    ///
+    /// 各顶点的遍历互不依赖，非 wasm 平台上用 rayon 把顶点区间分给各线程并行计算。
+    pub fn cell_neighbors(&self) -> Vec<Vec<u32>> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let iter = (0..self.point_count() as u32).into_par_iter();
+        #[cfg(target_arch = "wasm32")]
+        let iter = 0..self.point_count() as u32;
+        iter.map(|v| self.vertex_neighbors(v)).collect()
+    }
+
+    /// 单个顶点的 Delaunay 邻居（排序去重）
+    fn vertex_neighbors(&self, v: u32) -> Vec<u32> {
+        let (edges, is_closed) = self.edges_around_vertex(v);
+        let mut list: Vec<u32> = edges.iter().map(|&he| self.halfedge_end(he)).collect();
+        if !is_closed {
+            if let Some(&last) = edges.last() {
+                list.push(self.halfedge_start(Self::prev_halfedge(last)));
+            }
+        }
+        list.retain(|&u| u != v);
+        list.sort_unstable();
+        list.dedup();
+        list
+    }
+
+    /// 获取裁剪到矩形内的 Voronoi 单元格（有序、闭合）
+    ///
+    /// 凸包顶点的单元格是开放的：两端沿凸包边的外法线方向延伸出射线。
+    /// 这里把射线延长到远处，并在两条射线之间补一个角平分线方向的远点，
+    /// 使多边形在裁剪前覆盖整个开放区域，再用 Sutherland–Hodgman 算法
+    /// 裁剪到 `bounds`，矩形角点会自然插入。单元格与矩形不相交时返回空列表。
+    pub fn clipped_voronoi_cell(&self, v: u32, bounds: Rect) -> Vec<Pos2> {
+        let (edges, is_closed) = self.edges_around_vertex(v);
+        if edges.is_empty() {
+            return Vec::new();
+        }
+
+        let mut polygon: Vec<Pos2> = edges
+            .iter()
+            .map(|&he| self.circumcenter(Self::triangle_of_halfedge(he)))
+            .collect();
+
+        if !is_closed {
+            let site = self.points[v as usize];
+            let reach = polygon
+                .iter()
+                .chain([&bounds.min, &bounds.max])
+                .map(|p| p.distance(site))
+                .fold(0.0f32, f32::max);
+            let far = (reach + bounds.size().length()) * 4.0;
+
+            // 首条出边与末条入边都是凸包边
+            let first = edges[0];
+            let last = Self::prev_halfedge(edges[edges.len() - 1]);
+            let first_dir = self.outward_normal(first);
+            let last_dir = self.outward_normal(last);
+            let mid_dir = (first_dir + last_dir).normalized();
+            let mid_dir = if mid_dir.is_finite() {
+                mid_dir
+            } else {
+                first_dir.rot90()
+            };
+
+            let first_cc = polygon[0];
+            let last_cc = polygon[polygon.len() - 1];
+            polygon.push(last_cc + last_dir * far);
+            polygon.push(site + mid_dir * far);
+            polygon.push(first_cc + first_dir * far);
+        }
+
+        clip_polygon_to_rect(&polygon, bounds)
+    }
+
+    /// 边界半边的单位外法线（背离所在三角形第三个顶点的方向）
+    fn outward_normal(&self, he: u32) -> Vec2 {
+        let a = self.points[self.halfedge_start(he) as usize];
+        let b = self.points[self.halfedge_end(he) as usize];
+        let c = self.points[self.triangles[Self::prev_halfedge(he) as usize] as usize];
+
+        let normal = (b - a).rot90().normalized();
+        if normal.dot(c - a) > 0.0 {
+            -normal
+        } else {
+            normal
+        }
+    }
+
+    // ========================================================================
+    // 增量更新
+    // ========================================================================
+
+    /// 增量插入一个点（Bowyer–Watson）
+    ///
+    /// 1. 用 [`Self::locate_triangle`] 找到包含 `p` 的三角形
+    /// 2. 从它出发沿邻接扩散，收集外接圆包含 `p` 的三角形（空腔）
+    /// 3. 用空腔边界的每条边与 `p` 连成新三角形，复用被删除三角形的槽位，多出的追加在末尾
+    /// 4. 只重连新三角形之间及其与空腔外侧的对偶半边，并更新空腔顶点的出边
+    ///
+    /// 新顶点的索引为插入前的 `point_count()`。`p` 恰好落在凸包边上时该边被一分为二，
+    /// 新顶点插入 [`Self::hull`]；`p` 在凸包之外时整体重新三角剖分；
+    /// 与已有顶点重合的点被忽略。
+    pub fn insert_point(&mut self, p: Pos2) {
+        let Some(start) = self.locate_triangle(p) else {
+            let mut points = std::mem::take(&mut self.points);
+            points.push(p);
+            *self = crate::delaunay::triangulate_mesh(points);
+            return;
+        };
+        if self.triangle_points(start).contains(&p) {
+            return;
+        }
+
+        // 收集空腔：包含 p 的三角形必然在内，其余按外接圆测试扩散
+        let mut bad = vec![start];
+        let mut in_cavity = HashSet::from([start]);
+        let mut stack = vec![start];
+        while let Some(tri) = stack.pop() {
+            for neighbor in self.adjacent_triangles(tri) {
+                if in_cavity.contains(&neighbor) {
+                    continue;
+                }
+                let [a, b, c] = self.triangle_points(neighbor);
+                if Triangle::new([a, b, c]).contains_in_circumcircle(p) {
+                    in_cavity.insert(neighbor);
+                    bad.push(neighbor);
+                    stack.push(neighbor);
+                }
+            }
+        }
+
+        // 空腔边界：对偶为空或在空腔之外的半边 (a → b, 外侧对偶)。
+        // p 所在的凸包边不连成退化三角形，而是被 a → p、p → b 两条新边界取代
+        let mut boundary = Vec::new();
+        let mut split_hull_edge = None;
+        for &tri in &bad {
+            for he in Self::halfedge_of_triangle(tri)..Self::halfedge_of_triangle(tri) + 3 {
+                let twin = self.twin(he);
+                let (a, b) = (self.halfedge_start(he), self.halfedge_end(he));
+                if twin == EMPTY
+                    && orient(self.points[a as usize], self.points[b as usize], p) == 0.0
+                {
+                    split_hull_edge = Some((a, b));
+                } else if twin == EMPTY || !in_cavity.contains(&Self::triangle_of_halfedge(twin)) {
+                    boundary.push((a, b, twin));
+                }
+            }
+        }
+
+        let new_vertex = self.points.len() as u32;
+        self.points.push(p);
+        self.vertex_to_halfedge.push(EMPTY);
+
+        // 槽位：先复用空腔三角形，不够时追加
+        let mut slots = bad;
+        while slots.len() < boundary.len() {
+            slots.push(self.triangle_count() as u32);
+            self.triangles.extend([EMPTY; 3]);
+            self.halfedges.extend([EMPTY; 3]);
+        }
+
+        // 每个新三角形 (a, b, p)：半边 3s = a→b，3s+1 = b→p，3s+2 = p→a
+        let mut ending_at: HashMap<u32, u32> = HashMap::with_capacity(boundary.len());
+        for (&(a, b, twin), &slot) in boundary.iter().zip(&slots) {
+            let base = Self::halfedge_of_triangle(slot);
+            self.triangles[base as usize] = a;
+            self.triangles[base as usize + 1] = b;
+            self.triangles[base as usize + 2] = new_vertex;
+            self.link(base, twin);
+            self.halfedges[base as usize + 1] = EMPTY;
+            self.halfedges[base as usize + 2] = EMPTY;
+            ending_at.insert(b, slot);
+            self.vertex_to_halfedge[a as usize] = base;
+        }
+        for (&(a, _, _), &slot) in boundary.iter().zip(&slots) {
+            // p→a 与以 a 为终点的边界边所在三角形中的 a→p 互为对偶
+            if let Some(&prev_slot) = ending_at.get(&a) {
+                self.link(
+                    Self::halfedge_of_triangle(slot) + 2,
+                    Self::halfedge_of_triangle(prev_slot) + 1,
+                );
+            }
+        }
+        self.vertex_to_halfedge[new_vertex as usize] = Self::halfedge_of_triangle(slots[0]) + 2;
+
+        if let Some((a, b)) = split_hull_edge {
+            // a 不再是任何边界边的起点，改用以 a 为终点的新三角形中的 a → p
+            if let Some(&slot) = ending_at.get(&a) {
+                self.vertex_to_halfedge[a as usize] = Self::halfedge_of_triangle(slot) + 1;
+            }
+            let n = self.hull.len();
+            if let Some(i) = (0..n).find(|&i| {
+                let (u, v) = (self.hull[i], self.hull[(i + 1) % n]);
+                (u, v) == (a, b) || (u, v) == (b, a)
+            }) {
+                self.hull.insert(i + 1, new_vertex);
+            }
+        }
+    }
+
+    // ========================================================================
+    // 验证与修复
+    // ========================================================================
+
+    /// 验证网格是否满足 Delaunay 性质
+    pub fn validate(&self) -> bool {
+        validate_delaunay(&self.triangles, &self.points)
+    }
+
+    /// 验证半边拓扑的完整性
+    ///
+    /// 与只检查空外接圆性质的 [`Self::validate`] 互补，检查：
+    /// - `triangles` 与 `halfedges` 长度一致且顶点索引有效
+    /// - 每条非边界半边的对偶有效：索引不越界、`twin(twin(e)) == e`、方向相反
+    /// - `vertex_to_halfedge` 中的每条半边都从对应顶点出发（孤立顶点为 `EMPTY`）
+    ///
+    /// 返回遇到的第一个错误。
+    pub fn validate_topology(&self) -> Result<(), MeshError> {
+        let len = self.triangles.len();
+        if len % 3 != 0 || self.halfedges.len() != len {
+            return Err(MeshError::LengthMismatch {
+                triangles: len,
+                halfedges: self.halfedges.len(),
+            });
+        }
+
+        let n_points = self.points.len();
+        for (he, &vertex) in self.triangles.iter().enumerate() {
+            if vertex as usize >= n_points {
+                return Err(MeshError::VertexOutOfRange {
+                    halfedge: he as u32,
+                    vertex,
+                });
+            }
+        }
+
+        for (he, &twin) in self.halfedges.iter().enumerate() {
+            let he = he as u32;
+            if twin == EMPTY {
+                continue;
+            }
+            if twin as usize >= len {
+                return Err(MeshError::TwinOutOfRange { halfedge: he, twin });
+            }
+            if self.halfedges[twin as usize] != he {
+                return Err(MeshError::TwinNotSymmetric { halfedge: he, twin });
+            }
+            if self.halfedge_start(twin) != self.halfedge_end(he)
+                || self.halfedge_end(twin) != self.halfedge_start(he)
+            {
+                return Err(MeshError::TwinNotOpposite { halfedge: he, twin });
+            }
+        }
+
+        if self.vertex_to_halfedge.len() != n_points {
+            return Err(MeshError::VertexTableLength {
+                expected: n_points,
+                actual: self.vertex_to_halfedge.len(),
+            });
+        }
+        for (vertex, &he) in self.vertex_to_halfedge.iter().enumerate() {
+            if he != EMPTY && self.triangles.get(he as usize) != Some(&(vertex as u32)) {
+                return Err(MeshError::VertexHalfedgeMismatch {
+                    vertex: vertex as u32,
+                    halfedge: he,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 翻转所有不满足 Delaunay 性质的边，直到网格局部 Delaunay
+    ///
+    /// 使用与 [`validate_delaunay`] 相同的外接圆测试：若共享边对面的顶点
+    /// 落在三角形外接圆内，则翻转该边。凸包不受影响。
+    ///
+    /// # 返回值
+    /// 执行的翻转次数
+    pub fn flip_nondelaunay_edges(&mut self) -> usize {
+        let mut flips = 0;
+        // Lawson 翻转必然终止，这里的上限只是防止数值误差导致死循环
+        let max_passes = self.halfedge_count().max(1);
+
+        for _ in 0..max_passes {
+            let mut flipped = false;
+            for a in 0..self.halfedge_count() as u32 {
+                if self.flip_if_illegal(a) {
+                    flipped = true;
+                    flips += 1;
+                }
+            }
+            if !flipped {
+                break;
+            }
+        }
+
+        self.vertex_to_halfedge =
+            Self::build_vertex_to_halfedge(self.points.len(), &self.triangles);
+        flips
+    }
+
+    /// 若半边 `a` 所在的边不满足 Delaunay 性质则翻转它
+    fn flip_if_illegal(&mut self, a: u32) -> bool {
+        let b = self.twin(a);
+        if b == EMPTY {
+            return false;
+        }
+
+        //      pl                    pl
+        //     /||\                  /  \
+        //  al/ || \bl            al/    \a
+        //   /  ||  \              /      \
+        //  p0 a||b  p1    =>     p0 ------ p1
+        //   \  ||  /              \      /
+        //  ar\ || /br            ar\    /b
+        //     \||/                  \  /
+        //      pr                    pr
+        let ar = Self::prev_halfedge(a);
+        let al = Self::next_halfedge(a);
+        let bl = Self::prev_halfedge(b);
+
+        let p0 = self.triangles[ar as usize];
+        let pr = self.triangles[a as usize];
+        let pl = self.triangles[al as usize];
+        let p1 = self.triangles[bl as usize];
+
+        let triangle = Triangle::new([
+            self.points[p0 as usize],
+            self.points[pr as usize],
+            self.points[pl as usize],
+        ]);
+        if !triangle.contains_in_circumcircle(self.points[p1 as usize]) {
+            return false;
+        }
+
+        self.triangles[a as usize] = p1;
+        self.triangles[b as usize] = p0;
+
+        let hbl = self.twin(bl);
+        let har = self.twin(ar);
+        self.link(a, hbl);
+        self.link(b, har);
+        self.link(ar, bl);
+
+        true
+    }
+
+    /// 互相设置两条半边为对偶
+    fn link(&mut self, a: u32, b: u32) {
+        self.halfedges[a as usize] = b;
+        if b != EMPTY {
+            self.halfedges[b as usize] = a;
+        }
+    }
+
     // ========================================================================
     // 用于渲染的数据生成
     // ========================================================================
@@ -406,6 +983,11 @@ impl DelaunayMesh {
 // 辅助函数
 // ============================================================================
 
+/// 有向面积的两倍：`c` 在 `a → b` 左侧为正（y 向上的坐标系），共线为 0
+fn orient(a: Pos2, b: Pos2, c: Pos2) -> f32 {
+    (b - a).x * (c - a).y - (b - a).y * (c - a).x
+}
+
 /// 计算三角形的外心
 fn compute_circumcenter(a: Pos2, b: Pos2, c: Pos2) -> Pos2 {
     // 边的中点
@@ -527,6 +1109,120 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_clipped_voronoi_cell() {
+        let mesh = create_test_mesh();
+        let bounds = Rect::from_min_max(Pos2::new(-5.0, -5.0), Pos2::new(15.0, 15.0));
+
+        let mut total_area = 0.0;
+        for v in 0..mesh.point_count() as u32 {
+            let cell = mesh.clipped_voronoi_cell(v, bounds);
+            assert!(cell.len() >= 3, "顶点 {} 的单元格应闭合: {:?}", v, cell);
+            for p in &cell {
+                assert!(
+                    bounds.expand(1e-3).contains(*p),
+                    "顶点 {} 的单元格超出边界: {:?}",
+                    v,
+                    p
+                );
+            }
+
+            let area: f32 = (0..cell.len())
+                .map(|i| {
+                    let (a, b) = (cell[i], cell[(i + 1) % cell.len()]);
+                    a.x * b.y - b.x * a.y
+                })
+                .sum::<f32>()
+                * 0.5;
+            total_area += area.abs();
+        }
+
+        // 四个单元格恰好铺满边界矩形
+        assert!((total_area - 400.0).abs() < 1e-2, "{}", total_area);
+    }
+
+    #[test]
+    fn test_edges_around_boundary_vertex_complete() {
+        let mut points = Vec::new();
+        for y in 0..5 {
+            for x in 0..5 {
+                points.push(Pos2::new(
+                    x as f32 * 10.0 + (y % 2) as f32 * 0.7,
+                    y as f32 * 10.0 + (x % 3) as f32 * 0.4,
+                ));
+            }
+        }
+        let mesh = crate::delaunay::triangulate_mesh(points);
+
+        for v in 0..mesh.point_count() as u32 {
+            let (edges, _) = mesh.edges_around_vertex(v);
+            let incident = mesh.triangles.iter().filter(|&&t| t == v).count();
+            assert_eq!(edges.len(), incident, "顶点 {} 的出边不完整", v);
+        }
+    }
+
+    #[test]
+    fn test_cell_neighbors_parallel_matches_serial() {
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1328);
+        let points: Vec<Pos2> = (0..20_000)
+            .map(|_| Pos2::new(rng.random_range(0.0..1000.0), rng.random_range(0.0..1000.0)))
+            .collect();
+        let mesh = crate::delaunay::triangulate_mesh(points);
+
+        let serial: Vec<Vec<u32>> = (0..mesh.point_count() as u32)
+            .map(|v| mesh.vertex_neighbors(v))
+            .collect();
+        let parallel = mesh.cell_neighbors();
+
+        assert_eq!(parallel.len(), mesh.point_count());
+        assert_eq!(parallel, serial);
+    }
+
+    #[test]
+    fn test_cell_neighbors_grid() {
+        let mut points = Vec::new();
+        for y in 0..5 {
+            for x in 0..5 {
+                points.push(Pos2::new(x as f32 * 10.0, y as f32 * 10.0));
+            }
+        }
+        let mesh = crate::delaunay::triangulate_mesh(points);
+        let neighbors = mesh.cell_neighbors();
+        assert_eq!(neighbors.len(), 25);
+
+        // 按坐标查找网格索引，不依赖网格内部的点顺序
+        let index_of = |x: i32, y: i32| {
+            mesh.points
+                .iter()
+                .position(|p| *p == Pos2::new(x as f32 * 10.0, y as f32 * 10.0))
+                .map(|i| i as u32)
+        };
+
+        for (v, list) in neighbors.iter().enumerate() {
+            assert!(!list.contains(&(v as u32)), "顶点 {} 出现自环", v);
+            for &u in list {
+                assert!(neighbors[u as usize].contains(&(v as u32)), "邻接不对称");
+            }
+
+            let p = mesh.points[v];
+            let (x, y) = ((p.x / 10.0) as i32, (p.y / 10.0) as i32);
+            let interior = (1..4).contains(&x) && (1..4).contains(&y);
+            let incident = mesh.triangles.iter().filter(|&&t| t == v as u32).count();
+            if interior {
+                // 内部顶点：度数等于相邻三角形数，且包含四个轴向邻居
+                assert_eq!(list.len(), incident);
+                for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                    assert!(list.contains(&index_of(x + dx, y + dy).unwrap()));
+                }
+            } else {
+                // 边界顶点：扇形不闭合，邻居比三角形多一个
+                assert_eq!(list.len(), incident + 1);
+            }
+        }
+    }
+
     #[test]
     fn test_voronoi_render_data() {
         let mesh = create_test_mesh();
@@ -539,6 +1235,319 @@ mod tests {
         assert_eq!(indices.len(), 2); // 1 条边 × 2 个索引
     }
 
+    #[test]
+    fn test_locate_triangle() {
+        let mut points = Vec::new();
+        for i in 0..8 {
+            for j in 0..8 {
+                // 错开奇数行，避免共圆退化
+                let offset = if j % 2 == 0 { 0.0 } else { 3.0 };
+                points.push(Pos2::new(i as f32 * 10.0 + offset, j as f32 * 9.0));
+            }
+        }
+        let delaunay_points: Vec<delaunator::Point> = points
+            .iter()
+            .map(|p| delaunator::Point {
+                x: p.x as f64,
+                y: p.y as f64,
+            })
+            .collect();
+        let triangulation = delaunator::triangulate(&delaunay_points);
+        let mesh = DelaunayMesh::from_delaunator(points, &triangulation);
+
+        // 每个三角形的重心都定位到它自己
+        for tri in 0..mesh.triangle_count() as u32 {
+            let [a, b, c] = mesh.triangle_points(tri);
+            let centroid = Pos2::new((a.x + b.x + c.x) / 3.0, (a.y + b.y + c.y) / 3.0);
+            assert_eq!(mesh.locate_triangle(centroid), Some(tri));
+        }
+
+        // 顶点本身落在某个相邻三角形内
+        let corner = mesh.locate_triangle(Pos2::new(0.0, 0.0)).unwrap();
+        assert!(mesh.triangle_vertices(corner).contains(&0));
+
+        assert_eq!(mesh.locate_triangle(Pos2::new(-500.0, 30.0)), None);
+        assert_eq!(mesh.locate_triangle(Pos2::new(35.0, 1e4)), None);
+        assert_eq!(DelaunayMesh::new().locate_triangle(Pos2::ZERO), None);
+    }
+
+    #[test]
+    fn test_interpolate_barycentric() {
+        let mut points = Vec::new();
+        for i in 0..6 {
+            for j in 0..6 {
+                let offset = if j % 2 == 0 { 0.0 } else { 4.0 };
+                points.push(Pos2::new(i as f32 * 12.0 + offset, j as f32 * 10.0));
+            }
+        }
+        let delaunay_points: Vec<delaunator::Point> = points
+            .iter()
+            .map(|p| delaunator::Point {
+                x: p.x as f64,
+                y: p.y as f64,
+            })
+            .collect();
+        let triangulation = delaunator::triangulate(&delaunay_points);
+        let mesh = DelaunayMesh::from_delaunator(points, &triangulation);
+        let values: Vec<f32> = (0..mesh.point_count())
+            .map(|i| (i * 7 % 13) as f32)
+            .collect();
+
+        // 顶点处取该顶点的值
+        for (v, &p) in mesh.points.iter().enumerate() {
+            let sampled = mesh.interpolate(p, &values).unwrap();
+            assert!(
+                (sampled - values[v]).abs() < 1e-4,
+                "vertex {}: {}",
+                v,
+                sampled
+            );
+        }
+
+        // 重心处取三个顶点的平均值
+        for tri in 0..mesh.triangle_count() as u32 {
+            let [a, b, c] = mesh.triangle_points(tri);
+            let centroid = Pos2::new((a.x + b.x + c.x) / 3.0, (a.y + b.y + c.y) / 3.0);
+            let expected = mesh
+                .triangle_vertices(tri)
+                .iter()
+                .map(|&v| values[v as usize])
+                .sum::<f32>()
+                / 3.0;
+            let sampled = mesh.interpolate(centroid, &values).unwrap();
+            assert!(
+                (sampled - expected).abs() < 1e-4,
+                "{} vs {}",
+                sampled,
+                expected
+            );
+        }
+
+        // 线性场被精确重现
+        let linear: Vec<f32> = mesh.points.iter().map(|p| 2.0 * p.x - p.y).collect();
+        let sampled = mesh.interpolate(Pos2::new(31.0, 22.5), &linear).unwrap();
+        assert!((sampled - (62.0 - 22.5)).abs() < 1e-3);
+
+        assert_eq!(mesh.interpolate(Pos2::new(-100.0, 0.0), &values), None);
+    }
+
+    #[test]
+    fn test_validate_topology_accepts_built_mesh() {
+        let mut points = Vec::new();
+        for i in 0..6 {
+            for j in 0..6 {
+                let offset = if j % 2 == 0 { 0.0 } else { 4.0 };
+                points.push(Pos2::new(i as f32 * 12.0 + offset, j as f32 * 10.0));
+            }
+        }
+        let delaunay_points: Vec<delaunator::Point> = points
+            .iter()
+            .map(|p| delaunator::Point {
+                x: p.x as f64,
+                y: p.y as f64,
+            })
+            .collect();
+        let triangulation = delaunator::triangulate(&delaunay_points);
+        let mesh = DelaunayMesh::from_delaunator(points.clone(), &triangulation);
+        assert_eq!(mesh.validate_topology(), Ok(()));
+
+        let rebuilt = DelaunayMesh::from_triangles(points, &mesh.triangles);
+        assert_eq!(rebuilt.validate_topology(), Ok(()));
+        assert_eq!(create_test_mesh().validate_topology(), Ok(()));
+        assert_eq!(DelaunayMesh::new().validate_topology(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_topology_detects_corruption() {
+        let mesh = create_test_mesh();
+        let shared = (0..mesh.halfedge_count() as u32)
+            .find(|&he| !mesh.is_boundary(he))
+            .unwrap();
+        let twin = mesh.twin(shared);
+
+        // 对偶关系不对称：把 shared 的对偶指向同一三角形内的另一条半边
+        let mut broken = mesh.clone();
+        let wrong = DelaunayMesh::next_halfedge(twin);
+        broken.halfedges[shared as usize] = wrong;
+        assert_eq!(
+            broken.validate_topology(),
+            Err(MeshError::TwinNotSymmetric {
+                halfedge: shared,
+                twin: wrong
+            })
+        );
+
+        let mut broken = mesh.clone();
+        broken.halfedges[shared as usize] = 99;
+        assert_eq!(
+            broken.validate_topology(),
+            Err(MeshError::TwinOutOfRange {
+                halfedge: shared,
+                twin: 99
+            })
+        );
+
+        // 顶点表指向不从该顶点出发的半边
+        let mut broken = mesh.clone();
+        let he = broken.vertex_to_halfedge[0];
+        broken.vertex_to_halfedge[0] = DelaunayMesh::next_halfedge(he);
+        assert_eq!(
+            broken.validate_topology(),
+            Err(MeshError::VertexHalfedgeMismatch {
+                vertex: 0,
+                halfedge: DelaunayMesh::next_halfedge(he)
+            })
+        );
+    }
+
+    #[test]
+    fn test_hull_polygon_on_grid() {
+        let mut points = Vec::new();
+        for i in 0..5 {
+            for j in 0..5 {
+                points.push(Pos2::new(i as f32 * 10.0, j as f32 * 10.0));
+            }
+        }
+        let delaunay_points: Vec<delaunator::Point> = points
+            .iter()
+            .map(|p| delaunator::Point {
+                x: p.x as f64,
+                y: p.y as f64,
+            })
+            .collect();
+        let triangulation = delaunator::triangulate(&delaunay_points);
+        let mesh = DelaunayMesh::from_delaunator(points, &triangulation);
+
+        // 5x5 网格的边界上有 16 个点（包括边上共线的点）
+        let polygon = mesh.hull_polygon();
+        assert_eq!(polygon.len(), 16);
+        for corner in [
+            Pos2::new(0.0, 0.0),
+            Pos2::new(40.0, 0.0),
+            Pos2::new(40.0, 40.0),
+            Pos2::new(0.0, 40.0),
+        ] {
+            assert!(polygon.contains(&corner), "missing corner {:?}", corner);
+        }
+
+        // 相邻顶点沿网格边界前进一步
+        for k in 0..polygon.len() {
+            let d = polygon[k].distance(polygon[(k + 1) % polygon.len()]);
+            assert!((d - 10.0).abs() < 1e-4, "{:?}", polygon);
+        }
+
+        for v in 0..mesh.point_count() as u32 {
+            let p = mesh.points[v as usize];
+            let on_border = p.x == 0.0 || p.y == 0.0 || p.x == 40.0 || p.y == 40.0;
+            assert_eq!(mesh.is_hull_vertex(v), on_border, "vertex {}", v);
+        }
+    }
+
+    /// 三角形集合（顶点排序后比较，与顶点顺序和存储顺序无关）
+    fn triangle_set(mesh: &DelaunayMesh) -> HashSet<[u32; 3]> {
+        (0..mesh.triangle_count() as u32)
+            .map(|t| {
+                let mut v = mesh.triangle_vertices(t);
+                v.sort_unstable();
+                v
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_insert_point_matches_full_retriangulation() {
+        // 线性同余生成的伪随机点，避免四点共圆
+        let mut state = 12345u32;
+        let mut next = || {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (state >> 8) as f32 / (1u32 << 24) as f32
+        };
+        let mut points = vec![
+            Pos2::new(0.0, 0.0),
+            Pos2::new(100.0, 0.0),
+            Pos2::new(100.0, 100.0),
+            Pos2::new(0.0, 100.0),
+        ];
+        for _ in 0..40 {
+            points.push(Pos2::new(5.0 + next() * 90.0, 5.0 + next() * 90.0));
+        }
+
+        let mut mesh = crate::delaunay::triangulate_mesh(points.clone());
+        for p in [
+            Pos2::new(47.3, 52.9),
+            Pos2::new(12.1, 80.4),
+            Pos2::new(99.0, 3.7),
+        ] {
+            mesh.insert_point(p);
+            points.push(p);
+
+            assert_eq!(mesh.validate_topology(), Ok(()));
+            assert!(mesh.validate());
+            let full = crate::delaunay::triangulate_mesh(points.clone());
+            assert_eq!(mesh.point_count(), full.point_count());
+            assert_eq!(triangle_set(&mesh), triangle_set(&full));
+            assert!(!mesh
+                .triangles_around_vertex(points.len() as u32 - 1)
+                .is_empty());
+        }
+
+        // 凸包外的点整体重新剖分
+        mesh.insert_point(Pos2::new(150.0, 50.0));
+        points.push(Pos2::new(150.0, 50.0));
+        assert_eq!(mesh.validate_topology(), Ok(()));
+        assert_eq!(
+            triangle_set(&mesh),
+            triangle_set(&crate::delaunay::triangulate_mesh(points.clone()))
+        );
+
+        // 重合的点被忽略
+        let before = mesh.point_count();
+        mesh.insert_point(points[10]);
+        assert_eq!(mesh.point_count(), before);
+    }
+
+    #[test]
+    fn test_insert_point_on_hull_edge() {
+        let mut points = vec![
+            Pos2::new(0.0, 0.0),
+            Pos2::new(100.0, 0.0),
+            Pos2::new(100.0, 100.0),
+            Pos2::new(0.0, 100.0),
+            Pos2::new(37.0, 41.0),
+            Pos2::new(68.0, 23.0),
+            Pos2::new(55.0, 77.0),
+            Pos2::new(21.0, 64.0),
+        ];
+        let mut mesh = crate::delaunay::triangulate_mesh(points.clone());
+
+        // 恰好落在底边 (0,0)-(100,0) 上的点
+        let p = Pos2::new(50.0, 0.0);
+        mesh.insert_point(p);
+        points.push(p);
+        let new_vertex = points.len() as u32 - 1;
+
+        assert_eq!(mesh.validate_topology(), Ok(()));
+        assert!(mesh.validate());
+        let full = crate::delaunay::triangulate_mesh(points.clone());
+        assert_eq!(triangle_set(&mesh), triangle_set(&full));
+
+        // 没有退化三角形
+        for t in 0..mesh.triangle_count() as u32 {
+            let [a, b, c] = mesh.triangle_points(t);
+            assert!(orient(a, b, c).abs() > 0.0, "triangle {} is degenerate", t);
+        }
+
+        // 新顶点插入凸包，位于被拆分的边的两个端点之间
+        assert!(mesh.is_hull_vertex(new_vertex));
+        let n = mesh.hull.len();
+        assert_eq!(n, 5);
+        let i = mesh.hull.iter().position(|&v| v == new_vertex).unwrap();
+        let neighbors = [mesh.hull[(i + n - 1) % n], mesh.hull[(i + 1) % n]];
+        assert!(neighbors.contains(&0) && neighbors.contains(&1));
+        let (_, closed) = mesh.edges_around_vertex(new_vertex);
+        assert!(!closed);
+    }
+
     #[test]
     fn test_larger_mesh() {
         // 创建更大的测试网格
@@ -573,4 +1582,50 @@ mod tests {
         assert_eq!(voronoi_vertices.len(), mesh.triangle_count());
         assert!(!voronoi_indices.is_empty(), "应该有 Voronoi 边");
     }
+
+    #[test]
+    fn test_flip_nondelaunay_quad() {
+        // 菱形：短对角线 1-3 才是 Delaunay 边
+        let points = vec![
+            Pos2::new(0.0, 0.0),
+            Pos2::new(10.0, -3.0),
+            Pos2::new(20.0, 0.0),
+            Pos2::new(10.0, 3.0),
+        ];
+        // 故意使用长对角线 0-2
+        let mut mesh = DelaunayMesh::from_triangles(points, &[0, 1, 2, 0, 2, 3]);
+        assert!(!mesh.validate());
+        assert_eq!(mesh.halfedges.iter().filter(|&&h| h != EMPTY).count(), 2);
+        assert_eq!(mesh.hull.len(), 4);
+
+        let flips = mesh.flip_nondelaunay_edges();
+        assert_eq!(flips, 1);
+        assert!(mesh.validate());
+
+        // 翻转后共享边为 1-3
+        let shared: Vec<(u32, u32)> = (0..mesh.halfedge_count() as u32)
+            .filter(|&he| mesh.twin(he) != EMPTY)
+            .map(|he| {
+                let (s, e) = (mesh.halfedge_start(he), mesh.halfedge_end(he));
+                (s.min(e), s.max(e))
+            })
+            .collect();
+        assert_eq!(shared, vec![(1, 3), (1, 3)]);
+
+        // 对偶关系和顶点遍历仍然一致
+        for he in 0..mesh.halfedge_count() as u32 {
+            let twin = mesh.twin(he);
+            if twin != EMPTY {
+                assert_eq!(mesh.twin(twin), he);
+                assert_eq!(mesh.halfedge_start(twin), mesh.halfedge_end(he));
+            }
+        }
+        for v in 0..mesh.point_count() as u32 {
+            let (edges, _) = mesh.edges_around_vertex(v);
+            assert!(edges.iter().all(|&he| mesh.halfedge_start(he) == v));
+        }
+
+        // 再次修复不会有任何翻转
+        assert_eq!(mesh.flip_nondelaunay_edges(), 0);
+    }
 }
@@ -107,6 +107,9 @@ pub use delaunay::triangulate_mesh;
 /// 提供 O(1) 邻接查询和有序的 Voronoi 单元格遍历。
 pub use half_edge::DelaunayMesh;
 
+/// 半边网格拓扑错误
+pub use half_edge::MeshError;
+
 /// 无效索引常量
 pub use half_edge::EMPTY;
 
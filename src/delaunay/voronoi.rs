@@ -236,10 +236,93 @@ pub fn generate_voronoi_render_data(
     compute_indexed_voronoi(triangle_indices, points).get_render_data()
 }
 
+/// Lloyd 松弛
+///
+/// 每轮迭代重新三角剖分，计算每个点的 Voronoi 单元格质心，并把点移动到质心。
+/// 单元格先裁剪到输入点集的包围盒，使结果不会超出原有范围；
+/// 凸包上的点（单元格不闭合）保持不动，避免点集整体向内收缩。
+///
+/// # 参数
+/// - `points`: 待松弛的点集（原地修改）
+/// - `iterations`: 迭代次数
+pub fn lloyd_relax(points: &mut [Pos2], iterations: usize) {
+    if points.len() < 3 {
+        return;
+    }
+    let bounds = egui::Rect::from_points(points);
+
+    for _ in 0..iterations {
+        let mesh = super::triangulate_mesh(points.to_vec());
+        for (v, point) in points.iter_mut().enumerate() {
+            let (vertices, is_closed) = mesh.voronoi_cell_vertices(v as u32);
+            if !is_closed {
+                continue;
+            }
+            let clipped = clip_polygon_to_rect(&vertices, bounds);
+            if let Some(centroid) = polygon_centroid(&clipped) {
+                *point = centroid;
+            }
+        }
+    }
+}
+
 // ============================================================================
 // 内部实现
 // ============================================================================
 
+/// 使用 Sutherland–Hodgman 算法把凸多边形裁剪到矩形内
+pub(crate) fn clip_polygon_to_rect(polygon: &[Pos2], rect: egui::Rect) -> Vec<Pos2> {
+    // 四条裁剪边：(坐标轴, 边界值, 是否保留大于边界值的一侧)
+    let planes = [
+        (0, rect.min.x, true),
+        (0, rect.max.x, false),
+        (1, rect.min.y, true),
+        (1, rect.max.y, false),
+    ];
+
+    let mut output = polygon.to_vec();
+    for (axis, bound, keep_greater) in planes {
+        let inside = |p: Pos2| (p[axis] >= bound) == keep_greater || p[axis] == bound;
+        let intersect = |a: Pos2, b: Pos2| {
+            let t = (bound - a[axis]) / (b[axis] - a[axis]);
+            a + (b - a) * t
+        };
+
+        let input = std::mem::take(&mut output);
+        for (i, &current) in input.iter().enumerate() {
+            let previous = input[(i + input.len() - 1) % input.len()];
+            match (inside(previous), inside(current)) {
+                (true, true) => output.push(current),
+                (true, false) => output.push(intersect(previous, current)),
+                (false, true) => {
+                    output.push(intersect(previous, current));
+                    output.push(current);
+                }
+                (false, false) => {}
+            }
+        }
+    }
+    output
+}
+
+/// 多边形质心（面积为 0 时返回 None）
+fn polygon_centroid(polygon: &[Pos2]) -> Option<Pos2> {
+    let mut area = 0.0;
+    let (mut cx, mut cy) = (0.0, 0.0);
+    for i in 0..polygon.len() {
+        let a = polygon[i];
+        let b = polygon[(i + 1) % polygon.len()];
+        let cross = a.x * b.y - b.x * a.y;
+        area += cross;
+        cx += (a.x + b.x) * cross;
+        cy += (a.y + b.y) * cross;
+    }
+    if area.abs() <= f32::EPSILON {
+        return None;
+    }
+    Some(Pos2::new(cx / (3.0 * area), cy / (3.0 * area)))
+}
+
 /// 顶点坐标量化精度（用于去重）
 const VERTEX_QUANTIZATION: f64 = 10000.0;
 
@@ -298,4 +298,47 @@ mod voronoi_validation {
             );
         }
     }
+
+    /// 最近邻距离的方差（以均值平方归一化，不受整体尺度影响）
+    fn nearest_distance_variance(points: &[Pos2]) -> f32 {
+        let distances: Vec<f32> = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                points
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, q)| p.distance(*q))
+                    .fold(f32::INFINITY, f32::min)
+            })
+            .collect();
+        let mean = distances.iter().sum::<f32>() / distances.len() as f32;
+        let variance =
+            distances.iter().map(|d| (d - mean).powi(2)).sum::<f32>() / distances.len() as f32;
+        variance / (mean * mean)
+    }
+
+    #[test]
+    fn test_lloyd_relax_evens_out_clusters() {
+        use super::super::voronoi::lloyd_relax;
+        use rand::SeedableRng;
+
+        // 一半的点挤在左上角，另一半均匀分布
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        let mut points: Vec<Pos2> = (0..300)
+            .map(|i| {
+                let extent = if i % 2 == 0 { 25.0 } else { 100.0 };
+                Pos2::new(rng.random_range(0.0..extent), rng.random_range(0.0..extent))
+            })
+            .collect();
+        let bounds = egui::Rect::from_points(&points);
+        let before = nearest_distance_variance(&points);
+
+        lloyd_relax(&mut points, 1);
+
+        let after = nearest_distance_variance(&points);
+        assert!(after < before, "variance {} -> {}", before, after);
+        assert!(points.iter().all(|p| bounds.expand(1e-3).contains(*p)));
+    }
 }
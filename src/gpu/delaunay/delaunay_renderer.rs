@@ -4,12 +4,24 @@ use crate::gpu::canvas_uniform::CanvasUniforms;
 use crate::gpu::map_renderer::MapRenderer;
 use crate::resource::CanvasStateResource;
 use crate::spatial::EdgeIndex;
+use crate::terrain::HeightColorMap;
 use eframe::egui_wgpu::wgpu;
 use eframe::egui_wgpu::wgpu::util::DeviceExt;
 use egui::emath::TSTransform;
-use egui::{Pos2, Rect};
+use egui::{Color32, Pos2, Rect};
 
 const MAX_POINTS: usize = 100_000;
+const MAX_TRIANGLES: usize = MAX_POINTS * 2;
+
+/// 三角剖分的绘制模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DrawMode {
+    /// 只绘制三角形边
+    #[default]
+    Wireframe,
+    /// 按三个顶点的平均高度为每个三角形平涂着色
+    FilledFlat,
+}
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
@@ -36,8 +48,16 @@ impl Default for GPUTriangle {
 pub struct DelaunayRenderer {
     canvas_state_resource: CanvasStateResource,
     pub points: Vec<Pos2>,
-    /// 三角形边的索引（u32），每2个索引构成一条边
+    /// 当前绘制模式使用的索引（u32）：
+    /// 线框模式下每2个索引构成一条边，填充模式下每3个索引构成一个三角形
     pub triangle_indices: Vec<u32>,
+    /// 原始三角形索引（每3个索引构成一个三角形）
+    source_triangles: Vec<u32>,
+    /// 每个顶点的高度，用于填充模式着色
+    heights: Vec<u8>,
+    draw_mode: DrawMode,
+    /// 填充模式下每个三角形的颜色（打包为非预乘 RGBA8）
+    triangle_colors: Vec<u32>,
     /// 边的空间索引，用于快速视口裁剪
     edge_index: Option<EdgeIndex>,
     pub uniforms: CanvasUniforms,
@@ -46,6 +66,9 @@ pub struct DelaunayRenderer {
     pub uniform_buffer: wgpu::Buffer,
     pub delaunay_pipeline: wgpu::RenderPipeline,
     pub bind_group: wgpu::BindGroup,
+    pub triangle_colors_buffer: wgpu::Buffer,
+    pub fill_pipeline: wgpu::RenderPipeline,
+    pub fill_bind_group: wgpu::BindGroup,
 }
 
 impl DelaunayRenderer {
@@ -68,9 +91,18 @@ impl DelaunayRenderer {
             device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: Some("triangle_indices_buffer"),
                 contents: bytemuck::cast_slice(&triangle_indices),
-                usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+                // 填充模式下着色器直接读取索引，因此同时作为 storage 绑定
+                usage: wgpu::BufferUsages::INDEX
+                    | wgpu::BufferUsages::STORAGE
+                    | wgpu::BufferUsages::COPY_DST,
             });
 
+        let triangle_colors_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("triangle_colors_buffer"),
+            contents: bytemuck::cast_slice(&vec![0u32; MAX_TRIANGLES]),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        });
+
         let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("map_uniform_buffer"),
             contents: bytemuck::cast_slice(&[uniforms]),
@@ -134,10 +166,82 @@ impl DelaunayRenderer {
         let delaunay_pipeline =
             MapRenderer::create_delaunay_pipeline(device, &pipeline_layout, target_format);
 
+        // 填充管线：顶点、Uniform、三角形索引、三角形颜色
+        let storage_entry = |binding: u32, min_binding_size: u64| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::VERTEX,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size: NonZeroU64::new(min_binding_size),
+            },
+            count: None,
+        };
+        let fill_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("delaunay_fill_bind_group_layout"),
+                entries: &[
+                    storage_entry(0, std::mem::size_of::<Pos2>() as u64 * MAX_POINTS as u64),
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: NonZeroU64::new(
+                                std::mem::size_of::<CanvasUniforms>() as u64,
+                            ),
+                        },
+                        count: None,
+                    },
+                    storage_entry(2, std::mem::size_of::<u32>() as u64),
+                    storage_entry(3, std::mem::size_of::<u32>() as u64),
+                ],
+            });
+
+        let fill_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("delaunay_fill_bind_group"),
+            layout: &fill_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: points_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: triangle_indices_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: triangle_colors_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let fill_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("delaunay_fill_pipeline_layout"),
+            bind_group_layouts: &[&fill_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let fill_pipeline = MapRenderer::create_delaunay_fill_pipeline(
+            device,
+            &fill_pipeline_layout,
+            target_format,
+        );
+
         Self {
             canvas_state_resource,
             points,
             triangle_indices,
+            source_triangles: Vec::new(),
+            heights: Vec::new(),
+            draw_mode: DrawMode::default(),
+            triangle_colors: Vec::new(),
             edge_index: None,
             uniforms,
             points_buffer,
@@ -145,6 +249,9 @@ impl DelaunayRenderer {
             uniform_buffer,
             delaunay_pipeline,
             bind_group,
+            triangle_colors_buffer,
+            fill_pipeline,
+            fill_bind_group,
         }
     }
 
@@ -157,13 +264,59 @@ impl DelaunayRenderer {
     /// 更新索引数据
     ///
     /// 输入为三角形索引（每3个索引构成一个三角形），
-    /// 内部转换为线段列表格式（每2个索引构成一条边）。
+    /// 线框模式下内部转换为线段列表格式（每2个索引构成一条边）。
     pub fn update_indices(&mut self, indices: Vec<u32>) {
-        self.triangle_indices = self.make_line_list_indices(indices);
+        self.source_triangles = indices;
+        self.rebuild_indices();
+    }
+
+    /// 设置顶点高度，用于填充模式按平均高度着色
+    #[allow(dead_code)]
+    pub fn set_heights(&mut self, heights: Vec<u8>) {
+        self.heights = heights;
+        self.triangle_colors = Self::flat_triangle_colors(&self.source_triangles, &self.heights);
+    }
+
+    /// 切换线框/填充绘制模式，重新生成索引
+    #[allow(dead_code)]
+    pub fn set_draw_mode(&mut self, mode: DrawMode) {
+        if self.draw_mode != mode {
+            self.draw_mode = mode;
+            self.rebuild_indices();
+        }
+    }
+
+    fn rebuild_indices(&mut self) {
+        self.triangle_indices = Self::indices_for_mode(self.draw_mode, &self.source_triangles);
+        self.triangle_colors = Self::flat_triangle_colors(&self.source_triangles, &self.heights);
         // 索引更新后需要重建空间索引
         self.edge_index = None;
     }
 
+    /// 生成指定绘制模式的索引
+    fn indices_for_mode(mode: DrawMode, triangles: &[u32]) -> Vec<u32> {
+        match mode {
+            DrawMode::Wireframe => Self::make_line_list_indices(triangles),
+            DrawMode::FilledFlat => triangles.chunks_exact(3).flatten().copied().collect(),
+        }
+    }
+
+    /// 按三个顶点的平均高度计算每个三角形的颜色，缺少高度时为灰色
+    fn flat_triangle_colors(triangles: &[u32], heights: &[u8]) -> Vec<u32> {
+        triangles
+            .chunks_exact(3)
+            .map(|tri| {
+                let color = tri
+                    .iter()
+                    .map(|&v| heights.get(v as usize).map(|&h| h as u32))
+                    .sum::<Option<u32>>()
+                    .map(|sum| HeightColorMap::default_map().color((sum / 3) as u8))
+                    .unwrap_or(Color32::GRAY);
+                u32::from_le_bytes(color.to_srgba_unmultiplied())
+            })
+            .collect()
+    }
+
     /// 设置预构建的边空间索引
     ///
     /// 如果 MapSystem 已经构建了空间索引，可以直接使用避免重复构建。
@@ -214,7 +367,21 @@ impl DelaunayRenderer {
     pub fn upload_to_gpu(&mut self, queue: &wgpu::Queue) {
         queue.write_buffer(&self.points_buffer, 0, bytemuck::cast_slice(&self.points));
 
-        if !self.triangle_indices.is_empty() {
+        if self.draw_mode == DrawMode::FilledFlat {
+            // 填充模式按三角形着色，不做基于边的视口裁剪
+            if !self.triangle_indices.is_empty() {
+                queue.write_buffer(
+                    &self.triangle_indices_buffer,
+                    0,
+                    bytemuck::cast_slice(&self.triangle_indices),
+                );
+                queue.write_buffer(
+                    &self.triangle_colors_buffer,
+                    0,
+                    bytemuck::cast_slice(&self.triangle_colors),
+                );
+            }
+        } else if !self.triangle_indices.is_empty() {
             // 确保空间索引已构建
             self.ensure_edge_index();
 
@@ -257,6 +424,16 @@ impl DelaunayRenderer {
     }
 
     pub fn render(&self, render_pass: &mut wgpu::RenderPass<'static>) {
+        if self.draw_mode == DrawMode::FilledFlat {
+            // 着色器通过 storage 读取索引，按三角形编号取颜色
+            render_pass.set_pipeline(&self.fill_pipeline);
+            render_pass.set_bind_group(0, &self.fill_bind_group, &[]);
+            if !self.triangle_indices.is_empty() {
+                render_pass.draw(0..self.triangle_indices.len() as u32, 0..1);
+            }
+            return;
+        }
+
         render_pass.set_pipeline(&self.delaunay_pipeline);
         render_pass.set_bind_group(0, &self.bind_group, &[]);
         render_pass.set_index_buffer(
@@ -272,7 +449,7 @@ impl DelaunayRenderer {
 
     /// 将三角形索引转换为 LineList 需要的索引格式
     /// 每个三角形需要3条边，每条边2个顶点，共6个顶点
-    fn make_line_list_indices(&self, triangle_indices: Vec<u32>) -> Vec<u32> {
+    fn make_line_list_indices(triangle_indices: &[u32]) -> Vec<u32> {
         let mut line_indices = Vec::with_capacity(triangle_indices.len() * 2);
 
         for chunk in triangle_indices.chunks(3) {
@@ -294,3 +471,42 @@ impl DelaunayRenderer {
         line_indices
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw_mode_index_lengths() {
+        // 两个三角形组成的正方形
+        let triangles = vec![0, 1, 2, 0, 2, 3];
+
+        let wireframe = DelaunayRenderer::indices_for_mode(DrawMode::Wireframe, &triangles);
+        let filled = DelaunayRenderer::indices_for_mode(DrawMode::FilledFlat, &triangles);
+
+        // 线框：每个三角形3条边共6个索引；填充：每个三角形3个索引
+        assert_eq!(wireframe.len(), 12);
+        assert_eq!(filled.len(), 6);
+        assert_eq!(filled, triangles);
+    }
+
+    #[test]
+    fn test_flat_triangle_colors_use_average_height() {
+        let triangles = vec![0, 1, 2, 1, 2, 3];
+        let heights = vec![10, 20, 30, 200];
+
+        let colors = DelaunayRenderer::flat_triangle_colors(&triangles, &heights);
+        assert_eq!(colors.len(), 2);
+
+        let expected = HeightColorMap::default_map().color(20);
+        assert_eq!(
+            colors[0],
+            u32::from_le_bytes(expected.to_srgba_unmultiplied())
+        );
+
+        // 缺少高度数据时为灰色
+        let missing = DelaunayRenderer::flat_triangle_colors(&triangles, &heights[..2]);
+        let gray = u32::from_le_bytes(Color32::GRAY.to_srgba_unmultiplied());
+        assert!(missing.iter().all(|&c| c == gray));
+    }
+}
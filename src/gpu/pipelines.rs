@@ -87,6 +87,48 @@ impl MapRenderer {
         })
     }
 
+    pub fn create_delaunay_fill_pipeline(
+        device: &wgpu::Device,
+        pipeline_layout: &wgpu::PipelineLayout,
+        target_format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        let fill_shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("delaunay_fill_shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!(
+                "../../assets/shaders/delaunay_fill.wgsl"
+            ))),
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("delaunay_fill_pipeline"),
+            cache: None,
+            layout: Some(pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &fill_shader_module,
+                compilation_options: PipelineCompilationOptions::default(),
+                entry_point: Some("vs_main"), // 对应 WGSL 中的入口函数
+                buffers: &[],                 // 我们用 StorageBuffer，而不是传统的 VertexBuffer
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &fill_shader_module,
+                compilation_options: PipelineCompilationOptions::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: target_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        })
+    }
+
     pub fn create_voronoi_pipeline(
         device: &wgpu::Device,
         pipeline_layout: &wgpu::PipelineLayout,
@@ -128,4 +170,46 @@ impl MapRenderer {
             multiview: None,
         })
     }
+
+    pub fn create_voronoi_fill_pipeline(
+        device: &wgpu::Device,
+        pipeline_layout: &wgpu::PipelineLayout,
+        target_format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        let fill_shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("voronoi_fill_shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!(
+                "../../assets/shaders/voronoi_fill.wgsl"
+            ))),
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("voronoi_fill_pipeline"),
+            cache: None,
+            layout: Some(pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &fill_shader_module,
+                compilation_options: PipelineCompilationOptions::default(),
+                entry_point: Some("vs_main"), // 对应 WGSL 中的入口函数
+                buffers: &[],                 // 我们用 StorageBuffer，而不是传统的 VertexBuffer
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &fill_shader_module,
+                compilation_options: PipelineCompilationOptions::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: target_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        })
+    }
 }
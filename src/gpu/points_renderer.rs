@@ -3,17 +3,67 @@ use std::num::NonZeroU64;
 use eframe::egui_wgpu::wgpu;
 use eframe::egui_wgpu::wgpu::util::DeviceExt;
 use egui::emath::TSTransform;
-use egui::Pos2;
+use egui::{Color32, Pos2};
 
 use super::canvas_uniform::CanvasUniforms;
 use super::map_renderer::MapRenderer;
 
 const MAX_POINTS: usize = 100_000;
 
+/// 未设置逐点颜色时使用的默认颜色（非预乘 RGBA）
+const DEFAULT_POINT_COLOR: [u8; 4] = [230, 128, 128, 179];
+
+/// 逐点颜色数量与点数量不一致
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PointColorError {
+    pub expected: usize,
+    pub actual: usize,
+}
+
+impl std::fmt::Display for PointColorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "颜色数量 {} 与点数量 {} 不一致",
+            self.actual, self.expected
+        )
+    }
+}
+
+impl std::error::Error for PointColorError {}
+
+/// 将颜色打包为着色器使用的 u32（非预乘 RGBA，小端，对应 `unpack4x8unorm`）
+///
+/// 颜色数量必须与点数量一致
+#[allow(dead_code)]
+pub fn pack_point_colors(
+    colors: &[Color32],
+    point_count: usize,
+) -> Result<Vec<u32>, PointColorError> {
+    if colors.len() != point_count {
+        return Err(PointColorError {
+            expected: point_count,
+            actual: colors.len(),
+        });
+    }
+    Ok(colors
+        .iter()
+        .map(|color| u32::from_le_bytes(color.to_srgba_unmultiplied()))
+        .collect())
+}
+
+fn default_point_colors(point_count: usize) -> Vec<u32> {
+    vec![u32::from_le_bytes(DEFAULT_POINT_COLOR); point_count]
+}
+
 pub struct PointsRenderer {
     pub points: Vec<Pos2>,
+    /// 逐点颜色，与 `points` 一一对应
+    pub colors: Vec<u32>,
     pub uniforms: CanvasUniforms,
     pub points_buffer: wgpu::Buffer,
+    pub colors_buffer: wgpu::Buffer,
     pub uniform_buffer: wgpu::Buffer,
     pub points_pipeline: wgpu::RenderPipeline,
     pub bind_group: wgpu::BindGroup,
@@ -22,6 +72,7 @@ pub struct PointsRenderer {
 impl PointsRenderer {
     pub fn new(device: &wgpu::Device, target_format: wgpu::TextureFormat) -> Self {
         let points: Vec<Pos2> = vec![Pos2::new(0.0, 0.0); MAX_POINTS];
+        let colors = default_point_colors(MAX_POINTS);
         let uniforms = CanvasUniforms::new(egui::Rect::ZERO, TSTransform::IDENTITY);
 
         let points_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -30,6 +81,12 @@ impl PointsRenderer {
             usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
         });
 
+        let colors_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("points_colors_buffer"),
+            contents: bytemuck::cast_slice(&colors),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        });
+
         let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("map_uniform_buffer"),
             contents: bytemuck::cast_slice(&[uniforms]),
@@ -66,6 +123,19 @@ impl PointsRenderer {
                     },
                     count: None,
                 },
+                // 绑定逐点颜色
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: NonZeroU64::new(
+                            std::mem::size_of::<u32>() as u64 * colors.len() as u64,
+                        ),
+                    },
+                    count: None,
+                },
             ],
         });
 
@@ -82,6 +152,10 @@ impl PointsRenderer {
                     binding: 1,
                     resource: uniform_buffer.as_entire_binding(),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: colors_buffer.as_entire_binding(),
+                },
             ],
         });
 
@@ -96,8 +170,10 @@ impl PointsRenderer {
 
         Self {
             points,
+            colors,
             uniforms,
             points_buffer,
+            colors_buffer,
             uniform_buffer,
             points_pipeline,
             bind_group,
@@ -105,15 +181,29 @@ impl PointsRenderer {
     }
 
     pub fn update_points(&mut self, points: Vec<Pos2>) {
+        // 点数量变化后原有颜色不再对应，恢复默认颜色
+        if points.len() != self.colors.len() {
+            self.colors = default_point_colors(points.len());
+        }
         self.points = points;
     }
 
+    /// 设置逐点颜色（如按板块、高度或生物群系着色）
+    ///
+    /// 颜色数量必须与当前点数量一致，否则返回错误且不修改现有颜色
+    #[allow(dead_code)]
+    pub fn set_colors(&mut self, colors: &[Color32]) -> Result<(), PointColorError> {
+        self.colors = pack_point_colors(colors, self.points.len())?;
+        Ok(())
+    }
+
     pub fn update_uniforms(&mut self, rect: egui::Rect, transform: TSTransform) {
         self.uniforms = CanvasUniforms::new(rect, transform);
     }
 
     pub fn upload_to_gpu(&self, queue: &wgpu::Queue) {
         queue.write_buffer(&self.points_buffer, 0, bytemuck::cast_slice(&self.points));
+        queue.write_buffer(&self.colors_buffer, 0, bytemuck::cast_slice(&self.colors));
         queue.write_buffer(
             &self.uniform_buffer,
             0,
@@ -127,3 +217,25 @@ impl PointsRenderer {
         render_pass.draw(0..self.points.len() as u32 * 3, 0..1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_point_colors_requires_matching_length() {
+        let colors = vec![Color32::RED; 3];
+
+        assert_eq!(
+            pack_point_colors(&colors, 4),
+            Err(PointColorError {
+                expected: 4,
+                actual: 3
+            })
+        );
+
+        let packed = pack_point_colors(&colors, 3).unwrap();
+        assert_eq!(packed.len(), 3);
+        assert_eq!(packed[0].to_le_bytes(), [255, 0, 0, 255]);
+    }
+}
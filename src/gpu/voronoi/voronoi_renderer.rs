@@ -1,18 +1,139 @@
 use std::num::NonZeroU64;
 
+use bytemuck::{Pod, Zeroable};
 use eframe::egui_wgpu::wgpu;
 use eframe::egui_wgpu::wgpu::util::DeviceExt;
+use egui::ecolor::Hsva;
 use egui::emath::TSTransform;
-use egui::{Pos2, Rect};
+use egui::{Color32, Pos2, Rect};
 
+use crate::delaunay::voronoi::VoronoiCell;
 use crate::gpu::canvas_uniform::CanvasUniforms;
 use crate::gpu::map_renderer::MapRenderer;
 use crate::resource::CanvasStateResource;
 use crate::spatial::EdgeIndex;
+use crate::terrain::{Biome, HeightColorMap};
 
 const INITIAL_MAX_VORONOI_VERTICES: usize = 100_000;
 const INITIAL_MAX_VORONOI_INDICES: usize = 200_000;
 
+/// 单元格在屏幕上小于该像素尺寸时启用细节层次（LOD）剔除
+const LOD_CELL_PIXELS: f32 = 4.0;
+/// LOD 开启后，屏幕长度短于该像素数的边不再绘制
+const LOD_MIN_EDGE_PIXELS: f32 = 1.5;
+
+/// 按缩放级别计算需要绘制的最短边长（地图坐标）
+///
+/// 平均边长在屏幕上仍足够大时返回 0，即不剔除任何边。
+fn lod_min_edge_length(average_edge_length: f32, scale: f32) -> f32 {
+    if scale <= 0.0 || average_edge_length * scale >= LOD_CELL_PIXELS {
+        return 0.0;
+    }
+    LOD_MIN_EDGE_PIXELS / scale
+}
+
+/// 缺少着色数据的单元格使用的颜色
+const MISSING_FILL_COLOR: Color32 = Color32::GRAY;
+
+/// 单元格填充模式
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillMode {
+    /// 只绘制单元格边
+    #[default]
+    None,
+    /// 按高度配色表着色
+    Height,
+    /// 按生物群系着色
+    Biome,
+    /// 按板块编号着色
+    PlateId,
+}
+
+/// 填充着色的数据来源，按单元格索引对应
+#[derive(Debug, Clone, Default)]
+pub struct CellFillData {
+    pub heights: Vec<u8>,
+    pub biomes: Vec<Biome>,
+    pub plate_ids: Vec<u16>,
+}
+
+/// 单元格填充的 CPU 端状态
+///
+/// 切换模式或更新数据时重新生成逐单元格颜色（打包为非预乘 RGBA8），
+/// 颜色数量等于单元格数量，`FillMode::None` 时为空。
+#[derive(Debug, Clone, Default)]
+pub struct CellFill {
+    mode: FillMode,
+    cell_count: usize,
+    data: CellFillData,
+    colors: Vec<u32>,
+}
+
+impl CellFill {
+    pub fn mode(&self) -> FillMode {
+        self.mode
+    }
+
+    /// 逐单元格颜色
+    pub fn colors(&self) -> &[u32] {
+        &self.colors
+    }
+
+    pub fn set_mode(&mut self, mode: FillMode) {
+        self.mode = mode;
+        self.regenerate();
+    }
+
+    pub fn set_cell_count(&mut self, cell_count: usize) {
+        self.cell_count = cell_count;
+        self.regenerate();
+    }
+
+    pub fn set_data(&mut self, data: CellFillData) {
+        self.data = data;
+        self.regenerate();
+    }
+
+    fn regenerate(&mut self) {
+        let color_of = |i: usize| -> Color32 {
+            match self.mode {
+                FillMode::None => None,
+                FillMode::Height => self
+                    .data
+                    .heights
+                    .get(i)
+                    .map(|&h| HeightColorMap::default_map().color(h)),
+                FillMode::Biome => self.data.biomes.get(i).map(|b| b.color()),
+                FillMode::PlateId => self.data.plate_ids.get(i).map(|&id| plate_color(id)),
+            }
+            .unwrap_or(MISSING_FILL_COLOR)
+        };
+
+        self.colors = match self.mode {
+            FillMode::None => Vec::new(),
+            _ => (0..self.cell_count)
+                .map(|i| u32::from_le_bytes(color_of(i).to_srgba_unmultiplied()))
+                .collect(),
+        };
+    }
+}
+
+/// 板块编号对应的颜色：按黄金角旋转色相，相邻编号颜色差异明显
+pub fn plate_color(id: u16) -> Color32 {
+    let hue = (id as f32 * 0.618_034).fract();
+    Hsva::new(hue, 0.55, 0.85, 1.0).into()
+}
+
+/// 填充三角形顶点：位置与所属单元格
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct FillVertex {
+    x: f32,
+    y: f32,
+    cell: u32,
+}
+
 /// Voronoi 图渲染器
 ///
 /// 使用 `u32` 类型的索引，与 GPU 索引缓冲区兼容。
@@ -33,6 +154,16 @@ pub struct VoronoiRenderer {
     pub uniform_buffer: wgpu::Buffer,
     pub voronoi_pipeline: wgpu::RenderPipeline,
     pub bind_group: wgpu::BindGroup,
+    /// 单元格填充状态
+    fill: CellFill,
+    /// 填充三角形（扇形剖分，每3个顶点构成一个三角形）
+    fill_vertices: Vec<FillVertex>,
+    fill_vertices_capacity: usize,
+    fill_colors_capacity: usize,
+    fill_vertices_buffer: wgpu::Buffer,
+    fill_colors_buffer: wgpu::Buffer,
+    fill_pipeline: wgpu::RenderPipeline,
+    fill_bind_group: wgpu::BindGroup,
 }
 
 impl VoronoiRenderer {
@@ -133,6 +264,92 @@ impl VoronoiRenderer {
         let voronoi_pipeline =
             MapRenderer::create_voronoi_pipeline(device, &pipeline_layout, target_format);
 
+        // 填充管线：填充三角形、逐单元格颜色、Uniform
+        let fill_vertices_capacity = 1;
+        let fill_colors_capacity = 1;
+        let fill_vertices_buffer = Self::create_storage_buffer(
+            device,
+            "voronoi_fill_vertices_buffer",
+            std::mem::size_of::<FillVertex>() * fill_vertices_capacity,
+        );
+        let fill_colors_buffer = Self::create_storage_buffer(
+            device,
+            "voronoi_fill_colors_buffer",
+            std::mem::size_of::<u32>() * fill_colors_capacity,
+        );
+
+        let fill_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("voronoi_fill_bind_group_layout"),
+                entries: &[
+                    // 绑定填充三角形顶点
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: NonZeroU64::new(
+                                std::mem::size_of::<FillVertex>() as u64
+                            ),
+                        },
+                        count: None,
+                    },
+                    // 绑定逐单元格颜色
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: NonZeroU64::new(std::mem::size_of::<u32>() as u64),
+                        },
+                        count: None,
+                    },
+                    // 绑定Uniform缓冲区
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: NonZeroU64::new(
+                                std::mem::size_of::<CanvasUniforms>() as u64,
+                            ),
+                        },
+                        count: None,
+                    },
+                ],
+            });
+
+        let fill_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("voronoi_fill_bind_group"),
+            layout: &fill_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: fill_vertices_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: fill_colors_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let fill_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("voronoi_fill_pipeline_layout"),
+            bind_group_layouts: &[&fill_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let fill_pipeline =
+            MapRenderer::create_voronoi_fill_pipeline(device, &fill_pipeline_layout, target_format);
+
         Self {
             canvas_state_resource,
             vertices,
@@ -147,6 +364,14 @@ impl VoronoiRenderer {
             uniform_buffer,
             voronoi_pipeline,
             bind_group,
+            fill: CellFill::default(),
+            fill_vertices: Vec::new(),
+            fill_vertices_capacity,
+            fill_colors_capacity,
+            fill_vertices_buffer,
+            fill_colors_buffer,
+            fill_pipeline,
+            fill_bind_group,
         }
     }
 
@@ -174,6 +399,48 @@ impl VoronoiRenderer {
         self.edge_index = Some(edge_index);
     }
 
+    /// 设置单元格填充模式，重新生成逐单元格颜色
+    #[allow(dead_code)]
+    pub fn set_fill_mode(&mut self, mode: FillMode) {
+        self.fill.set_mode(mode);
+    }
+
+    /// 更新填充着色的数据来源
+    #[allow(dead_code)]
+    pub fn set_fill_data(&mut self, data: CellFillData) {
+        self.fill.set_data(data);
+    }
+
+    /// 更新单元格多边形，使用扇形剖分生成填充三角形
+    ///
+    /// `cells` 的顶点索引指向 `vertices`，需先调用 [`Self::update_vertices`]。
+    #[allow(dead_code)]
+    pub fn update_cells(&mut self, cells: &[VoronoiCell]) {
+        self.fill_vertices.clear();
+        for (cell_idx, cell) in cells.iter().enumerate() {
+            let polygon: Vec<Pos2> = cell
+                .vertex_indices
+                .iter()
+                .filter_map(|&i| self.vertices.get(i as usize).copied())
+                .collect();
+            if polygon.len() < 3 {
+                continue;
+            }
+
+            let vertex = |p: Pos2| FillVertex {
+                x: p.x,
+                y: p.y,
+                cell: cell_idx as u32,
+            };
+            for i in 1..polygon.len() - 1 {
+                self.fill_vertices.push(vertex(polygon[0]));
+                self.fill_vertices.push(vertex(polygon[i]));
+                self.fill_vertices.push(vertex(polygon[i + 1]));
+            }
+        }
+        self.fill.set_cell_count(cells.len());
+    }
+
     /// 确保空间索引已构建
     fn ensure_edge_index(&mut self) {
         if self.edge_index.is_none() && !self.vertices.is_empty() && !self.indices.is_empty() {
@@ -226,6 +493,15 @@ impl VoronoiRenderer {
         })
     }
 
+    fn create_storage_buffer(device: &wgpu::Device, label: &str, size: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: size.max(1) as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
     fn required_capacity(current: usize, required: usize) -> usize {
         if required <= current {
             return current.max(1);
@@ -285,6 +561,63 @@ impl VoronoiRenderer {
         }
     }
 
+    fn ensure_fill_capacity(&mut self, device: &wgpu::Device) {
+        let new_vertices_capacity =
+            Self::required_capacity(self.fill_vertices_capacity, self.fill_vertices.len());
+        let new_colors_capacity =
+            Self::required_capacity(self.fill_colors_capacity, self.fill.colors().len());
+
+        if new_vertices_capacity == self.fill_vertices_capacity
+            && new_colors_capacity == self.fill_colors_capacity
+        {
+            return;
+        }
+
+        if new_vertices_capacity != self.fill_vertices_capacity {
+            self.fill_vertices_buffer = Self::create_storage_buffer(
+                device,
+                "voronoi_fill_vertices_buffer",
+                std::mem::size_of::<FillVertex>() * new_vertices_capacity,
+            );
+            self.fill_vertices_capacity = new_vertices_capacity;
+        }
+
+        if new_colors_capacity != self.fill_colors_capacity {
+            self.fill_colors_buffer = Self::create_storage_buffer(
+                device,
+                "voronoi_fill_colors_buffer",
+                std::mem::size_of::<u32>() * new_colors_capacity,
+            );
+            self.fill_colors_capacity = new_colors_capacity;
+        }
+
+        let bind_group_layout = self.fill_pipeline.get_bind_group_layout(0);
+        self.fill_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("voronoi_fill_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.fill_vertices_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: self.fill_colors_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+    }
+
+    fn fill_visible(&self) -> bool {
+        self.fill.mode() != FillMode::None
+            && !self.fill_vertices.is_empty()
+            && !self.fill.colors().is_empty()
+    }
+
     pub fn upload_to_gpu(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
         let mut visible_indices: Vec<u32> = Vec::new();
 
@@ -304,7 +637,15 @@ impl VoronoiRenderer {
             });
 
             visible_indices = if let Some(ref edge_index) = self.edge_index {
-                edge_index.get_visible_indices(&self.vertices, &self.indices, view_rect)
+                // 缩小到单元格只有几个像素时跳过短边
+                let min_length =
+                    lod_min_edge_length(edge_index.average_edge_length(), self.uniforms.scale);
+                edge_index.get_visible_indices_lod(
+                    &self.vertices,
+                    &self.indices,
+                    view_rect,
+                    min_length,
+                )
             } else {
                 // 后备：如果没有空间索引，返回所有索引
                 self.indices.clone()
@@ -331,6 +672,20 @@ impl VoronoiRenderer {
             );
         }
 
+        if self.fill_visible() {
+            self.ensure_fill_capacity(device);
+            queue.write_buffer(
+                &self.fill_vertices_buffer,
+                0,
+                bytemuck::cast_slice(&self.fill_vertices),
+            );
+            queue.write_buffer(
+                &self.fill_colors_buffer,
+                0,
+                bytemuck::cast_slice(self.fill.colors()),
+            );
+        }
+
         queue.write_buffer(
             &self.uniform_buffer,
             0,
@@ -339,6 +694,13 @@ impl VoronoiRenderer {
     }
 
     pub fn render(&self, render_pass: &mut wgpu::RenderPass<'static>) {
+        // 先绘制填充，再在其上绘制单元格边
+        if self.fill_visible() {
+            render_pass.set_pipeline(&self.fill_pipeline);
+            render_pass.set_bind_group(0, &self.fill_bind_group, &[]);
+            render_pass.draw(0..self.fill_vertices.len() as u32, 0..1);
+        }
+
         if self.vertices.is_empty() || self.visible_indices_count == 0 {
             return;
         }
@@ -348,3 +710,51 @@ impl VoronoiRenderer {
         render_pass.draw(0..self.visible_indices_count as u32, 0..1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_switching_fill_mode_regenerates_colors() {
+        let mut fill = CellFill::default();
+        fill.set_cell_count(10);
+        fill.set_data(CellFillData {
+            heights: (0..10).map(|i| i * 25).collect(),
+            biomes: vec![Biome::Forest; 10],
+            plate_ids: (0..10).collect(),
+        });
+        assert!(fill.colors().is_empty());
+
+        fill.set_mode(FillMode::Height);
+        assert_eq!(fill.colors().len(), 10);
+        let by_height = fill.colors().to_vec();
+
+        fill.set_mode(FillMode::PlateId);
+        assert_eq!(fill.colors().len(), 10);
+        assert_ne!(fill.colors(), by_height.as_slice());
+
+        fill.set_mode(FillMode::Biome);
+        assert_eq!(fill.colors().len(), 10);
+        let forest = u32::from_le_bytes(Biome::Forest.color().to_srgba_unmultiplied());
+        assert!(fill.colors().iter().all(|&c| c == forest));
+
+        // 数据不足的单元格也有颜色
+        fill.set_cell_count(12);
+        assert_eq!(fill.colors().len(), 12);
+
+        fill.set_mode(FillMode::None);
+        assert!(fill.colors().is_empty());
+    }
+
+    #[test]
+    fn test_lod_only_at_low_zoom() {
+        // 平均边长 10：放大时不剔除
+        assert_eq!(lod_min_edge_length(10.0, 1.0), 0.0);
+        assert_eq!(lod_min_edge_length(10.0, 0.4), 0.0);
+
+        // 缩小到单元格不足几个像素时剔除屏幕上过短的边
+        let min_length = lod_min_edge_length(10.0, 0.1);
+        assert!((min_length * 0.1 - LOD_MIN_EDGE_PIXELS).abs() < 1e-5);
+    }
+}
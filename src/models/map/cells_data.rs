@@ -1,12 +1,15 @@
 #[derive(Debug, Clone)]
 pub struct CellsData {
     pub height: Vec<u8>,
+    /// 生物群系索引
+    pub biome: Vec<u8>,
 }
 
 impl CellsData {
     pub fn new(cells_count: usize) -> Self {
         Self {
             height: vec![0; cells_count],
+            biome: vec![0; cells_count],
         }
     }
 }
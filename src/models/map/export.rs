@@ -0,0 +1,441 @@
+// 地图栅格化与 PNG 导出
+
+use std::io;
+use std::path::Path;
+
+use egui::Pos2;
+
+use crate::terrain::{classify_land_sea, HeightColorMap, HydrologyGenerator};
+
+use super::world::World;
+
+/// 海岸线颜色
+const COASTLINE_COLOR: [u8; 3] = [24, 24, 24];
+/// 河流颜色
+const RIVER_COLOR: [u8; 3] = [40, 90, 200];
+/// 河流提取的流量阈值
+const RIVER_FLUX_THRESHOLD: u16 = 20;
+
+/// 导出时叠加的图层开关
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExportOverlays {
+    /// 山体阴影
+    pub hillshade: bool,
+    /// 海岸线
+    pub coastlines: bool,
+    /// 河流
+    pub rivers: bool,
+}
+
+impl Default for ExportOverlays {
+    fn default() -> Self {
+        Self {
+            hillshade: true,
+            coastlines: true,
+            rivers: true,
+        }
+    }
+}
+
+impl ExportOverlays {
+    /// 关闭所有叠加层，仅导出颜色图
+    pub fn none() -> Self {
+        Self {
+            hillshade: false,
+            coastlines: false,
+            rivers: false,
+        }
+    }
+}
+
+/// RGB 像素缓冲区
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RasterImage {
+    pub width: u32,
+    pub height: u32,
+    /// 按行存储的像素，长度为 width * height
+    pub pixels: Vec<[u8; 3]>,
+}
+
+impl RasterImage {
+    /// 创建纯色图像
+    pub fn new(width: u32, height: u32, fill: [u8; 3]) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![fill; width as usize * height as usize],
+        }
+    }
+
+    /// 读取像素
+    pub fn get(&self, x: u32, y: u32) -> [u8; 3] {
+        self.pixels[y as usize * self.width as usize + x as usize]
+    }
+
+    /// 写入像素，越界时忽略
+    pub fn set(&mut self, x: i32, y: i32, color: [u8; 3]) {
+        if x >= 0 && y >= 0 && (x as u32) < self.width && (y as u32) < self.height {
+            self.pixels[y as usize * self.width as usize + x as usize] = color;
+        }
+    }
+
+    /// 使用 Bresenham 算法绘制线段
+    pub fn draw_line(&mut self, from: Pos2, to: Pos2, color: [u8; 3]) {
+        let (mut x0, mut y0) = (from.x.floor() as i32, from.y.floor() as i32);
+        let (x1, y1) = (to.x.floor() as i32, to.y.floor() as i32);
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+
+        loop {
+            self.set(x0, y0, color);
+            if x0 == x1 && y0 == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x0 += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y0 += sy;
+            }
+        }
+    }
+
+    /// 编码为 PNG 文件字节，宽或高为 0 时返回错误
+    pub fn to_png(&self) -> io::Result<Vec<u8>> {
+        encode_png(self.width, self.height, &self.pixels)
+    }
+}
+
+impl World {
+    /// 将每个像素映射到最近的单元格
+    ///
+    /// 返回长度为 width * height 的单元格索引表，供颜色图和各叠加层共享。
+    pub fn rasterize_cells(&self) -> Vec<u32> {
+        let mut cells = Vec::with_capacity(self.width as usize * self.height as usize);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let pos = Pos2::new(x as f32 + 0.5, y as f32 + 0.5);
+                cells.push(
+                    self.point_index
+                        .find_nearest(&self.points, pos)
+                        .unwrap_or(0),
+                );
+            }
+        }
+        cells
+    }
+
+    /// 按高度着色的栅格图像
+    pub fn render_color_map(&self) -> RasterImage {
+        self.render_with_overlays(ExportOverlays::none())
+    }
+
+    /// 按高度着色并叠加指定图层
+    pub fn render_with_overlays(&self, overlays: ExportOverlays) -> RasterImage {
+        let mut image = RasterImage::new(self.width, self.height, [0, 0, 0]);
+        if self.points.is_empty() {
+            return image;
+        }
+
+        let cells = self.rasterize_cells();
+        let heights = &self.cells_data.height;
+        let color_map = HeightColorMap::for_sea_level(self.sea_level);
+
+        for (pixel, &cell) in image.pixels.iter_mut().zip(&cells) {
+            let color = color_map.color(heights[cell as usize]);
+            *pixel = [color.r(), color.g(), color.b()];
+        }
+
+        if overlays.hillshade {
+            self.apply_hillshade(&mut image, &cells);
+        }
+        if overlays.coastlines {
+            self.draw_coastlines(&mut image, &cells);
+        }
+        if overlays.rivers {
+            self.draw_rivers(&mut image);
+        }
+
+        image
+    }
+
+    /// 导出按高度着色的 PNG
+    pub fn export_png(&self) -> io::Result<Vec<u8>> {
+        self.render_color_map().to_png()
+    }
+
+    /// 导出合成了山体阴影、海岸线和河流的 PNG
+    pub fn export_png_with_overlays(&self, overlays: ExportOverlays) -> io::Result<Vec<u8>> {
+        self.render_with_overlays(overlays).to_png()
+    }
+
+    /// 导出 16 位灰度高度图 PNG
+    ///
+    /// 在 `width` × `height` 的规则网格上对地图取样（每个像素取最近的单元格），
+    /// 高度线性映射为灰度：`gray = height × 257`，即 0 → 0、255 → 65535。
+    /// 海平面对应的灰度为 `sea_level × 257`，外部工具可据此还原海岸线。
+    /// 宽或高为 0 时返回 [`io::ErrorKind::InvalidInput`]。
+    pub fn export_heightmap_png(&self, width: u32, height: u32, path: &Path) -> io::Result<()> {
+        check_dimensions(width, height)?;
+        let scale_x = self.width as f32 / width as f32;
+        let scale_y = self.height as f32 / height as f32;
+
+        let mut grays = Vec::with_capacity(width as usize * height as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let pos = Pos2::new((x as f32 + 0.5) * scale_x, (y as f32 + 0.5) * scale_y);
+                let gray = self
+                    .point_index
+                    .find_nearest(&self.points, pos)
+                    .map_or(0, |cell| self.cells_data.height[cell as usize] as u16 * 257);
+                grays.push(gray);
+            }
+        }
+
+        std::fs::write(path, encode_gray16_png(width, height, &grays)?)
+    }
+
+    /// 以西北方向光源对陆地像素做明暗调整
+    fn apply_hillshade(&self, image: &mut RasterImage, cells: &[u32]) {
+        let heights = &self.cells_data.height;
+        let (w, h) = (self.width as usize, self.height as usize);
+        let height_at = |x: usize, y: usize| heights[cells[y * w + x] as usize] as f32;
+
+        for y in 0..h {
+            for x in 0..w {
+                let center = heights[cells[y * w + x] as usize];
+                if center < self.sea_level {
+                    continue;
+                }
+
+                let dx = height_at((x + 1).min(w - 1), y) - height_at(x.saturating_sub(1), y);
+                let dy = height_at(x, (y + 1).min(h - 1)) - height_at(x, y.saturating_sub(1));
+
+                // 光源来自左上方：朝向光源的坡面变亮，背向的变暗
+                let shade = (1.0 - (dx + dy) * 0.02).clamp(0.5, 1.3);
+                let pixel = &mut image.pixels[y * w + x];
+                for channel in pixel.iter_mut() {
+                    *channel = (*channel as f32 * shade).min(255.0) as u8;
+                }
+            }
+        }
+    }
+
+    /// 在陆地与水体交界的像素上描绘海岸线
+    fn draw_coastlines(&self, image: &mut RasterImage, cells: &[u32]) {
+        let heights = &self.cells_data.height;
+        let (w, h) = (self.width as usize, self.height as usize);
+        let is_land = |x: usize, y: usize| heights[cells[y * w + x] as usize] >= self.sea_level;
+
+        for y in 0..h {
+            for x in 0..w {
+                if !is_land(x, y) {
+                    continue;
+                }
+                let touches_water = (x > 0 && !is_land(x - 1, y))
+                    || (x + 1 < w && !is_land(x + 1, y))
+                    || (y > 0 && !is_land(x, y - 1))
+                    || (y + 1 < h && !is_land(x, y + 1));
+                if touches_water {
+                    image.pixels[y * w + x] = COASTLINE_COLOR;
+                }
+            }
+        }
+    }
+
+    /// 沿水流方向连接河流单元格中心
+    fn draw_rivers(&self, image: &mut RasterImage) {
+        let heights = &self.cells_data.height;
+        let is_land = classify_land_sea(heights, self.sea_level);
+        let hydrology = HydrologyGenerator::new();
+        let flow = hydrology.compute_flow_direction(heights, &is_land, &self.neighbors);
+        let flux = hydrology.compute_flux(heights, &is_land, &flow, None);
+        let rivers = hydrology.extract_rivers(&flux, &flow, &is_land, RIVER_FLUX_THRESHOLD);
+
+        for river in &rivers {
+            for &cell in &river.cells {
+                if let Some(downstream) = flow[cell as usize] {
+                    image.draw_line(
+                        self.points[cell as usize],
+                        self.points[downstream as usize],
+                        RIVER_COLOR,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// 将 RGB 像素编码为 PNG（行优先，长度为 width × height）
+///
+/// 宽或高为 0 时返回 [`io::ErrorKind::InvalidInput`]。
+pub fn encode_png(width: u32, height: u32, pixels: &[[u8; 3]]) -> io::Result<Vec<u8>> {
+    let data: &[u8] = bytemuck::cast_slice(pixels);
+    encode_png_data(
+        width,
+        height,
+        png::ColorType::Rgb,
+        png::BitDepth::Eight,
+        data,
+    )
+}
+
+/// 将 16 位灰度像素编码为 PNG（行优先，长度为 width × height）
+///
+/// 宽或高为 0 时返回 [`io::ErrorKind::InvalidInput`]。
+pub fn encode_gray16_png(width: u32, height: u32, pixels: &[u16]) -> io::Result<Vec<u8>> {
+    let data: Vec<u8> = pixels.iter().flat_map(|gray| gray.to_be_bytes()).collect();
+    encode_png_data(
+        width,
+        height,
+        png::ColorType::Grayscale,
+        png::BitDepth::Sixteen,
+        &data,
+    )
+}
+
+/// 以给定颜色类型和位深编码按行存储的像素数据
+fn encode_png_data(
+    width: u32,
+    height: u32,
+    color: png::ColorType,
+    depth: png::BitDepth,
+    data: &[u8],
+) -> io::Result<Vec<u8>> {
+    check_dimensions(width, height)?;
+
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, width, height);
+    encoder.set_color(color);
+    encoder.set_depth(depth);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(data)?;
+    writer.finish()?;
+    Ok(out)
+}
+
+/// PNG 不允许宽或高为 0
+fn check_dimensions(width: u32, height: u32) -> io::Result<()> {
+    if width == 0 || height == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("image size must be non-zero, got {}x{}", width, height),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 创建一个中间为岛屿的小型世界
+    fn create_island_world() -> World {
+        let spacing = 4.0;
+        let mut points = Vec::new();
+        for y in 0..16 {
+            for x in 0..16 {
+                let offset = if y % 2 == 0 { 0.0 } else { spacing * 0.5 };
+                points.push(Pos2::new(
+                    x as f32 * spacing + offset + 2.0,
+                    y as f32 * spacing + 2.0,
+                ));
+            }
+        }
+        let mut world = World::new(66, 66, points);
+
+        let center = Pos2::new(33.0, 33.0);
+        for (i, p) in world.points.iter().enumerate() {
+            let d = (*p - center).length();
+            world.cells_data.height[i] = (120.0 - d * 5.0).clamp(5.0, 255.0) as u8;
+        }
+        world
+    }
+
+    #[test]
+    fn test_png_round_trip() {
+        let pixels = [[255, 0, 0], [0, 255, 0], [0, 0, 255], [9, 9, 9]];
+        let png = encode_png(2, 2, &pixels).unwrap();
+
+        let mut reader = png::Decoder::new(std::io::Cursor::new(png))
+            .read_info()
+            .unwrap();
+        let mut buf = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        assert_eq!((info.width, info.height), (2, 2));
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        assert_eq!(&buf[..info.buffer_size()], pixels.as_flattened());
+    }
+
+    #[test]
+    fn test_zero_sized_png_is_rejected() {
+        let err = encode_png(0, 4, &[]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        let world = create_island_world();
+        let path = std::env::temp_dir().join("sekai_zero_heightmap.png");
+        let err = world.export_heightmap_png(16, 0, &path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_overlays_disabled_matches_color_map() {
+        let world = create_island_world();
+        assert_eq!(
+            world
+                .export_png_with_overlays(ExportOverlays::none())
+                .unwrap(),
+            world.export_png().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_coastline_overlay_changes_boundary_pixels() {
+        let world = create_island_world();
+        let plain = world.render_color_map();
+        let coast = world.render_with_overlays(ExportOverlays {
+            coastlines: true,
+            ..ExportOverlays::none()
+        });
+
+        let cells = world.rasterize_cells();
+        let heights = &world.cells_data.height;
+        let mut changed = 0;
+        for (i, (a, b)) in plain.pixels.iter().zip(&coast.pixels).enumerate() {
+            if a != b {
+                // 只有靠近水体的陆地像素会被修改
+                assert!(heights[cells[i] as usize] >= world.sea_level);
+                assert_eq!(*b, COASTLINE_COLOR);
+                changed += 1;
+            }
+        }
+        assert!(
+            changed > 0,
+            "coastline overlay should modify boundary pixels"
+        );
+    }
+
+    #[test]
+    fn test_export_heightmap_png_header() {
+        let world = create_island_world();
+        let path = std::env::temp_dir().join(format!("sekai_heightmap_{}.png", std::process::id()));
+
+        world.export_heightmap_png(40, 30, &path).unwrap();
+        let png = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(&png[..8], &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 40);
+        assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 30);
+        assert_eq!(png[24], 16, "bit depth");
+        assert_eq!(png[25], 0, "grayscale");
+    }
+}
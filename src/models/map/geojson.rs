@@ -0,0 +1,119 @@
+// GeoJSON 导出
+//
+// 将每个单元格的 Voronoi 多边形（裁剪到地图范围）写为 GeoJSON Feature，
+// 坐标直接使用地图坐标系，便于在 QGIS 等 GIS 工具中查看。
+
+use std::fmt::Write as _;
+use std::io;
+use std::path::Path;
+
+use egui::Pos2;
+
+use super::world::World;
+
+impl World {
+    /// 导出 GeoJSON FeatureCollection 文件
+    ///
+    /// 每个单元格对应一个 Polygon Feature（顺序与单元格索引一致），属性包含
+    /// `height`、`is_ocean` 和 `plate_id`。多边形退化（裁剪后少于 3 个顶点）
+    /// 的单元格几何为 `null`，以保持 Feature 数量与单元格数量一致。
+    pub fn export_geojson(&self, path: &Path) -> io::Result<()> {
+        std::fs::write(path, self.to_geojson())
+    }
+
+    /// 生成 GeoJSON FeatureCollection 文本
+    pub fn to_geojson(&self) -> String {
+        let mesh = &self.mesh;
+        let bounds = self.bounds();
+
+        let mut out = String::from(r#"{"type":"FeatureCollection","features":["#);
+        for i in 0..self.cell_count() {
+            if i > 0 {
+                out.push(',');
+            }
+            let height = self.cells_data.height[i];
+            let polygon = mesh.clipped_voronoi_cell(i as u32, bounds);
+
+            out.push_str(r#"{"type":"Feature","geometry":"#);
+            write_polygon(&mut out, &polygon);
+            let _ = write!(
+                out,
+                r#","properties":{{"cell":{},"height":{},"is_ocean":{},"plate_id":{}}}}}"#,
+                i,
+                height,
+                height < self.sea_level,
+                self.plate_ids.get(i).copied().unwrap_or(0),
+            );
+        }
+        out.push_str("]}");
+        out
+    }
+}
+
+/// 写入闭合的 Polygon 几何（首尾顶点相同），顶点不足时写入 null
+fn write_polygon(out: &mut String, vertices: &[Pos2]) {
+    if vertices.len() < 3 {
+        out.push_str("null");
+        return;
+    }
+
+    out.push_str(r#"{"type":"Polygon","coordinates":[["#);
+    for (k, p) in vertices.iter().chain(vertices.first()).enumerate() {
+        if k > 0 {
+            out.push(',');
+        }
+        let _ = write!(out, "[{},{}]", p.x, p.y);
+    }
+    out.push_str("]]}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_geojson_round_trip() {
+        let points = (0..36)
+            .map(|i| {
+                let offset = if (i / 6) % 2 == 0 { 0.0 } else { 5.0 };
+                Pos2::new(
+                    (i % 6) as f32 * 10.0 + offset + 5.0,
+                    (i / 6) as f32 * 10.0 + 5.0,
+                )
+            })
+            .collect();
+        let mut world = World::new(70, 60, points);
+        for (i, h) in world.cells_data.height.iter_mut().enumerate() {
+            *h = if i % 3 == 0 { 5 } else { 90 };
+        }
+        world.plate_ids = (0..36).map(|i| (i % 4) as u16).collect();
+
+        let path = std::env::temp_dir().join(format!("sekai_cells_{}.geojson", std::process::id()));
+        world.export_geojson(&path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(json["type"], "FeatureCollection");
+        let features = json["features"].as_array().unwrap();
+        assert_eq!(features.len(), world.cell_count());
+
+        for (i, feature) in features.iter().enumerate() {
+            let props = &feature["properties"];
+            assert_eq!(props["height"], world.cells_data.height[i]);
+            assert_eq!(
+                props["is_ocean"],
+                world.cells_data.height[i] < world.sea_level
+            );
+            assert_eq!(props["plate_id"], world.plate_ids[i]);
+
+            let ring = feature["geometry"]["coordinates"][0].as_array().unwrap();
+            assert!(ring.len() >= 4);
+            assert_eq!(ring.first(), ring.last());
+            for coord in ring {
+                let (x, y) = (coord[0].as_f64().unwrap(), coord[1].as_f64().unwrap());
+                assert!((-1e-3..=70.001).contains(&x) && (-1e-3..=60.001).contains(&y));
+            }
+        }
+    }
+}
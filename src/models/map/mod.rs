@@ -1,4 +1,12 @@
 pub mod cells_data;
+pub mod export;
 pub mod feature;
+pub mod geojson;
 pub mod grid;
+pub mod obj;
+pub mod params;
+pub mod persist;
+pub mod sampling;
 pub mod system;
+pub mod vector;
+pub mod world;
@@ -0,0 +1,109 @@
+// Wavefront OBJ 地形网格导出
+
+use std::fmt::Write as _;
+use std::io;
+use std::path::Path;
+
+use super::world::World;
+
+impl World {
+    /// 导出地形表面为 Wavefront OBJ 文件
+    ///
+    /// 坐标约定（Y 轴向上的右手坐标系）：
+    /// - 每个单元格中心点输出一个顶点 `(x, height * z_scale, y)`，顺序与单元格索引一致；
+    ///   地图的 x 对应 OBJ 的 X，地图的 y（向下）对应 OBJ 的 Z，高度对应 OBJ 的 Y。
+    /// - 每个 Delaunay 三角形输出一个面 `f a b c`（索引从 1 开始），
+    ///   顶点顺序保证面法线朝 +Y（从上方看为逆时针）。
+    pub fn export_obj(&self, path: &Path, z_scale: f32) -> io::Result<()> {
+        std::fs::write(path, self.to_obj(z_scale))
+    }
+
+    /// 生成 OBJ 文本，坐标约定见 [`Self::export_obj`]
+    pub fn to_obj(&self, z_scale: f32) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "# sekai terrain: {} vertices, {} faces",
+            self.cell_count(),
+            self.triangles.len() / 3
+        );
+
+        for (p, &h) in self.points.iter().zip(&self.cells_data.height) {
+            let _ = writeln!(out, "v {} {} {}", p.x, h as f32 * z_scale, p.y);
+        }
+
+        for tri in self.triangles.chunks_exact(3) {
+            let [a, b, c] = [tri[0] as usize, tri[1] as usize, tri[2] as usize];
+            let (pa, pb, pc) = (self.points[a], self.points[b], self.points[c]);
+            // 地图坐标中顺时针的三角形在 (x, h, y) 空间中法线朝上
+            let cross = (pb - pa).x * (pc - pa).y - (pb - pa).y * (pc - pa).x;
+            let (b, c) = if cross < 0.0 { (b, c) } else { (c, b) };
+            let _ = writeln!(out, "f {} {} {}", a + 1, b + 1, c + 1);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Pos2;
+
+    use super::*;
+    use crate::delaunay;
+
+    #[test]
+    fn test_export_obj_counts() {
+        let points: Vec<Pos2> = (0..30)
+            .map(|i| {
+                let offset = if (i / 6) % 2 == 0 { 0.0 } else { 5.0 };
+                Pos2::new(
+                    (i % 6) as f32 * 10.0 + offset + 5.0,
+                    (i / 6) as f32 * 10.0 + 5.0,
+                )
+            })
+            .collect();
+        let mut world = World::new(70, 50, points.clone());
+        for (i, h) in world.cells_data.height.iter_mut().enumerate() {
+            *h = (i * 7 % 256) as u8;
+        }
+
+        let path = std::env::temp_dir().join(format!("sekai_terrain_{}.obj", std::process::id()));
+        world.export_obj(&path, 0.5).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let lines = |prefix: &str| -> Vec<Vec<f32>> {
+            text.lines()
+                .filter_map(|l| l.strip_prefix(prefix))
+                .map(|rest| {
+                    rest.split_whitespace()
+                        .map(|v| v.parse().unwrap())
+                        .collect()
+                })
+                .collect()
+        };
+        let vertices = lines("v ");
+        let faces = lines("f ");
+        assert_eq!(vertices.len(), points.len());
+        assert_eq!(
+            faces.len(),
+            delaunay::triangulate_mesh(points).triangle_count()
+        );
+
+        assert_eq!(
+            vertices[3],
+            vec![world.points[3].x, 21.0 * 0.5, world.points[3].y]
+        );
+
+        // 所有面的法线朝 +Y
+        for face in &faces {
+            let v = |k: usize| &vertices[face[k] as usize - 1];
+            let (e1, e2) = (
+                [v(1)[0] - v(0)[0], v(1)[2] - v(0)[2]],
+                [v(2)[0] - v(0)[0], v(2)[2] - v(0)[2]],
+            );
+            let normal_y = e1[1] * e2[0] - e1[0] * e2[1];
+            assert!(normal_y > 0.0, "face {:?}", face);
+        }
+    }
+}
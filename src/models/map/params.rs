@@ -0,0 +1,328 @@
+// 地图生成参数
+
+use crate::terrain::Topology;
+
+/// 地图生成参数
+///
+/// 描述一张地图所需的全部输入，相同参数总是生成相同的地图。
+/// 推荐通过 [`GenerationParameters::builder`] 构造，以便在生成前校验取值范围。
+/// 可通过 [`GenerationParameters::to_json`] / [`GenerationParameters::from_json`]
+/// 以 JSON 形式分享，缺省字段取默认值，未知字段会被拒绝。
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GenerationParameters {
+    /// 随机种子（点集和地形共用）
+    pub seed: u64,
+    /// 地图宽度
+    pub width: u32,
+    /// 地图高度
+    pub height: u32,
+    /// 单元格数量
+    pub num_points: usize,
+    /// 地形模板名称
+    pub template: String,
+    /// 地图拓扑
+    pub topology: Topology,
+    /// 目标海洋比例（0.0-1.0），None 表示使用模板建议值
+    pub ocean_ratio: Option<f32>,
+    /// 板块数量，None 表示使用模板建议值
+    pub num_plates: Option<usize>,
+    /// 初始点的分布方式
+    pub point_distribution: PointDistribution,
+    /// 预览模式：点数降为 `num_points` 的 1/8，跳过侵蚀和细节噪声并减少板块数量，
+    /// 用于拖动参数时快速预览；关闭后同一种子仍生成完整质量的地图
+    pub preview: bool,
+}
+
+/// 预览模式的点数缩减倍数
+const PREVIEW_POINT_DIVISOR: usize = 8;
+/// 预览模式的最少点数（`num_points` 更小时不缩减）
+const PREVIEW_MIN_POINTS: usize = 64;
+
+/// 初始点的分布方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PointDistribution {
+    /// 均匀随机点（单元格大小不一）
+    #[default]
+    Random,
+    /// 抖动网格，见 [`jittered_grid`](super::sampling::jittered_grid)
+    JitteredGrid,
+    /// 泊松圆盘采样，见 [`poisson_disk`](super::sampling::poisson_disk)；点数只近似等于 `num_points`
+    PoissonDisk,
+}
+
+impl Default for GenerationParameters {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            width: 2000,
+            height: 1000,
+            num_points: 10000,
+            template: "earth-like".to_string(),
+            topology: Topology::Plane,
+            ocean_ratio: None,
+            num_plates: None,
+            point_distribution: PointDistribution::Random,
+            preview: false,
+        }
+    }
+}
+
+impl GenerationParameters {
+    /// 从默认参数开始构造
+    pub fn builder() -> GenerationParametersBuilder {
+        GenerationParametersBuilder {
+            params: Self::default(),
+        }
+    }
+
+    /// 实际生成的点数，预览模式下按 [`PREVIEW_POINT_DIVISOR`] 缩减
+    pub fn point_count(&self) -> usize {
+        if self.preview {
+            (self.num_points / PREVIEW_POINT_DIVISOR).max(PREVIEW_MIN_POINTS.min(self.num_points))
+        } else {
+            self.num_points
+        }
+    }
+
+    /// 序列化为 JSON（枚举使用稳定的 snake_case 字符串标签）
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("GenerationParameters is always serializable")
+    }
+
+    /// 从 JSON 解析并校验参数
+    pub fn from_json(json: &str) -> Result<Self, ParamError> {
+        let params: Self =
+            serde_json::from_str(json).map_err(|e| ParamError::InvalidJson(e.to_string()))?;
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// 校验参数取值范围
+    pub fn validate(&self) -> Result<(), ParamError> {
+        if self.num_points <= 3 {
+            return Err(ParamError::TooFewPoints(self.num_points));
+        }
+        if self.width == 0 || self.height == 0 {
+            return Err(ParamError::EmptyMap {
+                width: self.width,
+                height: self.height,
+            });
+        }
+        if self.template.trim().is_empty() {
+            return Err(ParamError::EmptyTemplate);
+        }
+        if let Some(ratio) = self.ocean_ratio {
+            if !(0.0..=1.0).contains(&ratio) {
+                return Err(ParamError::OceanRatioOutOfRange(ratio));
+            }
+        }
+        if self.num_plates == Some(0) {
+            return Err(ParamError::NoPlates);
+        }
+        Ok(())
+    }
+}
+
+/// 生成参数校验错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamError {
+    /// 单元格数量不足以三角剖分（需要多于 3 个）
+    TooFewPoints(usize),
+    /// 地图宽或高为 0
+    EmptyMap { width: u32, height: u32 },
+    /// 模板名称为空
+    EmptyTemplate,
+    /// 海洋比例不在 0.0-1.0 范围内
+    OceanRatioOutOfRange(f32),
+    /// 板块数量为 0
+    NoPlates,
+    /// JSON 格式错误、字段类型不符或包含未知字段
+    InvalidJson(String),
+}
+
+impl std::fmt::Display for ParamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooFewPoints(n) => write!(f, "num_points must be greater than 3, got {}", n),
+            Self::EmptyMap { width, height } => {
+                write!(f, "map size must be non-zero, got {}x{}", width, height)
+            }
+            Self::EmptyTemplate => write!(f, "template name is empty"),
+            Self::OceanRatioOutOfRange(ratio) => {
+                write!(f, "ocean_ratio must be in 0..=1, got {}", ratio)
+            }
+            Self::NoPlates => write!(f, "num_plates must be at least 1"),
+            Self::InvalidJson(message) => write!(f, "invalid parameters JSON: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ParamError {}
+
+/// [`GenerationParameters`] 构造器
+#[derive(Debug, Clone)]
+pub struct GenerationParametersBuilder {
+    params: GenerationParameters,
+}
+
+impl GenerationParametersBuilder {
+    /// 设置随机种子
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.params.seed = seed;
+        self
+    }
+
+    /// 设置单元格数量
+    pub fn num_points(mut self, num_points: usize) -> Self {
+        self.params.num_points = num_points;
+        self
+    }
+
+    /// 设置地形模板名称
+    pub fn template(mut self, template: impl Into<String>) -> Self {
+        self.params.template = template.into();
+        self
+    }
+
+    /// 设置目标海洋比例
+    pub fn ocean_ratio(mut self, ocean_ratio: f32) -> Self {
+        self.params.ocean_ratio = Some(ocean_ratio);
+        self
+    }
+
+    /// 设置板块数量
+    pub fn num_plates(mut self, num_plates: usize) -> Self {
+        self.params.num_plates = Some(num_plates);
+        self
+    }
+
+    /// 设置地图尺寸
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.params.width = width;
+        self.params.height = height;
+        self
+    }
+
+    /// 设置地图拓扑
+    pub fn topology(mut self, topology: Topology) -> Self {
+        self.params.topology = topology;
+        self
+    }
+
+    /// 设置初始点的分布方式
+    pub fn point_distribution(mut self, point_distribution: PointDistribution) -> Self {
+        self.params.point_distribution = point_distribution;
+        self
+    }
+
+    /// 设置是否为预览模式
+    pub fn preview(mut self, preview: bool) -> Self {
+        self.params.preview = preview;
+        self
+    }
+
+    /// 校验并生成参数
+    pub fn build(self) -> Result<GenerationParameters, ParamError> {
+        self.params.validate()?;
+        Ok(self.params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_valid() {
+        let params = GenerationParameters::builder()
+            .seed(42)
+            .num_points(500)
+            .template("archipelago")
+            .ocean_ratio(0.6)
+            .num_plates(7)
+            .build()
+            .unwrap();
+
+        assert_eq!(params.seed, 42);
+        assert_eq!(params.num_points, 500);
+        assert_eq!(params.template, "archipelago");
+        assert_eq!(params.ocean_ratio, Some(0.6));
+        assert_eq!(params.num_plates, Some(7));
+        assert_eq!(params.topology, Topology::Plane);
+    }
+
+    #[test]
+    fn test_builder_validation_errors() {
+        let build = |builder: GenerationParametersBuilder| builder.build().unwrap_err();
+
+        assert_eq!(
+            build(GenerationParameters::builder().num_points(3)),
+            ParamError::TooFewPoints(3)
+        );
+        assert_eq!(
+            build(GenerationParameters::builder().size(0, 100)),
+            ParamError::EmptyMap {
+                width: 0,
+                height: 100
+            }
+        );
+        assert_eq!(
+            build(GenerationParameters::builder().template("  ")),
+            ParamError::EmptyTemplate
+        );
+        assert_eq!(
+            build(GenerationParameters::builder().ocean_ratio(1.5)),
+            ParamError::OceanRatioOutOfRange(1.5)
+        );
+        assert_eq!(
+            build(GenerationParameters::builder().ocean_ratio(-0.1)),
+            ParamError::OceanRatioOutOfRange(-0.1)
+        );
+        assert_eq!(
+            build(GenerationParameters::builder().num_plates(0)),
+            ParamError::NoPlates
+        );
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let params = GenerationParameters::builder()
+            .seed(1234)
+            .size(800, 400)
+            .num_points(3000)
+            .template("archipelago")
+            .ocean_ratio(0.65)
+            .num_plates(9)
+            .topology(Topology::CylinderX)
+            .point_distribution(PointDistribution::PoissonDisk)
+            .preview(true)
+            .build()
+            .unwrap();
+
+        let json = params.to_json();
+        assert!(json.contains("\"topology\": \"cylinder_x\""), "{}", json);
+        assert!(json.contains("\"point_distribution\": \"poisson_disk\""));
+        assert_eq!(GenerationParameters::from_json(&json).unwrap(), params);
+
+        // 缺省字段取默认值
+        let partial = GenerationParameters::from_json(r#"{"seed": 7}"#).unwrap();
+        assert_eq!(partial.seed, 7);
+        assert_eq!(partial.template, GenerationParameters::default().template);
+
+        // 未知字段、未知枚举标签和非法取值都会被拒绝
+        assert!(matches!(
+            GenerationParameters::from_json(r#"{"seed": 7, "sead": 8}"#),
+            Err(ParamError::InvalidJson(_))
+        ));
+        assert!(matches!(
+            GenerationParameters::from_json(r#"{"topology": "torus"}"#),
+            Err(ParamError::InvalidJson(_))
+        ));
+        assert_eq!(
+            GenerationParameters::from_json(r#"{"num_plates": 0}"#),
+            Err(ParamError::NoPlates)
+        );
+    }
+}
@@ -0,0 +1,281 @@
+// 世界的二进制存档格式
+//
+// 布局（全部为小端序）：
+// - 魔数 `SKWD`，格式版本 u32
+// - 地图宽高 u32 × 2，拓扑 u8，海平面 u8（版本 2 起）
+// - 单元格数量 n u32，点坐标 n × (f32, f32)
+// - 高度 n × u8，生物群系 n × u8，板块 ID n × u16
+// - 三角形索引数量 u32 及索引 u32
+// - 每个单元格的邻居数量 u32 及邻居索引 u32
+//
+// 布局完全由世界内容决定，相同的世界总是得到相同的字节。
+
+use egui::{Pos2, Rect};
+
+use crate::delaunay::DelaunayMesh;
+use crate::spatial::GridIndex;
+use crate::terrain::{Topology, SEA_LEVEL};
+
+use super::cells_data::CellsData;
+use super::world::{HeightHistory, World, WorldError};
+
+/// 存档魔数
+const MAGIC: &[u8; 4] = b"SKWD";
+/// 当前存档格式版本
+const FORMAT_VERSION: u32 = 2;
+/// 不含海平面的旧版本，加载时使用默认海平面
+const FORMAT_VERSION_V1: u32 = 1;
+
+impl World {
+    /// 将世界序列化为紧凑的二进制格式
+    ///
+    /// 保存点集、三角剖分、邻接关系、海平面、高度、生物群系和板块 ID。
+    /// 调试用的板块灰度场不会保存。
+    pub fn save_to_bytes(&self) -> Vec<u8> {
+        let n = self.points.len();
+        let neighbor_total: usize = self.neighbors.iter().map(Vec::len).sum();
+        let mut out =
+            Vec::with_capacity(32 + n * 16 + self.triangles.len() * 4 + neighbor_total * 4);
+
+        out.extend_from_slice(MAGIC);
+        put_u32(&mut out, FORMAT_VERSION);
+        put_u32(&mut out, self.width);
+        put_u32(&mut out, self.height);
+        out.push(topology_tag(self.topology));
+        out.push(self.sea_level);
+
+        put_u32(&mut out, n as u32);
+        for p in &self.points {
+            out.extend_from_slice(&p.x.to_le_bytes());
+            out.extend_from_slice(&p.y.to_le_bytes());
+        }
+        out.extend_from_slice(&self.cells_data.height);
+        out.extend_from_slice(&self.cells_data.biome);
+        for &id in &self.plate_ids {
+            out.extend_from_slice(&id.to_le_bytes());
+        }
+
+        put_u32(&mut out, self.triangles.len() as u32);
+        for &v in &self.triangles {
+            put_u32(&mut out, v);
+        }
+        for list in &self.neighbors {
+            put_u32(&mut out, list.len() as u32);
+            for &v in list {
+                put_u32(&mut out, v);
+            }
+        }
+
+        out
+    }
+
+    /// 从 [`World::save_to_bytes`] 生成的数据恢复世界
+    pub fn load_from_bytes(bytes: &[u8]) -> Result<World, WorldError> {
+        let mut reader = Reader { bytes, pos: 0 };
+
+        if reader.take(4)? != MAGIC {
+            return Err(WorldError::InvalidMagic);
+        }
+        let version = reader.u32()?;
+        if version != FORMAT_VERSION && version != FORMAT_VERSION_V1 {
+            return Err(WorldError::UnsupportedVersion(version));
+        }
+        let width = reader.u32()?;
+        let height = reader.u32()?;
+        let topology = match reader.take(1)?[0] {
+            0 => Topology::Plane,
+            1 => Topology::CylinderX,
+            2 => Topology::Sphere,
+            tag => return Err(WorldError::Corrupt(format!("unknown topology {}", tag))),
+        };
+        let sea_level = if version == FORMAT_VERSION_V1 {
+            SEA_LEVEL
+        } else {
+            reader.take(1)?[0]
+        };
+
+        let n = reader.u32()? as usize;
+        let points = (0..n)
+            .map(|_| Ok(Pos2::new(reader.f32()?, reader.f32()?)))
+            .collect::<Result<Vec<_>, WorldError>>()?;
+        let heights = reader.take(n)?.to_vec();
+        let biomes = reader.take(n)?.to_vec();
+        let plate_ids = (0..n)
+            .map(|_| reader.u16())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let triangle_len = reader.u32()? as usize;
+        let triangles = (0..triangle_len)
+            .map(|_| reader.index(n))
+            .collect::<Result<Vec<_>, _>>()?;
+        let neighbors = (0..n)
+            .map(|_| {
+                let count = reader.u32()? as usize;
+                (0..count).map(|_| reader.index(n)).collect()
+            })
+            .collect::<Result<Vec<Vec<u32>>, _>>()?;
+
+        if reader.pos != bytes.len() {
+            return Err(WorldError::Corrupt(format!(
+                "{} trailing bytes",
+                bytes.len() - reader.pos
+            )));
+        }
+
+        let bounds = Rect::from_min_max(Pos2::ZERO, Pos2::new(width as f32, height as f32));
+        let point_index = GridIndex::build_auto(&points, bounds);
+        let mesh = DelaunayMesh::from_triangles(points.clone(), &triangles);
+        let mut cells_data = CellsData::new(n);
+        cells_data.height = heights;
+        cells_data.biome = biomes;
+
+        Ok(World {
+            width,
+            height,
+            points,
+            triangles,
+            neighbors,
+            cells_data,
+            plate_ids,
+            plates: Vec::new(),
+            plate_field: None,
+            point_index,
+            mesh,
+            topology,
+            sea_level,
+            history: HeightHistory::default(),
+        })
+    }
+}
+
+fn topology_tag(topology: Topology) -> u8 {
+    match topology {
+        Topology::Plane => 0,
+        Topology::CylinderX => 1,
+        Topology::Sphere => 2,
+    }
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+/// 带边界检查的小端序读取器
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], WorldError> {
+        let end = self.pos.checked_add(len).ok_or(WorldError::Truncated)?;
+        let slice = self.bytes.get(self.pos..end).ok_or(WorldError::Truncated)?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u16(&mut self) -> Result<u16, WorldError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, WorldError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32, WorldError> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    /// 读取单元格索引并检查范围
+    fn index(&mut self, cell_count: usize) -> Result<u32, WorldError> {
+        let value = self.u32()?;
+        if value as usize >= cell_count {
+            return Err(WorldError::Corrupt(format!(
+                "index {} out of range (cell count {})",
+                value, cell_count
+            )));
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::TerrainConfig;
+
+    #[test]
+    fn test_save_load_round_trip() {
+        let mut points = Vec::new();
+        for y in 0..16 {
+            for x in 0..16 {
+                let offset = if y % 2 == 0 { 0.0 } else { 3.0 };
+                points.push(Pos2::new(
+                    x as f32 * 6.0 + offset + 2.0,
+                    y as f32 * 6.0 + 2.0,
+                ));
+            }
+        }
+        let mut world = World::new(100, 100, points);
+        let mut config = TerrainConfig::with_template("archipelago");
+        config.sea_level = 60;
+        world.generate_terrain(config);
+        assert_eq!(world.sea_level, 60);
+
+        let bytes = world.save_to_bytes();
+        let loaded = World::load_from_bytes(&bytes).unwrap();
+
+        assert_eq!(loaded.sea_level, 60);
+        assert_eq!(loaded.cells_data.height, world.cells_data.height);
+        assert_eq!(loaded.neighbors, world.neighbors);
+        assert_eq!(loaded.points, world.points);
+        assert_eq!(loaded.plate_ids, world.plate_ids);
+        assert_eq!(loaded.triangles, world.triangles);
+        assert_eq!(loaded.save_to_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_load_rejects_bad_data() {
+        let world = World::new(
+            10,
+            10,
+            vec![
+                Pos2::new(1.0, 1.0),
+                Pos2::new(9.0, 1.0),
+                Pos2::new(5.0, 9.0),
+            ],
+        );
+        let bytes = world.save_to_bytes();
+
+        assert_eq!(
+            World::load_from_bytes(b"nope").unwrap_err(),
+            WorldError::InvalidMagic
+        );
+        assert_eq!(
+            World::load_from_bytes(&bytes[..bytes.len() - 1]).unwrap_err(),
+            WorldError::Truncated
+        );
+
+        let mut versioned = bytes.clone();
+        versioned[4] = 9;
+        assert_eq!(
+            World::load_from_bytes(&versioned).unwrap_err(),
+            WorldError::UnsupportedVersion(9)
+        );
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(matches!(
+            World::load_from_bytes(&trailing),
+            Err(WorldError::Corrupt(_))
+        ));
+
+        // 版本 1 没有海平面字段，按默认海平面加载
+        let mut v1 = bytes[..17].to_vec();
+        v1[4..8].copy_from_slice(&FORMAT_VERSION_V1.to_le_bytes());
+        v1.extend_from_slice(&bytes[18..]);
+        let loaded = World::load_from_bytes(&v1).unwrap();
+        assert_eq!(loaded.sea_level, SEA_LEVEL);
+        assert_eq!(loaded.points, world.points);
+    }
+}
@@ -0,0 +1,167 @@
+// 初始点采样
+
+use egui::{Pos2, Rect, Vec2};
+use rand::{Rng, SeedableRng};
+
+/// 每个活跃点尝试生成候选点的次数（Bridson 算法的 k）
+const POISSON_ATTEMPTS: usize = 30;
+
+/// 泊松圆盘采样（Bridson 算法）
+///
+/// 在 `bounds` 内生成任意两点距离都不小于 `min_dist` 的点集，
+/// 比均匀随机点分布更均匀，Voronoi 单元格在松弛前就不会明显聚集。
+/// 相同的 `seed` 总是生成相同的点集。`min_dist` 非正或区域为空时返回空集。
+pub fn poisson_disk(bounds: Rect, min_dist: f32, seed: u64) -> Vec<Pos2> {
+    if min_dist.is_nan() || min_dist <= 0.0 || bounds.width() <= 0.0 || bounds.height() <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+
+    // 背景网格：单元格边长 r/√2，每个单元格最多容纳一个点
+    let cell_size = min_dist / std::f32::consts::SQRT_2;
+    let cols = (bounds.width() / cell_size).ceil() as usize + 1;
+    let rows = (bounds.height() / cell_size).ceil() as usize + 1;
+    let mut grid: Vec<Option<usize>> = vec![None; cols * rows];
+    let grid_pos = |p: Pos2| {
+        let gx = ((p.x - bounds.min.x) / cell_size) as usize;
+        let gy = ((p.y - bounds.min.y) / cell_size) as usize;
+        (gx.min(cols - 1), gy.min(rows - 1))
+    };
+
+    let first = Pos2::new(
+        rng.random_range(bounds.min.x..bounds.max.x),
+        rng.random_range(bounds.min.y..bounds.max.y),
+    );
+    let mut points = vec![first];
+    let mut active = vec![0];
+    let (gx, gy) = grid_pos(first);
+    grid[gy * cols + gx] = Some(0);
+
+    let min_dist_sq = min_dist * min_dist;
+    while !active.is_empty() {
+        let slot = rng.random_range(0..active.len());
+        let center = points[active[slot]];
+        let mut found = false;
+
+        for _ in 0..POISSON_ATTEMPTS {
+            // 在 [r, 2r) 的圆环内取候选点
+            let angle = rng.random_range(0.0..std::f32::consts::TAU);
+            let radius = rng.random_range(min_dist..2.0 * min_dist);
+            let candidate = center + Vec2::angled(angle) * radius;
+            if !bounds.contains(candidate) {
+                continue;
+            }
+
+            let (cx, cy) = grid_pos(candidate);
+            let too_close = (cy.saturating_sub(2)..(cy + 3).min(rows)).any(|y| {
+                (cx.saturating_sub(2)..(cx + 3).min(cols)).any(|x| {
+                    grid[y * cols + x]
+                        .is_some_and(|i| points[i].distance_sq(candidate) < min_dist_sq)
+                })
+            });
+            if too_close {
+                continue;
+            }
+
+            grid[cy * cols + cx] = Some(points.len());
+            active.push(points.len());
+            points.push(candidate);
+            found = true;
+            break;
+        }
+
+        if !found {
+            active.swap_remove(slot);
+        }
+    }
+
+    points
+}
+
+/// 抖动网格采样
+///
+/// 把 `bounds` 划分为 `cols × rows` 个网格单元，每个单元放一个点：
+/// 从单元中心向任意方向偏移最多 `jitter` 倍单元尺寸（`jitter` 取 0..=0.5，
+/// 0 时为规则网格，0.5 时点可以到达单元边缘）。不需要松弛即可得到接近均匀的单元格。
+pub fn jittered_grid(bounds: Rect, cols: usize, rows: usize, jitter: f32, seed: u64) -> Vec<Pos2> {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    let jitter = jitter.clamp(0.0, 0.5);
+    let cell = Vec2::new(
+        bounds.width() / cols.max(1) as f32,
+        bounds.height() / rows.max(1) as f32,
+    );
+
+    let mut points = Vec::with_capacity(cols * rows);
+    for y in 0..rows {
+        for x in 0..cols {
+            let center =
+                bounds.min + Vec2::new((x as f32 + 0.5) * cell.x, (y as f32 + 0.5) * cell.y);
+            let offset = if jitter > 0.0 {
+                Vec2::new(
+                    rng.random_range(-jitter..=jitter) * cell.x,
+                    rng.random_range(-jitter..=jitter) * cell.y,
+                )
+            } else {
+                Vec2::ZERO
+            };
+            points.push(center + offset);
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poisson_disk_respects_min_dist() {
+        let bounds = Rect::from_min_max(Pos2::ZERO, Pos2::new(400.0, 300.0));
+        let min_dist = 10.0;
+        let points = poisson_disk(bounds, min_dist, 42);
+
+        for (i, a) in points.iter().enumerate() {
+            assert!(bounds.contains(*a));
+            for b in &points[i + 1..] {
+                assert!(a.distance(*b) >= min_dist, "{:?} {:?}", a, b);
+            }
+        }
+
+        // 最大填充约为 面积 / (r² · √3/2)，Bridson 通常达到其 60%~90%
+        let densest = bounds.area() / (min_dist * min_dist * 3f32.sqrt() / 2.0);
+        let count = points.len() as f32;
+        assert!(
+            count > densest * 0.5 && count < densest,
+            "{} points, densest packing {}",
+            count,
+            densest
+        );
+
+        assert_eq!(points, poisson_disk(bounds, min_dist, 42));
+    }
+
+    #[test]
+    fn test_jittered_grid_without_jitter_is_regular() {
+        let bounds = Rect::from_min_max(Pos2::new(10.0, 20.0), Pos2::new(110.0, 70.0));
+        let points = jittered_grid(bounds, 4, 5, 0.0, 7);
+
+        assert_eq!(points.len(), 20);
+        for y in 0..5 {
+            for x in 0..4 {
+                let expected = Pos2::new(
+                    10.0 + 25.0 * (x as f32 + 0.5),
+                    20.0 + 10.0 * (y as f32 + 0.5),
+                );
+                assert_eq!(points[y * 4 + x], expected);
+            }
+        }
+
+        // 有抖动时每个点仍留在自己的网格单元内
+        let jittered = jittered_grid(bounds, 4, 5, 0.5, 7);
+        assert_ne!(jittered, points);
+        for (p, q) in jittered.iter().zip(&points) {
+            assert!((p.x - q.x).abs() <= 12.5 && (p.y - q.y).abs() <= 5.0);
+        }
+    }
+}
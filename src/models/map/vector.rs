@@ -0,0 +1,194 @@
+// 矢量要素提取（海岸线等）与折线简化
+
+use std::collections::HashMap;
+
+use egui::Pos2;
+
+use crate::delaunay;
+
+use super::world::World;
+
+impl World {
+    /// 提取海岸线折线
+    ///
+    /// 海岸线由陆地单元格与水体单元格之间的 Voronoi 边首尾相接而成，
+    /// 闭合的海岸线首尾点相同。`tolerance` 大于 0 时使用
+    /// [`simplify_polyline`] 简化，单位与地图坐标相同。
+    pub fn extract_coastlines(&self, tolerance: f32) -> Vec<Vec<Pos2>> {
+        let mesh = &self.mesh;
+        let heights = &self.cells_data.height;
+        let is_land = |cell: u32| heights[cell as usize] >= self.sea_level;
+
+        // 以三角形索引（Voronoi 顶点）为节点收集海岸边
+        let mut adjacency: HashMap<u32, Vec<u32>> = HashMap::new();
+        for he in 0..mesh.halfedge_count() as u32 {
+            let twin = mesh.twin(he);
+            if twin == delaunay::EMPTY || he > twin {
+                continue;
+            }
+            if is_land(mesh.halfedge_start(he)) == is_land(mesh.halfedge_end(he)) {
+                continue;
+            }
+            let a = delaunay::DelaunayMesh::triangle_of_halfedge(he);
+            let b = delaunay::DelaunayMesh::triangle_of_halfedge(twin);
+            adjacency.entry(a).or_default().push(b);
+            adjacency.entry(b).or_default().push(a);
+        }
+
+        // 先从端点（度为奇数，即在凸包处断开）出发，再处理闭合环
+        let mut starts: Vec<u32> = adjacency.keys().copied().collect();
+        starts.sort_unstable_by_key(|node| (adjacency[node].len() % 2 == 0, *node));
+
+        let mut lines = Vec::new();
+        for start in starts {
+            while let Some(next) = adjacency.get_mut(&start).and_then(|n| n.pop()) {
+                remove_edge(&mut adjacency, next, start);
+
+                let mut chain = vec![start, next];
+                let mut current = next;
+                while let Some(following) = adjacency.get_mut(&current).and_then(|n| n.pop()) {
+                    remove_edge(&mut adjacency, following, current);
+                    chain.push(following);
+                    current = following;
+                }
+
+                let polyline: Vec<Pos2> = chain.iter().map(|&tri| mesh.circumcenter(tri)).collect();
+                lines.push(if tolerance > 0.0 {
+                    simplify_polyline(&polyline, tolerance)
+                } else {
+                    polyline
+                });
+            }
+        }
+
+        lines
+    }
+}
+
+/// 从邻接表中删除一条有向记录
+fn remove_edge(adjacency: &mut HashMap<u32, Vec<u32>>, from: u32, to: u32) {
+    if let Some(list) = adjacency.get_mut(&from) {
+        if let Some(pos) = list.iter().position(|&n| n == to) {
+            list.swap_remove(pos);
+        }
+    }
+}
+
+/// 使用 Douglas–Peucker 算法简化折线
+///
+/// 保留首尾点，删除到简化结果的距离不超过 `tolerance` 的中间点。
+pub fn simplify_polyline(points: &[Pos2], tolerance: f32) -> Vec<Pos2> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = stack.pop() {
+        let mut max_dist = 0.0;
+        let mut index = first;
+        for (i, &p) in points.iter().enumerate().take(last).skip(first + 1) {
+            let dist = point_segment_distance(p, points[first], points[last]);
+            if dist > max_dist {
+                max_dist = dist;
+                index = i;
+            }
+        }
+
+        if max_dist > tolerance {
+            keep[index] = true;
+            stack.push((first, index));
+            stack.push((index, last));
+        }
+    }
+
+    points
+        .iter()
+        .zip(keep)
+        .filter_map(|(&p, k)| k.then_some(p))
+        .collect()
+}
+
+/// 点到线段的距离
+pub fn point_segment_distance(p: Pos2, a: Pos2, b: Pos2) -> f32 {
+    let ab = b - a;
+    let len_sq = ab.length_sq();
+    if len_sq <= f32::EPSILON {
+        return p.distance(a);
+    }
+    let t = ((p - a).dot(ab) / len_sq).clamp(0.0, 1.0);
+    p.distance(a + ab * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 点到折线的最短距离
+    fn polyline_distance(p: Pos2, line: &[Pos2]) -> f32 {
+        line.windows(2)
+            .map(|w| point_segment_distance(p, w[0], w[1]))
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    #[test]
+    fn test_simplify_polyline_tolerance() {
+        let original: Vec<Pos2> = (0..200)
+            .map(|i| {
+                let x = i as f32 * 0.5;
+                Pos2::new(x, (x * 0.3).sin() * 10.0 + (x * 2.1).sin())
+            })
+            .collect();
+
+        let mut previous = original.len();
+        for tolerance in [0.1, 0.5, 1.0, 3.0] {
+            let simplified = simplify_polyline(&original, tolerance);
+            assert!(simplified.len() < previous, "tolerance {}", tolerance);
+            assert_eq!(simplified.first(), original.first());
+            assert_eq!(simplified.last(), original.last());
+
+            for &p in &original {
+                assert!(polyline_distance(p, &simplified) <= tolerance + 1e-4);
+            }
+            previous = simplified.len();
+        }
+    }
+
+    #[test]
+    fn test_extract_coastlines_island() {
+        let mut points = Vec::new();
+        for y in 0..20 {
+            for x in 0..20 {
+                let offset = if y % 2 == 0 { 0.0 } else { 2.5 };
+                points.push(Pos2::new(
+                    x as f32 * 5.0 + offset + 2.0,
+                    y as f32 * 5.0 + 2.0,
+                ));
+            }
+        }
+        let mut world = World::new(104, 104, points);
+        let center = Pos2::new(52.0, 52.0);
+        for (i, p) in world.points.iter().enumerate() {
+            world.cells_data.height[i] = if p.distance(center) < 30.0 { 100 } else { 0 };
+        }
+
+        let raw = world.extract_coastlines(0.0);
+        assert_eq!(raw.len(), 1);
+        let coast = &raw[0];
+        assert_eq!(
+            coast.first(),
+            coast.last(),
+            "island coastline should be closed"
+        );
+        assert!(coast
+            .iter()
+            .all(|p| (p.distance(center) - 30.0).abs() < 6.0));
+
+        let simplified = world.extract_coastlines(2.0);
+        assert_eq!(simplified.len(), 1);
+        assert!(simplified[0].len() < coast.len());
+    }
+}
@@ -0,0 +1,1583 @@
+use std::collections::VecDeque;
+
+use egui::{Pos2, Rect, Vec2};
+use rand::{Rng, SeedableRng};
+
+use crate::delaunay::{self, DelaunayMesh};
+use crate::spatial::GridIndex;
+use crate::terrain::{
+    classify_biomes, get_template_by_name, latitudes, plate_debug_field, relative_approach,
+    simulate_moisture, BoundaryKind, Feature, FeatureDetector, FeatureType, TectonicPlate,
+    TerrainConfig, TerrainGenerator, Topology, SEA_LEVEL,
+};
+use crate::ui::canvas::state::CanvasState;
+
+use super::cells_data::CellsData;
+use super::params::{GenerationParameters, PointDistribution};
+use super::sampling::{jittered_grid, poisson_disk};
+
+/// 生物群系分类使用的盛行风方向（弧度，0 表示吹向 +X，即西风）
+const BIOME_WIND_ANGLE: f32 = 0.0;
+/// 生物群系分类时湿度平流的迭代次数
+const BIOME_MOISTURE_ITERATIONS: u32 = 40;
+
+/// 单元格标志位：陆地（高度 >= 海平面）
+pub const CELL_FLAG_LAND: u8 = 1 << 0;
+/// 单元格标志位：海岸（与水体相邻的陆地）
+pub const CELL_FLAG_COAST: u8 = 1 << 1;
+
+/// 打包单元格数据为一个 u32
+///
+/// 位布局（从低位到高位）：
+/// - bits 0-7:   高度（0-255）
+/// - bits 8-15:  生物群系索引
+/// - bits 16-23: 标志位（`CELL_FLAG_*`）
+/// - bits 24-31: 保留，恒为 0
+pub fn pack_cell(height: u8, biome: u8, flags: u8) -> u32 {
+    height as u32 | (biome as u32) << 8 | (flags as u32) << 16
+}
+
+/// 解包单元格数据，返回 (高度, 生物群系索引, 标志位)
+pub fn unpack_cell(packed: u32) -> (u8, u8, u8) {
+    (
+        (packed & 0xFF) as u8,
+        ((packed >> 8) & 0xFF) as u8,
+        ((packed >> 16) & 0xFF) as u8,
+    )
+}
+
+/// 单个生物群系所占的面积比例
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BiomeFraction {
+    /// 生物群系索引
+    pub biome: u8,
+    /// 占全部单元格面积的比例
+    pub total: f32,
+    /// 占陆地面积的比例（没有陆地时为 0）
+    pub land: f32,
+}
+
+/// 地形统计指标
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TerrainStats {
+    /// 水体单元格（高度 < 海平面，含湖泊）占全部单元格的比例
+    pub ocean_ratio: f32,
+    /// 陆地单元格数量
+    pub land_cells: usize,
+    /// 水体单元格数量
+    pub sea_cells: usize,
+    /// 互不连通的陆地块数量
+    pub island_count: usize,
+    /// 不与地图边界连通的水体数量
+    pub lake_count: usize,
+    /// 海岸线长度：一端为陆地、一端为水体的邻接边数量
+    pub coastline_edges: usize,
+}
+
+/// 世界相关操作的错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorldError {
+    /// 数据不是以世界文件的魔数开头
+    InvalidMagic,
+    /// 不支持的文件格式版本
+    UnsupportedVersion(u32),
+    /// 数据在读取完成前结束
+    Truncated,
+    /// 数据内容不一致（索引越界、多余字节等）
+    Corrupt(String),
+    /// 未知的地形模板名称
+    UnknownTemplate(String),
+    /// 单元格数量不足以三角剖分（需要多于 3 个）
+    TooFewPoints(usize),
+    /// 没有可生成的单元格（单元格数量为 0 或地图面积为 0）
+    EmptyCells,
+}
+
+impl std::fmt::Display for WorldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidMagic => write!(f, "not a world file"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported world format version {}", version)
+            }
+            Self::Truncated => write!(f, "world data is truncated"),
+            Self::Corrupt(reason) => write!(f, "corrupt world data: {}", reason),
+            Self::UnknownTemplate(name) => write!(f, "unknown terrain template '{}'", name),
+            Self::TooFewPoints(n) => write!(f, "need more than 3 points, got {}", n),
+            Self::EmptyCells => write!(f, "world has no cells to generate"),
+        }
+    }
+}
+
+impl std::error::Error for WorldError {}
+
+/// 撤销历史的默认深度
+pub const DEFAULT_UNDO_DEPTH: usize = 32;
+
+/// 高度快照环形缓冲区，超出深度时丢弃最旧的快照
+#[derive(Debug, Clone)]
+pub(super) struct HeightHistory {
+    snapshots: VecDeque<Vec<u8>>,
+    depth: usize,
+}
+
+impl Default for HeightHistory {
+    fn default() -> Self {
+        Self {
+            snapshots: VecDeque::new(),
+            depth: DEFAULT_UNDO_DEPTH,
+        }
+    }
+}
+
+/// 世界
+///
+/// 不依赖任何 GPU/UI 资源的地图数据，包含点集、三角剖分、
+/// 邻接关系和单元格属性。用于离线生成、测试和导出。
+#[derive(Debug, Clone)]
+pub struct World {
+    /// 地图宽度
+    pub width: u32,
+    /// 地图高度
+    pub height: u32,
+    /// 单元格中心点（Voronoi 生成点）
+    pub points: Vec<Pos2>,
+    /// Delaunay 三角剖分索引，每3个连续索引构成一个三角形
+    pub triangles: Vec<u32>,
+    /// 每个单元格的邻居（已排序）
+    pub neighbors: Vec<Vec<u32>>,
+    /// 单元格属性数据
+    pub cells_data: CellsData,
+    /// 每个单元格所属的板块 ID（0 表示未分配）
+    pub plate_ids: Vec<u16>,
+    /// 板块对象（运动方向和速度等），只有板块构造模拟会生成；
+    /// 分层生成和从存档加载的世界为空
+    pub plates: Vec<TectonicPlate>,
+    /// 调试用板块灰度场，仅在 `TerrainConfig::debug_plate_field` 开启时生成
+    pub plate_field: Option<Vec<u8>>,
+    /// 点的空间索引
+    pub point_index: GridIndex,
+    /// 由 `points` 和 `triangles` 构建的半边网格，用于定位包含某位置的三角形
+    pub(super) mesh: DelaunayMesh,
+    /// 地图拓扑
+    pub topology: Topology,
+    /// 海平面高度：低于此值的单元格为水体，由 [`TerrainConfig::sea_level`] 决定
+    pub sea_level: u8,
+    /// 撤销历史
+    pub(super) history: HeightHistory,
+}
+
+impl World {
+    /// 从点集创建世界（高度全部为 0）
+    pub fn new(width: u32, height: u32, points: Vec<Pos2>) -> Self {
+        Self::with_topology(width, height, points, Topology::Plane)
+    }
+
+    /// 从点集创建指定拓扑的世界（高度全部为 0）
+    ///
+    /// 环绕拓扑会连同影子点（见 [`Topology::ghost_points`]）一起三角剖分，
+    /// 再把影子点映射回原始单元格，使环绕边缘两侧的单元格互为邻居。
+    /// `triangles` 只保留三个顶点都是原始点的三角形。
+    pub fn with_topology(width: u32, height: u32, points: Vec<Pos2>, topology: Topology) -> Self {
+        let (triangles, neighbors) = Self::build_topology(&points, width, height, topology);
+        let bounds = Rect::from_min_max(Pos2::ZERO, Pos2::new(width as f32, height as f32));
+        let point_index = GridIndex::build_auto(&points, bounds);
+        let mesh = DelaunayMesh::from_triangles(points.clone(), &triangles);
+        let cells_data = CellsData::new(points.len());
+        let plate_ids = vec![0; points.len()];
+
+        Self {
+            width,
+            height,
+            points,
+            triangles,
+            neighbors,
+            cells_data,
+            plate_ids,
+            plates: Vec::new(),
+            plate_field: None,
+            point_index,
+            mesh,
+            topology,
+            sea_level: SEA_LEVEL,
+            history: HeightHistory::default(),
+        }
+    }
+
+    /// 按生成参数创建世界并生成地形
+    ///
+    /// 模板名称未知时回退到 earth-like 模板；单元格数量或地图尺寸无法生成时
+    /// 返回没有单元格的空世界。需要感知错误时使用 [`Self::try_generate`]。
+    pub fn generate(params: &GenerationParameters) -> Self {
+        match Self::try_generate(params) {
+            Ok(world) => world,
+            Err(WorldError::UnknownTemplate(_)) => {
+                Self::generate_unchecked(&GenerationParameters {
+                    template: "earth-like".to_string(),
+                    ..params.clone()
+                })
+            }
+            Err(_) => Self::with_topology(params.width, params.height, Vec::new(), params.topology),
+        }
+    }
+
+    /// 无界面生成：按生成参数创建世界并生成地形，供批处理工具和测试使用
+    ///
+    /// 只经过点采样、三角剖分和地形生成，不创建也不访问任何 `gpu`/`ui` 资源，
+    /// 不需要 eframe 上下文或事件循环，可以在任意线程调用。
+    /// 行为与 [`Self::generate`] 相同（未知模板回退到 earth-like）。
+    pub fn generate_headless(params: &GenerationParameters) -> Self {
+        Self::generate(params)
+    }
+
+    /// 按生成参数创建世界并生成地形，参数无法生成时返回错误
+    pub fn try_generate(params: &GenerationParameters) -> Result<Self, WorldError> {
+        if params.num_points == 0 || params.width == 0 || params.height == 0 {
+            return Err(WorldError::EmptyCells);
+        }
+        if params.num_points <= 3 {
+            return Err(WorldError::TooFewPoints(params.num_points));
+        }
+        if get_template_by_name(&params.template).is_none() {
+            return Err(WorldError::UnknownTemplate(params.template.clone()));
+        }
+        Ok(Self::generate_unchecked(params))
+    }
+
+    fn generate_unchecked(params: &GenerationParameters) -> Self {
+        let points = Self::sample_points(params);
+
+        let mut world = Self::with_topology(params.width, params.height, points, params.topology);
+        let mut config = TerrainConfig::with_template(params.template.clone());
+        config.tectonic.seed = params.seed;
+        config.ocean_ratio = params.ocean_ratio;
+        config.num_plates = params.num_plates;
+        config.preview = params.preview;
+        world.generate_terrain(config);
+        world
+    }
+
+    /// 按 [`GenerationParameters::point_distribution`] 生成初始点
+    fn sample_points(params: &GenerationParameters) -> Vec<Pos2> {
+        let (w, h) = (params.width as f32, params.height as f32);
+        let bounds = Rect::from_min_max(Pos2::ZERO, Pos2::new(w, h));
+        let n = params.point_count();
+        match params.point_distribution {
+            PointDistribution::Random => {
+                let mut rng = rand::rngs::StdRng::seed_from_u64(params.seed);
+                (0..n)
+                    .map(|_| Pos2::new(rng.random_range(0.0..w), rng.random_range(0.0..h)))
+                    .collect()
+            }
+            PointDistribution::JitteredGrid => {
+                // 按地图宽高比划分网格，列数 × 行数不少于 num_points
+                let cols = ((n as f32 * w / h).sqrt().round() as usize).max(1);
+                let rows = n.div_ceil(cols);
+                jittered_grid(bounds, cols, rows, 0.45, params.seed)
+            }
+            PointDistribution::PoissonDisk => {
+                // Bridson 采样约填满最密堆积的 70%，据此由目标点数反推最小间距
+                let min_dist = (0.8 * w * h / n as f32).sqrt();
+                poisson_disk(bounds, min_dist, params.seed)
+            }
+        }
+    }
+
+    /// 使用给定配置生成地形
+    ///
+    /// 配置中的拓扑会被替换为世界自身的拓扑，世界的海平面取自配置。
+    /// 地形生成后按高度、湿度和纬度重新分类每个单元格的生物群系。
+    pub fn generate_terrain(&mut self, mut config: TerrainConfig) {
+        config.topology = self.topology;
+        self.sea_level = config.sea_level;
+        let debug_plate_field = config.debug_plate_field;
+        let generator = TerrainGenerator::new(config);
+        let (heights, plates, plate_ids) = generator.generate(&self.points, &self.neighbors);
+        self.cells_data.height = heights;
+        self.plates = plates;
+        self.plate_field = debug_plate_field.then(|| plate_debug_field(&plate_ids));
+        self.plate_ids = plate_ids;
+        self.classify_biomes();
+    }
+
+    /// 按当前高度模拟湿度，并据此填充 `cells_data.biome`
+    fn classify_biomes(&mut self) {
+        let heights = &self.cells_data.height;
+        let moisture = simulate_moisture(
+            heights,
+            &self.points,
+            &self.neighbors,
+            BIOME_WIND_ANGLE,
+            BIOME_MOISTURE_ITERATIONS,
+            self.sea_level,
+        );
+        let latitude = latitudes(&self.points, self.height as f32);
+        self.cells_data.biome = classify_biomes(heights, &moisture, &latitude, self.sea_level)
+            .into_iter()
+            .map(|b| b as u8)
+            .collect();
+    }
+
+    /// 保存当前高度的快照，供 [`Self::undo`] 恢复
+    ///
+    /// 应在每次编辑之前调用。快照数量超过撤销深度时丢弃最旧的快照。
+    pub fn push_snapshot(&mut self) {
+        let history = &mut self.history;
+        if history.depth == 0 {
+            return;
+        }
+        while history.snapshots.len() >= history.depth {
+            history.snapshots.pop_front();
+        }
+        history.snapshots.push_back(self.cells_data.height.clone());
+    }
+
+    /// 恢复到最近一次快照，没有可撤销的快照时返回 false
+    pub fn undo(&mut self) -> bool {
+        match self.history.snapshots.pop_back() {
+            Some(heights) => {
+                self.cells_data.height = heights;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 设置撤销深度（默认 [`DEFAULT_UNDO_DEPTH`]），0 表示禁用撤销；
+    /// 已有快照超出新深度时丢弃最旧的
+    pub fn set_undo_depth(&mut self, depth: usize) {
+        let history = &mut self.history;
+        history.depth = depth;
+        while history.snapshots.len() > depth {
+            history.snapshots.pop_front();
+        }
+    }
+
+    /// 当前可撤销的步数
+    pub fn undo_len(&self) -> usize {
+        self.history.snapshots.len()
+    }
+
+    /// 笔刷编辑：抬升或降低 `center` 周围 `radius` 范围内的地形
+    ///
+    /// 范围内的单元格按到圆心的距离做 smoothstep 衰减，圆心处改变 `delta`，
+    /// 边缘处为 0，结果夹在 0..=255。之后在笔刷范围及其邻居上做一轮局部海岸线清理，
+    /// 去掉笔刷边缘产生的孤立海陆单元格。
+    pub fn apply_brush(&mut self, center: Pos2, radius: f32, delta: i16) {
+        if radius.is_nan() || radius <= 0.0 || delta == 0 {
+            return;
+        }
+
+        let affected = self.point_index.query_radius(&self.points, center, radius);
+        if affected.is_empty() {
+            return;
+        }
+
+        let heights = &mut self.cells_data.height;
+        for &i in &affected {
+            let t = 1.0 - self.points[i as usize].distance(center) / radius;
+            let falloff = t * t * (3.0 - 2.0 * t);
+            let h = heights[i as usize] as f32 + delta as f32 * falloff;
+            heights[i as usize] = h.round().clamp(0.0, 255.0) as u8;
+        }
+
+        let mut region: Vec<usize> = affected
+            .iter()
+            .flat_map(|&i| {
+                std::iter::once(i as usize)
+                    .chain(self.neighbors[i as usize].iter().map(|&n| n as usize))
+            })
+            .collect();
+        region.sort_unstable();
+        region.dedup();
+        FeatureDetector::default().smooth_coastline_at(heights, &self.neighbors, &region);
+    }
+
+    /// 板块边界
+    ///
+    /// 返回所有跨越板块边界的相邻单元格对 `(a, b)`（`a < b`，两者板块 ID 不同且均非 0），
+    /// 以及按两侧板块在该边法向上的相对运动分类的边界类别。
+    /// 缺少板块运动信息（见 [`Self::plates`]）的板块视为静止。
+    pub fn plate_boundaries(&self) -> Vec<(usize, usize, BoundaryKind)> {
+        let velocity = |pid: u16| {
+            self.plates
+                .iter()
+                .find(|p| p.id == pid)
+                .map_or(Pos2::ZERO, |p| p.velocity_vector())
+        };
+
+        let mut boundaries = Vec::new();
+        for (a, list) in self.neighbors.iter().enumerate() {
+            let pid_a = self.plate_ids[a];
+            for &b in list {
+                let b = b as usize;
+                let pid_b = self.plate_ids[b];
+                if b <= a || pid_a == 0 || pid_b == 0 || pid_a == pid_b {
+                    continue;
+                }
+
+                let mut delta = self.points[b] - self.points[a];
+                if self.topology.wraps_x() && delta.x.abs() > self.width as f32 / 2.0 {
+                    delta.x -= self.width as f32 * delta.x.signum();
+                }
+                let normal = delta.normalized();
+                let approach = relative_approach(velocity(pid_a), velocity(pid_b), normal);
+                boundaries.push((a, b, BoundaryKind::from_approach(approach)));
+            }
+        }
+        boundaries
+    }
+
+    /// 单元格数量
+    pub fn cell_count(&self) -> usize {
+        self.points.len()
+    }
+
+    /// 获取地图边界框
+    pub fn bounds(&self) -> Rect {
+        Rect::from_min_max(Pos2::ZERO, Pos2::new(self.width as f32, self.height as f32))
+    }
+
+    /// 拾取屏幕位置对应的单元格
+    ///
+    /// 先用 [`CanvasState::screen_to_world`] 将屏幕坐标转换为地图坐标，
+    /// 再交给 [`Self::pick_cell`]。位置超出地图范围时返回 None。
+    pub fn cell_at_screen(&self, canvas_state: &CanvasState, screen_pos: Pos2) -> Option<usize> {
+        let world_pos = canvas_state.screen_to_world(screen_pos);
+        if !self.bounds().contains(world_pos) {
+            return None;
+        }
+        self.pick_cell(world_pos)
+    }
+
+    /// 拾取地图坐标所在的单元格
+    ///
+    /// 地图范围内通过空间索引查找最近的单元格（即包含该位置的 Voronoi 单元格）；
+    /// 范围之外不属于任何单元格，退回到遍历所有点求最近点。没有单元格时返回 None。
+    pub fn pick_cell(&self, world_pos: Pos2) -> Option<usize> {
+        let indexed = if self.bounds().contains(world_pos) {
+            self.point_index.find_nearest(&self.points, world_pos)
+        } else {
+            None
+        };
+
+        indexed.map(|idx| idx as usize).or_else(|| {
+            self.points
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| {
+                    a.distance_sq(world_pos)
+                        .total_cmp(&b.distance_sq(world_pos))
+                })
+                .map(|(idx, _)| idx)
+        })
+    }
+
+    /// 查询地图坐标处的高度
+    ///
+    /// 返回离该位置最近的单元格（即包含它的 Voronoi 单元格）的高度，
+    /// 位置超出地图范围时返回 None。
+    pub fn height_at(&self, pos: Pos2) -> Option<u8> {
+        if !self.bounds().contains(pos) {
+            return None;
+        }
+        self.point_index
+            .find_nearest(&self.points, pos)
+            .and_then(|idx| self.cells_data.height.get(idx as usize).copied())
+    }
+
+    /// 查询地图坐标处的插值高度
+    ///
+    /// 由 [`DelaunayMesh::interpolate`] 在包含该位置的 Delaunay 三角形内按重心坐标
+    /// 混合三个顶点的高度，结果随位置连续变化。位置在三角剖分之外（凸包外侧的
+    /// 边缘地带）或三角形退化时退回到 [`Self::height_at`]，超出地图范围时返回 None。
+    pub fn height_at_interpolated(&self, pos: Pos2) -> Option<f32> {
+        if !self.bounds().contains(pos) {
+            return None;
+        }
+        let heights_f32: Vec<f32> = self.cells_data.height.iter().map(|&h| h as f32).collect();
+        self.mesh
+            .interpolate(pos, &heights_f32)
+            .or_else(|| self.height_at(pos).map(f32::from))
+    }
+
+    /// 查找包含指定位置的三角形，返回其三个顶点索引
+    ///
+    /// 从最近单元格所在的三角形出发在半边网格上行走，只访问附近的三角形。
+    fn enclosing_triangle(&self, pos: Pos2) -> Option<[usize; 3]> {
+        let nearest = self.point_index.find_nearest(&self.points, pos)?;
+        let start = self
+            .mesh
+            .vertex_to_halfedge
+            .get(nearest as usize)
+            .filter(|&&he| he != delaunay::EMPTY)
+            .map_or(0, |&he| DelaunayMesh::triangle_of_halfedge(he));
+        let tri = self.mesh.locate_triangle_from(pos, start)?;
+        Some(self.mesh.triangle_vertices(tri).map(|v| v as usize))
+    }
+
+    /// 采样任意位置的地形梯度
+    ///
+    /// 用包含该位置的三角形三个顶点的高度拟合平面，返回平面的梯度
+    /// （每单位地图距离的高度变化），方向指向上坡。
+    /// 位置在三角剖分之外或三角形退化时返回零向量。
+    pub fn sample_gradient_at(&self, pos: Pos2) -> Vec2 {
+        let Some([a, b, c]) = self.enclosing_triangle(pos) else {
+            return Vec2::ZERO;
+        };
+
+        let heights = &self.cells_data.height;
+        let (pa, pb, pc) = (self.points[a], self.points[b], self.points[c]);
+        let (ha, hb, hc) = (heights[a] as f32, heights[b] as f32, heights[c] as f32);
+
+        // 解 h = gx * x + gy * y + k
+        let (e1, e2) = (pb - pa, pc - pa);
+        let (dh1, dh2) = (hb - ha, hc - ha);
+        let det = e1.x * e2.y - e1.y * e2.x;
+        if det.abs() < f32::EPSILON {
+            return Vec2::ZERO;
+        }
+
+        Vec2::new(
+            (dh1 * e2.y - dh2 * e1.y) / det,
+            (e1.x * dh2 - e2.x * dh1) / det,
+        )
+    }
+
+    /// 将高度、生物群系和标志位打包为交错的 u32 缓冲区
+    ///
+    /// 每个单元格一个 u32，位布局见 [`pack_cell`]，可一次性上传到 GPU。
+    /// 海岸标志需要查询邻居，因此在这里统一计算。
+    pub fn merge_neighbor_metadata(&self) -> Vec<u32> {
+        let heights = &self.cells_data.height;
+
+        (0..self.cell_count())
+            .map(|i| {
+                let height = heights[i];
+                let biome = self.cells_data.biome[i];
+
+                let mut flags = 0u8;
+                if height >= self.sea_level {
+                    flags |= CELL_FLAG_LAND;
+                    if self.neighbors[i]
+                        .iter()
+                        .any(|&n| heights[n as usize] < self.sea_level)
+                    {
+                        flags |= CELL_FLAG_COAST;
+                    }
+                }
+
+                pack_cell(height, biome, flags)
+            })
+            .collect()
+    }
+
+    /// 计算每个单元格（Voronoi 多边形）的面积
+    ///
+    /// 凸包上的单元格不闭合，使用闭合单元格的平均面积代替。
+    pub fn cell_areas(&self) -> Vec<f32> {
+        let mut areas = vec![None; self.cell_count()];
+        for (v, area) in areas.iter_mut().enumerate() {
+            let (vertices, is_closed) = self.mesh.voronoi_cell_vertices(v as u32);
+            if is_closed && vertices.len() >= 3 {
+                *area = Some(polygon_area(&vertices));
+            }
+        }
+
+        let closed: Vec<f32> = areas.iter().flatten().copied().collect();
+        let fallback = if closed.is_empty() {
+            (self.width * self.height) as f32 / self.cell_count().max(1) as f32
+        } else {
+            closed.iter().sum::<f32>() / closed.len() as f32
+        };
+
+        areas.into_iter().map(|a| a.unwrap_or(fallback)).collect()
+    }
+
+    /// 统计每个生物群系所占的面积比例
+    ///
+    /// 按单元格面积加权，返回按生物群系索引排序、只包含出现过的生物群系的列表。
+    pub fn biome_fractions(&self) -> Vec<BiomeFraction> {
+        let areas = self.cell_areas();
+        let mut total = [0.0f64; 256];
+        let mut land = [0.0f64; 256];
+        let mut present = [false; 256];
+
+        for (i, &area) in areas.iter().enumerate() {
+            let biome = self.cells_data.biome[i] as usize;
+            present[biome] = true;
+            total[biome] += area as f64;
+            if self.cells_data.height[i] >= self.sea_level {
+                land[biome] += area as f64;
+            }
+        }
+
+        let total_area: f64 = total.iter().sum();
+        let land_area: f64 = land.iter().sum();
+        let ratio = |value: f64, sum: f64| if sum > 0.0 { (value / sum) as f32 } else { 0.0 };
+
+        (0..256)
+            .filter(|&b| present[b])
+            .map(|b| BiomeFraction {
+                biome: b as u8,
+                total: ratio(total[b], total_area),
+                land: ratio(land[b], land_area),
+            })
+            .collect()
+    }
+
+    /// 标记位于地图边界上的单元格
+    ///
+    /// 到最近的非环绕地图边缘的距离小于平均点间距的单元格视为边界。
+    /// 圆柱面只有上下边界，球面没有边界。
+    pub fn border_cells(&self) -> Vec<bool> {
+        let (w, h) = (self.width as f32, self.height as f32);
+        let spacing = (w * h / self.cell_count().max(1) as f32).sqrt();
+
+        self.points
+            .iter()
+            .map(|p| {
+                let edge_dist = match self.topology {
+                    Topology::Plane => p.x.min(w - p.x).min(p.y).min(h - p.y),
+                    Topology::CylinderX => p.y.min(h - p.y),
+                    Topology::Sphere => f32::INFINITY,
+                };
+                edge_dist < spacing
+            })
+            .collect()
+    }
+
+    /// 检测所有地形特征（岛屿、湖泊、海洋）
+    ///
+    /// 每个特征是一块连通的陆地或水体，`area` 为其单元格面积之和
+    /// （见 [`Self::cell_areas`]），特征 ID 从 1 开始按最小单元格索引递增。
+    pub fn features(&self) -> Vec<Feature> {
+        let (mut features, _) = self.feature_detector().detect_features(
+            &self.cells_data.height,
+            &self.neighbors,
+            &self.border_cells(),
+        );
+        let areas = self.cell_areas();
+        for feature in &mut features {
+            feature.area = feature.cells.iter().map(|&i| areas[i]).sum();
+        }
+        features
+    }
+
+    /// 最大陆块
+    ///
+    /// 返回单元格数最多的连通陆地的单元格索引（升序）；没有陆地时返回空列表。
+    /// 大小相同时取最小单元格索引更小的陆块。
+    pub fn largest_landmass(&self) -> Vec<usize> {
+        let (features, _) = self.feature_detector().detect_features(
+            &self.cells_data.height,
+            &self.neighbors,
+            &self.border_cells(),
+        );
+        let mut cells = features
+            .into_iter()
+            .filter(|f| f.is_land())
+            .max_by_key(|f| {
+                let min_cell = f.cells.iter().min().copied().unwrap_or(usize::MAX);
+                (f.size(), std::cmp::Reverse(min_cell))
+            })
+            .map(|f| f.cells)
+            .unwrap_or_default();
+        cells.sort_unstable();
+        cells
+    }
+
+    /// 按世界海平面区分海陆的特征检测器
+    fn feature_detector(&self) -> FeatureDetector {
+        FeatureDetector::default().with_sea_level(self.sea_level)
+    }
+
+    /// 计算地形统计指标
+    ///
+    /// 岛屿和湖泊由 [`FeatureDetector`] 的连通区域检测得到。
+    pub fn terrain_stats(&self) -> TerrainStats {
+        let heights = &self.cells_data.height;
+        let is_land = |i: usize| heights[i] >= self.sea_level;
+
+        let land_cells = (0..heights.len()).filter(|&i| is_land(i)).count();
+        let sea_cells = heights.len() - land_cells;
+
+        let (features, _) =
+            self.feature_detector()
+                .detect_features(heights, &self.neighbors, &self.border_cells());
+        let count = |kind| features.iter().filter(|f| f.feature_type == kind).count();
+
+        // 每条无向边只计一次
+        let coastline_edges = self
+            .neighbors
+            .iter()
+            .enumerate()
+            .flat_map(|(i, list)| list.iter().map(move |&j| (i, j as usize)))
+            .filter(|&(i, j)| i < j && is_land(i) != is_land(j))
+            .count();
+
+        TerrainStats {
+            ocean_ratio: if heights.is_empty() {
+                0.0
+            } else {
+                sea_cells as f32 / heights.len() as f32
+            },
+            land_cells,
+            sea_cells,
+            island_count: count(FeatureType::Island),
+            lake_count: count(FeatureType::Lake),
+            coastline_edges,
+        }
+    }
+
+    /// 按拓扑三角剖分点集，返回 (三角形索引, 每个点的邻居)
+    ///
+    /// 环绕拓扑会加入影子点一起剖分，再把影子点映射回原始点，
+    /// 使跨越环绕边缘的单元格互为邻居；返回的三角形只包含全部由原始点构成的那些。
+    fn build_topology(
+        points: &[Pos2],
+        width: u32,
+        height: u32,
+        topology: Topology,
+    ) -> (Vec<u32>, Vec<Vec<u32>>) {
+        let n = points.len();
+        let ghosts = topology.ghost_points(points, width as f32, height as f32);
+        if ghosts.is_empty() {
+            let triangles = delaunay::triangulate(points);
+            let neighbors = Self::extract_neighbors(&triangles, n);
+            return (triangles, neighbors);
+        }
+
+        let mut all_points = points.to_vec();
+        all_points.extend(ghosts.iter().map(|&(p, _)| p));
+        let source: Vec<u32> = (0..n as u32)
+            .chain(ghosts.iter().map(|&(_, i)| i))
+            .collect();
+        let all_triangles = delaunay::triangulate(&all_points);
+
+        // 只有包含原始点的三角形才有意义，外圈影子点之间的三角形会产生虚假邻接
+        let mut mapped = Vec::new();
+        let mut triangles = Vec::new();
+        for tri in all_triangles.chunks_exact(3) {
+            if tri.iter().all(|&v| v as usize >= n) {
+                continue;
+            }
+            if tri.iter().all(|&v| (v as usize) < n) {
+                triangles.extend_from_slice(tri);
+            }
+            mapped.extend(tri.iter().map(|&v| source[v as usize]));
+        }
+
+        let mut neighbors = Self::extract_neighbors(&mapped, n);
+        for (i, list) in neighbors.iter_mut().enumerate() {
+            list.retain(|&j| j as usize != i);
+        }
+        (triangles, neighbors)
+    }
+
+    /// 从Delaunay三角剖分提取每个点的邻居（升序、去重）
+    fn extract_neighbors(triangles: &[u32], num_points: usize) -> Vec<Vec<u32>> {
+        let mut neighbors: Vec<Vec<u32>> = vec![Vec::new(); num_points];
+
+        for chunk in triangles.chunks(3) {
+            if chunk.len() == 3 {
+                let (a, b, c) = (chunk[0] as usize, chunk[1] as usize, chunk[2] as usize);
+                neighbors[a].extend([chunk[1], chunk[2]]);
+                neighbors[b].extend([chunk[0], chunk[2]]);
+                neighbors[c].extend([chunk[0], chunk[1]]);
+            }
+        }
+
+        // 排序去重，保证结果与三角形顺序无关
+        for list in &mut neighbors {
+            list.sort_unstable();
+            list.dedup();
+        }
+
+        neighbors
+    }
+}
+
+/// 多边形面积（鞋带公式）
+fn polygon_area(vertices: &[Pos2]) -> f32 {
+    let mut sum = 0.0;
+    for i in 0..vertices.len() {
+        let a = vertices[i];
+        let b = vertices[(i + 1) % vertices.len()];
+        sum += a.x * b.y - b.x * a.y;
+    }
+    (sum * 0.5).abs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::test_grid::grid_neighbors;
+
+    /// 创建规则排列的小型世界
+    fn create_test_world(cols: u32, rows: u32) -> World {
+        let spacing = 10.0;
+        let mut points = Vec::new();
+        for y in 0..rows {
+            for x in 0..cols {
+                // 轻微错位，避免完全共圆的退化情况
+                let offset = if y % 2 == 0 { 0.0 } else { spacing * 0.5 };
+                points.push(Pos2::new(
+                    x as f32 * spacing + offset + 5.0,
+                    y as f32 * spacing + 5.0,
+                ));
+            }
+        }
+        World::new(cols * 10 + 10, rows * 10 + 10, points)
+    }
+
+    #[test]
+    fn test_preview_generates_coarse_map() {
+        let full_params = GenerationParameters::builder()
+            .seed(9)
+            .num_points(4000)
+            .size(400, 200)
+            .build()
+            .unwrap();
+        let preview_params = GenerationParameters {
+            preview: true,
+            ..full_params.clone()
+        };
+
+        let preview = World::generate(&preview_params);
+        assert_eq!(preview.cell_count(), 500);
+        assert!(preview.cells_data.height.iter().any(|&h| h >= SEA_LEVEL));
+        assert_eq!(
+            World::generate(&preview_params).cells_data.height,
+            preview.cells_data.height
+        );
+
+        // 完整质量不受预览影响，同一种子可复现
+        let full = World::generate(&full_params);
+        assert_eq!(full.cell_count(), 4000);
+        assert_eq!(
+            World::generate(&full_params).cells_data.height,
+            full.cells_data.height
+        );
+    }
+
+    #[test]
+    fn test_generate_headless_on_plain_thread() {
+        let params = GenerationParameters::builder()
+            .seed(21)
+            .num_points(1500)
+            .size(300, 200)
+            .template("archipelago")
+            .build()
+            .unwrap();
+
+        // 在没有任何事件循环的普通线程上生成
+        let world = std::thread::spawn(move || World::generate_headless(&params))
+            .join()
+            .unwrap();
+
+        assert_eq!(world.cell_count(), 1500);
+        assert!(!world.triangles.is_empty());
+        assert!(world.neighbors.iter().all(|n| !n.is_empty()));
+        let heights = &world.cells_data.height;
+        assert!(heights.iter().any(|&h| h >= SEA_LEVEL));
+        assert!(heights.iter().any(|&h| h < SEA_LEVEL));
+    }
+
+    #[test]
+    fn test_generate_classifies_biomes() {
+        let params = GenerationParameters::builder()
+            .seed(5)
+            .num_points(1500)
+            .size(300, 200)
+            .template("earth-like")
+            .build()
+            .unwrap();
+        let world = World::generate(&params);
+
+        let biomes = &world.cells_data.biome;
+        assert_eq!(biomes.len(), world.cell_count());
+        let first = biomes[0];
+        assert!(biomes.iter().any(|&b| b != first));
+        for (i, &b) in biomes.iter().enumerate() {
+            let ocean = b == crate::terrain::Biome::Ocean as u8;
+            assert_eq!(ocean, world.cells_data.height[i] < world.sea_level);
+        }
+    }
+
+    #[test]
+    fn test_try_generate_errors() {
+        let params = GenerationParameters::builder()
+            .num_points(200)
+            .size(200, 100)
+            .template("no-such-template")
+            .build()
+            .unwrap();
+        assert_eq!(
+            World::try_generate(&params).unwrap_err(),
+            WorldError::UnknownTemplate("no-such-template".to_string())
+        );
+        // 兼容接口回退到 earth-like
+        let fallback = World::generate(&params);
+        let earth_like = World::generate(&GenerationParameters {
+            template: "earth-like".to_string(),
+            ..params.clone()
+        });
+        assert_eq!(fallback.cells_data.height, earth_like.cells_data.height);
+
+        let too_few = GenerationParameters {
+            num_points: 3,
+            ..params.clone()
+        };
+        assert_eq!(
+            World::try_generate(&too_few).unwrap_err(),
+            WorldError::TooFewPoints(3)
+        );
+
+        let empty = GenerationParameters {
+            num_points: 0,
+            ..params
+        };
+        assert_eq!(
+            World::try_generate(&empty).unwrap_err(),
+            WorldError::EmptyCells
+        );
+    }
+
+    #[test]
+    fn test_generate_falls_back_to_empty_world_on_invalid_params() {
+        let params = GenerationParameters::builder()
+            .num_points(200)
+            .size(200, 100)
+            .build()
+            .unwrap();
+
+        // EmptyCells：单元格数量为 0 或地图面积为 0
+        for invalid in [
+            GenerationParameters {
+                num_points: 0,
+                ..params.clone()
+            },
+            GenerationParameters {
+                width: 0,
+                ..params.clone()
+            },
+            GenerationParameters {
+                height: 0,
+                ..params.clone()
+            },
+        ] {
+            assert_eq!(
+                World::try_generate(&invalid).unwrap_err(),
+                WorldError::EmptyCells
+            );
+            let world = World::generate(&invalid);
+            assert_eq!(world.cell_count(), 0);
+            assert!(world.cells_data.height.is_empty());
+        }
+
+        // TooFewPoints：不会生成退化的 2 单元格世界
+        for n in [1, 2, 3] {
+            let invalid = GenerationParameters {
+                num_points: n,
+                ..params.clone()
+            };
+            assert_eq!(
+                World::try_generate(&invalid).unwrap_err(),
+                WorldError::TooFewPoints(n)
+            );
+            let world = World::generate(&invalid);
+            assert_eq!(world.cell_count(), 0);
+            assert_eq!((world.width, world.height), (200, 100));
+        }
+    }
+
+    #[test]
+    fn test_plate_boundaries_two_plates() {
+        let mut world = create_test_world(6, 6);
+        // 左半边板块 1 向右运动，右半边板块 2 向左运动
+        world.plate_ids = world
+            .points
+            .iter()
+            .map(|p| if p.x < 35.0 { 1 } else { 2 })
+            .collect();
+        let mut left = TectonicPlate::new(1, crate::terrain::plate::PlateType::Continental);
+        left.direction = 0.0;
+        let mut right = TectonicPlate::new(2, crate::terrain::plate::PlateType::Oceanic);
+        right.direction = std::f32::consts::PI;
+        world.plates = vec![left, right];
+
+        let boundaries = world.plate_boundaries();
+        assert!(!boundaries.is_empty());
+        for &(a, b, kind) in &boundaries {
+            assert!(a < b);
+            assert!(world.neighbors[a].contains(&(b as u32)));
+            assert_ne!(world.plate_ids[a], world.plate_ids[b]);
+            assert_eq!(kind, BoundaryKind::Convergent);
+        }
+
+        // 所有跨板块的邻接边都被返回
+        let crossing = (0..world.cell_count())
+            .flat_map(|a| world.neighbors[a].iter().map(move |&b| (a, b as usize)))
+            .filter(|&(a, b)| a < b && world.plate_ids[a] != world.plate_ids[b])
+            .count();
+        assert_eq!(boundaries.len(), crossing);
+
+        // 没有运动信息时视为静止的转换边界
+        world.plates.clear();
+        assert!(world
+            .plate_boundaries()
+            .iter()
+            .all(|&(_, _, kind)| kind == BoundaryKind::Transform));
+    }
+
+    #[test]
+    fn test_cell_at_screen() {
+        use egui::emath::TSTransform;
+
+        let world = create_test_world(8, 8);
+        let canvas_state = CanvasState {
+            transform: TSTransform::new(egui::vec2(30.0, -12.0), 2.5),
+            ..Default::default()
+        };
+
+        for idx in [0, 9, 27, 63] {
+            let screen_pos = canvas_state.to_screen(world.points[idx]);
+            assert_eq!(world.cell_at_screen(&canvas_state, screen_pos), Some(idx));
+        }
+
+        // 地图范围之外
+        let outside = canvas_state.to_screen(Pos2::new(-50.0, -50.0));
+        assert_eq!(world.cell_at_screen(&canvas_state, outside), None);
+    }
+
+    #[test]
+    fn test_pick_cell() {
+        let world = create_test_world(8, 8);
+        let brute_force = |p: Pos2| {
+            (0..world.points.len())
+                .min_by(|&a, &b| {
+                    world.points[a]
+                        .distance_sq(p)
+                        .total_cmp(&world.points[b].distance_sq(p))
+                })
+                .unwrap()
+        };
+
+        // 单元格 27 的 Voronoi 区域内（离站点不到半个间距）
+        for offset in [
+            egui::vec2(0.0, 0.0),
+            egui::vec2(3.0, -2.0),
+            egui::vec2(-2.5, 3.5),
+        ] {
+            assert_eq!(world.pick_cell(world.points[27] + offset), Some(27));
+        }
+
+        // 任意位置都与最近点一致
+        for p in [
+            Pos2::new(12.3, 47.9),
+            Pos2::new(88.0, 1.0),
+            Pos2::new(44.4, 66.6),
+        ] {
+            assert_eq!(world.pick_cell(p), Some(brute_force(p)));
+        }
+
+        // 地图范围之外退回到最近点
+        assert_eq!(world.pick_cell(Pos2::new(-50.0, -50.0)), Some(0));
+        let far = Pos2::new(400.0, 45.0);
+        assert_eq!(world.pick_cell(far), Some(brute_force(far)));
+    }
+
+    #[test]
+    fn test_features_two_islands_one_lake() {
+        let (cols, rows) = (9usize, 7usize);
+        let mut world = create_test_world(cols as u32, rows as u32);
+        world.neighbors = grid_neighbors(cols, rows);
+
+        world.cells_data.height = vec![0; cols * rows];
+        // 小岛 (1,1)-(2,1)；大岛 x 4..=7, y 1..=5，中间 (5,3)-(6,3) 为湖泊
+        let mut land = vec![(1, 1), (2, 1)];
+        for y in 1..=5 {
+            for x in 4..=7 {
+                if !(y == 3 && (x == 5 || x == 6)) {
+                    land.push((x, y));
+                }
+            }
+        }
+        for &(x, y) in &land {
+            world.cells_data.height[y * cols + x] = SEA_LEVEL + 10;
+        }
+
+        let features = world.features();
+        let of_type = |kind| {
+            features
+                .iter()
+                .filter(|f| f.feature_type == kind)
+                .collect::<Vec<_>>()
+        };
+        let islands = of_type(FeatureType::Island);
+        let lakes = of_type(FeatureType::Lake);
+        assert_eq!(islands.len(), 2);
+        assert_eq!(lakes.len(), 1);
+        assert_eq!(of_type(FeatureType::Ocean).len(), 1);
+
+        let mut island_sizes: Vec<usize> = islands.iter().map(|f| f.size()).collect();
+        island_sizes.sort();
+        assert_eq!(island_sizes, vec![2, 18]);
+        let mut lake_cells = lakes[0].cells.clone();
+        lake_cells.sort();
+        assert_eq!(lake_cells, vec![3 * cols + 5, 3 * cols + 6]);
+
+        let areas = world.cell_areas();
+        for f in &features {
+            let expected: f32 = f.cells.iter().map(|&i| areas[i]).sum();
+            assert!(f.area > 0.0 && (f.area - expected).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_apply_brush_tapers_to_radius() {
+        let mut world = create_test_world(20, 20);
+        world.cells_data.height = vec![100; world.cell_count()];
+        let center = world.points[10 * 20 + 10];
+        let radius = 60.0;
+
+        world.apply_brush(center, radius, 50);
+
+        let heights = &world.cells_data.height;
+        let center_idx = 10 * 20 + 10;
+        assert_eq!(heights[center_idx], 150);
+        for (i, &p) in world.points.iter().enumerate() {
+            let d = p.distance(center);
+            if d >= radius {
+                assert_eq!(heights[i], 100, "cell {} outside the brush changed", i);
+            } else if i != center_idx {
+                assert!(heights[i] <= heights[center_idx]);
+                assert!(heights[i] >= 100);
+            }
+            // 靠近边缘的单元格几乎不变
+            if d > radius * 0.9 && d < radius {
+                assert!(heights[i] <= 101, "cell {} at {}: {}", i, d, heights[i]);
+            }
+        }
+
+        // 距离越远抬升越少
+        let ring_mean = |lo: f32, hi: f32| {
+            let ring: Vec<f32> = (0..world.cell_count())
+                .filter(|&i| (lo..hi).contains(&world.points[i].distance(center)))
+                .map(|i| heights[i] as f32)
+                .collect();
+            ring.iter().sum::<f32>() / ring.len() as f32
+        };
+        assert!(ring_mean(5.0, 20.0) > ring_mean(20.0, 40.0));
+        assert!(ring_mean(20.0, 40.0) > ring_mean(40.0, 60.0));
+
+        // 降低并夹到 0
+        world.apply_brush(center, radius, -1000);
+        assert_eq!(world.cells_data.height[center_idx], 0);
+    }
+
+    #[test]
+    fn test_undo_restores_heights() {
+        let mut world = create_test_world(12, 12);
+        world.cells_data.height = (0..world.cell_count()).map(|i| (i % 200) as u8).collect();
+        let original = world.cells_data.height.clone();
+        let center = world.points[6 * 12 + 6];
+
+        world.push_snapshot();
+        world.apply_brush(center, 40.0, 30);
+        let after_first = world.cells_data.height.clone();
+        world.push_snapshot();
+        world.apply_brush(center, 25.0, -60);
+        assert_ne!(world.cells_data.height, after_first);
+
+        assert!(world.undo());
+        assert_eq!(world.cells_data.height, after_first);
+        assert!(world.undo());
+        assert_eq!(world.cells_data.height, original);
+        assert!(!world.undo());
+
+        // 深度为 2 时只保留最近两次快照
+        world.set_undo_depth(2);
+        for delta in [10, 20, 30] {
+            world.push_snapshot();
+            world.apply_brush(center, 40.0, delta);
+        }
+        assert_eq!(world.undo_len(), 2);
+        assert!(world.undo() && world.undo());
+        assert!(!world.undo());
+        assert_ne!(world.cells_data.height, original);
+    }
+
+    #[test]
+    fn test_height_at_cell_center() {
+        let mut world = create_test_world(8, 6);
+        world.cells_data.height = (0..world.cell_count()).map(|i| (i * 5) as u8).collect();
+
+        for (i, &p) in world.points.iter().enumerate() {
+            assert_eq!(world.height_at(p), Some(world.cells_data.height[i]));
+            if let Some(h) = world.height_at_interpolated(p) {
+                assert!((h - world.cells_data.height[i] as f32).abs() < 1e-3);
+            }
+        }
+        assert_eq!(world.height_at(Pos2::new(-1.0, 5.0)), None);
+        assert_eq!(world.height_at_interpolated(Pos2::new(5.0, 1e4)), None);
+
+        // 三角形内部的插值落在三个顶点高度之间
+        let [a, b, c] =
+            [world.triangles[0], world.triangles[1], world.triangles[2]].map(|v| v as usize);
+        let centroid = Pos2::new(
+            (world.points[a].x + world.points[b].x + world.points[c].x) / 3.0,
+            (world.points[a].y + world.points[b].y + world.points[c].y) / 3.0,
+        );
+        let expected = [a, b, c]
+            .iter()
+            .map(|&v| world.cells_data.height[v] as f32)
+            .sum::<f32>()
+            / 3.0;
+        let h = world.height_at_interpolated(centroid).unwrap();
+        assert!((h - expected).abs() < 1e-3, "{} vs {}", h, expected);
+    }
+
+    #[test]
+    fn test_largest_landmass() {
+        let (cols, rows) = (8usize, 6usize);
+        let mut world = create_test_world(cols as u32, rows as u32);
+        world.neighbors = grid_neighbors(cols, rows);
+        world.cells_data.height = vec![0; cols * rows];
+        assert!(world.largest_landmass().is_empty());
+
+        // 小岛 (1,1)；大岛 x 3..=6, y 2..=4
+        let set_land = |world: &mut World, x: usize, y: usize| {
+            world.cells_data.height[y * cols + x] = SEA_LEVEL + 10;
+        };
+        set_land(&mut world, 1, 1);
+        let mut big = Vec::new();
+        for y in 2..=4 {
+            for x in 3..=6 {
+                set_land(&mut world, x, y);
+                big.push(y * cols + x);
+            }
+        }
+        assert_eq!(world.largest_landmass(), big);
+
+        // 大小相同时取最小索引更小的陆块
+        world.cells_data.height = vec![0; cols * rows];
+        set_land(&mut world, 5, 4);
+        set_land(&mut world, 1, 1);
+        assert_eq!(world.largest_landmass(), vec![cols + 1]);
+    }
+
+    #[test]
+    fn test_terrain_stats_two_islands() {
+        // 6x6 网格，使用手工构建的四邻接关系
+        let (cols, rows) = (6usize, 6usize);
+        let mut world = create_test_world(cols as u32, rows as u32);
+        world.neighbors = grid_neighbors(cols, rows);
+
+        world.cells_data.height = vec![0; cols * rows];
+        // 单格岛 (1,1)：4 条海岸边；两格岛 (4,3)-(4,4)：6 条海岸边
+        for (x, y) in [(1, 1), (4, 3), (4, 4)] {
+            world.cells_data.height[y * cols + x] = SEA_LEVEL + 10;
+        }
+
+        let stats = world.terrain_stats();
+        assert_eq!(stats.island_count, 2);
+        assert_eq!(stats.lake_count, 0);
+        assert_eq!(stats.land_cells, 3);
+        assert_eq!(stats.sea_cells, 33);
+        assert_eq!(stats.coastline_edges, 10);
+        assert!((stats.ocean_ratio - 33.0 / 36.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_custom_sea_level_drives_land_sea_split() {
+        let (cols, rows) = (6usize, 6usize);
+        let mut world = create_test_world(cols as u32, rows as u32);
+        world.neighbors = grid_neighbors(cols, rows);
+        world.cells_data.height = vec![0; cols * rows];
+        // 高于默认海平面但低于自定义海平面的单元格应视为水体
+        world.cells_data.height[cols + 1] = SEA_LEVEL + 20;
+        world.cells_data.height[3 * cols + 4] = 120;
+
+        assert_eq!(world.terrain_stats().land_cells, 2);
+
+        world.sea_level = SEA_LEVEL + 40;
+        let stats = world.terrain_stats();
+        assert_eq!(stats.land_cells, 1);
+        assert_eq!(stats.island_count, 1);
+
+        let (_, _, flags) = unpack_cell(world.merge_neighbor_metadata()[cols + 1]);
+        assert_eq!(flags & CELL_FLAG_LAND, 0);
+        let (_, _, flags) = unpack_cell(world.merge_neighbor_metadata()[3 * cols + 4]);
+        assert_ne!(flags & CELL_FLAG_LAND, 0);
+    }
+
+    #[test]
+    fn test_pack_unpack_cell() {
+        let packed = pack_cell(200, 7, CELL_FLAG_LAND | CELL_FLAG_COAST);
+        assert_eq!(
+            unpack_cell(packed),
+            (200, 7, CELL_FLAG_LAND | CELL_FLAG_COAST)
+        );
+        assert_eq!(packed >> 24, 0);
+    }
+
+    #[test]
+    fn test_merge_neighbor_metadata_round_trip() {
+        let mut world = create_test_world(8, 8);
+        let n = world.cell_count();
+        for i in 0..n {
+            world.cells_data.height[i] = (i * 37 % 256) as u8;
+            world.cells_data.biome[i] = (i % 12) as u8;
+        }
+
+        let packed = world.merge_neighbor_metadata();
+        assert_eq!(packed.len(), n);
+
+        for (i, &value) in packed.iter().enumerate() {
+            let (height, biome, flags) = unpack_cell(value);
+            assert_eq!(height, world.cells_data.height[i]);
+            assert_eq!(biome, world.cells_data.biome[i]);
+            assert_eq!(flags & CELL_FLAG_LAND != 0, height >= SEA_LEVEL);
+            if flags & CELL_FLAG_COAST != 0 {
+                assert!(flags & CELL_FLAG_LAND != 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_biome_fractions() {
+        let mut world = create_test_world(10, 10);
+        let n = world.cell_count();
+        for i in 0..n {
+            world.cells_data.biome[i] = (i % 3) as u8;
+            world.cells_data.height[i] = if i % 4 == 0 { 0 } else { 100 };
+        }
+
+        let fractions = world.biome_fractions();
+        assert_eq!(
+            fractions.iter().map(|f| f.biome).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+
+        let total_sum: f32 = fractions.iter().map(|f| f.total).sum();
+        let land_sum: f32 = fractions.iter().map(|f| f.land).sum();
+        assert!((total_sum - 1.0).abs() < 1e-5);
+        assert!((land_sum - 1.0).abs() < 1e-5);
+
+        // 与逐单元格直接统计的结果一致
+        let areas = world.cell_areas();
+        let total_area: f32 = areas.iter().sum();
+        let land_area: f32 = (0..n)
+            .filter(|&i| world.cells_data.height[i] >= SEA_LEVEL)
+            .map(|i| areas[i])
+            .sum();
+        for fraction in &fractions {
+            let cells = (0..n).filter(|&i| world.cells_data.biome[i] == fraction.biome);
+            let (mut total, mut land) = (0.0, 0.0);
+            for i in cells {
+                total += areas[i];
+                if world.cells_data.height[i] >= SEA_LEVEL {
+                    land += areas[i];
+                }
+            }
+            assert!((fraction.total - total / total_area).abs() < 1e-4);
+            assert!((fraction.land - land / land_area).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_sample_gradient_on_ramp() {
+        let mut world = create_test_world(10, 10);
+        for i in 0..world.cell_count() {
+            // 沿 +X 方向线性升高，每单位 2
+            world.cells_data.height[i] = (world.points[i].x * 2.0) as u8;
+        }
+
+        for pos in [
+            Pos2::new(20.0, 20.0),
+            Pos2::new(47.0, 63.0),
+            Pos2::new(81.0, 33.0),
+        ] {
+            let gradient = world.sample_gradient_at(pos);
+            assert!((gradient.x - 2.0).abs() < 1e-3, "{:?}", gradient);
+            assert!(gradient.y.abs() < 1e-3, "{:?}", gradient);
+        }
+
+        // 三角剖分之外
+        assert_eq!(
+            world.sample_gradient_at(Pos2::new(-10.0, -10.0)),
+            Vec2::ZERO
+        );
+    }
+
+    #[test]
+    fn test_enclosing_triangle_matches_full_scan() {
+        let params = GenerationParameters::builder()
+            .seed(5)
+            .num_points(800)
+            .size(200, 100)
+            .build()
+            .unwrap();
+        let world = World::generate(&params);
+
+        let contains = |tri: &[usize], pos: Pos2| {
+            let [pa, pb, pc] = [tri[0], tri[1], tri[2]].map(|v| world.points[v]);
+            let cross = |a: Pos2, b: Pos2| (b - a).x * (pos - a).y - (b - a).y * (pos - a).x;
+            let d = [cross(pa, pb), cross(pb, pc), cross(pc, pa)];
+            d.iter().all(|&v| v >= 0.0) || d.iter().all(|&v| v <= 0.0)
+        };
+
+        for y in (0..100).step_by(7) {
+            for x in (0..200).step_by(9) {
+                let pos = Pos2::new(x as f32 + 0.3, y as f32 + 0.6);
+                let scanned = world
+                    .triangles
+                    .chunks_exact(3)
+                    .any(|tri| contains(&tri.iter().map(|&v| v as usize).collect::<Vec<_>>(), pos));
+                match world.enclosing_triangle(pos) {
+                    Some(tri) => assert!(contains(&tri, pos), "{:?}", pos),
+                    None => assert!(!scanned, "{:?}", pos),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_plate_debug_field() {
+        use crate::terrain::TerrainGenerationMode;
+
+        let config = |debug: bool| TerrainConfig {
+            mode: TerrainGenerationMode::Layered {
+                seed: 3,
+                num_plates: 5,
+            },
+            debug_plate_field: debug,
+            ..Default::default()
+        };
+
+        let mut plain = create_test_world(12, 12);
+        plain.generate_terrain(config(false));
+        assert!(plain.plate_field.is_none());
+
+        let mut debug = create_test_world(12, 12);
+        debug.generate_terrain(config(true));
+
+        // 真实高度不受影响
+        assert_eq!(debug.cells_data.height, plain.cells_data.height);
+        assert_eq!(debug.plate_ids, plain.plate_ids);
+
+        // 每个板块对应一个不同的灰度值
+        let field = debug.plate_field.as_ref().unwrap();
+        let mut mapping = std::collections::HashMap::new();
+        for (&id, &gray) in debug.plate_ids.iter().zip(field) {
+            assert_eq!(*mapping.entry(id).or_insert(gray), gray);
+        }
+        let mut grays: Vec<u8> = mapping.values().copied().collect();
+        grays.sort_unstable();
+        grays.dedup();
+        assert_eq!(grays.len(), mapping.len());
+        assert!(mapping.len() > 1);
+    }
+
+    #[test]
+    fn test_wrapping_topology_seam_continuity() {
+        use crate::terrain::TerrainGenerationMode;
+
+        // 跨越左右接缝的邻居对的平均高差，与全部邻居对的平均高差之比
+        let seam_ratio = |world: &World| {
+            let half_width = world.width as f32 / 2.0;
+            let (mut seam, mut seam_count) = (0.0, 0);
+            let (mut all, mut all_count) = (0.0, 0);
+            for (i, list) in world.neighbors.iter().enumerate() {
+                for &j in list {
+                    let diff = (world.cells_data.height[i] as f32
+                        - world.cells_data.height[j as usize] as f32)
+                        .abs();
+                    all += diff;
+                    all_count += 1;
+                    if (world.points[i].x - world.points[j as usize].x).abs() > half_width {
+                        seam += diff;
+                        seam_count += 1;
+                    }
+                }
+            }
+            assert!(seam_count > 0, "no neighbors across the seam");
+            (seam / seam_count as f32) / (all / all_count as f32).max(1.0)
+        };
+
+        for topology in [Topology::CylinderX, Topology::Sphere] {
+            let params = GenerationParameters::builder()
+                .seed(17)
+                .num_points(3000)
+                .size(400, 200)
+                .topology(topology)
+                .build()
+                .unwrap();
+            let world = World::generate(&params);
+            let ratio = seam_ratio(&world);
+            assert!(ratio < 2.0, "{:?} template seam ratio {}", topology, ratio);
+
+            // 分层模式：构造、区域和细节层都按拓扑采样
+            let mut layered = World::with_topology(400, 200, world.points.clone(), topology);
+            layered.generate_terrain(TerrainConfig {
+                mode: TerrainGenerationMode::Layered {
+                    seed: 17,
+                    num_plates: 6,
+                },
+                ..Default::default()
+            });
+            let ratio = seam_ratio(&layered);
+            assert!(ratio < 2.0, "{:?} layered seam ratio {}", topology, ratio);
+        }
+    }
+
+    #[test]
+    fn test_topology_edges() {
+        let (width, height) = (300, 150);
+        let mut points = Vec::new();
+        for y in 0..15 {
+            for x in 0..30 {
+                let offset = if y % 2 == 0 { 0.0 } else { 2.5 };
+                points.push(Pos2::new(
+                    x as f32 * 10.0 + offset + 3.0,
+                    y as f32 * 10.0 + 5.0,
+                ));
+            }
+        }
+
+        let build = |topology| World::with_topology(width, height, points.clone(), topology);
+        let linked = |world: &World, a: &dyn Fn(Pos2) -> bool, b: &dyn Fn(Pos2) -> bool| {
+            (0..world.cell_count()).any(|i| {
+                a(world.points[i])
+                    && world.neighbors[i]
+                        .iter()
+                        .any(|&j| b(world.points[j as usize]))
+            })
+        };
+        let left = |p: Pos2| p.x < 10.0;
+        let right = |p: Pos2| p.x > width as f32 - 10.0;
+        let top = |p: Pos2| p.y < 10.0;
+        let bottom = |p: Pos2| p.y > height as f32 - 10.0;
+        let far_top = |p: Pos2| p.y < 10.0 && (p.x - 150.0).abs() < 40.0;
+        let min_degree = |world: &World| world.neighbors.iter().map(Vec::len).min().unwrap();
+
+        let plane = build(Topology::Plane);
+        assert!(!linked(&plane, &left, &right));
+        assert!(min_degree(&plane) <= 3);
+
+        let cylinder = build(Topology::CylinderX);
+        assert!(linked(&cylinder, &left, &right));
+        assert!(!linked(&cylinder, &top, &bottom));
+        assert!(!linked(&cylinder, &left, &far_top));
+
+        let sphere = build(Topology::Sphere);
+        assert!(linked(&sphere, &left, &right));
+        // 极点附近的单元格与经度相差半圈的单元格相邻
+        let near_zero = |p: Pos2| p.y < 10.0 && p.x < 40.0;
+        assert!(linked(&sphere, &near_zero, &far_top));
+        assert!(min_degree(&sphere) >= 5, "{}", min_degree(&sphere));
+        assert_eq!(sphere.neighbors, build(Topology::Sphere).neighbors);
+    }
+}
@@ -26,6 +26,10 @@ pub struct EdgeIndex {
     /// cells[y * grid_width + x] = 该格子内的边索引列表
     /// 边索引 i 对应 indices[i*2] 和 indices[i*2+1]
     cells: Vec<Vec<u32>>,
+    /// 每条边的包围盒
+    edge_bounds: Vec<Rect>,
+    /// 每条边的长度，用于按缩放级别剔除短边
+    edge_lengths: Vec<f32>,
 }
 
 impl EdgeIndex {
@@ -43,6 +47,8 @@ impl EdgeIndex {
         let grid_height = ((bounds.height() / cell_size).ceil() as usize).max(1);
 
         let mut cells = vec![Vec::new(); grid_width * grid_height];
+        let mut edge_bounds = Vec::with_capacity(indices.len() / 2);
+        let mut edge_lengths = Vec::with_capacity(indices.len() / 2);
 
         // 遍历每条边
         for (edge_idx, chunk) in indices.chunks(2).enumerate() {
@@ -55,6 +61,8 @@ impl EdgeIndex {
 
             // 获取边的包围盒覆盖的所有格子
             let edge_rect = Rect::from_two_pos(p1, p2);
+            edge_bounds.push(edge_rect);
+            edge_lengths.push(p1.distance(p2));
             let cell_indices =
                 Self::get_covered_cells(edge_rect, bounds, cell_size, grid_width, grid_height);
 
@@ -70,6 +78,8 @@ impl EdgeIndex {
             grid_height,
             bounds,
             cells,
+            edge_bounds,
+            edge_lengths,
         }
     }
 
@@ -84,6 +94,8 @@ impl EdgeIndex {
                 grid_height: 1,
                 bounds,
                 cells: vec![Vec::new()],
+                edge_bounds: Vec::new(),
+                edge_lengths: Vec::new(),
             };
         }
 
@@ -160,6 +172,34 @@ impl EdgeIndex {
         result
     }
 
+    /// 获取包围盒与矩形相交的所有边索引
+    ///
+    /// 只比较包围盒，不需要顶点数据，适合做粗筛。跨越多个格子的长边
+    /// 会在它覆盖的每个格子中出现，结果已去重并按边索引升序排列。
+    ///
+    /// # 参数
+    /// - `min`: 查询矩形的最小角
+    /// - `max`: 查询矩形的最大角
+    pub fn edges_in_rect(&self, min: Pos2, max: Pos2) -> Vec<usize> {
+        let query = Rect::from_min_max(min, max);
+        let cell_indices = Self::get_covered_cells(
+            query,
+            self.bounds,
+            self.cell_size,
+            self.grid_width,
+            self.grid_height,
+        );
+
+        let mut result: Vec<usize> = cell_indices
+            .into_iter()
+            .flat_map(|cell_idx| self.cells[cell_idx].iter().map(|&e| e as usize))
+            .filter(|&e| self.edge_bounds[e].intersects(query))
+            .collect();
+        result.sort_unstable();
+        result.dedup();
+        result
+    }
+
     /// 获取与矩形视口相交的边索引（返回原始 indices 数组格式）
     ///
     /// 直接返回用于渲染的索引数组片段。
@@ -168,11 +208,28 @@ impl EdgeIndex {
         vertices: &[Pos2],
         indices: &[u32],
         view_rect: Rect,
+    ) -> Vec<u32> {
+        self.get_visible_indices_lod(vertices, indices, view_rect, 0.0)
+    }
+
+    /// 获取与视口相交且长度不小于 `min_length` 的边索引（返回原始 indices 数组格式）
+    ///
+    /// 用于低缩放级别的细节层次（LOD）：屏幕上短于一两个像素的边既看不清
+    /// 又会产生锯齿，直接跳过。`min_length` 为 0 时等价于 [`Self::get_visible_indices`]。
+    pub fn get_visible_indices_lod(
+        &self,
+        vertices: &[Pos2],
+        indices: &[u32],
+        view_rect: Rect,
+        min_length: f32,
     ) -> Vec<u32> {
         let visible_edges = self.query_visible_edges(vertices, indices, view_rect);
 
         let mut result = Vec::with_capacity(visible_edges.len() * 2);
         for edge_idx in visible_edges {
+            if self.edge_lengths[edge_idx as usize] < min_length {
+                continue;
+            }
             let i = edge_idx as usize * 2;
             if i + 1 < indices.len() {
                 result.push(indices[i]);
@@ -183,6 +240,14 @@ impl EdgeIndex {
         result
     }
 
+    /// 所有边的平均长度，没有边时为 0
+    pub fn average_edge_length(&self) -> f32 {
+        if self.edge_lengths.is_empty() {
+            return 0.0;
+        }
+        self.edge_lengths.iter().sum::<f32>() / self.edge_lengths.len() as f32
+    }
+
     /// 获取格子尺寸
     pub fn cell_size(&self) -> f32 {
         self.cell_size
@@ -348,4 +413,98 @@ mod tests {
             rect
         ));
     }
+
+    #[test]
+    fn test_edges_in_rect() {
+        let vertices = vec![
+            Pos2::new(5.0, 5.0),
+            Pos2::new(15.0, 15.0),
+            Pos2::new(0.0, 50.0),
+            Pos2::new(100.0, 55.0),
+            Pos2::new(80.0, 80.0),
+            Pos2::new(95.0, 95.0),
+            Pos2::new(40.0, 0.0),
+            Pos2::new(45.0, 100.0),
+        ];
+        // 边 0: 左上角短边；边 1: 横贯全图的长边；边 2: 右下角短边；边 3: 纵贯全图的长边
+        let indices = vec![0, 1, 2, 3, 4, 5, 6, 7];
+        let bounds = Rect::from_min_max(Pos2::ZERO, Pos2::new(100.0, 100.0));
+        let index = EdgeIndex::build(&vertices, &indices, bounds, 10.0);
+
+        // 查询左侧中部：只与两条长边相交，长边跨越多个格子但只返回一次
+        let result = index.edges_in_rect(Pos2::new(20.0, 30.0), Pos2::new(60.0, 60.0));
+        assert_eq!(result, vec![1, 3]);
+
+        let result = index.edges_in_rect(Pos2::new(0.0, 0.0), Pos2::new(20.0, 20.0));
+        assert_eq!(result, vec![0]);
+
+        let result = index.edges_in_rect(Pos2::new(0.0, 0.0), Pos2::new(100.0, 100.0));
+        assert_eq!(result, vec![0, 1, 2, 3]);
+
+        assert!(index
+            .edges_in_rect(Pos2::new(60.0, 10.0), Pos2::new(90.0, 30.0))
+            .is_empty());
+    }
+
+    /// 20x20 格点上的横边和竖边，间距 10
+    fn lattice_edges() -> (Vec<Pos2>, Vec<u32>) {
+        let n = 20;
+        let vertices: Vec<Pos2> = (0..n * n)
+            .map(|i| Pos2::new((i % n) as f32 * 10.0, (i / n) as f32 * 10.0))
+            .collect();
+        let mut indices = Vec::new();
+        for y in 0..n {
+            for x in 0..n {
+                let i = (y * n + x) as u32;
+                if x + 1 < n {
+                    indices.extend([i, i + 1]);
+                }
+                if y + 1 < n {
+                    indices.extend([i, i + n as u32]);
+                }
+            }
+        }
+        (vertices, indices)
+    }
+
+    #[test]
+    fn test_viewport_culling_subset_and_full() {
+        let (vertices, indices) = lattice_edges();
+        let bounds = Rect::from_min_max(Pos2::ZERO, Pos2::new(190.0, 190.0));
+        let index = EdgeIndex::build_auto(&vertices, &indices, bounds);
+        let edge_count = indices.len() / 2;
+
+        // 很小的视口（包含格点 (50, 50)）只返回附近的几条边
+        let tiny = Rect::from_min_max(Pos2::new(48.0, 48.0), Pos2::new(53.0, 53.0));
+        let visible = index.get_visible_indices(&vertices, &indices, tiny);
+        assert!(!visible.is_empty());
+        assert!(visible.len() / 2 < 10, "{} edges", visible.len() / 2);
+
+        // 覆盖全图的视口返回所有边
+        let full = bounds.expand(1.0);
+        let mut visible = index.query_visible_edges(&vertices, &indices, full);
+        visible.sort_unstable();
+        assert_eq!(visible, (0..edge_count as u32).collect::<Vec<_>>());
+        assert_eq!(
+            index.get_visible_indices(&vertices, &indices, full).len(),
+            indices.len()
+        );
+    }
+
+    #[test]
+    fn test_lod_skips_short_edges() {
+        let (mut vertices, indices) = lattice_edges();
+        // 把一个角上的格点压扁，得到几条很短的边
+        vertices[0] = Pos2::new(9.5, 0.0);
+        let bounds = Rect::from_min_max(Pos2::ZERO, Pos2::new(190.0, 190.0));
+        let index = EdgeIndex::build_auto(&vertices, &indices, bounds);
+        assert!((index.average_edge_length() - 10.0).abs() < 0.1);
+
+        let full = bounds.expand(1.0);
+        let all = index.get_visible_indices_lod(&vertices, &indices, full, 0.0);
+        let coarse = index.get_visible_indices_lod(&vertices, &indices, full, 1.0);
+        assert_eq!(all.len(), indices.len());
+        assert_eq!(coarse.len(), indices.len() - 2);
+        assert!(!coarse.chunks(2).any(|e| e == [0, 1]));
+    }
 }
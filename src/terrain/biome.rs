@@ -0,0 +1,174 @@
+// 生物群系分类
+//
+// 参考 Whittaker 生物群系图：由温度和湿度两个维度查表决定生物群系。
+// 温度由纬度和海拔估算，湿度由调用方提供（例如降水模拟的结果）。
+
+use eframe::egui::{Color32, Pos2};
+
+use super::climate::{lapse_rate, temperature_field};
+
+/// 海平面以上多少高度内的陆地视为海滩
+const BEACH_HEIGHT: u8 = 2;
+
+/// 生物群系
+///
+/// 判别值即 `CellsData::biome` 中存储的索引。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum Biome {
+    Ocean = 0,
+    Beach,
+    Desert,
+    Savanna,
+    Grassland,
+    Forest,
+    Rainforest,
+    Taiga,
+    Tundra,
+    Snow,
+}
+
+impl Biome {
+    /// 所有生物群系，按索引排序
+    pub const ALL: [Biome; 10] = [
+        Biome::Ocean,
+        Biome::Beach,
+        Biome::Desert,
+        Biome::Savanna,
+        Biome::Grassland,
+        Biome::Forest,
+        Biome::Rainforest,
+        Biome::Taiga,
+        Biome::Tundra,
+        Biome::Snow,
+    ];
+
+    /// 存储用的索引
+    pub fn index(self) -> u8 {
+        self as u8
+    }
+
+    /// 从索引还原，未知索引返回 None
+    pub fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+
+    /// 渲染用的代表色
+    pub fn color(self) -> Color32 {
+        match self {
+            Biome::Ocean => Color32::from_rgb(32, 80, 170),
+            Biome::Beach => Color32::from_rgb(210, 180, 140),
+            Biome::Desert => Color32::from_rgb(222, 196, 128),
+            Biome::Savanna => Color32::from_rgb(177, 170, 84),
+            Biome::Grassland => Color32::from_rgb(132, 170, 80),
+            Biome::Forest => Color32::from_rgb(46, 120, 56),
+            Biome::Rainforest => Color32::from_rgb(20, 92, 44),
+            Biome::Taiga => Color32::from_rgb(74, 110, 88),
+            Biome::Tundra => Color32::from_rgb(150, 156, 132),
+            Biome::Snow => Color32::from_rgb(240, 244, 248),
+        }
+    }
+
+    /// Whittaker 查表
+    ///
+    /// `temperature` 和 `moisture` 都是 0.0-1.0 的归一化值。
+    pub fn whittaker(temperature: f32, moisture: f32) -> Self {
+        match temperature {
+            t if t < 0.1 => Biome::Snow,
+            t if t < 0.25 => Biome::Tundra,
+            t if t < 0.45 => {
+                if moisture < 0.25 {
+                    Biome::Grassland
+                } else {
+                    Biome::Taiga
+                }
+            }
+            t if t < 0.7 => match moisture {
+                m if m < 0.2 => Biome::Desert,
+                m if m < 0.45 => Biome::Grassland,
+                _ => Biome::Forest,
+            },
+            _ => match moisture {
+                m if m < 0.2 => Biome::Desert,
+                m if m < 0.45 => Biome::Savanna,
+                m if m < 0.75 => Biome::Forest,
+                _ => Biome::Rainforest,
+            },
+        }
+    }
+}
+
+/// 由单元格位置计算纬度
+///
+/// 返回 -1.0（地图顶端，北极）到 1.0（地图底端，南极）的归一化纬度，0.0 为赤道。
+pub fn latitudes(points: &[Pos2], map_height: f32) -> Vec<f32> {
+    let h = map_height.max(1.0);
+    points
+        .iter()
+        .map(|p| (p.y / h * 2.0 - 1.0).clamp(-1.0, 1.0))
+        .collect()
+}
+
+/// 按高度、湿度和纬度为每个单元格分类生物群系
+///
+/// 温度由 [`temperature_field`] 按 [`lapse_rate`] 计算（最高点比海平面低 0.6）。
+/// 低于 `sea_level` 为海洋；海平面附近的温暖陆地为海滩；其余查 Whittaker 表。
+pub fn classify_biomes(
+    heights: &[u8],
+    moisture: &[f32],
+    latitude: &[f32],
+    sea_level: u8,
+) -> Vec<Biome> {
+    let temperatures = temperature_field(heights, latitude, lapse_rate(sea_level), sea_level);
+
+    heights
+        .iter()
+        .zip(moisture)
+        .zip(temperatures)
+        .map(|((&h, &m), temperature)| {
+            if h < sea_level {
+                return Biome::Ocean;
+            }
+
+            if h < sea_level.saturating_add(BEACH_HEIGHT) && temperature >= 0.25 {
+                Biome::Beach
+            } else {
+                Biome::whittaker(temperature, m.clamp(0.0, 1.0))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::heightmap::SEA_LEVEL;
+
+    #[test]
+    fn test_classify_snow_and_forest() {
+        let heights = [250, SEA_LEVEL + 30, SEA_LEVEL - 5, SEA_LEVEL];
+        let moisture = [0.5, 0.6, 0.5, 0.5];
+        let latitude = [0.7, 0.05, 0.0, 0.0];
+
+        let biomes = classify_biomes(&heights, &moisture, &latitude, SEA_LEVEL);
+        assert_eq!(biomes[0], Biome::Snow);
+        assert_eq!(biomes[1], Biome::Forest);
+        assert_eq!(biomes[2], Biome::Ocean);
+        assert_eq!(biomes[3], Biome::Beach);
+    }
+
+    #[test]
+    fn test_latitudes_and_index_round_trip() {
+        let points = [
+            Pos2::new(0.0, 0.0),
+            Pos2::new(5.0, 50.0),
+            Pos2::new(9.0, 100.0),
+        ];
+        assert_eq!(latitudes(&points, 100.0), vec![-1.0, 0.0, 1.0]);
+
+        for biome in Biome::ALL {
+            assert_eq!(Biome::from_index(biome.index()), Some(biome));
+        }
+        assert_eq!(Biome::from_index(200), None);
+    }
+}
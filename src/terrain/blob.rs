@@ -11,6 +11,9 @@ use std::collections::VecDeque;
 /// Bounds info: (points, (min_x, max_x, min_y, max_y), (width, height))
 type BoundsInfo<'a> = (&'a [Pos2], (f32, f32, f32, f32), (f32, f32));
 
+/// 路径方向偏置强度：垂直于目标方向的一步代价放大到 `1 + PATH_ANGLE_BIAS` 倍
+const PATH_ANGLE_BIAS: f32 = 1.5;
+
 /// Blob 生成器配置
 #[derive(Debug, Clone)]
 pub struct BlobConfig {
@@ -94,6 +97,17 @@ impl BlobConfig {
             skip_probability: 0.07,
         }
     }
+
+    /// 用命令级参数覆盖衰减因子和随机扰动，None 表示保留原值
+    pub fn with_overrides(mut self, blob_power: Option<f32>, jitter: Option<f32>) -> Self {
+        if let Some(blob_power) = blob_power {
+            self.blob_power = blob_power;
+        }
+        if let Some(jitter) = jitter {
+            self.jitter = jitter;
+        }
+        self
+    }
 }
 
 /// BFS 扩散式地形生成器
@@ -354,6 +368,7 @@ impl BlobGenerator {
     /// 添加 BFS 扩散式山脉
     ///
     /// 首先找到从起点到终点的路径，然后从路径向两侧扩散。
+    /// `angle_bias` 为山脉走向（弧度），用于让路径保持笔直，见 [`Self::find_path`]。
     #[allow(clippy::too_many_arguments)]
     pub fn add_range(
         &self,
@@ -363,6 +378,7 @@ impl BlobGenerator {
         start_idx: usize,
         end_idx: usize,
         height: f32,
+        angle_bias: Option<f32>,
         rng: &mut impl Rng,
     ) {
         if start_idx >= heights.len() || end_idx >= heights.len() {
@@ -370,7 +386,7 @@ impl BlobGenerator {
         }
 
         // 1. 找到从起点到终点的路径
-        let range = self.find_path(cells, neighbors, start_idx, end_idx, rng);
+        let range = self.find_path(cells, neighbors, start_idx, end_idx, angle_bias, rng);
         if range.is_empty() {
             return;
         }
@@ -467,6 +483,7 @@ impl BlobGenerator {
         start_idx: usize,
         end_idx: usize,
         depth: f32,
+        angle_bias: Option<f32>,
         rng: &mut impl Rng,
     ) {
         if start_idx >= heights.len() || end_idx >= heights.len() {
@@ -474,7 +491,7 @@ impl BlobGenerator {
         }
 
         // 找到路径
-        let range = self.find_path(cells, neighbors, start_idx, end_idx, rng);
+        let range = self.find_path(cells, neighbors, start_idx, end_idx, angle_bias, rng);
         if range.is_empty() {
             return;
         }
@@ -524,12 +541,16 @@ impl BlobGenerator {
     ///
     /// 使用贪心算法，每一步选择距离终点最近的邻居。
     /// 加入随机性使路径不完全直线。
+    ///
+    /// `angle_bias` 为期望的行进方向（弧度）：偏离该方向的一步会按
+    /// `1 + PATH_ANGLE_BIAS × (1 - cos 夹角)` 放大距离代价，使路径贴近指定走向。
     fn find_path(
         &self,
         cells: &[Pos2],
         neighbors: &[Vec<u32>],
         start: usize,
         end: usize,
+        angle_bias: Option<f32>,
         rng: &mut impl Rng,
     ) -> Vec<usize> {
         let mut path = vec![start];
@@ -538,6 +559,7 @@ impl BlobGenerator {
 
         let mut current = start;
         let end_pos = cells[end];
+        let direction = angle_bias.map(|a| (a.cos(), a.sin()));
 
         while current != end {
             let mut best_neighbor = None;
@@ -563,6 +585,12 @@ impl BlobGenerator {
                     dist /= 2.0;
                 }
 
+                if let Some((dx, dy)) = direction {
+                    let step = pos - cells[current];
+                    let alignment = (step.x * dx + step.y * dy) / step.length().max(f32::EPSILON);
+                    dist *= 1.0 + PATH_ANGLE_BIAS * (1.0 - alignment);
+                }
+
                 if dist < best_dist {
                     best_dist = dist;
                     best_neighbor = Some(n);
@@ -633,4 +661,59 @@ mod tests {
         let nearest = BlobGenerator::find_nearest_cell(&cells, 4.0, 4.0);
         assert_eq!(nearest, 2); // 最接近 (5, 5)
     }
+
+    #[test]
+    fn test_find_path_follows_angle() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        let (cols, rows, spacing) = (30usize, 20usize, 10.0f32);
+        let cells: Vec<Pos2> = (0..cols * rows)
+            .map(|i| {
+                Pos2::new(
+                    ((i % cols) as f32 + rng.random_range(0.1..0.9)) * spacing,
+                    ((i / cols) as f32 + rng.random_range(0.1..0.9)) * spacing,
+                )
+            })
+            .collect();
+        // 八邻接，近似 Voronoi 邻接
+        let neighbors: Vec<Vec<u32>> = (0..cols * rows)
+            .map(|i| {
+                let (x, y) = ((i % cols) as i32, (i / cols) as i32);
+                let mut list = Vec::new();
+                for (dx, dy) in [
+                    (-1, -1),
+                    (0, -1),
+                    (1, -1),
+                    (-1, 0),
+                    (1, 0),
+                    (-1, 1),
+                    (0, 1),
+                    (1, 1),
+                ] {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if nx >= 0 && ny >= 0 && (nx as usize) < cols && (ny as usize) < rows {
+                        list.push((ny as usize * cols + nx as usize) as u32);
+                    }
+                }
+                list
+            })
+            .collect();
+
+        let start = BlobGenerator::find_nearest_cell(&cells, 30.0, 100.0);
+        let end = BlobGenerator::find_nearest_cell(&cells, 270.0, 100.0);
+        let generator = BlobGenerator::new(BlobConfig::default());
+        let path = generator.find_path(&cells, &neighbors, start, end, Some(0.0), &mut rng);
+
+        assert_eq!(*path.last().unwrap(), end);
+        let net = cells[end] - cells[start];
+        assert!(net.x.abs() > net.y.abs() * 5.0, "{:?}", net);
+        // 路径贴近水平线，且每一步都不向后走
+        let y0 = cells[start].y;
+        for pair in path.windows(2) {
+            let (a, b) = (cells[pair[0]], cells[pair[1]]);
+            assert!(b.x >= a.x - spacing * 0.5, "step back {:?} -> {:?}", a, b);
+            assert!((b.y - y0).abs() < spacing * 3.0, "drifted to {:?}", b);
+        }
+    }
 }
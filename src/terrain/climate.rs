@@ -0,0 +1,174 @@
+// 气候模拟（湿度、降水、温度）
+//
+// 湿气从海洋出发，沿盛行风方向在单元格图上逐步平流：
+// 平地上缓慢降水，迎风坡因地形抬升大量降水，越过山脊后空气变干形成雨影区。
+// 温度由纬度决定基础值，再按海拔递减。
+
+use eframe::egui::Pos2;
+
+use super::heightmap::SEA_LEVEL;
+
+/// 平地上每步降下的空气湿度比例
+const BASE_RAIN: f32 = 0.05;
+/// 地形抬升降水系数：上升 1 个归一化高度单位时的降水比例增量
+const OROGRAPHIC_RAIN: f32 = 4.0;
+
+/// 默认气温直减率：从默认海平面到最高点 (255) 降温 0.6
+pub const DEFAULT_LAPSE_RATE: f32 = 0.6 / (255.0 - SEA_LEVEL as f32);
+
+/// 给定海平面下的气温直减率：从海平面到最高点 (255) 降温 0.6
+pub fn lapse_rate(sea_level: u8) -> f32 {
+    0.6 / (255.0 - sea_level.min(254) as f32)
+}
+
+/// 计算每个单元格的温度
+///
+/// 温度 = `1 - |纬度|` - `lapse_rate × (高度 - sea_level)`，海平面以下不降温。
+/// `latitude` 为 -1.0（北极）到 1.0（南极）的归一化纬度（见 [`super::biome::latitudes`]），
+/// `lapse_rate` 为每单位高度的降温量。结果限制在 0.0-1.0：
+/// 1.0 为赤道海平面，0.0 为极点海平面或更冷。
+pub fn temperature_field(
+    heights: &[u8],
+    latitude: &[f32],
+    lapse_rate: f32,
+    sea_level: u8,
+) -> Vec<f32> {
+    heights
+        .iter()
+        .zip(latitude)
+        .map(|(&h, &lat)| {
+            let base = 1.0 - lat.abs().min(1.0);
+            let above_sea = h.saturating_sub(sea_level) as f32;
+            (base - lapse_rate * above_sea).clamp(0.0, 1.0)
+        })
+        .collect()
+}
+
+/// 模拟盛行风驱动的湿度分布
+///
+/// `wind_angle` 为风吹向的方向（弧度，0 表示吹向 +X）。低于 `sea_level` 的
+/// 海洋单元格的空气湿度恒为 1；
+/// 每一步陆地单元格从上风邻居（按与风向夹角加权）接收空气湿度，按
+/// `BASE_RAIN + OROGRAPHIC_RAIN × 上升高度` 的比例降水，剩余湿度继续向下风传播。
+/// `iterations` 决定湿气最多能传播多少个单元格。
+///
+/// 返回每个单元格 0.0-1.0 的湿度：海洋为 1，陆地为
+/// `到达的空气湿度 × (0.5 + 降水比例)`，因此迎风坡湿润、背风坡干燥。
+pub fn simulate_moisture(
+    heights: &[u8],
+    cells: &[Pos2],
+    neighbors: &[Vec<u32>],
+    wind_angle: f32,
+    iterations: u32,
+    sea_level: u8,
+) -> Vec<f32> {
+    let n = heights.len();
+    let wind = (wind_angle.cos(), wind_angle.sin());
+    let is_ocean = |i: usize| heights[i] < sea_level;
+
+    // 上风邻居及其权重（从邻居指向本单元格的方向与风向的余弦）
+    let upwind: Vec<Vec<(usize, f32)>> = (0..n)
+        .map(|i| {
+            neighbors[i]
+                .iter()
+                .filter_map(|&j| {
+                    let j = j as usize;
+                    let (dx, dy) = (cells[i].x - cells[j].x, cells[i].y - cells[j].y);
+                    let len = (dx * dx + dy * dy).sqrt();
+                    let w = (dx * wind.0 + dy * wind.1) / len.max(f32::EPSILON);
+                    (w > 0.0).then_some((j, w))
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut air: Vec<f32> = (0..n)
+        .map(|i| if is_ocean(i) { 1.0 } else { 0.0 })
+        .collect();
+    let mut incoming = vec![0.0f32; n];
+    let mut rate = vec![BASE_RAIN; n];
+
+    for _ in 0..iterations {
+        let mut next = air.clone();
+        for i in (0..n).filter(|&i| !is_ocean(i)) {
+            let total: f32 = upwind[i].iter().map(|&(_, w)| w).sum();
+            if total <= 0.0 {
+                incoming[i] = 0.0;
+                next[i] = 0.0;
+                continue;
+            }
+
+            let humidity = upwind[i].iter().map(|&(j, w)| air[j] * w).sum::<f32>() / total;
+            let upwind_height = upwind[i]
+                .iter()
+                .map(|&(j, w)| heights[j] as f32 * w)
+                .sum::<f32>()
+                / total;
+            let rise = (heights[i] as f32 - upwind_height).max(0.0) / 255.0;
+
+            rate[i] = (BASE_RAIN + OROGRAPHIC_RAIN * rise).min(1.0);
+            incoming[i] = humidity;
+            next[i] = humidity * (1.0 - rate[i]);
+        }
+        air = next;
+    }
+
+    (0..n)
+        .map(|i| {
+            if is_ocean(i) {
+                1.0
+            } else {
+                (incoming[i] * (0.5 + rate[i])).clamp(0.0, 1.0)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::test_grid::grid;
+
+    #[test]
+    fn test_temperature_lapse_rate() {
+        let lapse_rate = 0.004;
+        let heights = [SEA_LEVEL + 10, SEA_LEVEL + 110, SEA_LEVEL - 10];
+        let latitude = [0.2, 0.2, 1.0];
+
+        let t = temperature_field(&heights, &latitude, lapse_rate, SEA_LEVEL);
+        let expected = lapse_rate * 100.0;
+        assert!((t[0] - t[1] - expected).abs() < 1e-4, "{:?}", t);
+        assert!((t[0] - (0.8 - lapse_rate * 10.0)).abs() < 1e-4);
+        // 极点海面为 0
+        assert_eq!(t[2], 0.0);
+    }
+
+    #[test]
+    fn test_rain_shadow_behind_ridge() {
+        let (cols, rows) = (20usize, 6usize);
+        let (cells, neighbors) = grid(cols, rows, 10.0);
+
+        // 西侧三列海洋，第 10 列是南北走向的山脊，风向东吹
+        let heights: Vec<u8> = (0..cols * rows)
+            .map(|i| match i % cols {
+                x if x < 3 => 5,
+                10 => 200,
+                _ => 40,
+            })
+            .collect();
+
+        let moisture = simulate_moisture(&heights, &cells, &neighbors, 0.0, 40, SEA_LEVEL);
+        assert!(moisture.iter().all(|m| (0.0..=1.0).contains(m)));
+
+        let column =
+            |x: usize| (0..rows).map(|y| moisture[y * cols + x]).sum::<f32>() / rows as f32;
+        let windward = column(9) + column(10);
+        let leeward = column(11) + column(12);
+        assert!(
+            windward > leeward * 2.0,
+            "windward {} vs leeward {}",
+            windward,
+            leeward
+        );
+    }
+}
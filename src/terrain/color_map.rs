@@ -0,0 +1,482 @@
+// 高度配色表
+//
+// 将高度值映射为颜色，供 GPU 渲染和图像导出共用。
+
+use eframe::egui::Color32;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use super::heightmap::SEA_LEVEL;
+
+/// 默认环境光下限：完全背光处仍保留基础颜色的比例
+pub const DEFAULT_AMBIENT: f32 = 0.35;
+
+/// 颜色停止点
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorStop {
+    /// 停止点所在高度（0-255，可以是小数）
+    pub height: f32,
+    /// 该高度处的颜色
+    pub color: Color32,
+    /// 高度带名称，从本停止点开始直到下一个带名称的停止点
+    pub label: Option<String>,
+}
+
+impl ColorStop {
+    pub fn new(height: f32, rgb: (u8, u8, u8)) -> Self {
+        Self {
+            height,
+            color: Color32::from_rgb(rgb.0, rgb.1, rgb.2),
+            label: None,
+        }
+    }
+
+    /// 设置高度带名称
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+}
+
+/// 配色文件解析错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum RampError {
+    /// 读取文件失败
+    Io(String),
+    /// 行格式不是 `height r g b` 或数值无法解析
+    Parse { line: usize, message: String },
+    /// 高度不在 0..=255 范围内
+    HeightOutOfRange { line: usize, height: f32 },
+    /// 高度没有严格递增
+    NotIncreasing {
+        line: usize,
+        height: f32,
+        previous: f32,
+    },
+    /// 没有任何停止点
+    Empty,
+}
+
+impl std::fmt::Display for RampError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(message) => write!(f, "failed to read color ramp: {}", message),
+            Self::Parse { line, message } => write!(f, "Line {}: {}", line, message),
+            Self::HeightOutOfRange { line, height } => {
+                write!(f, "Line {}: height {} is outside 0..=255", line, height)
+            }
+            Self::NotIncreasing {
+                line,
+                height,
+                previous,
+            } => write!(
+                f,
+                "Line {}: height {} is not greater than previous height {}",
+                line, height, previous
+            ),
+            Self::Empty => write!(f, "color ramp has no stops"),
+        }
+    }
+}
+
+impl std::error::Error for RampError {}
+
+/// 高度配色表
+///
+/// 停止点按高度升序排列，相邻停止点之间使用 smoothstep 插值。
+/// 两个停止点高度相同时在该处形成断层（例如海平面处海洋与沙滩的分界）。
+///
+/// 停止点颜色按 sRGB 编码给出。默认直接在 sRGB 数值上插值；启用
+/// [`with_linear_interpolation`](Self::with_linear_interpolation) 后先按 sRGB 传递函数
+/// 解码到线性光强、插值后再编码回 sRGB，差异较大的两色之间的中间色不会发灰发暗。
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeightColorMap {
+    stops: Vec<ColorStop>,
+    /// 山体阴影的环境光下限 (0..1)，见 [`HeightColorMap::color_with_shade`]
+    ambient: f32,
+    /// 是否在线性 RGB 空间插值
+    linear: bool,
+}
+
+static DEFAULT_MAP: LazyLock<HeightColorMap> = LazyLock::new(HeightColorMap::default);
+
+impl Default for HeightColorMap {
+    /// 默认配色，海岸位于默认海平面 [`SEA_LEVEL`]（见 [`HeightColorMap::for_sea_level`]）
+    fn default() -> Self {
+        Self::for_sea_level(SEA_LEVEL)
+    }
+}
+
+impl HeightColorMap {
+    /// 默认配色：深蓝海洋渐变到浅水，陆地从沙滩经绿地、丘陵、山地到雪峰
+    ///
+    /// 海洋停止点按 `sea_level` 等比缩放，陆地停止点分布在 `sea_level` 到 255 之间。
+    pub fn for_sea_level(sea_level: u8) -> Self {
+        let sea = sea_level as f32;
+        let land = |ratio: f32| sea + (255.0 - sea) * ratio;
+
+        Self::new(vec![
+            ColorStop::new(0.0, (8, 24, 58)).with_label("deep sea"), // 深海
+            ColorStop::new(sea * 0.3, (16, 48, 120)),                // 中深海
+            ColorStop::new(sea * 0.7, (32, 80, 170)).with_label("shallows"), // 浅海
+            ColorStop::new(sea, (60, 120, 190)),                     // 近岸浅水
+            ColorStop::new(sea, (210, 180, 140)).with_label("coast"), // 沙滩/海岸
+            ColorStop::new(land(0.05), (34, 120, 50)).with_label("lowland"), // 低地森林
+            ColorStop::new(land(0.15), (50, 150, 50)),               // 中绿
+            ColorStop::new(land(0.25), (100, 170, 60)),              // 浅绿
+            ColorStop::new(land(0.35), (160, 180, 70)).with_label("upland"), // 草地/灌木
+            ColorStop::new(land(0.45), (200, 170, 80)).with_label("hills"), // 干草/丘陵
+            ColorStop::new(land(0.55), (180, 130, 70)),              // 低山
+            ColorStop::new(land(0.70), (130, 100, 70)).with_label("mountain"), // 山地
+            ColorStop::new(land(0.85), (150, 145, 140)).with_label("alpine"), // 岩石
+            ColorStop::new(255.0, (255, 255, 255)),                  // 雪峰
+        ])
+    }
+
+    /// 从停止点创建配色表，停止点会按高度排序
+    pub fn new(mut stops: Vec<ColorStop>) -> Self {
+        stops.sort_by(|a, b| a.height.total_cmp(&b.height));
+        Self {
+            stops,
+            ambient: DEFAULT_AMBIENT,
+            linear: false,
+        }
+    }
+
+    /// 链式设置是否在线性 RGB 空间插值（默认在 sRGB 空间插值）
+    pub fn with_linear_interpolation(mut self, linear: bool) -> Self {
+        self.linear = linear;
+        self
+    }
+
+    /// 链式设置环境光下限（0..1）
+    pub fn with_ambient(mut self, ambient: f32) -> Self {
+        self.ambient = ambient.clamp(0.0, 1.0);
+        self
+    }
+
+    /// 环境光下限
+    pub fn ambient(&self) -> f32 {
+        self.ambient
+    }
+
+    /// 从配色文件加载
+    ///
+    /// 文件格式见 [`Self::from_ramp_str`]。
+    pub fn from_ramp_file(path: &Path) -> Result<Self, RampError> {
+        let text = std::fs::read_to_string(path).map_err(|e| RampError::Io(e.to_string()))?;
+        Self::from_ramp_str(&text)
+    }
+
+    /// 解析配色文本
+    ///
+    /// 每行一个停止点 `height r g b`，高度为 0..=255 的数（可以是小数），
+    /// 颜色分量为 0-255 的整数；空行和 `#` 开头的注释行被忽略。
+    /// 高度必须严格递增，因此自定义配色不支持同高度断层。
+    pub fn from_ramp_str(text: &str) -> Result<Self, RampError> {
+        let mut stops: Vec<ColorStop> = Vec::new();
+
+        for (idx, raw) in text.lines().enumerate() {
+            let line = idx + 1;
+            let content = raw.trim();
+            if content.is_empty() || content.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = content.split_whitespace().collect();
+            if fields.len() != 4 {
+                return Err(RampError::Parse {
+                    line,
+                    message: format!("expected `height r g b`, got {} fields", fields.len()),
+                });
+            }
+            let height: f32 = fields[0].parse().map_err(|_| RampError::Parse {
+                line,
+                message: format!("invalid height '{}'", fields[0]),
+            })?;
+            let channel = |s: &str| {
+                s.parse::<u8>().map_err(|_| RampError::Parse {
+                    line,
+                    message: format!("invalid color component '{}'", s),
+                })
+            };
+            let rgb = (
+                channel(fields[1])?,
+                channel(fields[2])?,
+                channel(fields[3])?,
+            );
+
+            if !(0.0..=255.0).contains(&height) {
+                return Err(RampError::HeightOutOfRange { line, height });
+            }
+            if let Some(prev) = stops.last() {
+                if height <= prev.height {
+                    return Err(RampError::NotIncreasing {
+                        line,
+                        height,
+                        previous: prev.height,
+                    });
+                }
+            }
+            stops.push(ColorStop::new(height, rgb));
+        }
+
+        if stops.is_empty() {
+            return Err(RampError::Empty);
+        }
+        Ok(Self::new(stops))
+    }
+
+    /// 共享的默认配色表
+    pub fn default_map() -> &'static HeightColorMap {
+        &DEFAULT_MAP
+    }
+
+    /// 所有停止点
+    pub fn stops(&self) -> &[ColorStop] {
+        &self.stops
+    }
+
+    /// 获取高度对应的颜色
+    pub fn color(&self, height: u8) -> Color32 {
+        let Some(last) = self.stops.last() else {
+            return Color32::BLACK;
+        };
+        let h = height as f32;
+
+        // 区间为左闭右开，相同高度的停止点之间不会被选中
+        for pair in self.stops.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            if h >= a.height && h < b.height {
+                let t = (h - a.height) / (b.height - a.height);
+                return if self.linear {
+                    lerp_color_linear(a.color, b.color, t)
+                } else {
+                    lerp_color(a.color, b.color, t)
+                };
+            }
+        }
+
+        if h < self.stops[0].height {
+            self.stops[0].color
+        } else {
+            last.color
+        }
+    }
+
+    /// 获取叠加山体阴影后的颜色
+    ///
+    /// `shade` 为 [`hillshade`](super::relief::hillshade) 的输出 (0..1)：
+    /// 1.0 返回原色，0.0 返回原色乘以环境光下限，中间线性过渡，阴影不会变成纯黑。
+    pub fn color_with_shade(&self, height: u8, shade: f32) -> Color32 {
+        let base = self.color(height);
+        let factor = self.ambient + (1.0 - self.ambient) * shade.clamp(0.0, 1.0);
+        let scale = |c: u8| (c as f32 * factor).round() as u8;
+        Color32::from_rgb(scale(base.r()), scale(base.g()), scale(base.b()))
+    }
+
+    /// 获取高度所在高度带的名称
+    pub fn label(&self, height: u8) -> &str {
+        let h = height as f32;
+        self.stops
+            .iter()
+            .filter(|s| s.height <= h)
+            .filter_map(|s| s.label.as_deref())
+            .next_back()
+            .unwrap_or("")
+    }
+
+    /// 生成图例
+    ///
+    /// 在 0-255 范围内均匀采样 `steps` 个高度（包含两端），
+    /// 返回 (高度, RGBA 颜色, 高度带名称)。
+    pub fn legend(&self, steps: usize) -> Vec<(u8, [u8; 4], String)> {
+        let steps = steps.max(2);
+        (0..steps)
+            .map(|i| {
+                let height = (i as f32 * 255.0 / (steps - 1) as f32).round() as u8;
+                let color = self.color(height).to_array();
+                (height, color, self.label(height).to_string())
+            })
+            .collect()
+    }
+}
+
+/// 在两个颜色之间平滑插值
+fn lerp_color(c1: Color32, c2: Color32, t: f32) -> Color32 {
+    // 使用平滑的 smoothstep 插值，避免线性过渡的生硬感
+    let t = t.clamp(0.0, 1.0);
+    let t_smooth = t * t * (3.0 - 2.0 * t);
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t_smooth) as u8;
+    Color32::from_rgb(
+        mix(c1.r(), c2.r()),
+        mix(c1.g(), c2.g()),
+        mix(c1.b(), c2.b()),
+    )
+}
+
+/// 在线性 RGB 空间中平滑插值
+///
+/// sRGB 分量先解码为线性光强（sRGB 传递函数，近似 gamma 2.2），插值后再编码回 sRGB。
+fn lerp_color_linear(c1: Color32, c2: Color32, t: f32) -> Color32 {
+    let t = t.clamp(0.0, 1.0);
+    let t_smooth = t * t * (3.0 - 2.0 * t);
+    let mix = |a: u8, b: u8| {
+        let (la, lb) = (srgb_to_linear(a), srgb_to_linear(b));
+        linear_to_srgb(la + (lb - la) * t_smooth)
+    };
+    Color32::from_rgb(
+        mix(c1.r(), c2.r()),
+        mix(c1.g(), c2.g()),
+        mix(c1.b(), c2.b()),
+    )
+}
+
+/// sRGB 分量 (0-255) 解码为线性光强 (0..1)
+fn srgb_to_linear(c: u8) -> f32 {
+    let c = c as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// 线性光强 (0..1) 编码为 sRGB 分量 (0-255)
+fn linear_to_srgb(l: f32) -> u8 {
+    let l = l.clamp(0.0, 1.0);
+    let c = if l <= 0.0031308 {
+        l * 12.92
+    } else {
+        1.055 * l.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sea_level_discontinuity() {
+        let map = HeightColorMap::default();
+        assert_eq!(map.color(0), Color32::from_rgb(8, 24, 58));
+        assert_eq!(map.color(SEA_LEVEL), Color32::from_rgb(210, 180, 140));
+        assert_eq!(map.color(255), Color32::WHITE);
+        assert_eq!(map.label(SEA_LEVEL - 1), "shallows");
+        assert_eq!(map.label(SEA_LEVEL), "coast");
+    }
+
+    #[test]
+    fn test_linear_interpolation_midpoint() {
+        let stops = vec![
+            ColorStop::new(0.0, (0, 0, 0)),
+            ColorStop::new(200.0, (255, 255, 255)),
+        ];
+        let srgb = HeightColorMap::new(stops.clone());
+        let linear = HeightColorMap::new(stops).with_linear_interpolation(true);
+
+        // 黑白中点：sRGB 插值得到数值中点，线性插值得到 50% 光强（sRGB 约 188）
+        assert_eq!(srgb.color(100), Color32::from_rgb(127, 127, 127));
+        assert_eq!(linear.color(100), Color32::from_rgb(188, 188, 188));
+
+        // 两端不受插值空间影响
+        for h in [0, 200] {
+            assert_eq!(srgb.color(h), linear.color(h));
+        }
+    }
+
+    #[test]
+    fn test_from_ramp_file() {
+        let path = std::env::temp_dir().join("sekai_test_ramp.txt");
+        std::fs::write(
+            &path,
+            "# 自定义配色\n0 0 0 80\n20 40 90 200\n\n120.5 90 160 60\n255 250 250 250\n",
+        )
+        .unwrap();
+        let map = HeightColorMap::from_ramp_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let heights: Vec<f32> = map.stops().iter().map(|s| s.height).collect();
+        assert_eq!(heights, vec![0.0, 20.0, 120.5, 255.0]);
+        assert_eq!(map.color(20), Color32::from_rgb(40, 90, 200));
+        assert_eq!(map.color(255), Color32::from_rgb(250, 250, 250));
+
+        assert_eq!(
+            HeightColorMap::from_ramp_str("0 0 0 0\n100 1 1 1\n50 2 2 2\n"),
+            Err(RampError::NotIncreasing {
+                line: 3,
+                height: 50.0,
+                previous: 100.0
+            })
+        );
+        assert_eq!(
+            HeightColorMap::from_ramp_str("0 0 0 0\n300 1 1 1\n"),
+            Err(RampError::HeightOutOfRange {
+                line: 2,
+                height: 300.0
+            })
+        );
+        assert!(matches!(
+            HeightColorMap::from_ramp_str("0 0 0\n"),
+            Err(RampError::Parse { line: 1, .. })
+        ));
+        assert_eq!(
+            HeightColorMap::from_ramp_str("# empty\n"),
+            Err(RampError::Empty)
+        );
+        assert!(matches!(
+            HeightColorMap::from_ramp_file(Path::new("/nonexistent/ramp.txt")),
+            Err(RampError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_color_with_shade() {
+        let map = HeightColorMap::default().with_ambient(0.5);
+        let height = SEA_LEVEL + 60;
+        let base = map.color(height);
+
+        assert_eq!(map.color_with_shade(height, 1.0), base);
+        let half = |c: u8| (c as f32 * 0.5).round() as u8;
+        assert_eq!(
+            map.color_with_shade(height, 0.0),
+            Color32::from_rgb(half(base.r()), half(base.g()), half(base.b()))
+        );
+
+        // 阴影越深颜色越暗，超出范围的值按边界处理
+        let mid = map.color_with_shade(height, 0.5);
+        assert!(mid.g() < base.g() && mid.g() > half(base.g()));
+        assert_eq!(map.color_with_shade(height, 2.0), base);
+    }
+
+    #[test]
+    fn test_legend_covers_range_and_labels_align() {
+        let map = HeightColorMap::default();
+        let legend = map.legend(16);
+
+        assert_eq!(legend.len(), 16);
+        assert_eq!(legend.first().unwrap().0, 0);
+        assert_eq!(legend.last().unwrap().0, 255);
+        assert!(legend.windows(2).all(|w| w[0].0 < w[1].0));
+
+        for (height, color, label) in &legend {
+            assert_eq!(*color, map.color(*height).to_array());
+
+            // 名称来自高度不超过当前值的最后一个带名称的停止点
+            let expected = map
+                .stops()
+                .iter()
+                .rfind(|s| s.label.is_some() && s.height <= *height as f32)
+                .and_then(|s| s.label.clone())
+                .unwrap();
+            assert_eq!(label, &expected);
+        }
+
+        // 每个高度带的起点都以该带命名
+        for stop in map.stops().iter().filter(|s| s.label.is_some()) {
+            let start = stop.height.ceil() as u8;
+            assert_eq!(map.label(start), stop.label.as_deref().unwrap());
+        }
+    }
+}
@@ -11,8 +11,16 @@
 // Mask 3
 // SeaRatio 0.7
 // ```
+//
+// 支持 `#` / `//` 单行注释、`/* ... */` 块注释、`@section name` 段落标记，
+// `let NAME = value` 常量定义（之后的数值参数可直接使用 NAME），
+// 以及 `Repeat N { ... }` 循环块（块内数值参数可使用从 0 开始的循环序号 `$i`，
+// 并支持 `+`、`*` 简单运算，如 `20+$i*15`）
 
-use super::template::{InvertAxis, MaskMode, StraitDirection, TerrainCommand, TerrainTemplate};
+use super::template::{
+    CurveKind, InvertAxis, MaskMode, SmoothTarget, StraitDirection, TerrainCommand, TerrainTemplate,
+};
+use std::collections::HashMap;
 use std::f32::consts::PI;
 
 /// DSL 解析错误
@@ -28,6 +36,23 @@ impl std::fmt::Display for ParseError {
     }
 }
 
+/// 是否是合法的常量名（字母或下划线开头，仅含字母、数字、下划线）
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// 数值解析失败时的错误信息，未定义的常量名单独提示
+fn number_error(s: &str) -> String {
+    let s = s.trim();
+    if is_identifier(s) {
+        format!("Undefined variable: {}", s)
+    } else {
+        format!("Invalid number: {}", s)
+    }
+}
+
 /// 解析数值范围 (如 "40-60" 或 "50")
 fn parse_range(s: &str) -> Result<(f32, f32), String> {
     if s.contains('-') {
@@ -38,26 +63,29 @@ fn parse_range(s: &str) -> Result<(f32, f32), String> {
         let min: f32 = parts[0]
             .trim()
             .parse()
-            .map_err(|_| format!("Invalid number: {}", parts[0]))?;
+            .map_err(|_| number_error(parts[0]))?;
         let max: f32 = parts[1]
             .trim()
             .parse()
-            .map_err(|_| format!("Invalid number: {}", parts[1]))?;
+            .map_err(|_| number_error(parts[1]))?;
         Ok((min, max))
     } else {
-        let val: f32 = s
-            .trim()
-            .parse()
-            .map_err(|_| format!("Invalid number: {}", s))?;
+        let val: f32 = s.trim().parse().map_err(|_| number_error(s))?;
         Ok((val, val))
     }
 }
 
 /// 解析单个数值
 fn parse_f32(s: &str) -> Result<f32, String> {
-    s.trim()
-        .parse()
-        .map_err(|_| format!("Invalid number: {}", s))
+    s.trim().parse().map_err(|_| number_error(s))
+}
+
+/// 解析可选的覆盖参数：缺省或 `_` 表示不覆盖
+fn parse_override(arg: Option<&&str>) -> Result<Option<f32>, String> {
+    match arg {
+        None | Some(&"_") => Ok(None),
+        Some(s) => parse_f32(s).map(Some),
+    }
 }
 
 /// 解析整数
@@ -101,11 +129,14 @@ fn parse_line(line: &str, line_num: usize) -> Result<Option<TerrainCommand>, Par
     };
 
     match cmd.as_str() {
-        // Hill count height x y [radius]
+        // Hill count height x y [radius] [blob_power] [jitter]
         // 示例: Hill 3 80-120 20-80 20-80
+        // 示例: Hill 1 90 50 50 10 0.99 0.2（更平缓、更规则的大丘陵；`_` 表示保留默认值）
         "hill" => {
             if args.len() < 4 {
-                return Err(make_err("Hill requires: count height x y [radius]"));
+                return Err(make_err(
+                    "Hill requires: count height x y [radius] [blob_power] [jitter]",
+                ));
             }
             let count = parse_u32(args[0]).map_err(|e| make_err(&e))?;
             let height = parse_range(args[1]).map_err(|e| make_err(&e))?;
@@ -117,12 +148,17 @@ fn parse_line(line: &str, line_num: usize) -> Result<Option<TerrainCommand>, Par
                 (0.08, 0.15) // 默认半径
             };
 
+            let blob_power = parse_override(args.get(5)).map_err(|e| make_err(&e))?;
+            let jitter = parse_override(args.get(6)).map_err(|e| make_err(&e))?;
+
             Ok(Some(TerrainCommand::Hill {
                 count,
                 height,
                 x,
                 y,
                 radius,
+                blob_power,
+                jitter,
             }))
         }
 
@@ -203,10 +239,12 @@ fn parse_line(line: &str, line_num: usize) -> Result<Option<TerrainCommand>, Par
             }))
         }
 
-        // Pit count depth x y [radius]
+        // Pit count depth x y [radius] [blob_power] [jitter]
         "pit" => {
             if args.len() < 4 {
-                return Err(make_err("Pit requires: count depth x y [radius]"));
+                return Err(make_err(
+                    "Pit requires: count depth x y [radius] [blob_power] [jitter]",
+                ));
             }
             let count = parse_u32(args[0]).map_err(|e| make_err(&e))?;
             let depth = parse_range(args[1]).map_err(|e| make_err(&e))?;
@@ -218,12 +256,71 @@ fn parse_line(line: &str, line_num: usize) -> Result<Option<TerrainCommand>, Par
                 (0.08, 0.15)
             };
 
+            let blob_power = parse_override(args.get(5)).map_err(|e| make_err(&e))?;
+            let jitter = parse_override(args.get(6)).map_err(|e| make_err(&e))?;
+
             Ok(Some(TerrainCommand::Pit {
                 count,
                 depth,
                 x,
                 y,
                 radius,
+                blob_power,
+                jitter,
+            }))
+        }
+
+        // Island target_cells height x y
+        // 示例: Island 400 60-80 40-60 40-60（约 400 个单元格的连通岛屿）
+        "island" => {
+            if args.len() < 4 {
+                return Err(make_err("Island requires: target_cells height x y"));
+            }
+            let target_cells = parse_u32(args[0]).map_err(|e| make_err(&e))?;
+            let height = parse_range(args[1]).map_err(|e| make_err(&e))?;
+            let x = percent_to_ratio(parse_range(args[2]).map_err(|e| make_err(&e))?);
+            let y = percent_to_ratio(parse_range(args[3]).map_err(|e| make_err(&e))?);
+            Ok(Some(TerrainCommand::Island {
+                target_cells,
+                height,
+                x,
+                y,
+            }))
+        }
+
+        // Caldera height x y radius
+        // 示例: Caldera 60-80 40-60 40-60 8-12
+        "caldera" => {
+            if args.len() < 4 {
+                return Err(make_err("Caldera requires: height x y radius"));
+            }
+            let height = parse_range(args[0]).map_err(|e| make_err(&e))?;
+            let x = percent_to_ratio(parse_range(args[1]).map_err(|e| make_err(&e))?);
+            let y = percent_to_ratio(parse_range(args[2]).map_err(|e| make_err(&e))?);
+            let radius = percent_to_ratio(parse_range(args[3]).map_err(|e| make_err(&e))?);
+            Ok(Some(TerrainCommand::Caldera {
+                height,
+                x,
+                y,
+                radius,
+            }))
+        }
+
+        // Atolls count radius x y
+        // 示例: Atolls 3 5-8 20-80 20-80
+        "atolls" | "atoll" => {
+            if args.len() < 4 {
+                return Err(make_err("Atolls requires: count radius x y"));
+            }
+            let count = parse_u32(args[0]).map_err(|e| make_err(&e))?;
+            let radius = percent_to_ratio(parse_range(args[1]).map_err(|e| make_err(&e))?);
+            let x = percent_to_ratio(parse_range(args[2]).map_err(|e| make_err(&e))?);
+            let y = percent_to_ratio(parse_range(args[3]).map_err(|e| make_err(&e))?);
+            Ok(Some(TerrainCommand::Atolls {
+                count,
+                radius,
+                x,
+                y,
             }))
         }
 
@@ -254,6 +351,61 @@ fn parse_line(line: &str, line_num: usize) -> Result<Option<TerrainCommand>, Par
             Ok(Some(TerrainCommand::Smooth { iterations }))
         }
 
+        // SmoothCoast iterations band
+        // 示例: SmoothCoast 2 3（平滑海岸线两侧 3 步以内的单元格 2 轮）
+        "smoothcoast" => {
+            if args.len() < 2 {
+                return Err(make_err("SmoothCoast requires: iterations band"));
+            }
+            let iterations = parse_u32(args[0]).map_err(|e| make_err(&e))?;
+            let band = parse_u32(args[1]).map_err(|e| make_err(&e))?;
+            Ok(Some(TerrainCommand::SmoothCoast { iterations, band }))
+        }
+
+        // Terrace steps [strength]
+        // 示例: Terrace 6 0.7
+        "terrace" => {
+            if args.is_empty() {
+                return Err(make_err("Terrace requires: steps [strength]"));
+            }
+            let steps = parse_u32(args[0]).map_err(|e| make_err(&e))?;
+            let strength = match args.get(1) {
+                Some(s) => parse_f32(s).map_err(|e| make_err(&e))?,
+                None => 1.0,
+            };
+            Ok(Some(TerrainCommand::Terrace { steps, strength }))
+        }
+
+        // FloodFill x y level
+        // 示例: FloodFill 50 50 25（从地图中心所在的盆地开始，填平到高度 25）
+        "floodfill" | "fill" => {
+            if args.len() < 3 {
+                return Err(make_err("FloodFill requires: x y level"));
+            }
+            let start_x = parse_f32(args[0]).map_err(|e| make_err(&e))? / 100.0;
+            let start_y = parse_f32(args[1]).map_err(|e| make_err(&e))? / 100.0;
+            let level = parse_f32(args[2]).map_err(|e| make_err(&e))?;
+            Ok(Some(TerrainCommand::FloodFill {
+                start_x,
+                start_y,
+                level,
+            }))
+        }
+
+        // Warp strength frequency
+        // 示例: Warp 30 0.01（最大偏移 30 个地图单位）
+        "warp" => {
+            if args.len() < 2 {
+                return Err(make_err("Warp requires: strength frequency"));
+            }
+            let strength = parse_f32(args[0]).map_err(|e| make_err(&e))?;
+            let frequency = parse_f32(args[1]).map_err(|e| make_err(&e))?;
+            Ok(Some(TerrainCommand::Warp {
+                strength,
+                frequency,
+            }))
+        }
+
         // Erode iterations [rain] [capacity] [deposition]
         "erode" => {
             if args.is_empty() {
@@ -285,23 +437,122 @@ fn parse_line(line: &str, line_num: usize) -> Result<Option<TerrainCommand>, Par
             }))
         }
 
-        // Mask mode [strength]
+        // WindErosion iterations direction_degrees [strength]
+        // 示例: Wind 6 90 0.4（风吹向 +Y）
+        "winderosion" | "wind" => {
+            if args.len() < 2 {
+                return Err(make_err(
+                    "WindErosion requires: iterations direction [strength]",
+                ));
+            }
+            let iterations = parse_u32(args[0]).map_err(|e| make_err(&e))?;
+            let direction = parse_f32(args[1]).map_err(|e| make_err(&e))?.to_radians();
+            let strength = if args.len() > 2 {
+                parse_f32(args[2]).map_err(|e| make_err(&e))?
+            } else {
+                0.3
+            };
+            Ok(Some(TerrainCommand::WindErosion {
+                direction,
+                strength,
+                iterations,
+            }))
+        }
+
+        // Gradient angle strength（角度为弧度）
+        // 示例: Gradient 0.785 40（向右下方升高）
+        "gradient" | "tilt" => {
+            if args.len() < 2 {
+                return Err(make_err("Gradient requires: angle strength"));
+            }
+            let angle = parse_f32(args[0]).map_err(|e| make_err(&e))?;
+            let strength = parse_f32(args[1]).map_err(|e| make_err(&e))?;
+            Ok(Some(TerrainCommand::Gradient { angle, strength }))
+        }
+
+        // River count source_height [meander]
+        // 示例: River 3 180 0.3
+        "river" => {
+            if args.len() < 2 {
+                return Err(make_err("River requires: count source_height [meander]"));
+            }
+            let count = parse_u32(args[0]).map_err(|e| make_err(&e))?;
+            let source_height = parse_f32(args[1]).map_err(|e| make_err(&e))?;
+            let meander = if args.len() > 2 {
+                parse_f32(args[2]).map_err(|e| make_err(&e))?
+            } else {
+                0.2
+            };
+            Ok(Some(TerrainCommand::River {
+                count,
+                source_height,
+                meander,
+            }))
+        }
+
+        // Subtract template [weight]
+        // 示例: Subtract archipelago 0.5
+        "subtract" => {
+            if args.is_empty() {
+                return Err(make_err("Subtract requires: template [weight]"));
+            }
+            let weight = if args.len() > 1 {
+                parse_f32(args[1]).map_err(|e| make_err(&e))?
+            } else {
+                1.0
+            };
+            Ok(Some(TerrainCommand::Subtract {
+                template: args[0].to_string(),
+                weight,
+            }))
+        }
+
+        // Mask mode [strength] [center_x center_y] [radius]
         // mode: 1=EdgeFade, 2=CenterBoost, 3=RadialGradient (或直接用名字)
+        // 径向渐变可指定中心和边缘倍数: radial:inner:outer
+        // 圆心和半径为百分比，省略时以地图中心为圆心
+        // 示例: Mask radial:1.2:0.3 0.5, Mask radial 0.5 30 40 60
         "mask" => {
             if args.is_empty() {
                 return Err(make_err("Mask requires: mode [strength]"));
             }
-            let mode = match args[0].to_lowercase().as_str() {
+            let mode_arg = args[0].to_lowercase();
+            let mut mode_parts = mode_arg.split(':');
+            let mode = match mode_parts.next().unwrap_or_default() {
                 "1" | "edge" | "edgefade" => MaskMode::EdgeFade,
                 "2" | "center" | "centerboost" => MaskMode::CenterBoost,
-                _ => MaskMode::RadialGradient,
+                _ => match (mode_parts.next(), mode_parts.next()) {
+                    (Some(inner), Some(outer)) => MaskMode::RadialGradient {
+                        inner: parse_f32(inner).map_err(|e| make_err(&e))?,
+                        outer: parse_f32(outer).map_err(|e| make_err(&e))?,
+                    },
+                    _ => MaskMode::radial(),
+                },
             };
             let strength = if args.len() > 1 {
                 parse_f32(args[1]).map_err(|e| make_err(&e))?
             } else {
                 0.5
             };
-            Ok(Some(TerrainCommand::Mask { mode, strength }))
+            // 可选的圆心和半径（百分比）
+            let center = match (args.get(2), args.get(3)) {
+                (Some(x), Some(y)) => Some((
+                    parse_f32(x).map_err(|e| make_err(&e))? / 100.0,
+                    parse_f32(y).map_err(|e| make_err(&e))? / 100.0,
+                )),
+                (Some(_), None) => return Err(make_err("Mask center requires both x and y")),
+                _ => None,
+            };
+            let radius = match args.get(4) {
+                Some(r) => Some(parse_f32(r).map_err(|e| make_err(&e))? / 100.0),
+                None => None,
+            };
+            Ok(Some(TerrainCommand::Mask {
+                mode,
+                strength,
+                center,
+                radius,
+            }))
         }
 
         // Strait width direction position depth
@@ -396,31 +647,380 @@ fn parse_line(line: &str, line_num: usize) -> Result<Option<TerrainCommand>, Par
             }))
         }
 
+        // Mesa height x y radius top_fraction (平顶陡壁的方山)
+        // 示例: Mesa 40 50 50 15 0.6
+        "mesa" => {
+            if args.len() < 5 {
+                return Err(make_err("Mesa requires: height x y radius top_fraction"));
+            }
+            let height = parse_f32(args[0]).map_err(|e| make_err(&e))?;
+            let x = parse_f32(args[1]).map_err(|e| make_err(&e))? / 100.0;
+            let y = parse_f32(args[2]).map_err(|e| make_err(&e))? / 100.0;
+            let radius = parse_f32(args[3]).map_err(|e| make_err(&e))? / 100.0;
+            let top_fraction = parse_f32(args[4]).map_err(|e| make_err(&e))?;
+            Ok(Some(TerrainCommand::Mesa {
+                height,
+                x,
+                y,
+                radius,
+                top_fraction,
+            }))
+        }
+
+        // Remap smoothstep | gamma <exponent> | scurve <k>
+        // 示例: Remap gamma 1.5
+        "remap" | "curve" => {
+            if args.is_empty() {
+                return Err(make_err(
+                    "Remap requires: smoothstep | gamma exp | scurve k",
+                ));
+            }
+            let kind = match args[0].to_lowercase().as_str() {
+                "smoothstep" | "smooth" => CurveKind::Smoothstep,
+                "gamma" => {
+                    let exp = args
+                        .get(1)
+                        .ok_or_else(|| make_err("Remap gamma requires: exponent"))?;
+                    CurveKind::Gamma(parse_f32(exp).map_err(|e| make_err(&e))?)
+                }
+                "scurve" | "s" => {
+                    let k = args
+                        .get(1)
+                        .ok_or_else(|| make_err("Remap scurve requires: k"))?;
+                    CurveKind::SCurve(parse_f32(k).map_err(|e| make_err(&e))?)
+                }
+                other => return Err(make_err(&format!("Unknown curve kind: {}", other))),
+            };
+            Ok(Some(TerrainCommand::RemapCurve { kind }))
+        }
+
+        // Scatter density [land|ocean|all] <command...>
+        // 示例: Scatter 0.01 ocean Hill 1 15-25 0-100 0-100
+        "scatter" => {
+            if args.len() < 2 {
+                return Err(make_err(
+                    "Scatter requires: density [land|ocean|all] command",
+                ));
+            }
+            let density = parse_f32(args[0]).map_err(|e| make_err(&e))?;
+            let (mask, rest) = match args[1].to_lowercase().as_str() {
+                "land" => (Some(SmoothTarget::Land), &args[2..]),
+                "ocean" | "water" => (Some(SmoothTarget::Ocean), &args[2..]),
+                "all" => (None, &args[2..]),
+                _ => (None, &args[1..]),
+            };
+            let inner = parse_line(&rest.join(" "), line_num)?
+                .ok_or_else(|| make_err("Scatter requires an inner command"))?;
+            Ok(Some(TerrainCommand::Scatter {
+                feature: Box::new(inner),
+                density,
+                mask,
+            }))
+        }
+
         _ => Err(make_err(&format!("Unknown command: {}", cmd))),
     }
 }
 
-/// 从文本解析模板
-pub fn parse_template(
+/// 带附加信息的模板解析结果
+#[derive(Debug, Clone)]
+pub struct ParsedTemplate {
+    pub template: TerrainTemplate,
+    /// `@section name` 标记：(段名, 该段第一条命令在 `commands` 中的索引)
+    pub sections: Vec<(String, usize)>,
+}
+
+/// 去除一行中的块注释 `/* ... */`
+///
+/// `in_block` 记录上一行结束时是否仍处于块注释中，并在返回前更新。
+/// 块注释之外遇到 `#` 或 `//` 时，其后直到行尾都是单行注释，
+/// 其中的 `/*` 不会开始块注释。
+fn strip_block_comments(line: &str, in_block: &mut bool) -> String {
+    let mut kept = String::new();
+    let mut rest = line;
+
+    loop {
+        if *in_block {
+            match rest.find("*/") {
+                Some(end) => {
+                    rest = &rest[end + 2..];
+                    *in_block = false;
+                    kept.push(' ');
+                }
+                None => return kept,
+            }
+        } else {
+            let line_comment = [rest.find('#'), rest.find("//")]
+                .into_iter()
+                .flatten()
+                .min();
+            match rest.find("/*") {
+                Some(start) if line_comment.is_none_or(|comment| start < comment) => {
+                    kept.push_str(&rest[..start]);
+                    rest = &rest[start + 2..];
+                    *in_block = true;
+                }
+                _ => {
+                    kept.push_str(rest);
+                    return kept;
+                }
+            }
+        }
+    }
+}
+
+/// 计算只含 `+` 和 `*` 的简单表达式 (如 `10+$i*20` 展开后的 `10+2*20`)
+///
+/// 因为 `-` 已用于范围，这里不支持减法。任一操作数无法解析时返回 None。
+fn eval_expr(expr: &str, constants: &HashMap<String, f32>) -> Option<f32> {
+    expr.split('+')
+        .map(|term| {
+            term.split('*')
+                .map(|factor| {
+                    constants
+                        .get(factor)
+                        .copied()
+                        .or_else(|| factor.parse().ok())
+                })
+                .product::<Option<f32>>()
+        })
+        .sum()
+}
+
+/// 命令 `cmd`（小写）的第 `index` 个参数是否为数值参数
+///
+/// 模板名、遮罩模式、方向、轴和曲线类型等关键字参数不是数值参数。
+fn is_numeric_arg(cmd: &str, index: usize) -> bool {
+    match cmd {
+        "subtract" | "mask" | "remap" | "curve" => index > 0,
+        "strait" | "invert" => index != 1,
+        // 尾随的 land/ocean 目标是关键字
+        "multiply" | "mult" | "smooth" => index == 0,
+        "normalize" | "norm" => false,
+        _ => true,
+    }
+}
+
+/// 把数值参数中已定义的常量名替换为数值，并计算 `+`/`*` 表达式
+///
+/// 命令名和关键字参数（见 [`is_numeric_arg`]）不替换，因此常量可以与关键字同名；
+/// 范围参数 (如 `low-peak`) 的两端分别替换。Scatter 的内层命令按其自身规则替换。
+fn substitute_constants(line: &str, constants: &HashMap<String, f32>) -> String {
+    let lookup = |token: &str| {
+        let value = if token.contains(['+', '*']) {
+            eval_expr(token, constants)
+        } else {
+            constants.get(token).copied()
+        };
+        value.map_or_else(|| token.to_string(), |v| v.to_string())
+    };
+    let substitute = |token: &str| match token.split_once('-') {
+        Some((min, max)) if !min.is_empty() => format!("{}-{}", lookup(min), lookup(max)),
+        _ => lookup(token),
+    };
+
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let Some((&cmd, args)) = tokens.split_first() else {
+        return String::new();
+    };
+    let cmd_lower = cmd.to_lowercase();
+
+    let mut out = vec![cmd.to_string()];
+    if cmd_lower == "scatter" && args.len() > 1 {
+        // Scatter density [land|ocean|all] <command...>
+        out.push(substitute(args[0]));
+        let rest = match args[1].to_lowercase().as_str() {
+            "land" | "ocean" | "water" | "all" => {
+                out.push(args[1].to_string());
+                &args[2..]
+            }
+            _ => &args[1..],
+        };
+        out.push(substitute_constants(&rest.join(" "), constants));
+        return out.join(" ");
+    }
+
+    for (index, &token) in args.iter().enumerate() {
+        if is_numeric_arg(&cmd_lower, index) {
+            out.push(substitute(token));
+        } else {
+            out.push(token.to_string());
+        }
+    }
+    out.join(" ")
+}
+
+/// 解析 `let NAME = value` 常量定义，值可以引用已定义的常量
+fn parse_let(
+    rest: &str,
+    line_num: usize,
+    constants: &HashMap<String, f32>,
+) -> Result<(String, f32), ParseError> {
+    let make_err = |msg: String| ParseError {
+        line: line_num,
+        message: format!("{}: let {}", msg, rest.trim()),
+    };
+
+    let (name, value) = rest
+        .split_once('=')
+        .ok_or_else(|| make_err("let requires: NAME = value".to_string()))?;
+    let name = name.trim();
+    if !is_identifier(name) {
+        return Err(make_err(format!("Invalid variable name: {}", name)));
+    }
+    let value = value.trim();
+    let value = match constants.get(value) {
+        Some(&v) => v,
+        None => parse_f32(value).map_err(make_err)?,
+    };
+    Ok((name.to_string(), value))
+}
+
+/// Repeat 块内收集到的 (行号, 文本)
+type RepeatBody = Vec<(usize, String)>;
+
+/// 从文本解析模板，同时返回段落标记
+pub fn parse_template_full(
     name: &str,
     description: &str,
     text: &str,
-) -> Result<TerrainTemplate, ParseError> {
+) -> Result<ParsedTemplate, ParseError> {
     let mut commands = Vec::new();
+    let mut sections = Vec::new();
+    let mut constants = HashMap::new();
+    let mut in_block = false;
+    let mut block_start = 0;
+    // 正在收集的 Repeat 块：(次数, 起始行号, 块内的 (行号, 文本))
+    let mut repeat: Option<(u32, usize, RepeatBody)> = None;
+
+    for (i, raw) in text.lines().enumerate() {
+        let line_num = i + 1;
+        if !in_block {
+            block_start = line_num;
+        }
+        let line = strip_block_comments(raw, &mut in_block);
+        let trimmed = line.trim();
+        let make_err = |msg: &str| ParseError {
+            line: line_num,
+            message: format!("{}: {}", msg, trimmed),
+        };
 
-    for (i, line) in text.lines().enumerate() {
-        if let Some(cmd) = parse_line(line, i + 1)? {
+        // Repeat N { ... }
+        if trimmed == "}" {
+            let (count, _, body) = repeat.take().ok_or_else(|| make_err("Unmatched '}'"))?;
+            for index in 0..count {
+                for (body_line, text) in &body {
+                    let text = text.replace("$i", &index.to_string());
+                    let text = substitute_constants(&text, &constants);
+                    if let Some(cmd) = parse_line(&text, *body_line)? {
+                        commands.push(cmd);
+                    }
+                }
+            }
+            continue;
+        }
+        if let Some(header) = trimmed.strip_suffix('{') {
+            let header = substitute_constants(header, &constants);
+            let mut parts = header.split_whitespace();
+            if !parts
+                .next()
+                .is_some_and(|w| w.eq_ignore_ascii_case("repeat"))
+            {
+                return Err(make_err("Only Repeat blocks may use '{'"));
+            }
+            if repeat.is_some() {
+                return Err(make_err("Nested Repeat blocks are not supported"));
+            }
+            let count = match (parts.next(), parts.next()) {
+                (Some(n), None) => n.parse().map_err(|_| make_err(&number_error(n)))?,
+                _ => return Err(make_err("Repeat requires: Repeat N {")),
+            };
+            repeat = Some((count, line_num, Vec::new()));
+            continue;
+        }
+        if trimmed.contains(['{', '}']) {
+            return Err(make_err(
+                "Braces must be on the Repeat line or on their own line",
+            ));
+        }
+        if let Some((_, _, body)) = repeat.as_mut() {
+            if trimmed.starts_with('@') || trimmed.to_lowercase().starts_with("let ") {
+                return Err(make_err("Directives are not allowed inside Repeat"));
+            }
+            body.push((line_num, line.clone()));
+            continue;
+        }
+
+        // @section name
+        if let Some(rest) = trimmed.strip_prefix('@') {
+            let mut parts = rest.split_whitespace();
+            match parts.next().map(str::to_lowercase).as_deref() {
+                Some("section") => {
+                    let section = parts.collect::<Vec<_>>().join(" ");
+                    if section.is_empty() {
+                        return Err(ParseError {
+                            line: line_num,
+                            message: format!("@section requires a name: {}", trimmed),
+                        });
+                    }
+                    sections.push((section, commands.len()));
+                    continue;
+                }
+                _ => {
+                    return Err(ParseError {
+                        line: line_num,
+                        message: format!("Unknown directive: {}", trimmed),
+                    })
+                }
+            }
+        }
+
+        // let NAME = value
+        let mut words = trimmed.splitn(2, char::is_whitespace);
+        if words.next().is_some_and(|w| w.eq_ignore_ascii_case("let")) {
+            let (name, value) = parse_let(words.next().unwrap_or(""), line_num, &constants)?;
+            constants.insert(name, value);
+            continue;
+        }
+
+        let line = substitute_constants(&line, &constants);
+        if let Some(cmd) = parse_line(&line, line_num)? {
             commands.push(cmd);
         }
     }
 
-    Ok(TerrainTemplate {
-        name: name.to_string(),
-        description: description.to_string(),
-        commands,
+    if in_block {
+        return Err(ParseError {
+            line: block_start,
+            message: "Unterminated block comment".to_string(),
+        });
+    }
+    if let Some((_, start, _)) = repeat {
+        return Err(ParseError {
+            line: start,
+            message: "Unclosed Repeat block: missing '}'".to_string(),
+        });
+    }
+
+    Ok(ParsedTemplate {
+        template: TerrainTemplate {
+            name: name.to_string(),
+            description: description.to_string(),
+            commands,
+        },
+        sections,
     })
 }
 
+/// 从文本解析模板
+pub fn parse_template(
+    name: &str,
+    description: &str,
+    text: &str,
+) -> Result<TerrainTemplate, ParseError> {
+    parse_template_full(name, description, text).map(|parsed| parsed.template)
+}
+
 /// 将模板转换为 DSL 文本
 pub fn template_to_dsl(template: &TerrainTemplate) -> String {
     let mut lines = Vec::new();
@@ -429,182 +1029,345 @@ pub fn template_to_dsl(template: &TerrainTemplate) -> String {
     lines.push(String::new());
 
     for cmd in &template.commands {
-        let line = match cmd {
-            TerrainCommand::Hill {
+        lines.push(command_to_dsl(cmd));
+    }
+
+    lines.join("\n")
+}
+
+/// Hill/Pit 的 blob_power、jitter 覆盖参数（未设置时为空串）
+fn blob_overrides_to_dsl(blob_power: Option<f32>, jitter: Option<f32>) -> String {
+    let arg = |v: Option<f32>| v.map_or_else(|| "_".to_string(), |v| v.to_string());
+    match (blob_power, jitter) {
+        (None, None) => String::new(),
+        (blob_power, None) => format!(" {}", arg(blob_power)),
+        (blob_power, jitter) => format!(" {} {}", arg(blob_power), arg(jitter)),
+    }
+}
+
+/// 将单个命令转换为 DSL 行
+fn command_to_dsl(cmd: &TerrainCommand) -> String {
+    match cmd {
+        TerrainCommand::Hill {
+            count,
+            height,
+            x,
+            y,
+            radius,
+            blob_power,
+            jitter,
+        } => {
+            format!(
+                "Hill {} {}-{} {}-{} {}-{} {}-{}{}",
                 count,
-                height,
-                x,
-                y,
-                radius,
-            } => {
-                format!(
-                    "Hill {} {}-{} {}-{} {}-{} {}-{}",
-                    count,
-                    height.0,
-                    height.1,
-                    (x.0 * 100.0) as i32,
-                    (x.1 * 100.0) as i32,
-                    (y.0 * 100.0) as i32,
-                    (y.1 * 100.0) as i32,
-                    (radius.0 * 100.0) as i32,
-                    (radius.1 * 100.0) as i32
-                )
-            }
-            TerrainCommand::BoundedHill {
+                height.0,
+                height.1,
+                (x.0 * 100.0) as i32,
+                (x.1 * 100.0) as i32,
+                (y.0 * 100.0) as i32,
+                (y.1 * 100.0) as i32,
+                (radius.0 * 100.0) as i32,
+                (radius.1 * 100.0) as i32,
+                blob_overrides_to_dsl(*blob_power, *jitter)
+            )
+        }
+        TerrainCommand::BoundedHill {
+            count,
+            height,
+            x,
+            y,
+            bounds,
+        } => {
+            format!(
+                "BoundedHill {} {}-{} {}-{} {}-{} bounds({}-{},{}-{})",
                 count,
-                height,
-                x,
-                y,
-                bounds,
-            } => {
-                format!(
-                    "BoundedHill {} {}-{} {}-{} {}-{} bounds({}-{},{}-{})",
-                    count,
-                    height.0,
-                    height.1,
-                    (x.0 * 100.0) as i32,
-                    (x.1 * 100.0) as i32,
-                    (y.0 * 100.0) as i32,
-                    (y.1 * 100.0) as i32,
-                    (bounds.0 * 100.0) as i32,
-                    (bounds.1 * 100.0) as i32,
-                    (bounds.2 * 100.0) as i32,
-                    (bounds.3 * 100.0) as i32
-                )
-            }
-            TerrainCommand::Range {
+                height.0,
+                height.1,
+                (x.0 * 100.0) as i32,
+                (x.1 * 100.0) as i32,
+                (y.0 * 100.0) as i32,
+                (y.1 * 100.0) as i32,
+                (bounds.0 * 100.0) as i32,
+                (bounds.1 * 100.0) as i32,
+                (bounds.2 * 100.0) as i32,
+                (bounds.3 * 100.0) as i32
+            )
+        }
+        TerrainCommand::Range {
+            count,
+            height,
+            x,
+            y,
+            length,
+            width,
+            angle: _,
+        } => {
+            format!(
+                "Range {} {}-{} {}-{} {}-{} {}-{} {}-{}",
                 count,
-                height,
-                x,
-                y,
-                length,
-                width,
-                angle: _,
-            } => {
-                format!(
-                    "Range {} {}-{} {}-{} {}-{} {}-{} {}-{}",
-                    count,
-                    height.0,
-                    height.1,
-                    (x.0 * 100.0) as i32,
-                    (x.1 * 100.0) as i32,
-                    (y.0 * 100.0) as i32,
-                    (y.1 * 100.0) as i32,
-                    (length.0 * 100.0) as i32,
-                    (length.1 * 100.0) as i32,
-                    (width.0 * 100.0) as i32,
-                    (width.1 * 100.0) as i32
-                )
-            }
-            TerrainCommand::Trough {
+                height.0,
+                height.1,
+                (x.0 * 100.0) as i32,
+                (x.1 * 100.0) as i32,
+                (y.0 * 100.0) as i32,
+                (y.1 * 100.0) as i32,
+                (length.0 * 100.0) as i32,
+                (length.1 * 100.0) as i32,
+                (width.0 * 100.0) as i32,
+                (width.1 * 100.0) as i32
+            )
+        }
+        TerrainCommand::Trough {
+            count,
+            depth,
+            x,
+            y,
+            length,
+            width,
+            angle: _,
+        } => {
+            format!(
+                "Trough {} {}-{} {}-{} {}-{} {}-{} {}-{}",
                 count,
-                depth,
-                x,
-                y,
-                length,
-                width,
-                angle: _,
-            } => {
-                format!(
-                    "Trough {} {}-{} {}-{} {}-{} {}-{} {}-{}",
-                    count,
-                    depth.0,
-                    depth.1,
-                    (x.0 * 100.0) as i32,
-                    (x.1 * 100.0) as i32,
-                    (y.0 * 100.0) as i32,
-                    (y.1 * 100.0) as i32,
-                    (length.0 * 100.0) as i32,
-                    (length.1 * 100.0) as i32,
-                    (width.0 * 100.0) as i32,
-                    (width.1 * 100.0) as i32
-                )
-            }
-            TerrainCommand::Pit {
+                depth.0,
+                depth.1,
+                (x.0 * 100.0) as i32,
+                (x.1 * 100.0) as i32,
+                (y.0 * 100.0) as i32,
+                (y.1 * 100.0) as i32,
+                (length.0 * 100.0) as i32,
+                (length.1 * 100.0) as i32,
+                (width.0 * 100.0) as i32,
+                (width.1 * 100.0) as i32
+            )
+        }
+        TerrainCommand::Pit {
+            count,
+            depth,
+            x,
+            y,
+            radius,
+            blob_power,
+            jitter,
+        } => {
+            format!(
+                "Pit {} {}-{} {}-{} {}-{} {}-{}{}",
                 count,
-                depth,
-                x,
-                y,
-                radius,
-            } => {
-                format!(
-                    "Pit {} {}-{} {}-{} {}-{} {}-{}",
-                    count,
-                    depth.0,
-                    depth.1,
-                    (x.0 * 100.0) as i32,
-                    (x.1 * 100.0) as i32,
-                    (y.0 * 100.0) as i32,
-                    (y.1 * 100.0) as i32,
-                    (radius.0 * 100.0) as i32,
-                    (radius.1 * 100.0) as i32
-                )
-            }
-            TerrainCommand::Mountain {
+                depth.0,
+                depth.1,
+                (x.0 * 100.0) as i32,
+                (x.1 * 100.0) as i32,
+                (y.0 * 100.0) as i32,
+                (y.1 * 100.0) as i32,
+                (radius.0 * 100.0) as i32,
+                (radius.1 * 100.0) as i32,
+                blob_overrides_to_dsl(*blob_power, *jitter)
+            )
+        }
+        TerrainCommand::Atolls {
+            count,
+            radius,
+            x,
+            y,
+        } => {
+            format!(
+                "Atolls {} {}-{} {}-{} {}-{}",
+                count,
+                (radius.0 * 100.0) as i32,
+                (radius.1 * 100.0) as i32,
+                (x.0 * 100.0) as i32,
+                (x.1 * 100.0) as i32,
+                (y.0 * 100.0) as i32,
+                (y.1 * 100.0) as i32
+            )
+        }
+        TerrainCommand::Caldera {
+            height,
+            x,
+            y,
+            radius,
+        } => {
+            format!(
+                "Caldera {}-{} {}-{} {}-{} {}-{}",
+                height.0,
+                height.1,
+                (x.0 * 100.0) as i32,
+                (x.1 * 100.0) as i32,
+                (y.0 * 100.0) as i32,
+                (y.1 * 100.0) as i32,
+                (radius.0 * 100.0) as i32,
+                (radius.1 * 100.0) as i32
+            )
+        }
+        TerrainCommand::Island {
+            target_cells,
+            height,
+            x,
+            y,
+        } => {
+            format!(
+                "Island {} {}-{} {}-{} {}-{}",
+                target_cells,
+                height.0,
+                height.1,
+                (x.0 * 100.0) as i32,
+                (x.1 * 100.0) as i32,
+                (y.0 * 100.0) as i32,
+                (y.1 * 100.0) as i32
+            )
+        }
+        TerrainCommand::Mountain {
+            height,
+            x,
+            y,
+            radius,
+        } => {
+            format!(
+                "Mountain {} {} {} {}",
                 height,
-                x,
-                y,
-                radius,
-            } => {
-                format!(
-                    "Mountain {} {} {} {}",
-                    height,
-                    (x * 100.0) as i32,
-                    (y * 100.0) as i32,
-                    (radius * 100.0) as i32
-                )
-            }
-            TerrainCommand::Add { value } => format!("Add {}", value),
-            TerrainCommand::Multiply { factor } => format!("Multiply {}", factor),
-            TerrainCommand::Smooth { iterations } => format!("Smooth {}", iterations),
-            TerrainCommand::Erode {
-                iterations,
-                rain,
-                capacity,
-                deposition,
-            } => format!("Erode {} {} {} {}", iterations, rain, capacity, deposition),
-            TerrainCommand::Mask { mode, strength } => {
-                let mode_str = match mode {
-                    MaskMode::EdgeFade => "edge",
-                    MaskMode::CenterBoost => "center",
-                    MaskMode::RadialGradient => "radial",
-                };
-                format!("Mask {} {}", mode_str, strength)
+                (x * 100.0) as i32,
+                (y * 100.0) as i32,
+                (radius * 100.0) as i32
+            )
+        }
+        TerrainCommand::Mesa {
+            height,
+            x,
+            y,
+            radius,
+            top_fraction,
+        } => {
+            format!(
+                "Mesa {} {} {} {} {}",
+                height,
+                (x * 100.0) as i32,
+                (y * 100.0) as i32,
+                (radius * 100.0) as i32,
+                top_fraction
+            )
+        }
+        TerrainCommand::Add { value } => format!("Add {}", value),
+        TerrainCommand::Multiply { factor } => format!("Multiply {}", factor),
+        TerrainCommand::Smooth { iterations } => format!("Smooth {}", iterations),
+        TerrainCommand::SmoothCoast { iterations, band } => {
+            format!("SmoothCoast {} {}", iterations, band)
+        }
+        TerrainCommand::Terrace { steps, strength } => format!("Terrace {} {}", steps, strength),
+        TerrainCommand::FloodFill {
+            start_x,
+            start_y,
+            level,
+        } => format!(
+            "FloodFill {} {} {}",
+            start_x * 100.0,
+            start_y * 100.0,
+            level
+        ),
+        TerrainCommand::Warp {
+            strength,
+            frequency,
+        } => format!("Warp {} {}", strength, frequency),
+        TerrainCommand::Erode {
+            iterations,
+            rain,
+            capacity,
+            deposition,
+        } => format!("Erode {} {} {} {}", iterations, rain, capacity, deposition),
+        TerrainCommand::WindErosion {
+            direction,
+            strength,
+            iterations,
+        } => format!(
+            "WindErosion {} {} {}",
+            iterations,
+            direction.to_degrees(),
+            strength
+        ),
+        TerrainCommand::Gradient { angle, strength } => {
+            format!("Gradient {} {}", angle, strength)
+        }
+        TerrainCommand::River {
+            count,
+            source_height,
+            meander,
+        } => format!("River {} {} {}", count, source_height, meander),
+        TerrainCommand::Subtract { template, weight } => {
+            format!("Subtract {} {}", template, weight)
+        }
+        TerrainCommand::Mask {
+            mode,
+            strength,
+            center,
+            radius,
+        } => {
+            let mode_str = match *mode {
+                MaskMode::EdgeFade => "edge".to_string(),
+                MaskMode::CenterBoost => "center".to_string(),
+                mode if mode == MaskMode::radial() => "radial".to_string(),
+                MaskMode::RadialGradient { inner, outer } => format!("radial:{}:{}", inner, outer),
+            };
+            let mut line = format!("Mask {} {}", mode_str, strength);
+            if center.is_some() || radius.is_some() {
+                let (x, y) = center.unwrap_or((0.5, 0.5));
+                line.push_str(&format!(" {} {}", x * 100.0, y * 100.0));
             }
-            TerrainCommand::Strait {
-                width,
-                direction,
-                position,
-                depth,
-            } => {
-                let dir = match direction {
-                    StraitDirection::Vertical => "vertical",
-                    StraitDirection::Horizontal => "horizontal",
-                };
-                format!(
-                    "Strait {} {} {} {}",
-                    (width * 100.0) as i32,
-                    dir,
-                    (position * 100.0) as i32,
-                    depth
-                )
-            }
-            TerrainCommand::Invert { axis, probability } => {
-                let axis_str = match axis {
-                    InvertAxis::X => "x",
-                    InvertAxis::Y => "y",
-                    InvertAxis::Both => "both",
-                };
-                format!("Invert {} {}", probability, axis_str)
-            }
-            TerrainCommand::Normalize => "Normalize".to_string(),
-            TerrainCommand::SetSeaLevel { level } => format!("SeaLevel {}", level),
-            TerrainCommand::AdjustSeaRatio { ocean_ratio } => format!("SeaRatio {}", ocean_ratio),
-        };
-        lines.push(line);
+            if let Some(r) = radius {
+                line.push_str(&format!(" {}", r * 100.0));
+            }
+            line
+        }
+        TerrainCommand::Strait {
+            width,
+            direction,
+            position,
+            depth,
+        } => {
+            let dir = match direction {
+                StraitDirection::Vertical => "vertical",
+                StraitDirection::Horizontal => "horizontal",
+            };
+            format!(
+                "Strait {} {} {} {}",
+                (width * 100.0) as i32,
+                dir,
+                (position * 100.0) as i32,
+                depth
+            )
+        }
+        TerrainCommand::Invert { axis, probability } => {
+            let axis_str = match axis {
+                InvertAxis::X => "x",
+                InvertAxis::Y => "y",
+                InvertAxis::Both => "both",
+            };
+            format!("Invert {} {}", probability, axis_str)
+        }
+        TerrainCommand::Normalize => "Normalize".to_string(),
+        TerrainCommand::SetSeaLevel { level } => format!("SeaLevel {}", level),
+        TerrainCommand::AdjustSeaRatio { ocean_ratio } => format!("SeaRatio {}", ocean_ratio),
+        TerrainCommand::RemapCurve { kind } => match kind {
+            CurveKind::Smoothstep => "Remap smoothstep".to_string(),
+            CurveKind::Gamma(exp) => format!("Remap gamma {}", exp),
+            CurveKind::SCurve(k) => format!("Remap scurve {}", k),
+        },
+        TerrainCommand::Scatter {
+            feature,
+            density,
+            mask,
+        } => {
+            let mask_str = match mask {
+                Some(SmoothTarget::Land) => "land",
+                Some(SmoothTarget::Ocean) => "ocean",
+                None => "all",
+            };
+            format!(
+                "Scatter {} {} {}",
+                density,
+                mask_str,
+                command_to_dsl(feature)
+            )
+        }
     }
-
-    lines.join("\n")
 }
 
 // ============================================================================
@@ -790,6 +1553,33 @@ mod tests {
         assert_eq!(parse_range("50").unwrap(), (50.0, 50.0));
     }
 
+    #[test]
+    fn test_parse_hill_blob_overrides() {
+        let plain = parse_line("Hill 1 90 50 50 10", 1).unwrap().unwrap();
+        assert!(matches!(
+            plain,
+            TerrainCommand::Hill {
+                blob_power: None,
+                jitter: None,
+                ..
+            }
+        ));
+
+        let cmd = parse_line("Pit 1 20 50 50 10 _ 0.1", 1).unwrap().unwrap();
+        assert!(matches!(
+            cmd,
+            TerrainCommand::Pit {
+                blob_power: None,
+                jitter: Some(j),
+                ..
+            } if j == 0.1
+        ));
+        assert_eq!(command_to_dsl(&cmd), "Pit 1 20-20 50-50 50-50 10-10 _ 0.1");
+
+        let cmd = parse_line("Hill 1 90 50 50 10 0.99", 1).unwrap().unwrap();
+        assert_eq!(command_to_dsl(&cmd), "Hill 1 90-90 50-50 50-50 10-10 0.99");
+    }
+
     #[test]
     fn test_parse_hill() {
         let cmd = parse_line("Hill 3 80-120 20-80 20-80", 1).unwrap().unwrap();
@@ -810,6 +1600,52 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_scatter() {
+        let cmd = parse_line("Scatter 0.02 ocean Hill 1 15-25 0-100 0-100 1-2", 1)
+            .unwrap()
+            .unwrap();
+        match &cmd {
+            TerrainCommand::Scatter {
+                feature,
+                density,
+                mask,
+            } => {
+                assert_eq!(*density, 0.02);
+                assert_eq!(*mask, Some(SmoothTarget::Ocean));
+                assert!(matches!(**feature, TerrainCommand::Hill { count: 1, .. }));
+            }
+            _ => panic!("Expected Scatter command"),
+        }
+
+        // 导出后可以重新解析
+        let line = command_to_dsl(&cmd);
+        assert!(line.starts_with("Scatter 0.02 ocean Hill"));
+        assert!(matches!(
+            parse_line(&line, 1).unwrap(),
+            Some(TerrainCommand::Scatter { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_remap_round_trip() {
+        let kinds = [
+            CurveKind::Smoothstep,
+            CurveKind::Gamma(1.5),
+            CurveKind::SCurve(2.0),
+        ];
+        for kind in kinds {
+            let line = command_to_dsl(&TerrainCommand::RemapCurve { kind });
+            match parse_line(&line, 1).unwrap().unwrap() {
+                TerrainCommand::RemapCurve { kind: parsed } => assert_eq!(parsed, kind),
+                _ => panic!("Expected RemapCurve command"),
+            }
+        }
+
+        assert!(parse_line("Remap gamma", 1).is_err());
+        assert!(parse_line("Remap cubic", 1).is_err());
+    }
+
     #[test]
     fn test_parse_template() {
         let template = parse_template(
@@ -822,6 +1658,188 @@ mod tests {
         assert_eq!(template.commands.len(), 4);
     }
 
+    #[test]
+    fn test_block_marker_inside_line_comment() {
+        let text =
+            "# see /* below\nSmooth 2\n// glob src/*.rs\nNormalize\n/* a */ # b /* c\nSeaRatio 0.7";
+        let template = parse_template("Test", "Line comment", text).unwrap();
+        assert_eq!(template.commands.len(), 3);
+        assert!(matches!(
+            template.commands[0],
+            TerrainCommand::Smooth { .. }
+        ));
+        assert!(matches!(template.commands[1], TerrainCommand::Normalize));
+        assert!(matches!(
+            template.commands[2],
+            TerrainCommand::AdjustSeaRatio { .. }
+        ));
+    }
+
+    #[test]
+    fn test_block_comment_straddling_command() {
+        let text =
+            "Hill 1 50 50 50 /* first\nSmooth 2\nstill comment */ Normalize\nSeaRatio 0.7\nBogus 1";
+        let err = parse_template("Test", "Block comment", text).unwrap_err();
+        // 块注释跨越多行后行号仍然正确
+        assert_eq!(err.line, 5);
+
+        let text = text.trim_end_matches("\nBogus 1");
+        let template = parse_template("Test", "Block comment", text).unwrap();
+        assert_eq!(template.commands.len(), 3);
+        assert!(matches!(template.commands[0], TerrainCommand::Hill { .. }));
+        assert!(matches!(template.commands[1], TerrainCommand::Normalize));
+        assert!(matches!(
+            template.commands[2],
+            TerrainCommand::AdjustSeaRatio { .. }
+        ));
+
+        let err =
+            parse_template("Test", "Unterminated", "Smooth 1\n/* open\nSmooth 2").unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+
+    #[test]
+    fn test_section_marker() {
+        let text = "@section base\nHill 1 50 50 50\nSmooth 2\n@section coast\nSeaRatio 0.7";
+        let parsed = parse_template_full("Test", "Sections", text).unwrap();
+        assert_eq!(parsed.template.commands.len(), 3);
+        assert_eq!(
+            parsed.sections,
+            vec![("base".to_string(), 0), ("coast".to_string(), 2)]
+        );
+
+        assert_eq!(parse_template("Test", "", "@section").unwrap_err().line, 1);
+        assert!(parse_template("Test", "", "@unknown x").is_err());
+    }
+
+    #[test]
+    fn test_let_constants() {
+        let text = "let peak = 200\nlet low = 40\nlet top = peak\nMountain top 50 50 15\nHill 2 low-peak 20-80 20-80";
+        let template = parse_template("Test", "Constants", text).unwrap();
+        assert_eq!(template.commands.len(), 2);
+        match &template.commands[0] {
+            TerrainCommand::Mountain { height, .. } => assert_eq!(*height, 200.0),
+            _ => panic!("Expected Mountain command"),
+        }
+        match &template.commands[1] {
+            TerrainCommand::Hill { height, .. } => assert_eq!(*height, (40.0, 200.0)),
+            _ => panic!("Expected Hill command"),
+        }
+    }
+
+    #[test]
+    fn test_let_constants_do_not_replace_keywords() {
+        let text = "let x = 5\nlet archipelago = 3\nlet radial = 2\nlet land = 1\nlet v = 4\n\
+            Invert 0.5 x\n\
+            Subtract archipelago 0.5\n\
+            Mask radial x\n\
+            Strait v v\n\
+            Multiply 0.9 land\n\
+            Scatter 0.1 land Hill land 50 50 50";
+        let template = parse_template("Test", "Keywords", text).unwrap();
+        assert_eq!(template.commands.len(), 6);
+        assert!(matches!(
+            template.commands[0],
+            TerrainCommand::Invert {
+                axis: InvertAxis::X,
+                ..
+            }
+        ));
+        match &template.commands[1] {
+            TerrainCommand::Subtract { template, weight } => {
+                assert_eq!(template, "archipelago");
+                assert_eq!(*weight, 0.5);
+            }
+            other => panic!("Expected Subtract command, got {:?}", other),
+        }
+        match &template.commands[2] {
+            TerrainCommand::Mask { mode, strength, .. } => {
+                assert!(matches!(mode, MaskMode::RadialGradient { .. }));
+                assert_eq!(*strength, 5.0);
+            }
+            other => panic!("Expected Mask command, got {:?}", other),
+        }
+        match &template.commands[3] {
+            TerrainCommand::Strait {
+                width, direction, ..
+            } => {
+                assert!((width - 0.04).abs() < 1e-6);
+                assert_eq!(*direction, StraitDirection::Vertical);
+            }
+            other => panic!("Expected Strait command, got {:?}", other),
+        }
+        match &template.commands[5] {
+            TerrainCommand::Scatter { feature, mask, .. } => {
+                assert_eq!(*mask, Some(SmoothTarget::Land));
+                assert!(matches!(**feature, TerrainCommand::Hill { count: 1, .. }));
+            }
+            other => panic!("Expected Scatter command, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_let_undefined_variable() {
+        let text = "let peak = 200\nSmooth 2\nMountain summit 50 50 15";
+        let err = parse_template("Test", "Undefined", text).unwrap_err();
+        assert_eq!(err.line, 3);
+        assert!(
+            err.message.contains("Undefined variable: summit"),
+            "{}",
+            err
+        );
+
+        let err = parse_template("Test", "Undefined", "let a = b").unwrap_err();
+        assert_eq!(err.line, 1);
+        assert!(err.message.contains("Undefined variable: b"), "{}", err);
+        assert!(parse_template("Test", "Bad name", "let 1x = 3").is_err());
+    }
+
+    #[test]
+    fn test_repeat_block() {
+        let text = "Hill 1 50 50 50\nRepeat 3 {\n  Smooth 1\n  Multiply 0.9 land\n}\nNormalize";
+        let template = parse_template("Test", "Repeat", text).unwrap();
+        assert_eq!(template.commands.len(), 8);
+        let smooths = template
+            .commands
+            .iter()
+            .filter(|c| matches!(c, TerrainCommand::Smooth { .. }))
+            .count();
+        assert_eq!(smooths, 3);
+        assert!(matches!(template.commands[7], TerrainCommand::Normalize));
+    }
+
+    #[test]
+    fn test_repeat_index_substitution() {
+        let text = "let step = 20\nRepeat 3 {\nMountain 100 20+$i*step 50 10\n}";
+        let template = parse_template("Test", "Repeat index", text).unwrap();
+        let xs: Vec<f32> = template
+            .commands
+            .iter()
+            .map(|c| match c {
+                TerrainCommand::Mountain { x, .. } => *x,
+                _ => panic!("Expected Mountain command"),
+            })
+            .collect();
+        assert_eq!(xs.len(), 3);
+        for (x, expected) in xs.iter().zip([0.2, 0.4, 0.6]) {
+            assert!((x - expected).abs() < 1e-6, "{:?}", xs);
+        }
+    }
+
+    #[test]
+    fn test_repeat_unmatched_braces() {
+        let err = parse_template("Test", "", "Smooth 1\nRepeat 2 {\nSmooth 1").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(err.message.contains("Unclosed Repeat"), "{}", err);
+
+        let err = parse_template("Test", "", "Smooth 1\n}").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(err.message.contains("Unmatched"), "{}", err);
+
+        let err = parse_template("Test", "", "Repeat 2 {\nRepeat 2 {\n}\n}").unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+
     #[test]
     fn test_preset_volcano() {
         let template = parse_template("Volcano", "Volcanic island", presets::VOLCANO).unwrap();
@@ -21,6 +21,9 @@ pub struct Feature {
     pub feature_type: FeatureType,
     pub cells: Vec<usize>,
     pub is_border: bool, // 是否接触地图边缘
+    /// 面积；检测器只知道拓扑，按单元格数量计，
+    /// `World::features` 用 Voronoi 单元格的实际面积
+    pub area: f32,
 }
 
 impl Feature {
@@ -43,6 +46,10 @@ pub struct FeatureDetector {
     pub min_island_size: usize,
     /// 最小湖泊大小（小于此值的将被填充）
     pub min_lake_size: usize,
+    /// 最小海洋大小（小于此值的边缘水域将被填充，0 表示不清理海洋）
+    pub min_ocean_size: usize,
+    /// 海平面高度（低于此值为水体）
+    pub sea_level: u8,
 }
 
 impl Default for FeatureDetector {
@@ -50,6 +57,8 @@ impl Default for FeatureDetector {
         Self {
             min_island_size: 3,
             min_lake_size: 2,
+            min_ocean_size: 0,
+            sea_level: SEA_LEVEL,
         }
     }
 }
@@ -59,9 +68,74 @@ impl FeatureDetector {
         Self {
             min_island_size,
             min_lake_size,
+            min_ocean_size: 0,
+            sea_level: SEA_LEVEL,
         }
     }
 
+    /// 链式设置最小海洋大小
+    pub fn with_min_ocean_size(mut self, min_ocean_size: usize) -> Self {
+        self.min_ocean_size = min_ocean_size;
+        self
+    }
+
+    /// 链式设置海平面高度
+    pub fn with_sea_level(mut self, sea_level: u8) -> Self {
+        self.sea_level = sea_level;
+        self
+    }
+
+    /// 计算海洋掩码
+    ///
+    /// 从地图边缘的水体单元格出发做多源 BFS，
+    /// 所有与边缘连通的水体标记为海洋（true）
+    pub fn ocean_mask(
+        &self,
+        heights: &[u8],
+        neighbors: &[Vec<u32>],
+        border_cells: &[bool],
+    ) -> Vec<bool> {
+        let n = heights.len();
+        let mut is_ocean = vec![false; n];
+        let mut queue = VecDeque::new();
+
+        for i in 0..n {
+            if heights[i] < self.sea_level && border_cells.get(i).copied().unwrap_or(false) {
+                is_ocean[i] = true;
+                queue.push_back(i);
+            }
+        }
+
+        while let Some(current) = queue.pop_front() {
+            for &neighbor in &neighbors[current] {
+                let n_idx = neighbor as usize;
+                if !is_ocean[n_idx] && heights[n_idx] < self.sea_level {
+                    is_ocean[n_idx] = true;
+                    queue.push_back(n_idx);
+                }
+            }
+        }
+
+        is_ocean
+    }
+
+    /// 计算湖泊掩码
+    ///
+    /// 低于海平面但不与地图边缘连通的水体标记为湖泊（true）
+    pub fn lake_mask(
+        &self,
+        heights: &[u8],
+        neighbors: &[Vec<u32>],
+        border_cells: &[bool],
+    ) -> Vec<bool> {
+        let is_ocean = self.ocean_mask(heights, neighbors, border_cells);
+        heights
+            .iter()
+            .zip(is_ocean.iter())
+            .map(|(&h, &ocean)| h < self.sea_level && !ocean)
+            .collect()
+    }
+
     /// 检测所有地形特征
     ///
     /// 返回 (特征列表, 每个单元格对应的特征 ID)
@@ -86,7 +160,7 @@ impl FeatureDetector {
         {
             search_start = start + 1;
             current_id += 1;
-            let is_land = heights[start] >= SEA_LEVEL;
+            let is_land = heights[start] >= self.sea_level;
             let mut is_border = border_cells.get(start).copied().unwrap_or(false);
             let mut cells = Vec::new();
 
@@ -108,7 +182,7 @@ impl FeatureDetector {
                         continue;
                     }
 
-                    let neighbor_is_land = heights[n_idx] >= SEA_LEVEL;
+                    let neighbor_is_land = heights[n_idx] >= self.sea_level;
                     if neighbor_is_land == is_land {
                         feature_ids[n_idx] = current_id;
                         queue.push_back(n_idx);
@@ -127,6 +201,7 @@ impl FeatureDetector {
             features.push(Feature {
                 id: current_id,
                 feature_type,
+                area: cells.len() as f32,
                 cells,
                 is_border,
             });
@@ -139,6 +214,7 @@ impl FeatureDetector {
     ///
     /// - 太小的岛屿会被淹没（变成海洋）
     /// - 太小的湖泊会被填充（变成陆地）
+    /// - 太小的海洋（仅接触边缘的零碎水域）按 `min_ocean_size` 填充
     pub fn cleanup_small_features(&self, heights: &mut [u8], features: &[Feature]) -> usize {
         let mut cleaned = 0;
 
@@ -148,7 +224,7 @@ impl FeatureDetector {
                     if feature.size() < self.min_island_size {
                         // 淹没小岛
                         for &cell in &feature.cells {
-                            heights[cell] = SEA_LEVEL - 1;
+                            heights[cell] = self.sea_level - 1;
                         }
                         cleaned += feature.size();
                     }
@@ -157,13 +233,19 @@ impl FeatureDetector {
                     if feature.size() < self.min_lake_size {
                         // 填充小湖
                         for &cell in &feature.cells {
-                            heights[cell] = SEA_LEVEL;
+                            heights[cell] = self.sea_level;
                         }
                         cleaned += feature.size();
                     }
                 }
                 FeatureType::Ocean => {
-                    // 海洋不清理
+                    if feature.size() < self.min_ocean_size {
+                        // 填充边缘的零碎水域
+                        for &cell in &feature.cells {
+                            heights[cell] = self.sea_level;
+                        }
+                        cleaned += feature.size();
+                    }
                 }
             }
         }
@@ -178,14 +260,14 @@ impl FeatureDetector {
         let mut coastline = Vec::new();
 
         for (i, &h) in heights.iter().enumerate() {
-            if h < SEA_LEVEL {
+            if h < self.sea_level {
                 continue; // 跳过海洋
             }
 
             // 检查是否有海洋邻居
             let has_ocean_neighbor = neighbors[i]
                 .iter()
-                .any(|&n| heights[n as usize] < SEA_LEVEL);
+                .any(|&n| heights[n as usize] < self.sea_level);
 
             if has_ocean_neighbor {
                 coastline.push(i);
@@ -205,9 +287,9 @@ impl FeatureDetector {
 
         // 标记海岸线
         for (i, &h) in heights.iter().enumerate() {
-            let is_land = h >= SEA_LEVEL;
+            let is_land = h >= self.sea_level;
             let has_opposite_neighbor = neighbors[i].iter().any(|&n| {
-                let neighbor_is_land = heights[n as usize] >= SEA_LEVEL;
+                let neighbor_is_land = heights[n as usize] >= self.sea_level;
                 neighbor_is_land != is_land
             });
 
@@ -275,32 +357,63 @@ impl FeatureDetector {
         for _ in 0..iterations {
             let original = heights.to_vec();
 
-            for (i, &h) in original.iter().enumerate() {
-                let is_land = h >= SEA_LEVEL;
+            for (i, height) in heights.iter_mut().enumerate() {
+                if let Some(h) = self.coastline_fix(&original, neighbors, i) {
+                    *height = h;
+                    changed += 1;
+                }
+            }
+        }
 
-                // 统计同类型邻居数量
-                let same_type_count = neighbors[i]
-                    .iter()
-                    .filter(|&&n| (original[n as usize] >= SEA_LEVEL) == is_land)
-                    .count();
+        changed
+    }
 
-                let total_neighbors = neighbors[i].len();
+    /// 只在指定单元格上平滑一轮海岸线（规则同 [`Self::smooth_coastline`]）
+    ///
+    /// 用于局部编辑后的清理，范围外的单元格保持不变。
+    pub fn smooth_coastline_at(
+        &self,
+        heights: &mut [u8],
+        neighbors: &[Vec<u32>],
+        cells: &[usize],
+    ) -> usize {
+        let original = heights.to_vec();
+        let mut changed = 0;
 
-                // 如果大多数邻居是不同类型，则转换
-                if total_neighbors > 0 && same_type_count <= total_neighbors / 4 {
-                    if is_land {
-                        heights[i] = SEA_LEVEL - 1;
-                    } else {
-                        heights[i] = SEA_LEVEL;
-                    }
-                    changed += 1;
-                }
+        for &i in cells {
+            if let Some(h) = self.coastline_fix(&original, neighbors, i) {
+                heights[i] = h;
+                changed += 1;
             }
         }
 
         changed
     }
 
+    /// 单元格 `i` 的大多数邻居与它海陆类型不同时，返回翻转类型后的高度
+    fn coastline_fix(&self, heights: &[u8], neighbors: &[Vec<u32>], i: usize) -> Option<u8> {
+        let is_land = heights[i] >= self.sea_level;
+
+        // 统计同类型邻居数量
+        let same_type_count = neighbors[i]
+            .iter()
+            .filter(|&&n| (heights[n as usize] >= self.sea_level) == is_land)
+            .count();
+
+        let total_neighbors = neighbors[i].len();
+
+        // 如果大多数邻居是不同类型，则转换
+        if total_neighbors > 0 && same_type_count <= total_neighbors / 4 {
+            Some(if is_land {
+                self.sea_level - 1
+            } else {
+                self.sea_level
+            })
+        } else {
+            None
+        }
+    }
+
     /// 约束噪声，防止在海岸线附近产生散点
     ///
     /// 返回每个单元格允许的最大噪声幅度
@@ -329,9 +442,14 @@ impl FeatureDetector {
 
 /// 应用约束噪声
 ///
-/// 确保噪声不会改变海陆类型
-pub fn apply_constrained_noise(heights: &mut [f32], noise: &[f32], constraints: &[f32]) {
-    let sea_level = SEA_LEVEL as f32;
+/// 确保噪声不会改变以 `sea_level` 划分的海陆类型
+pub fn apply_constrained_noise(
+    heights: &mut [f32],
+    noise: &[f32],
+    constraints: &[f32],
+    sea_level: u8,
+) {
+    let sea_level = sea_level as f32;
 
     for i in 0..heights.len() {
         let original = heights[i];
@@ -418,6 +536,110 @@ mod tests {
         assert!(heights[4] < SEA_LEVEL);
     }
 
+    /// 4x4 网格的四邻接关系，外圈为边界
+    fn create_grid_4x4() -> (Vec<Vec<u32>>, Vec<bool>) {
+        let neighbors: Vec<Vec<u32>> = (0..16)
+            .map(|i| {
+                let row = i / 4;
+                let col = i % 4;
+                let mut n = Vec::new();
+                if row > 0 {
+                    n.push((i - 4) as u32);
+                }
+                if row < 3 {
+                    n.push((i + 4) as u32);
+                }
+                if col > 0 {
+                    n.push((i - 1) as u32);
+                }
+                if col < 3 {
+                    n.push((i + 1) as u32);
+                }
+                n
+            })
+            .collect();
+        let borders = (0..16)
+            .map(|i| {
+                let (row, col) = (i / 4, i % 4);
+                row == 0 || row == 3 || col == 0 || col == 3
+            })
+            .collect();
+        (neighbors, borders)
+    }
+
+    #[test]
+    fn test_enclosed_water_is_lake() {
+        let (neighbors, borders) = create_grid_4x4();
+        // 中心 2x2 为低于海平面的洼地，四周被陆地包围
+        let heights = vec![
+            30, 30, 30, 30, //
+            30, 10, 10, 30, //
+            30, 10, 10, 30, //
+            30, 30, 30, 30, //
+        ];
+
+        let detector = FeatureDetector::default();
+        let ocean = detector.ocean_mask(&heights, &neighbors, &borders);
+        let lake = detector.lake_mask(&heights, &neighbors, &borders);
+        assert!(ocean.iter().all(|&o| !o));
+        for i in [5, 6, 9, 10] {
+            assert!(lake[i]);
+        }
+
+        let (features, ids) = detector.detect_features(&heights, &neighbors, &borders);
+        let pocket = features.iter().find(|f| f.id == ids[5]).unwrap();
+        assert_eq!(pocket.feature_type, FeatureType::Lake);
+    }
+
+    #[test]
+    fn test_edge_connected_water_is_ocean() {
+        let (neighbors, borders) = create_grid_4x4();
+        // 同样的洼地，但通过单元格 7 与地图右边缘相连
+        let heights = vec![
+            30, 30, 30, 30, //
+            30, 10, 10, 10, //
+            30, 10, 10, 30, //
+            30, 30, 30, 30, //
+        ];
+
+        let detector = FeatureDetector::default();
+        let ocean = detector.ocean_mask(&heights, &neighbors, &borders);
+        let lake = detector.lake_mask(&heights, &neighbors, &borders);
+        for i in [5, 6, 7, 9, 10] {
+            assert!(ocean[i]);
+        }
+        assert!(lake.iter().all(|&l| !l));
+
+        let (features, ids) = detector.detect_features(&heights, &neighbors, &borders);
+        let sea = features.iter().find(|f| f.id == ids[5]).unwrap();
+        assert_eq!(sea.feature_type, FeatureType::Ocean);
+    }
+
+    #[test]
+    fn test_lake_and_ocean_thresholds_are_independent() {
+        let (neighbors, borders) = create_grid_4x4();
+        // 单元格 5 为内陆湖，单元格 15 为边缘海
+        let mut heights = vec![
+            30, 30, 30, 30, //
+            30, 10, 30, 30, //
+            30, 30, 30, 30, //
+            30, 30, 30, 10, //
+        ];
+
+        // 只清理湖泊，保留海洋
+        let detector = FeatureDetector::new(1, 2);
+        let (features, _) = detector.detect_features(&heights, &neighbors, &borders);
+        detector.cleanup_small_features(&mut heights, &features);
+        assert!(heights[5] >= SEA_LEVEL);
+        assert!(heights[15] < SEA_LEVEL);
+
+        // 提高海洋阈值后，零碎边缘海也被填充
+        let detector = FeatureDetector::new(1, 2).with_min_ocean_size(2);
+        let (features, _) = detector.detect_features(&heights, &neighbors, &borders);
+        detector.cleanup_small_features(&mut heights, &features);
+        assert!(heights[15] >= SEA_LEVEL);
+    }
+
     #[test]
     fn test_coastline_detection() {
         let heights = vec![
@@ -0,0 +1,304 @@
+// 分步地形生成
+//
+// 将 TerrainGenerator::generate 拆分为多个小步骤，
+// 便于在 wasm 等单线程环境中由异步循环驱动，避免阻塞 UI。
+
+use super::heightmap::{GenerationPlan, LayeredPlan, TectonicState, TerrainGenerator};
+use super::layered_generator::LayeredGenerator;
+use super::layers::{self, LayerOutput};
+use super::plate::TectonicPlate;
+use eframe::egui::Pos2;
+use std::future::Future;
+
+/// 生成结果: (heights, plates, plate_id)
+type GenerationResult = (Vec<u8>, Vec<TectonicPlate>, Vec<u16>);
+
+/// 构造模拟阶段缺少中间状态时的提示
+const TECTONIC_STATE: &str = "tectonic stage requires plate state";
+
+/// 当前执行阶段
+enum Stage {
+    /// 生成板块并分析边界
+    TectonicPlates,
+    /// 第 n 次构造模拟迭代
+    TectonicIteration(u32),
+    /// 板块浮力、中尺度噪声和侵蚀
+    TectonicRegional,
+    /// 小尺度细节噪声
+    TectonicDetail,
+    /// 平滑、重映射和后处理
+    TectonicFinalize,
+    /// 执行第 n 个生成层
+    Layer(usize),
+    /// 分块将浮点高度转换为 u8
+    Quantize {
+        next: usize,
+        min_h: f32,
+        max_h: f32,
+    },
+    /// 应用模板修饰
+    Modifiers,
+    /// 特征清理和海岸线优化
+    PostProcess,
+    /// 不支持分步的模式（传统模板执行器），一次性生成
+    Monolithic,
+    Done,
+}
+
+/// 分步地形生成任务
+///
+/// 每次调用 [`step`](Self::step) 执行一个生成层、一次构造模拟迭代或一个构造阶段，
+/// 或最多 `chunk_size` 个单元格的逐单元格处理。无论分块大小如何，最终结果都与同步的
+/// [`TerrainGenerator::generate`] 完全一致。
+///
+/// 传统模板执行器不支持拆分，会在单个步骤内完成。
+pub struct TerrainGenerationTask<'a> {
+    generator: &'a TerrainGenerator,
+    cells: &'a [Pos2],
+    neighbors: &'a [Vec<u32>],
+    layer_cells: Vec<layers::Pos2>,
+    chunk_size: usize,
+    layered: Option<(LayeredGenerator, LayeredPlan)>,
+    tectonic: Option<TectonicState>,
+    stage: Stage,
+    output: LayerOutput,
+    heights: Vec<u8>,
+    result: Option<GenerationResult>,
+}
+
+impl<'a> TerrainGenerationTask<'a> {
+    pub fn new(
+        generator: &'a TerrainGenerator,
+        cells: &'a [Pos2],
+        neighbors: &'a [Vec<u32>],
+        chunk_size: usize,
+    ) -> Self {
+        // 与 TerrainGenerator::generate 共用同一个执行计划
+        let (layered, stage) = match generator.plan() {
+            GenerationPlan::Tectonic => (None, Stage::TectonicPlates),
+            GenerationPlan::Layered(plan) => {
+                let layers =
+                    generator.build_layered_generator(plan.seed, plan.num_plates, plan.ocean_ratio);
+                (Some((layers, plan)), Stage::Layer(0))
+            }
+            GenerationPlan::NamedTemplate(_) | GenerationPlan::SeededTemplate(..) => {
+                (None, Stage::Monolithic)
+            }
+        };
+
+        Self {
+            generator,
+            cells,
+            neighbors,
+            layer_cells: Vec::new(),
+            chunk_size: chunk_size.max(1),
+            layered,
+            tectonic: None,
+            stage,
+            output: LayerOutput::with_size(cells.len()),
+            heights: Vec::new(),
+            result: None,
+        }
+    }
+
+    /// 执行一个步骤
+    ///
+    /// 返回 true 表示生成已完成
+    pub fn step(&mut self) -> bool {
+        let stage = std::mem::replace(&mut self.stage, Stage::Done);
+        self.stage = match stage {
+            Stage::Monolithic => {
+                self.result = Some(self.generator.generate(self.cells, self.neighbors));
+                Stage::Done
+            }
+            Stage::TectonicPlates => {
+                self.tectonic = Some(
+                    self.generator
+                        .init_plate_tectonics(self.cells, self.neighbors),
+                );
+                Stage::TectonicIteration(0)
+            }
+            Stage::TectonicIteration(i) if i < self.generator.config().tectonic.iterations => {
+                let state = self.tectonic.as_mut().expect(TECTONIC_STATE);
+                self.generator.tectonic_iteration(state, self.neighbors);
+                Stage::TectonicIteration(i + 1)
+            }
+            Stage::TectonicIteration(_) => Stage::TectonicRegional,
+            Stage::TectonicRegional => {
+                let state = self.tectonic.as_mut().expect(TECTONIC_STATE);
+                self.generator.tectonic_regional(
+                    self.cells,
+                    self.neighbors,
+                    &mut state.heights,
+                    &state.plates,
+                    &state.plate_id,
+                );
+                Stage::TectonicDetail
+            }
+            Stage::TectonicDetail => {
+                let state = self.tectonic.as_mut().expect(TECTONIC_STATE);
+                self.generator.tectonic_detail(
+                    self.cells,
+                    self.neighbors,
+                    &mut state.heights,
+                    &state.plates,
+                    &state.plate_id,
+                );
+                Stage::TectonicFinalize
+            }
+            Stage::TectonicFinalize => {
+                let state = self.tectonic.take().expect(TECTONIC_STATE);
+                let heights = self
+                    .generator
+                    .tectonic_finalize(&state.heights, self.neighbors);
+                self.result = Some((heights, state.plates, state.plate_id));
+                Stage::Done
+            }
+            Stage::Layer(index) => {
+                let (layers, _) = self
+                    .layered
+                    .as_ref()
+                    .expect("layered stage requires a plan");
+                if self.layer_cells.is_empty() {
+                    self.layer_cells = self
+                        .cells
+                        .iter()
+                        .map(|p| layers::Pos2::new(p.x, p.y))
+                        .collect();
+                }
+
+                match layers.generate_layer(index, &self.layer_cells, self.neighbors, &self.output)
+                {
+                    Some(output) => {
+                        self.output = output;
+                        Stage::Layer(index + 1)
+                    }
+                    None => {
+                        let (min_h, max_h) =
+                            TerrainGenerator::layered_height_range(&self.output.heights);
+                        self.heights = Vec::with_capacity(self.cells.len());
+                        Stage::Quantize {
+                            next: 0,
+                            min_h,
+                            max_h,
+                        }
+                    }
+                }
+            }
+            Stage::Quantize { next, min_h, max_h } => {
+                let end = (next + self.chunk_size).min(self.output.heights.len());
+                self.heights
+                    .extend(self.output.heights[next..end].iter().map(|&h| {
+                        TerrainGenerator::layered_height_to_u8(
+                            h,
+                            min_h,
+                            max_h,
+                            self.generator.config().sea_level,
+                        )
+                    }));
+                if end < self.output.heights.len() {
+                    Stage::Quantize {
+                        next: end,
+                        min_h,
+                        max_h,
+                    }
+                } else {
+                    Stage::Modifiers
+                }
+            }
+            Stage::Modifiers => {
+                let (_, plan) = self
+                    .layered
+                    .as_ref()
+                    .expect("layered stage requires a plan");
+                if let Some(template) = &plan.template {
+                    self.generator.apply_template_modifiers(
+                        &mut self.heights,
+                        template,
+                        self.cells,
+                        self.neighbors,
+                    );
+                }
+                Stage::PostProcess
+            }
+            Stage::PostProcess => {
+                let (_, plan) = self
+                    .layered
+                    .as_ref()
+                    .expect("layered stage requires a plan");
+                if plan.post_process {
+                    self.generator
+                        .post_process(&mut self.heights, self.neighbors);
+                }
+                let plate_ids = self
+                    .output
+                    .plate_ids
+                    .take()
+                    .unwrap_or_else(|| vec![0; self.cells.len()]);
+                self.result = Some((std::mem::take(&mut self.heights), Vec::new(), plate_ids));
+                Stage::Done
+            }
+            Stage::Done => Stage::Done,
+        };
+
+        self.is_finished()
+    }
+
+    /// 是否已完成
+    pub fn is_finished(&self) -> bool {
+        matches!(self.stage, Stage::Done)
+    }
+
+    /// 大致进度 (0.0-1.0)
+    pub fn progress(&self) -> f32 {
+        let layer_count = self
+            .layered
+            .as_ref()
+            .map(|(layers, _)| layers.layer_count())
+            .unwrap_or(0);
+        let iterations = self.generator.config().tectonic.iterations as f32;
+        let total = if self.layered.is_some() {
+            // 层 + 量化 + 修饰 + 后处理
+            (layer_count + 3) as f32
+        } else {
+            // 板块 + 迭代 + 区域 + 细节 + 收尾
+            iterations + 4.0
+        };
+        let done = match &self.stage {
+            Stage::Monolithic => 0.0,
+            Stage::TectonicPlates => 0.0,
+            Stage::TectonicIteration(i) => 1.0 + (*i as f32).min(iterations),
+            Stage::TectonicRegional => iterations + 1.0,
+            Stage::TectonicDetail => iterations + 2.0,
+            Stage::TectonicFinalize => iterations + 3.0,
+            Stage::Layer(index) => *index as f32,
+            Stage::Quantize { next, .. } => {
+                layer_count as f32 + *next as f32 / self.cells.len().max(1) as f32
+            }
+            Stage::Modifiers => layer_count as f32 + 1.0,
+            Stage::PostProcess => layer_count as f32 + 2.0,
+            Stage::Done => return 1.0,
+        };
+        done / total
+    }
+
+    /// 取出生成结果，未完成时返回 None
+    pub fn into_result(self) -> Option<GenerationResult> {
+        self.result
+    }
+
+    /// 在异步循环中驱动任务
+    ///
+    /// 每执行一步后调用 `yield_now(progress)` 并等待其返回的 future，
+    /// 调用方可在其中让出控制权（如 wasm 上等待下一帧）。
+    pub async fn run<F, Fut>(mut self, mut yield_now: F) -> GenerationResult
+    where
+        F: FnMut(f32) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        while !self.step() {
+            yield_now(self.progress()).await;
+        }
+        self.into_result().unwrap_or_default()
+    }
+}
@@ -1,8 +1,10 @@
 // 高度图生成
 
-use super::layered_generator::LayeredGenerator;
+use super::generation_task::TerrainGenerationTask;
+use super::hydrology::FlowNetwork;
+use super::layered_generator::{layer_seed, LayerCheckpoints, LayeredGenerator};
 use super::layers::{
-    DetailLayer, PlateConfig, PostprocessConfig, PostprocessLayer, RegionalLayer,
+    map_cells, DetailLayer, PlateConfig, PostprocessConfig, PostprocessLayer, RegionalLayer,
     TectonicConfig as LayeredTectonicConfig, TectonicLayer,
 };
 use super::noise::{NoiseConfig, NoiseGenerator};
@@ -14,11 +16,12 @@ use super::template::{
     should_use_layered_generation, TerrainTemplate,
 };
 use super::template_executor::TemplateExecutor;
+use super::topology::Topology;
 use eframe::egui::Pos2;
-#[cfg(not(target_arch = "wasm32"))]
-use rayon::prelude::*;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
 
-/// 海平面高度阈值
+/// 默认海平面高度阈值（[`TerrainConfig::sea_level`] 的默认值）
 pub const SEA_LEVEL: u8 = 20;
 
 /// 地形生成模式
@@ -54,10 +57,38 @@ pub struct TerrainConfig {
     pub min_island_size: usize,
     /// 最小湖泊大小（小于此值的湖泊会被填充）
     pub min_lake_size: usize,
+    /// 最小海洋大小（小于此值的边缘连通水域会被填充，0 表示不清理）
+    pub min_ocean_size: usize,
     /// 海岸线平滑迭代次数
     pub coastline_smoothing: u32,
     /// 是否使用约束噪声（防止噪声产生散点）
     pub use_constrained_noise: bool,
+    /// 模板修饰强度（分层生成后 Range/Strait/Trough 对板块地形的影响比例）
+    pub template_modifier_strength: f32,
+    /// 调试：额外输出板块灰度场（见 `plate_debug_field`），不影响高度
+    pub debug_plate_field: bool,
+    /// 地图拓扑，影响遮罩和噪声采样（邻接关系由调用方按同一拓扑构建）
+    pub topology: Topology,
+    /// 覆盖模板建议的海洋比例（分层生成和板块构造模拟使用）
+    pub ocean_ratio: Option<f32>,
+    /// 覆盖模板建议的板块数量（仅分层生成使用）
+    pub num_plates: Option<usize>,
+    /// 是否用 rayon 并行执行逐单元格的独立计算，包括分层生成的各层
+    ///
+    /// 非 wasm 平台默认启用，wasm 上始终串行。
+    /// 每个单元格的计算与执行顺序无关，结果与串行完全一致。
+    pub parallel: bool,
+    /// 海平面高度：低于此值的单元格为水体（默认 [`SEA_LEVEL`]）
+    pub sea_level: u8,
+    /// 后生成噪声的域扭曲强度（地图单位），`None` 时直接采样 fBm
+    pub post_noise_warp: Option<f64>,
+    /// 细节噪声在乘以强度前做一次邻域平滑，消除高频噪声造成的孤立尖峰
+    pub smooth_detail_noise: bool,
+    /// 板块构造模拟的细节噪声改用脊状噪声（山脊更尖锐）
+    pub ridged_detail: bool,
+    /// 预览模式：跳过侵蚀和细节噪声（分层生成跳过细节层），板块数量减半，
+    /// 用于拖动参数时快速生成粗略地图
+    pub preview: bool,
 }
 
 impl Default for TerrainConfig {
@@ -76,8 +107,20 @@ impl Default for TerrainConfig {
             enable_feature_cleanup: true, // 默认启用
             min_island_size: 15,          // 大幅增加最小岛屿大小
             min_lake_size: 10,            // 大幅增加最小湖泊大小
+            min_ocean_size: 0,            // 海洋默认不清理
             coastline_smoothing: 1,
             use_constrained_noise: true, // 默认启用约束噪声
+            template_modifier_strength: 0.3,
+            debug_plate_field: false,
+            topology: Topology::Plane,
+            ocean_ratio: None,
+            num_plates: None,
+            parallel: cfg!(not(target_arch = "wasm32")),
+            sea_level: SEA_LEVEL,
+            post_noise_warp: None,
+            smooth_detail_noise: false,
+            ridged_detail: false,
+            preview: false,
         }
     }
 }
@@ -117,39 +160,422 @@ impl TerrainConfig {
     }
 }
 
+/// 地形生成结果: (heights, plates, plate_id)
+pub type TerrainOutput = (Vec<u8>, Vec<TectonicPlate>, Vec<u16>);
+
+/// 由生成模式解析出的执行计划，同步生成和分步任务共用同一份分支逻辑
+pub(super) enum GenerationPlan {
+    /// 板块构造模拟
+    Tectonic,
+    /// 分层系统
+    Layered(LayeredPlan),
+    /// 传统模板执行器，按名称查找模板
+    NamedTemplate(String),
+    /// 传统模板执行器，使用指定模板和种子
+    SeededTemplate(TerrainTemplate, u64),
+}
+
+/// 分层系统的参数：分层生成后可选应用模板修饰和后处理
+pub(super) struct LayeredPlan {
+    pub seed: u64,
+    pub num_plates: usize,
+    pub ocean_ratio: f32,
+    pub template: Option<TerrainTemplate>,
+    pub post_process: bool,
+}
+
+/// 板块构造模拟的中间状态，供分步生成逐次迭代
+pub(super) struct TectonicState {
+    pub heights: Vec<f32>,
+    pub plates: Vec<TectonicPlate>,
+    pub plate_id: Vec<u16>,
+    boundaries: Vec<PlateBoundary>,
+}
+
+/// 板块模拟缓存的键
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct GenCacheKey {
+    seed: u64,
+    num_plates: u32,
+    /// 单元格位置和邻接关系的哈希
+    geometry: u64,
+    /// 其余板块构造参数的哈希
+    params: u64,
+}
+
+/// 板块构造模拟的缓存
+///
+/// 保存最近一次板块分配和构造基础高度（浮力、噪声和海陆比例之前），
+/// 以种子和板块数量（以及其余构造参数和单元格几何）为键。
+/// 只修改后处理参数（如 `ocean_ratio`、平滑、噪声强度）时，
+/// [`TerrainGenerator::generate_cached`] 可以跳过昂贵的板块生成。
+#[derive(Debug, Clone, Default)]
+pub struct GenCache {
+    key: Option<GenCacheKey>,
+    plates: Vec<TectonicPlate>,
+    plate_ids: Vec<u16>,
+    base_heights: Vec<f32>,
+    plate_generations: usize,
+}
+
+impl GenCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 缓存的板块 ID（尚未缓存时为 None）
+    pub fn plate_ids(&self) -> Option<&[u16]> {
+        self.key.map(|_| self.plate_ids.as_slice())
+    }
+
+    /// 实际执行板块生成的次数（缓存未命中次数）
+    pub fn plate_generations(&self) -> usize {
+        self.plate_generations
+    }
+
+    /// 清空缓存
+    pub fn clear(&mut self) {
+        self.key = None;
+        self.plates.clear();
+        self.plate_ids.clear();
+        self.base_heights.clear();
+    }
+}
+
+/// 地形生成输入错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TerrainInputError {
+    /// 单元格数量与邻居列表数量不一致
+    LengthMismatch { cells: usize, neighbors: usize },
+    /// 邻居索引超出单元格范围
+    NeighborOutOfRange {
+        cell: usize,
+        neighbor: u32,
+        cell_count: usize,
+    },
+}
+
+impl std::fmt::Display for TerrainInputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LengthMismatch { cells, neighbors } => write!(
+                f,
+                "cells and neighbors length mismatch: {} cells, {} neighbor lists",
+                cells, neighbors
+            ),
+            Self::NeighborOutOfRange {
+                cell,
+                neighbor,
+                cell_count,
+            } => write!(
+                f,
+                "cell {} has neighbor {} out of range (cell count {})",
+                cell, neighbor, cell_count
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TerrainInputError {}
+
+/// 地形生成阶段，按流水线顺序排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GenStage {
+    /// 板块划分
+    Plates,
+    /// 板块构造模拟（边界效应、地壳均衡）
+    Tectonics,
+    /// 区域尺度地貌（中尺度噪声、侵蚀、模板命令）
+    Regional,
+    /// 细节噪声
+    Detail,
+    /// 重映射与后处理
+    Postprocess,
+}
+
 /// 地形生成器
 pub struct TerrainGenerator {
     config: TerrainConfig,
+    /// 分层生成共享的检查点缓存
+    checkpoints: Option<Arc<LayerCheckpoints>>,
 }
 
 impl TerrainGenerator {
     pub fn new(config: TerrainConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            checkpoints: None,
+        }
+    }
+
+    /// 分层生成时使用检查点缓存，只修改靠后层的参数时复用前面各层的结果
+    pub fn with_checkpoints(mut self, checkpoints: Arc<LayerCheckpoints>) -> Self {
+        self.checkpoints = Some(checkpoints);
+        self
+    }
+
+    /// 获取生成配置
+    pub fn config(&self) -> &TerrainConfig {
+        &self.config
+    }
+
+    /// 检查生成输入是否一致
+    ///
+    /// 要求每个单元格都有一个邻居列表，且所有邻居索引都在范围内。
+    /// 各生成阶段直接按索引访问高度、邻居和板块数组，
+    /// 在入口统一检查可以避免深处出现难以定位的越界 panic。
+    pub fn validate_inputs(
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+    ) -> Result<(), TerrainInputError> {
+        if cells.len() != neighbors.len() {
+            return Err(TerrainInputError::LengthMismatch {
+                cells: cells.len(),
+                neighbors: neighbors.len(),
+            });
+        }
+
+        for (cell, list) in neighbors.iter().enumerate() {
+            if let Some(&neighbor) = list.iter().find(|&&n| n as usize >= cells.len()) {
+                return Err(TerrainInputError::NeighborOutOfRange {
+                    cell,
+                    neighbor,
+                    cell_count: cells.len(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 检查输入后生成完整地形，输入不一致时返回错误而不是 panic
+    pub fn try_generate(
+        &self,
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+    ) -> Result<TerrainOutput, TerrainInputError> {
+        Self::validate_inputs(cells, neighbors)?;
+        Ok(self.generate_unchecked(cells, neighbors, &mut |_, _| {}))
     }
 
     /// 生成完整地形
     /// 返回: (heights, plates, plate_id)
+    ///
+    /// # Panics
+    /// 输入不一致时 panic 并给出描述信息，需要处理错误时请使用
+    /// [`try_generate`](Self::try_generate)。
     pub fn generate(
         &self,
         cells: &[Pos2],
         neighbors: &[Vec<u32>],
     ) -> (Vec<u8>, Vec<TectonicPlate>, Vec<u16>) {
+        if let Err(err) = Self::validate_inputs(cells, neighbors) {
+            panic!("invalid terrain input: {}", err);
+        }
+        self.generate_unchecked(cells, neighbors, &mut |_, _| {})
+    }
+
+    /// 生成完整地形并报告进度
+    ///
+    /// `on_progress(stage, t)` 中 `t` 为该阶段内的进度 (0..1)，每个阶段结束时至少以
+    /// `t = 1.0` 调用一次，阶段按 [`GenStage`] 的顺序到达，最后一次调用为
+    /// `(GenStage::Postprocess, 1.0)`。模式中不存在的阶段（如模板模式的板块阶段）
+    /// 直接报告完成。结果与 [`Self::generate`] 相同。
+    ///
+    /// # Panics
+    /// 输入不一致时 panic，与 [`Self::generate`] 相同。
+    pub fn generate_with_progress(
+        &self,
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+        mut on_progress: impl FnMut(GenStage, f32),
+    ) -> TerrainOutput {
+        if let Err(err) = Self::validate_inputs(cells, neighbors) {
+            panic!("invalid terrain input: {}", err);
+        }
+        self.generate_unchecked(cells, neighbors, &mut on_progress)
+    }
+
+    /// 使用缓存生成地形
+    ///
+    /// 板块构造模拟模式下，种子、板块数量等构造参数不变时复用 `cache` 中的
+    /// 板块分配和基础高度，只重新执行后续阶段；参数变化时重新生成并更新缓存。
+    /// 其他模式没有可复用的板块阶段，等同于 [`Self::generate`]。
+    ///
+    /// # Panics
+    /// 输入不一致时 panic，与 [`Self::generate`] 相同。
+    pub fn generate_cached(
+        &self,
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+        cache: &mut GenCache,
+    ) -> TerrainOutput {
+        if !matches!(self.config.mode, TerrainGenerationMode::TectonicSimulation) {
+            return self.generate(cells, neighbors);
+        }
+        if let Err(err) = Self::validate_inputs(cells, neighbors) {
+            panic!("invalid terrain input: {}", err);
+        }
+
+        let tectonic = &self.config.tectonic;
+        let key = GenCacheKey {
+            seed: tectonic.seed,
+            num_plates: self.plate_count_for_quality(tectonic.plate_count as usize) as u32,
+            geometry: Self::geometry_hash(cells, neighbors),
+            params: super::layers::param_hash(tectonic),
+        };
+
+        if cache.key != Some(key) {
+            let (heights, plates, plate_ids) =
+                self.simulate_plate_tectonics(cells, neighbors, &mut |_, _| {});
+            *cache = GenCache {
+                key: Some(key),
+                plates,
+                plate_ids,
+                base_heights: heights,
+                plate_generations: cache.plate_generations + 1,
+            };
+        }
+
+        self.finish_tectonic(
+            cells,
+            neighbors,
+            cache.base_heights.clone(),
+            cache.plates.clone(),
+            cache.plate_ids.clone(),
+            &mut |_, _| {},
+        )
+    }
+
+    /// 生成地形并计算水流网络
+    ///
+    /// 与 [`Self::generate`] 相同，额外返回基于最终高度计算的 [`FlowNetwork`]。
+    pub fn generate_with_flow(
+        &self,
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+    ) -> (Vec<u8>, Vec<TectonicPlate>, Vec<u16>, FlowNetwork) {
+        let (heights, plates, plate_ids) = self.generate(cells, neighbors);
+        let flow = FlowNetwork::compute(
+            &heights,
+            neighbors,
+            FlowNetwork::DEFAULT_RIVER_THRESHOLD,
+            self.config.sea_level,
+        );
+        (heights, plates, plate_ids, flow)
+    }
+
+    fn generate_unchecked(
+        &self,
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+        progress: &mut dyn FnMut(GenStage, f32),
+    ) -> (Vec<u8>, Vec<TectonicPlate>, Vec<u16>) {
+        match self.plan() {
+            GenerationPlan::Tectonic => self.generate_tectonic(cells, neighbors, progress),
+            GenerationPlan::Layered(plan) => {
+                let (mut heights_u8, plates, plate_ids) = self.generate_layered(
+                    cells,
+                    neighbors,
+                    plan.seed,
+                    plan.num_plates,
+                    plan.ocean_ratio,
+                    progress,
+                );
+                self.finish_layered(&plan, &mut heights_u8, cells, neighbors);
+                progress(GenStage::Postprocess, 1.0);
+                (heights_u8, plates, plate_ids)
+            }
+            GenerationPlan::NamedTemplate(template_name) => {
+                self.generate_from_template(cells, neighbors, &template_name, progress)
+            }
+            GenerationPlan::SeededTemplate(template, seed) => {
+                self.generate_from_template_with_seed(cells, neighbors, template, seed, progress)
+            }
+        }
+    }
+
+    /// 按生成模式构建执行计划
+    ///
+    /// 建议使用分层系统的模板走分层生成（`num_plates`、`ocean_ratio` 配置优先于
+    /// 模板建议值），其余模板走传统模板执行器；直接的分层模式不做模板修饰和后处理。
+    pub(super) fn plan(&self) -> GenerationPlan {
+        let layered_template = |template: Option<TerrainTemplate>, name: &str, seed: u64| {
+            GenerationPlan::Layered(LayeredPlan {
+                seed,
+                num_plates: self
+                    .config
+                    .num_plates
+                    .unwrap_or_else(|| get_suggested_plate_count(name)),
+                ocean_ratio: self
+                    .config
+                    .ocean_ratio
+                    .unwrap_or_else(|| get_suggested_ocean_ratio(name)),
+                template,
+                post_process: true,
+            })
+        };
+
         match &self.config.mode {
-            TerrainGenerationMode::TectonicSimulation => self.generate_tectonic(cells, neighbors),
-            TerrainGenerationMode::Template(template_name) => {
-                self.generate_from_template(cells, neighbors, template_name)
+            TerrainGenerationMode::TectonicSimulation => GenerationPlan::Tectonic,
+            TerrainGenerationMode::Template(name) if should_use_layered_generation(name) => {
+                layered_template(get_template_by_name(name), name, self.config.tectonic.seed)
+            }
+            TerrainGenerationMode::Template(name) => GenerationPlan::NamedTemplate(name.clone()),
+            TerrainGenerationMode::TemplateWithSeed(template, seed)
+                if should_use_layered_generation(&template.name) =>
+            {
+                layered_template(Some(template.clone()), &template.name, *seed)
             }
             TerrainGenerationMode::TemplateWithSeed(template, seed) => {
-                self.generate_from_template_with_seed(cells, neighbors, template.clone(), *seed)
+                GenerationPlan::SeededTemplate(template.clone(), *seed)
             }
+            // Default ocean ratio for direct Layered mode
             TerrainGenerationMode::Layered { seed, num_plates } => {
-                // Default ocean ratio for direct Layered mode
-                self.generate_layered(cells, neighbors, *seed, *num_plates, 0.65)
+                GenerationPlan::Layered(LayeredPlan {
+                    seed: *seed,
+                    num_plates: *num_plates,
+                    ocean_ratio: 0.65,
+                    template: None,
+                    post_process: false,
+                })
             }
         }
     }
 
+    /// 分层生成之后的模板修饰和后处理
+    pub(super) fn finish_layered(
+        &self,
+        plan: &LayeredPlan,
+        heights: &mut [u8],
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+    ) {
+        // Apply template-specific modifiers as subtle adjustments
+        if let Some(template) = &plan.template {
+            self.apply_template_modifiers(heights, template, cells, neighbors);
+        }
+        if plan.post_process {
+            self.post_process(heights, neighbors);
+        }
+    }
+
+    /// 创建分步生成任务
+    ///
+    /// 每步最多处理 `chunk_size` 个单元格，可由异步循环驱动以保持 UI 响应，
+    /// 最终结果与 [`generate`](Self::generate) 相同。
+    pub fn generate_async<'a>(
+        &'a self,
+        cells: &'a [Pos2],
+        neighbors: &'a [Vec<u32>],
+        chunk_size: usize,
+    ) -> TerrainGenerationTask<'a> {
+        TerrainGenerationTask::new(self, cells, neighbors, chunk_size)
+    }
+
     /// 使用新的分层系统生成地形
+    ///
+    /// 后处理层完成时只报告 `(Postprocess, 0.5)`，由调用方在最终后处理后报告完成。
     fn generate_layered(
         &self,
         cells: &[Pos2],
@@ -157,10 +583,107 @@ impl TerrainGenerator {
         seed: u64,
         num_plates: usize,
         ocean_ratio: f32,
+        progress: &mut dyn FnMut(GenStage, f32),
     ) -> (Vec<u8>, Vec<TectonicPlate>, Vec<u16>) {
         #[cfg(debug_assertions)]
         println!("使用分层系统生成地形: seed={}, plates={}", seed, num_plates);
 
+        let mut generator = self.build_layered_generator(seed, num_plates, ocean_ratio);
+        if let Some(checkpoints) = &self.checkpoints {
+            generator = generator.with_checkpoints(checkpoints.clone());
+        }
+
+        // 每层结束（或从检查点恢复）时报告对应阶段
+        let preview = self.config.preview;
+        let output = generator.generate_with_progress(cells, neighbors, &mut |name| {
+            match name {
+                // 构造层同时完成板块划分和构造模拟
+                "Tectonic" => {
+                    progress(GenStage::Plates, 1.0);
+                    progress(GenStage::Tectonics, 1.0);
+                }
+                "Regional" => {
+                    progress(GenStage::Regional, 1.0);
+                    // 预览模式没有细节层
+                    if preview {
+                        progress(GenStage::Detail, 1.0);
+                    }
+                }
+                "Detail" => progress(GenStage::Detail, 1.0),
+                "Postprocess" => progress(GenStage::Postprocess, 0.5),
+                _ => {}
+            }
+        });
+
+        // 转换高度值到 u8 范围
+        let (min_h, max_h) = Self::layered_height_range(&output.heights);
+        let heights_u8: Vec<u8> = output
+            .heights
+            .iter()
+            .map(|&h| Self::layered_height_to_u8(h, min_h, max_h, self.config.sea_level))
+            .collect();
+
+        // 提取板块信息
+        let plate_ids = output.plate_ids.unwrap_or_else(|| vec![0; cells.len()]);
+
+        // 暂时不返回详细的板块对象
+        let plates = Vec::new();
+
+        (heights_u8, plates, plate_ids)
+    }
+
+    /// 单元格位置和邻接关系的哈希，点集相同但位置变化时也会不同
+    fn geometry_hash(cells: &[Pos2], neighbors: &[Vec<u32>]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for p in cells {
+            p.x.to_bits().hash(&mut hasher);
+            p.y.to_bits().hash(&mut hasher);
+        }
+        neighbors.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// 单元格包围盒的宽高，作为模板执行和噪声采样的地图尺寸
+    fn cell_extent(cells: &[Pos2]) -> (u32, u32) {
+        let (min_x, max_x, min_y, max_y) = cells.iter().fold(
+            (
+                f32::INFINITY,
+                f32::NEG_INFINITY,
+                f32::INFINITY,
+                f32::NEG_INFINITY,
+            ),
+            |(min_x, max_x, min_y, max_y), pos| {
+                (
+                    min_x.min(pos.x),
+                    max_x.max(pos.x),
+                    min_y.min(pos.y),
+                    max_y.max(pos.y),
+                )
+            },
+        );
+        ((max_x - min_x) as u32, (max_y - min_y) as u32)
+    }
+
+    /// 预览模式下板块数量减半（至少 2 个）
+    fn plate_count_for_quality(&self, plate_count: usize) -> usize {
+        if self.config.preview {
+            (plate_count / 2).max(2)
+        } else {
+            plate_count
+        }
+    }
+
+    /// 构建分层生成器（构造→区域→细节→后处理）
+    ///
+    /// 预览模式不添加细节层。
+    pub(super) fn build_layered_generator(
+        &self,
+        seed: u64,
+        num_plates: usize,
+        ocean_ratio: f32,
+    ) -> LayeredGenerator {
+        let num_plates = self.plate_count_for_quality(num_plates);
+
         // Continental ratio derived from ocean ratio:
         // more ocean → fewer continental plates
         let continental_ratio = (1.0 - ocean_ratio).clamp(0.2, 0.5);
@@ -171,6 +694,7 @@ impl TerrainGenerator {
             continental_ratio,
             continental_base: 80.0,
             oceanic_base: -50.0,
+            continental_count: None,
         };
 
         // 配置构造层
@@ -189,100 +713,77 @@ impl TerrainGenerator {
             min_lake_size: self.config.min_lake_size,
             smoothing_iterations: self.config.coastline_smoothing,
             ocean_ratio,
+            ..Default::default()
         };
 
-        // 构建分层生成器
-        let generator = LayeredGenerator::new()
+        // 构建分层生成器，噪声按地图拓扑采样，逐单元格计算按配置并行
+        let topology = self.config.topology;
+        let parallel = self.config.parallel;
+        let mut generator = LayeredGenerator::new()
             .with_seed(seed)
-            .add_layer(TectonicLayer::new(tectonic_config).with_seed(seed))
-            .add_layer(RegionalLayer::new().with_seed((seed + 100) as u32))
-            .add_layer(DetailLayer::new().with_seed((seed + 200) as u32))
-            .add_layer(PostprocessLayer::new(postprocess_config));
-
-        // 生成地形
-        let output = generator.generate(cells, neighbors);
-
-        // 转换高度值到 u8 范围
-        // 保持海平面在固定位置 (SEA_LEVEL = 20)
-        // 海平面 (0.0) 映射到 20，负值映射到 0-20，正值映射到 20-255
-        let min_h = output.heights.iter().cloned().fold(f32::INFINITY, f32::min);
-        let max_h = output
-            .heights
-            .iter()
-            .cloned()
-            .fold(f32::NEG_INFINITY, f32::max);
-
-        let heights_u8: Vec<u8> = output
-            .heights
-            .iter()
-            .map(|&h| {
-                if h <= 0.0 {
-                    // 海洋：映射到 0-20
-                    // min_h (最深) -> 0, 0 (海平面) -> 20
-                    if min_h >= 0.0 {
-                        SEA_LEVEL
-                    } else {
-                        let t = (h - min_h) / (0.0 - min_h);
-                        (t * SEA_LEVEL as f32).clamp(0.0, SEA_LEVEL as f32) as u8
-                    }
-                } else {
-                    // 陆地：映射到 20-255
-                    // 0 (海平面) -> 20, max_h (最高) -> 255
-                    if max_h <= 0.0 {
-                        SEA_LEVEL
-                    } else {
-                        let t = h / max_h;
-                        (SEA_LEVEL as f32 + t * (255.0 - SEA_LEVEL as f32))
-                            .clamp(SEA_LEVEL as f32, 255.0) as u8
-                    }
-                }
-            })
-            .collect();
-
-        // 提取板块信息
-        let plate_ids = output.plate_ids.unwrap_or_else(|| vec![0; cells.len()]);
+            .add_layer(
+                TectonicLayer::new(tectonic_config)
+                    .with_seed(layer_seed(seed, 0))
+                    .with_topology(topology)
+                    .with_parallel(parallel),
+            )
+            .add_layer(
+                RegionalLayer::new()
+                    .with_seed(layer_seed(seed, 1) as u32)
+                    .with_topology(topology)
+                    .with_parallel(parallel),
+            );
+        if !self.config.preview {
+            generator = generator.add_layer(
+                DetailLayer::new()
+                    .with_seed(layer_seed(seed, 2) as u32)
+                    .with_topology(topology)
+                    .with_parallel(parallel),
+            );
+        }
+        generator.add_layer(PostprocessLayer::new(postprocess_config))
+    }
 
-        // 暂时不返回详细的板块对象
-        let plates = Vec::new();
+    /// 分层输出的高度范围 (min, max)
+    pub(super) fn layered_height_range(heights: &[f32]) -> (f32, f32) {
+        let min_h = heights.iter().cloned().fold(f32::INFINITY, f32::min);
+        let max_h = heights.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        (min_h, max_h)
+    }
 
-        (heights_u8, plates, plate_ids)
+    /// 将分层输出的高度转换为 u8
+    ///
+    /// 保持海平面在固定位置 `sea_level`：
+    /// 海平面 (0.0) 映射到 sea_level，负值映射到 0-sea_level，正值映射到 sea_level-255
+    pub(super) fn layered_height_to_u8(h: f32, min_h: f32, max_h: f32, sea_level: u8) -> u8 {
+        let sea = sea_level as f32;
+        if h <= 0.0 {
+            // 海洋：min_h (最深) -> 0, 0 (海平面) -> sea_level
+            if min_h >= 0.0 {
+                sea_level
+            } else {
+                let t = (h - min_h) / (0.0 - min_h);
+                (t * sea).clamp(0.0, sea) as u8
+            }
+        } else {
+            // 陆地：0 (海平面) -> sea_level, max_h (最高) -> 255
+            if max_h <= 0.0 {
+                sea_level
+            } else {
+                let t = h / max_h;
+                (sea + t * (255.0 - sea)).clamp(sea, 255.0) as u8
+            }
+        }
     }
 
-    /// 使用模板生成地形
+    /// 使用传统模板执行器生成地形
     fn generate_from_template(
         &self,
         cells: &[Pos2],
         neighbors: &[Vec<u32>],
         template_name: &str,
+        progress: &mut dyn FnMut(GenStage, f32),
     ) -> (Vec<u8>, Vec<TectonicPlate>, Vec<u16>) {
-        // 检查是否应该使用新的分层系统
-        if should_use_layered_generation(template_name) {
-            let num_plates = get_suggested_plate_count(template_name);
-            #[cfg(debug_assertions)]
-            println!(
-                "模板 '{}' 使用分层系统 (plates={})",
-                template_name, num_plates
-            );
-            let ocean_ratio = get_suggested_ocean_ratio(template_name);
-            let (mut heights_u8, plates, plate_ids) = self.generate_layered(
-                cells,
-                neighbors,
-                self.config.tectonic.seed,
-                num_plates,
-                ocean_ratio,
-            );
-
-            // Apply template-specific modifiers as subtle adjustments
-            if let Some(template) = get_template_by_name(template_name) {
-                self.apply_template_modifiers(&mut heights_u8, &template, cells, neighbors);
-            }
-
-            // Post-process
-            self.post_process(&mut heights_u8, neighbors);
-
-            return (heights_u8, plates, plate_ids);
-        }
-
         #[cfg(debug_assertions)]
         println!("使用传统模板生成地形: {}", template_name);
 
@@ -296,62 +797,59 @@ impl TerrainGenerator {
         });
 
         // 计算地图尺寸
-        let (min_x, max_x, min_y, max_y) = cells.iter().fold(
-            (
-                f32::INFINITY,
-                f32::NEG_INFINITY,
-                f32::INFINITY,
-                f32::NEG_INFINITY,
-            ),
-            |(min_x, max_x, min_y, max_y), pos| {
-                (
-                    min_x.min(pos.x),
-                    max_x.max(pos.x),
-                    min_y.min(pos.y),
-                    max_y.max(pos.y),
-                )
-            },
-        );
-        let width = (max_x - min_x) as u32;
-        let height = (max_y - min_y) as u32;
+        let (width, height) = Self::cell_extent(cells);
 
         // 执行模板
-        let executor = TemplateExecutor::new(width, height, self.config.tectonic.seed);
+        let executor = TemplateExecutor::new(width, height, self.config.tectonic.seed)
+            .with_topology(self.config.topology)
+            .with_sea_level(self.config.sea_level);
         let mut heights = executor.execute(&template, cells, neighbors);
+        // 模板模式没有板块阶段，模板命令对应区域阶段
+        progress(GenStage::Plates, 1.0);
+        progress(GenStage::Tectonics, 1.0);
+        progress(GenStage::Regional, 1.0);
 
         // 可选：添加细节噪声（简化版，避免产生太多碎片）
-        if self.config.detail_noise_strength > 0.0 {
+        if self.config.detail_noise_strength > 0.0 && !self.config.preview {
             // 中等尺度噪声 - 增加地形变化但不产生碎片
-            let medium_noise_config = NoiseConfig {
-                octaves: 4,
-                base_frequency: 0.002, // 低频率，大尺度变化
-                persistence: 0.5,
-                lacunarity: 2.0,
-                seed: (self.config.tectonic.seed + 1) as u32,
-            };
+            let medium_noise_config =
+                NoiseConfig::continental().with_seed((self.config.tectonic.seed + 1) as u32);
 
-            let generator = NoiseGenerator::new(medium_noise_config.seed);
+            let generator = NoiseGenerator::new(medium_noise_config.seed).with_topology(
+                self.config.topology,
+                width as f32,
+                height as f32,
+            );
             let strengths = vec![self.config.detail_noise_strength; cells.len()];
-            let noise_values =
-                generator.generate_constrained_noise(cells, &medium_noise_config, &strengths);
+            let noise_values = self.constrained_noise(
+                &generator,
+                cells,
+                neighbors,
+                &medium_noise_config,
+                &strengths,
+            );
 
             for (i, &noise) in noise_values.iter().enumerate() {
                 heights[i] += noise * 20.0;
             }
 
             // 细节噪声 - 中等尺度，给陆地添加变化
-            let detail_noise_config = NoiseConfig {
-                octaves: 3,
-                base_frequency: 0.005,
-                persistence: 0.4,
-                lacunarity: 2.0,
-                seed: (self.config.tectonic.seed + 2) as u32,
-            };
+            let detail_noise_config =
+                NoiseConfig::regional().with_seed((self.config.tectonic.seed + 2) as u32);
 
-            let generator2 = NoiseGenerator::new(detail_noise_config.seed);
+            let generator2 = NoiseGenerator::new(detail_noise_config.seed).with_topology(
+                self.config.topology,
+                width as f32,
+                height as f32,
+            );
             let strengths2 = vec![self.config.detail_noise_strength * 0.5; cells.len()];
-            let noise_values2 =
-                generator2.generate_constrained_noise(cells, &detail_noise_config, &strengths2);
+            let noise_values2 = self.constrained_noise(
+                &generator2,
+                cells,
+                neighbors,
+                &detail_noise_config,
+                &strengths2,
+            );
 
             for (i, &noise) in noise_values2.iter().enumerate() {
                 heights[i] += noise * 12.0;
@@ -359,10 +857,18 @@ impl TerrainGenerator {
         }
 
         // 后生成噪声叠加：打破残余的放射状图案
-        self.apply_post_generation_noise(&mut heights, cells, self.config.tectonic.seed);
+        self.apply_post_generation_noise(
+            &mut heights,
+            cells,
+            (width, height),
+            self.config.tectonic.seed,
+            self.config.post_noise_warp,
+        );
+
+        progress(GenStage::Detail, 1.0);
 
         // 可选：侵蚀
-        if self.config.enable_erosion {
+        if self.config.enable_erosion && !self.config.preview {
             self.thermal_erosion(&mut heights, neighbors, self.config.erosion_iterations);
         }
 
@@ -379,6 +885,7 @@ impl TerrainGenerator {
 
         // 后处理：特征清理和海岸线优化
         self.post_process(&mut heights_u8, neighbors);
+        progress(GenStage::Postprocess, 1.0);
 
         // 模板模式下不生成板块数据
         let plates = Vec::new();
@@ -387,70 +894,48 @@ impl TerrainGenerator {
         (heights_u8, plates, plate_id)
     }
 
-    /// 使用模板和指定种子生成地形
+    /// 使用传统模板执行器和指定种子生成地形
     fn generate_from_template_with_seed(
         &self,
         cells: &[Pos2],
         neighbors: &[Vec<u32>],
         template: TerrainTemplate,
         seed: u64,
+        progress: &mut dyn FnMut(GenStage, f32),
     ) -> (Vec<u8>, Vec<TectonicPlate>, Vec<u16>) {
         #[cfg(debug_assertions)]
         println!("使用模板 '{}' 和种子 {} 生成地形", template.name, seed);
 
-        // Use the layered pipeline (same as generate_from_template)
-        // This ensures the sekai app and generate_screenshots use identical generation
-        if should_use_layered_generation(&template.name) {
-            let num_plates = get_suggested_plate_count(&template.name);
-            let ocean_ratio = get_suggested_ocean_ratio(&template.name);
-            let (mut heights_u8, plates, plate_ids) =
-                self.generate_layered(cells, neighbors, seed, num_plates, ocean_ratio);
-
-            self.apply_template_modifiers(&mut heights_u8, &template, cells, neighbors);
-            self.post_process(&mut heights_u8, neighbors);
-
-            return (heights_u8, plates, plate_ids);
-        }
-
-        // Fallback: legacy template executor
         // 计算地图尺寸
-        let (min_x, max_x, min_y, max_y) = cells.iter().fold(
-            (
-                f32::INFINITY,
-                f32::NEG_INFINITY,
-                f32::INFINITY,
-                f32::NEG_INFINITY,
-            ),
-            |(min_x, max_x, min_y, max_y), pos| {
-                (
-                    min_x.min(pos.x),
-                    max_x.max(pos.x),
-                    min_y.min(pos.y),
-                    max_y.max(pos.y),
-                )
-            },
-        );
-        let width = (max_x - min_x) as u32;
-        let height = (max_y - min_y) as u32;
+        let (width, height) = Self::cell_extent(cells);
 
         // 使用指定种子执行模板
-        let executor = TemplateExecutor::new(width, height, seed);
+        let executor = TemplateExecutor::new(width, height, seed)
+            .with_topology(self.config.topology)
+            .with_sea_level(self.config.sea_level);
         let mut heights = executor.execute(&template, cells, neighbors);
+        // 模板模式没有板块阶段，模板命令对应区域阶段
+        progress(GenStage::Plates, 1.0);
+        progress(GenStage::Tectonics, 1.0);
+        progress(GenStage::Regional, 1.0);
 
         // 可选：添加细节噪声
-        if self.config.detail_noise_strength > 0.0 {
-            let detail_noise_config = NoiseConfig {
-                octaves: 4,
-                base_frequency: 0.08,
-                persistence: 0.4,
-                lacunarity: 2.2,
-                seed: (seed + 1) as u32,
-            };
+        if self.config.detail_noise_strength > 0.0 && !self.config.preview {
+            let detail_noise_config = NoiseConfig::fine_detail().with_seed((seed + 1) as u32);
 
-            let generator = NoiseGenerator::new(detail_noise_config.seed);
+            let generator = NoiseGenerator::new(detail_noise_config.seed).with_topology(
+                self.config.topology,
+                width as f32,
+                height as f32,
+            );
             let strengths = vec![self.config.detail_noise_strength; cells.len()];
-            let noise_values =
-                generator.generate_constrained_noise(cells, &detail_noise_config, &strengths);
+            let noise_values = self.constrained_noise(
+                &generator,
+                cells,
+                neighbors,
+                &detail_noise_config,
+                &strengths,
+            );
 
             for (i, &noise) in noise_values.iter().enumerate() {
                 heights[i] += noise * 30.0;
@@ -458,10 +943,18 @@ impl TerrainGenerator {
         }
 
         // 后生成噪声叠加：打破残余的放射状图案
-        self.apply_post_generation_noise(&mut heights, cells, seed);
+        self.apply_post_generation_noise(
+            &mut heights,
+            cells,
+            (width, height),
+            seed,
+            self.config.post_noise_warp,
+        );
+
+        progress(GenStage::Detail, 1.0);
 
         // 可选：侵蚀
-        if self.config.enable_erosion {
+        if self.config.enable_erosion && !self.config.preview {
             self.thermal_erosion(&mut heights, neighbors, self.config.erosion_iterations);
         }
 
@@ -478,6 +971,7 @@ impl TerrainGenerator {
 
         // 后处理：特征清理和海岸线优化
         self.post_process(&mut heights_u8, neighbors);
+        progress(GenStage::Postprocess, 1.0);
 
         // 模板模式下不生成板块数据
         let plates = Vec::new();
@@ -491,26 +985,55 @@ impl TerrainGenerator {
         &self,
         cells: &[Pos2],
         neighbors: &[Vec<u32>],
+        progress: &mut dyn FnMut(GenStage, f32),
     ) -> (Vec<u8>, Vec<TectonicPlate>, Vec<u16>) {
         // ====== 阶段 1: 板块构造模拟 ======
-        let (mut heights, plates, plate_id) = self.simulate_plate_tectonics(cells, neighbors);
+        let (heights, plates, plate_id) = self.simulate_plate_tectonics(cells, neighbors, progress);
+        self.finish_tectonic(cells, neighbors, heights, plates, plate_id, progress)
+    }
+
+    /// 板块构造模拟之后的各阶段（浮力、噪声、侵蚀、重映射和后处理）
+    fn finish_tectonic(
+        &self,
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+        mut heights: Vec<f32>,
+        plates: Vec<TectonicPlate>,
+        plate_id: Vec<u16>,
+        progress: &mut dyn FnMut(GenStage, f32),
+    ) -> (Vec<u8>, Vec<TectonicPlate>, Vec<u16>) {
+        self.tectonic_regional(cells, neighbors, &mut heights, &plates, &plate_id);
+        progress(GenStage::Regional, 1.0);
+
+        self.tectonic_detail(cells, neighbors, &mut heights, &plates, &plate_id);
+        progress(GenStage::Detail, 1.0);
+
+        let heights_u8 = self.tectonic_finalize(&heights, neighbors);
+        progress(GenStage::Postprocess, 1.0);
+
+        (heights_u8, plates, plate_id)
+    }
 
+    /// 板块浮力、中尺度噪声和侵蚀（区域阶段）
+    pub(super) fn tectonic_regional(
+        &self,
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+        heights: &mut [f32],
+        plates: &[TectonicPlate],
+        plate_id: &[u16],
+    ) {
         // 根据板块类型和到边界距离加入浮力偏移，形成更稳定的海陆双峰分布
-        self.apply_plate_buoyancy(&mut heights, &plates, &plate_id, neighbors);
+        self.apply_plate_buoyancy(heights, plates, plate_id, neighbors);
 
         // ====== 阶段 2: 中尺度噪声（大地貌） ======
-        let medium_noise_config = NoiseConfig {
-            octaves: 3,
-            base_frequency: 0.01,
-            persistence: 0.5,
-            lacunarity: 2.0,
-            seed: self.config.tectonic.seed as u32,
-        };
+        let medium_noise_config =
+            NoiseConfig::medium_scale().with_seed(self.config.tectonic.seed as u32);
 
         self.apply_detail_noise(
-            &mut heights,
-            &plates,
-            &plate_id,
+            heights,
+            plates,
+            plate_id,
             cells,
             neighbors,
             &medium_noise_config,
@@ -518,30 +1041,41 @@ impl TerrainGenerator {
         );
 
         // ====== 阶段 3: 侵蚀模拟（可选） ======
-        if self.config.enable_erosion {
-            self.thermal_erosion(&mut heights, neighbors, self.config.erosion_iterations);
+        if self.config.enable_erosion && !self.config.preview {
+            self.thermal_erosion(heights, neighbors, self.config.erosion_iterations);
         }
+    }
 
+    /// 小尺度细节噪声（细节阶段，预览模式跳过）
+    pub(super) fn tectonic_detail(
+        &self,
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+        heights: &mut [f32],
+        plates: &[TectonicPlate],
+        plate_id: &[u16],
+    ) {
         // ====== 阶段 4: 小尺度噪声（细节） ======
-        let detail_noise_config = NoiseConfig {
-            octaves: 5,
-            base_frequency: 0.05,
-            persistence: 0.4,
-            lacunarity: 2.2,
-            seed: (self.config.tectonic.seed + 1) as u32,
-        };
-
-        self.apply_detail_noise(
-            &mut heights,
-            &plates,
-            &plate_id,
-            cells,
-            neighbors,
-            &detail_noise_config,
-            self.config.detail_noise_strength,
-        );
+        if !self.config.preview {
+            let detail_noise_config =
+                NoiseConfig::detail_scale().with_seed((self.config.tectonic.seed + 1) as u32);
+
+            self.apply_detail_noise(
+                heights,
+                plates,
+                plate_id,
+                cells,
+                neighbors,
+                &detail_noise_config,
+                self.config.detail_noise_strength,
+            );
+        }
+    }
 
+    /// 平滑、重映射到 u8 并后处理（后处理阶段）
+    pub(super) fn tectonic_finalize(&self, heights: &[f32], neighbors: &[Vec<u32>]) -> Vec<u8> {
         // ====== 阶段 5: 地貌整形与后处理 ======
+        let mut heights = heights.to_vec();
         if self.config.smoothing > 0 {
             self.smooth_heights(&mut heights, neighbors, self.config.smoothing);
         }
@@ -551,8 +1085,7 @@ impl TerrainGenerator {
 
         // 后处理：特征清理和海岸线优化
         self.post_process(&mut heights_u8, neighbors);
-
-        (heights_u8, plates, plate_id)
+        heights_u8
     }
 
     /// 板块构造模拟
@@ -560,14 +1093,36 @@ impl TerrainGenerator {
         &self,
         cells: &[Pos2],
         neighbors: &[Vec<u32>],
+        progress: &mut dyn FnMut(GenStage, f32),
     ) -> (Vec<f32>, Vec<TectonicPlate>, Vec<u16>) {
-        let generator = PlateGenerator::new(self.config.tectonic.clone());
+        let mut state = self.init_plate_tectonics(cells, neighbors);
+        progress(GenStage::Plates, 1.0);
+
+        let iterations = self.config.tectonic.iterations;
+        for i in 0..iterations {
+            progress(GenStage::Tectonics, i as f32 / iterations as f32);
+            self.tectonic_iteration(&mut state, neighbors);
+        }
+        progress(GenStage::Tectonics, 1.0);
+
+        (state.heights, state.plates, state.plate_id)
+    }
+
+    /// 生成板块、初始化高度并分析边界（板块阶段）
+    pub(super) fn init_plate_tectonics(
+        &self,
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+    ) -> TectonicState {
+        let mut tectonic = self.config.tectonic.clone();
+        tectonic.plate_count = self.plate_count_for_quality(tectonic.plate_count as usize) as _;
+        let generator = PlateGenerator::new(tectonic);
 
         // 1. 生成板块
         let (plates, plate_id) = generator.generate_plates(cells, neighbors);
 
         // 2. 初始化高度（基于板块类型）
-        let mut heights: Vec<f32> = plate_id
+        let heights: Vec<f32> = plate_id
             .iter()
             .map(|&pid| {
                 if pid == 0 {
@@ -581,20 +1136,30 @@ impl TerrainGenerator {
         // 3. 分析边界
         let boundaries = generator.analyze_boundaries(&plates, &plate_id, cells, neighbors);
 
-        // 4. 迭代模拟
-        for _ in 0..self.config.tectonic.iterations {
-            // 应用边界效应
-            self.apply_boundary_effects(&mut heights, &boundaries, &plate_id, neighbors);
-
-            // 地壳均衡调整
-            self.apply_isostasy(&mut heights, neighbors);
+        TectonicState {
+            heights,
+            plates,
+            plate_id,
+            boundaries,
         }
+    }
 
-        (heights, plates, plate_id)
+    /// 一次构造模拟迭代：边界效应 + 地壳均衡（构造阶段）
+    pub(super) fn tectonic_iteration(&self, state: &mut TectonicState, neighbors: &[Vec<u32>]) {
+        // 应用边界效应
+        self.apply_boundary_effects(
+            &mut state.heights,
+            &state.boundaries,
+            &state.plate_id,
+            neighbors,
+        );
+
+        // 地壳均衡调整
+        self.apply_isostasy(&mut state.heights, neighbors);
     }
 
     /// 应用边界效应
-    fn apply_boundary_effects(
+    pub(super) fn apply_boundary_effects(
         &self,
         heights: &mut [f32],
         boundaries: &[PlateBoundary],
@@ -619,8 +1184,8 @@ impl TerrainGenerator {
                 BoundaryType::Divergent { intensity } => {
                     self.apply_divergent_effects(heights, boundary, plate_id, neighbors, intensity);
                 }
-                BoundaryType::Transform { .. } => {
-                    // 转换边界对高度影响较小，暂不处理
+                BoundaryType::Transform { intensity } => {
+                    self.apply_transform_effects(heights, boundary, plate_id, neighbors, intensity);
                 }
             }
         }
@@ -731,13 +1296,81 @@ impl TerrainGenerator {
         }
     }
 
+    /// 应用转换边界效应
+    ///
+    /// 走滑错动在两侧形成错开的低矮脊与谷：`plate_a` 一侧略微抬升、`plate_b` 一侧略微下沉，
+    /// 再叠加按单元格哈希的轻微起伏。幅度约为汇聚边界隆起的五分之一。
+    fn apply_transform_effects(
+        &self,
+        heights: &mut [f32],
+        boundary: &PlateBoundary,
+        plate_id: &[u16],
+        neighbors: &[Vec<u32>],
+        intensity: f32,
+    ) {
+        let boundary_width = self.config.tectonic.boundary_width as usize;
+        let amplitude = self.config.tectonic.collision_uplift_rate * intensity * 0.03;
+
+        for &cell_idx in &boundary.cells {
+            let cell_idx = cell_idx as usize;
+
+            let mut visited = vec![false; heights.len()];
+            let mut queue = std::collections::VecDeque::new();
+            queue.push_back((cell_idx, 0));
+            visited[cell_idx] = true;
+
+            while let Some((current, distance)) = queue.pop_front() {
+                if distance >= boundary_width {
+                    continue;
+                }
+
+                let falloff = 1.0 - (distance as f32 / boundary_width as f32);
+
+                // 错开的脊/谷
+                let side = if plate_id[current] == boundary.plate_a {
+                    1.0
+                } else if plate_id[current] == boundary.plate_b {
+                    -1.0
+                } else {
+                    0.0
+                };
+                // 轻微粗糙化：由单元格索引和板块对决定的 [-1, 1] 伪随机值
+                let hash = (current as u64
+                    ^ ((boundary.plate_a as u64) << 32 | boundary.plate_b as u64))
+                    .wrapping_mul(0x9E37_79B9_7F4A_7C15);
+                let roughness = (hash >> 40) as f32 / (1u64 << 24) as f32 * 2.0 - 1.0;
+
+                heights[current] += amplitude * falloff * (side + 0.5 * roughness);
+
+                for &neighbor_idx in &neighbors[current] {
+                    let neighbor_idx = neighbor_idx as usize;
+                    if !visited[neighbor_idx] {
+                        visited[neighbor_idx] = true;
+                        queue.push_back((neighbor_idx, distance + 1));
+                    }
+                }
+            }
+        }
+    }
+
+    /// 逐单元格计算 `f(0..n)`，按 `parallel` 配置决定是否并行（见 [`map_cells`]）
+    fn map_cells<T, F>(&self, n: usize, f: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(usize) -> T + Sync + Send,
+    {
+        map_cells(n, self.config.parallel, f)
+    }
+
     /// 地壳均衡调整
     fn apply_isostasy(&self, heights: &mut [f32], neighbors: &[Vec<u32>]) {
-        let original = heights.to_vec();
-
-        for i in 0..heights.len() {
+        // 双缓冲：所有单元格都基于调整前的高度计算
+        let original = &*heights;
+        let rate = self.config.tectonic.isostasy_rate;
+        let adjusted = self.map_cells(original.len(), |i| {
+            let h = original[i];
             if neighbors[i].is_empty() {
-                continue;
+                return h;
             }
 
             let neighbor_avg: f32 = neighbors[i]
@@ -746,7 +1379,24 @@ impl TerrainGenerator {
                 .sum::<f32>()
                 / neighbors[i].len() as f32;
 
-            heights[i] += (neighbor_avg - heights[i]) * self.config.tectonic.isostasy_rate;
+            h + (neighbor_avg - h) * rate
+        });
+        heights.copy_from_slice(&adjusted);
+    }
+
+    /// 生成带约束的细节噪声，`smooth_detail_noise` 开启时先做邻域平滑
+    fn constrained_noise(
+        &self,
+        generator: &NoiseGenerator,
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+        config: &NoiseConfig,
+        strengths: &[f32],
+    ) -> Vec<f32> {
+        if self.config.smooth_detail_noise {
+            generator.generate_constrained_noise_smoothed(cells, config, strengths, neighbors)
+        } else {
+            generator.generate_constrained_noise(cells, config, strengths)
         }
     }
 
@@ -762,46 +1412,50 @@ impl TerrainGenerator {
         noise_config: &NoiseConfig,
         base_strength: f32,
     ) {
-        let generator = NoiseGenerator::new(noise_config.seed);
+        let (width, height) = Self::cell_extent(cells);
+        let generator = NoiseGenerator::new(noise_config.seed).with_topology(
+            self.config.topology,
+            width as f32,
+            height as f32,
+        );
 
         // 计算每个单元格的噪声强度
-        #[cfg(not(target_arch = "wasm32"))]
-        let iter = (0..heights.len()).into_par_iter();
-        #[cfg(target_arch = "wasm32")]
-        let iter = 0..heights.len();
-        let strengths: Vec<f32> = iter
-            .map(|i| {
-                let pid = plate_id[i];
-                if pid == 0 {
-                    return 0.0;
-                }
-
-                let plate = &plates[(pid - 1) as usize];
+        let strengths: Vec<f32> = self.map_cells(heights.len(), |i| {
+            let pid = plate_id[i];
+            if pid == 0 {
+                return 0.0;
+            }
 
-                // 基础强度（受板块类型影响）
-                let type_strength = match plate.plate_type {
-                    PlateType::Continental => base_strength * self.config.continental_noise_mult,
-                    PlateType::Oceanic => base_strength * self.config.oceanic_noise_mult,
-                };
+            let plate = &plates[(pid - 1) as usize];
 
-                // 边界抑制
-                let boundary_dist = self.calculate_boundary_distance(i, plate, neighbors);
-                let boundary_suppression = 1.0 - (-boundary_dist * 5.0).exp();
+            // 基础强度（受板块类型影响）
+            let type_strength = match plate.plate_type {
+                PlateType::Continental => base_strength * self.config.continental_noise_mult,
+                PlateType::Oceanic => base_strength * self.config.oceanic_noise_mult,
+            };
 
-                // 高度调制
-                let h = heights[i];
-                let erosion_factor = if h > SEA_LEVEL as f32 {
-                    1.0 + (h - SEA_LEVEL as f32) / 255.0 * 0.5
-                } else {
-                    0.5
-                };
+            // 边界抑制
+            let boundary_dist = self.calculate_boundary_distance(i, plate, neighbors);
+            let boundary_suppression = 1.0 - (-boundary_dist * 5.0).exp();
+
+            // 高度调制
+            let h = heights[i];
+            let sea = self.config.sea_level as f32;
+            let erosion_factor = if h > sea {
+                1.0 + (h - sea) / 255.0 * 0.5
+            } else {
+                0.5
+            };
 
-                type_strength * boundary_suppression * erosion_factor
-            })
-            .collect();
+            type_strength * boundary_suppression * erosion_factor
+        });
 
         // 生成并应用噪声
-        let noise_values = generator.generate_constrained_noise(cells, noise_config, &strengths);
+        let noise_values = if self.config.ridged_detail {
+            generator.generate_constrained_ridged(cells, noise_config, &strengths)
+        } else {
+            self.constrained_noise(&generator, cells, neighbors, noise_config, &strengths)
+        };
 
         for (i, &noise) in noise_values.iter().enumerate() {
             heights[i] += noise * 255.0;
@@ -881,10 +1535,10 @@ impl TerrainGenerator {
             return;
         }
 
-        for i in 0..heights.len() {
+        let offsets = self.map_cells(heights.len(), |i| {
             let pid = plate_id[i];
             if pid == 0 {
-                continue;
+                return 0.0;
             }
 
             let plate = &plates[(pid - 1) as usize];
@@ -893,15 +1547,15 @@ impl TerrainGenerator {
                 .min(12.0);
 
             match plate.plate_type {
-                PlateType::Continental => {
-                    // 大陆内部更厚、更高；边界保留一定起伏给造山带
-                    heights[i] += 6.0 + dist * 1.1;
-                }
-                PlateType::Oceanic => {
-                    // 海洋板块总体更低，内部更深，形成深海平原
-                    heights[i] -= 10.0 + dist * 0.9;
-                }
+                // 大陆内部更厚、更高；边界保留一定起伏给造山带
+                PlateType::Continental => 6.0 + dist * 1.1,
+                // 海洋板块总体更低，内部更深，形成深海平原
+                PlateType::Oceanic => -(10.0 + dist * 0.9),
             }
+        });
+
+        for (h, offset) in heights.iter_mut().zip(offsets) {
+            *h += offset;
         }
     }
 
@@ -914,15 +1568,16 @@ impl TerrainGenerator {
         let mut sorted = heights.to_vec();
         sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
 
-        let target_ocean_ratio =
-            (0.85 - self.config.tectonic.continental_ratio * 0.55).clamp(0.45, 0.80);
+        let target_ocean_ratio = self.config.ocean_ratio.unwrap_or_else(|| {
+            (0.85 - self.config.tectonic.continental_ratio * 0.55).clamp(0.45, 0.80)
+        });
         let idx = ((sorted.len() as f32) * target_ocean_ratio) as usize;
         let idx = idx.min(sorted.len() - 1);
         let sea_threshold = sorted[idx];
 
         let min_h = sorted[0];
         let max_h = sorted[sorted.len() - 1];
-        let sea = SEA_LEVEL as f32;
+        let sea = self.config.sea_level as f32;
 
         heights
             .iter()
@@ -954,7 +1609,7 @@ impl TerrainGenerator {
     /// Only Range and Strait commands are applied (as mountain chains and water channels).
     /// Hill/Mountain commands are skipped since the plate system already handles landmasses.
     /// The modifier strength is reduced to 30% to keep plate structure dominant.
-    fn apply_template_modifiers(
+    pub(super) fn apply_template_modifiers(
         &self,
         heights: &mut [u8],
         template: &TerrainTemplate,
@@ -983,7 +1638,8 @@ impl TerrainGenerator {
         let w = max_x - min_x;
         let h = max_y - min_y;
 
-        let modifier_strength = 0.3; // Only 30% of template effect
+        // 默认只应用 30% 的模板效果，避免压过板块地形
+        let modifier_strength = self.config.template_modifier_strength;
 
         for cmd in &template.commands {
             match cmd {
@@ -1073,7 +1729,16 @@ impl TerrainGenerator {
     /// 使用两层噪声：
     /// - 低频层：大尺度形变，使整体地形不对称
     /// - 中频层：中等尺度扰动，打破局部的圆形等高线
-    fn apply_post_generation_noise(&self, heights: &mut [f32], cells: &[Pos2], seed: u64) {
+    ///
+    /// `warp_strength` 为 `Some` 时两层都改用域扭曲采样，地形轮廓更自然。
+    fn apply_post_generation_noise(
+        &self,
+        heights: &mut [f32],
+        cells: &[Pos2],
+        (width, height): (u32, u32),
+        seed: u64,
+        warp_strength: Option<f64>,
+    ) {
         if heights.is_empty() {
             return;
         }
@@ -1083,28 +1748,33 @@ impl TerrainGenerator {
         let amplitude = max_h.abs().max(50.0); // 至少有一些影响
 
         // 低频噪声：大尺度形变
-        let low_freq_config = NoiseConfig {
-            octaves: 2,
-            base_frequency: 0.003,
-            persistence: 0.6,
-            lacunarity: 2.0,
-            seed: (seed + 42) as u32,
-        };
-        let gen_low = NoiseGenerator::new(low_freq_config.seed);
+        let low_freq_config = NoiseConfig::billowy().with_seed((seed + 42) as u32);
+        let topology = self.config.topology;
+        let gen_low = NoiseGenerator::new(low_freq_config.seed).with_topology(
+            topology,
+            width as f32,
+            height as f32,
+        );
 
         // 中频噪声：打破局部圆形
-        let mid_freq_config = NoiseConfig {
-            octaves: 3,
-            base_frequency: 0.008,
-            persistence: 0.45,
-            lacunarity: 2.2,
-            seed: (seed + 99) as u32,
-        };
-        let gen_mid = NoiseGenerator::new(mid_freq_config.seed);
+        let mid_freq_config = NoiseConfig::undulating().with_seed((seed + 99) as u32);
+        let gen_mid = NoiseGenerator::new(mid_freq_config.seed).with_topology(
+            topology,
+            width as f32,
+            height as f32,
+        );
 
         for (i, pos) in cells.iter().enumerate() {
-            let low_noise = gen_low.fbm(pos.x as f64, pos.y as f64, &low_freq_config) as f32;
-            let mid_noise = gen_mid.fbm(pos.x as f64, pos.y as f64, &mid_freq_config) as f32;
+            let (low_noise, mid_noise) = match warp_strength {
+                Some(warp) => (
+                    gen_low.warped_fbm_at(*pos, &low_freq_config, warp) as f32,
+                    gen_mid.warped_fbm_at(*pos, &mid_freq_config, warp) as f32,
+                ),
+                None => (
+                    gen_low.fbm_at(*pos, &low_freq_config) as f32,
+                    gen_mid.fbm_at(*pos, &mid_freq_config) as f32,
+                ),
+            };
 
             // Scale noise relative to terrain amplitude
             // Low freq: ~8% of amplitude, mid freq: ~5%
@@ -1155,7 +1825,7 @@ impl TerrainGenerator {
     ///
     /// 使用 Azgaar 风格的算法清理孤立的小岛和小湖，
     /// 并平滑海岸线以消除噪点。
-    fn post_process(&self, heights: &mut [u8], neighbors: &[Vec<u32>]) {
+    pub(super) fn post_process(&self, heights: &mut [u8], neighbors: &[Vec<u32>]) {
         use super::features::FeatureDetector;
 
         // 计算边界单元格（简化版：假设边缘索引的单元格是边界）
@@ -1168,7 +1838,9 @@ impl TerrainGenerator {
             })
             .collect();
 
-        let detector = FeatureDetector::new(self.config.min_island_size, self.config.min_lake_size);
+        let detector = FeatureDetector::new(self.config.min_island_size, self.config.min_lake_size)
+            .with_min_ocean_size(self.config.min_ocean_size)
+            .with_sea_level(self.config.sea_level);
 
         // 1. 检测所有连通区域（特征）
         let (features, _feature_ids) = detector.detect_features(heights, neighbors, &border_cells);
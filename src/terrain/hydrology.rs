@@ -1,7 +1,8 @@
 // 水系生成（河流、湖泊）
 
-use super::heightmap::SEA_LEVEL;
-use std::collections::HashMap;
+use super::features::{Feature, FeatureType};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
 
 /// 河流
 #[derive(Debug, Clone)]
@@ -91,6 +92,8 @@ impl HydrologyGenerator {
     }
 
     /// 提取河流
+    ///
+    /// 河口为流量达到阈值、流入海洋或终止于内陆洼地的陆地单元格。
     pub fn extract_rivers(
         &self,
         flux: &[u16],
@@ -101,12 +104,12 @@ impl HydrologyGenerator {
         let mut visited = vec![false; flux.len()];
         let mut rivers = Vec::new();
 
-        // 找到所有河口（流量大于阈值且流入海洋）
+        // 找到所有河口（流量大于阈值且流入海洋或没有下游）
         let mouths: Vec<usize> = (0..flux.len())
             .filter(|&i| {
                 flux[i] >= threshold
                     && is_land[i]
-                    && flow_direction[i].is_some_and(|d| !is_land[d as usize])
+                    && flow_direction[i].is_none_or(|d| !is_land[d as usize])
             })
             .collect();
 
@@ -285,9 +288,80 @@ impl HydrologyGenerator {
     }
 }
 
-/// 分析海陆分布
-pub fn classify_land_sea(heights: &[u8]) -> Vec<bool> {
-    heights.iter().map(|&h| h >= SEA_LEVEL).collect()
+/// 水流网络
+///
+/// 记录每个单元格的下游单元格、累积流量和河流折线，
+/// 供生物群系布置、地名标注等下游逻辑使用。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FlowNetwork {
+    /// 每个单元格的下游单元格（见 [`HydrologyGenerator::compute_flow_direction`]），
+    /// 海洋和洼地为 None
+    pub downhill: Vec<Option<u32>>,
+    /// 累积流量（每个单元格自身贡献 1，只沿陆地累积）
+    pub flux: Vec<f32>,
+    /// 河流折线，每条为从源头到河口（或汇入点）的单元格索引
+    pub rivers: Vec<Vec<usize>>,
+}
+
+impl FlowNetwork {
+    /// 默认的成河流量阈值
+    pub const DEFAULT_RIVER_THRESHOLD: f32 = 30.0;
+
+    /// 计算水流网络
+    ///
+    /// 基于 [`HydrologyGenerator`] 的流向、流量和河流提取：
+    /// 陆地（高度不低于 `sea_level`）上流量不低于 `river_threshold` 的单元格构成河流，
+    /// 每条河流树按源头从高到低拆成折线，沿下游走到海洋、洼地或已有折线为止。
+    pub fn compute(
+        heights: &[u8],
+        neighbors: &[Vec<u32>],
+        river_threshold: f32,
+        sea_level: u8,
+    ) -> Self {
+        let generator = HydrologyGenerator::new();
+        let is_land = classify_land_sea(heights, sea_level);
+        let downhill = generator.compute_flow_direction(heights, &is_land, neighbors);
+        let flux = generator.compute_flux(heights, &is_land, &downhill, None);
+        let trees =
+            generator.extract_rivers(&flux, &downhill, &is_land, river_threshold.ceil() as u16);
+
+        let mut sources: Vec<usize> = trees
+            .iter()
+            .flat_map(|river| river.source_cells.iter().map(|&c| c as usize))
+            .collect();
+        sources.sort_by(|&a, &b| heights[b].cmp(&heights[a]));
+
+        let mut in_river = vec![false; heights.len()];
+        let rivers = sources
+            .into_iter()
+            .map(|source| {
+                let mut path = vec![source];
+                in_river[source] = true;
+                let mut current = source;
+                while let Some(down) = downhill[current] {
+                    let down = down as usize;
+                    path.push(down);
+                    if in_river[down] || !is_land[down] {
+                        break;
+                    }
+                    in_river[down] = true;
+                    current = down;
+                }
+                path
+            })
+            .collect();
+
+        Self {
+            downhill,
+            flux: flux.into_iter().map(f32::from).collect(),
+            rivers,
+        }
+    }
+}
+
+/// 分析海陆分布：高度不低于 `sea_level` 的单元格为陆地
+pub fn classify_land_sea(heights: &[u8], sea_level: u8) -> Vec<bool> {
+    heights.iter().map(|&h| h >= sea_level).collect()
 }
 
 /// 连通分量检测（用于识别独立的大陆和岛屿）
@@ -347,14 +421,230 @@ pub fn find_coastline_cells(is_land: &[bool], neighbors: &[Vec<u32>]) -> Vec<u32
         .collect()
 }
 
+/// 填平内流盆地，形成湖泊
+///
+/// 优先队列洪水填充（priority-flood）：以所有低于 `sea_level` 的单元格为出水口，
+/// 按水面高度从低到高向内扩散；遇到比当前水面低的单元格时把它抬升到水面高度，
+/// 即其所在洼地的溢出高度。被抬升的单元格按连通性和水面高度分组，
+/// 每组作为一个湖泊特征返回（`area` 按单元格数量计），湖面完全平坦。
+/// 没有低于海平面的单元格时没有出水口，不做任何修改。
+pub fn fill_lakes(heights: &mut [u8], neighbors: &[Vec<u32>], sea_level: u8) -> Vec<Feature> {
+    let n = heights.len();
+    let mut visited = vec![false; n];
+    let mut raised = vec![false; n];
+    let mut queue = BinaryHeap::new();
+
+    for (i, &h) in heights.iter().enumerate() {
+        if h < sea_level {
+            visited[i] = true;
+            queue.push(Reverse((h, i)));
+        }
+    }
+
+    while let Some(Reverse((level, cell))) = queue.pop() {
+        for &neighbor in &neighbors[cell] {
+            let neighbor = neighbor as usize;
+            if visited[neighbor] {
+                continue;
+            }
+            visited[neighbor] = true;
+            if heights[neighbor] < level {
+                heights[neighbor] = level;
+                raised[neighbor] = true;
+            }
+            queue.push(Reverse((heights[neighbor], neighbor)));
+        }
+    }
+
+    // 按连通性和水面高度分组
+    let mut lakes = Vec::new();
+    let mut grouped = vec![false; n];
+    for start in 0..n {
+        if !raised[start] || grouped[start] {
+            continue;
+        }
+
+        let level = heights[start];
+        let mut cells = Vec::new();
+        let mut stack = vec![start];
+        grouped[start] = true;
+        while let Some(cell) = stack.pop() {
+            cells.push(cell);
+            for &neighbor in &neighbors[cell] {
+                let neighbor = neighbor as usize;
+                if raised[neighbor] && !grouped[neighbor] && heights[neighbor] == level {
+                    grouped[neighbor] = true;
+                    stack.push(neighbor);
+                }
+            }
+        }
+
+        cells.sort_unstable();
+        lakes.push(Feature {
+            id: (lakes.len() + 1) as u16,
+            feature_type: FeatureType::Lake,
+            area: cells.len() as f32,
+            cells,
+            is_border: false,
+        });
+    }
+
+    lakes
+}
+
+/// 流域划分
+///
+/// 每个陆地单元格（高度 >= `sea_level`）沿最陡下降方向流向更低的陆地邻居，
+/// 直到没有更低的陆地邻居为止；停下的单元格即汇点（内陆洼地或入海口），
+/// 汇点相同的单元格属于同一流域。
+/// 返回每个单元格的流域 ID（从 1 开始，按单元格索引顺序编号），水体单元格为 0。
+pub fn watersheds(heights: &[u8], neighbors: &[Vec<u32>], sea_level: u8) -> Vec<u32> {
+    let n = heights.len();
+    let is_land = |i: usize| heights[i] >= sea_level;
+
+    // 每个陆地单元格的最陡下游（只在陆地之间流动）
+    let downhill: Vec<Option<usize>> = (0..n)
+        .map(|i| {
+            if !is_land(i) {
+                return None;
+            }
+            neighbors[i]
+                .iter()
+                .map(|&nb| nb as usize)
+                .filter(|&nb| is_land(nb) && heights[nb] < heights[i])
+                .min_by_key(|&nb| (heights[nb], nb))
+        })
+        .collect();
+
+    // 沿下游链找到汇点，并把路径上的单元格一起记下
+    let mut sink = vec![usize::MAX; n];
+    let mut path = Vec::new();
+    for start in 0..n {
+        if !is_land(start) || sink[start] != usize::MAX {
+            continue;
+        }
+        let mut cell = start;
+        while sink[cell] == usize::MAX {
+            path.push(cell);
+            match downhill[cell] {
+                Some(next) => cell = next,
+                None => {
+                    sink[cell] = cell;
+                    break;
+                }
+            }
+        }
+        let end = sink[cell];
+        for c in path.drain(..) {
+            sink[c] = end;
+        }
+    }
+
+    let mut ids = HashMap::new();
+    (0..n)
+        .map(|i| {
+            if !is_land(i) {
+                return 0;
+            }
+            let next_id = ids.len() as u32 + 1;
+            *ids.entry(sink[i]).or_insert(next_id)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::terrain::heightmap::SEA_LEVEL;
+    use crate::terrain::test_grid::grid_neighbors;
+
+    #[test]
+    fn test_watersheds_split_at_ridge() {
+        // 12x7 四邻接网格，左右两列是海。山脊在第 5、6 列之间，
+        // 两端（第 0、6 行）最高、中间行是鞍部；两侧坡面先汇向中间行再流向各自的海
+        let (cols, rows) = (12usize, 7usize);
+        let mid = 3i32;
+        let mut heights = Vec::new();
+        for r in 0..rows {
+            for c in 0..cols {
+                heights.push(if c == 0 || c == cols - 1 {
+                    5
+                } else {
+                    let from_coast = (c - 1).min(cols - 2 - c) as i32;
+                    (30 + 4 * from_coast + 5 * (r as i32 - mid).abs()) as u8
+                });
+            }
+        }
+        let neighbors = grid_neighbors(cols, rows);
+
+        let basins = watersheds(&heights, &neighbors, SEA_LEVEL);
+
+        let west = basins[mid as usize * cols + 1];
+        let east = basins[mid as usize * cols + cols - 2];
+        assert_ne!(west, 0);
+        assert_ne!(east, 0);
+        assert_ne!(west, east);
+        for r in 0..rows {
+            for c in 0..cols {
+                let expected = match c {
+                    0 => 0,
+                    c if c == cols - 1 => 0,
+                    1..=5 => west,
+                    _ => east,
+                };
+                assert_eq!(basins[r * cols + c], expected, "cell ({}, {})", c, r);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fill_lakes_bowl_becomes_flat_lake() {
+        // 13x13 四邻接网格：最左列是海，中心 (7, 6) 周围一圈高 80 的环形山，
+        // 西侧缺口高 60；环内是 40~50 的碗，环外是 50 的平地
+        let size = 13usize;
+        let center = (7i32, 6i32);
+        let mut heights = Vec::new();
+        for r in 0..size {
+            for c in 0..size {
+                let ring = (c as i32 - center.0).abs().max((r as i32 - center.1).abs());
+                heights.push(match (c, ring) {
+                    (0, _) => 10,
+                    (_, 3) if (c as i32, r as i32) == (center.0 - 3, center.1) => 60,
+                    (_, 3) => 80,
+                    (_, 0..=2) => 40 + 5 * ring as u8,
+                    _ => 50,
+                });
+            }
+        }
+        let neighbors = grid_neighbors(size, size);
+        let original = heights.clone();
+
+        let lakes = fill_lakes(&mut heights, &neighbors, 20);
+
+        assert_eq!(lakes.len(), 1);
+        let lake = &lakes[0];
+        assert_eq!(lake.feature_type, FeatureType::Lake);
+        assert_eq!(lake.size(), 25);
+        for i in 0..heights.len() {
+            let (c, r) = ((i % size) as i32, (i / size) as i32);
+            let inside = (c - center.0).abs().max((r - center.1).abs()) <= 2;
+            if inside {
+                // 碗内填平到缺口的溢出高度
+                assert_eq!(heights[i], 60, "cell {}", i);
+                assert!(lake.cells.contains(&i));
+            } else {
+                assert_eq!(heights[i], original[i], "cell {}", i);
+            }
+        }
+
+        // 再次填充不再产生湖泊
+        assert!(fill_lakes(&mut heights, &neighbors, 20).is_empty());
+    }
 
     #[test]
     fn test_classify_land_sea() {
         let heights = vec![10, 20, 30, 5, 25];
-        let is_land = classify_land_sea(&heights);
+        let is_land = classify_land_sea(&heights, SEA_LEVEL);
 
         assert_eq!(is_land, vec![false, true, true, false, true]);
     }
@@ -373,4 +663,53 @@ mod tests {
         assert_eq!(flow_dir[1], Some(2));
         assert_eq!(flow_dir[3], Some(4));
     }
+
+    #[test]
+    fn test_flow_network_flux_accumulates_downhill() {
+        // 8x4 网格，高度沿 x 递减，最低点在左侧
+        let (cols, rows) = (8usize, 4usize);
+        let heights: Vec<u8> = (0..cols * rows)
+            .map(|i| 30 + (i % cols) as u8 * 10 + (i / cols) as u8)
+            .collect();
+        let neighbors = grid_neighbors(cols, rows);
+
+        let network = FlowNetwork::compute(&heights, &neighbors, 4.0, SEA_LEVEL);
+        let lowest = 0;
+        assert_eq!(network.downhill[lowest], None);
+        assert_eq!(network.flux[lowest], (cols * rows) as f32);
+
+        // 沿下游方向流量单调增加
+        for start in 0..heights.len() {
+            let mut current = start;
+            while let Some(down) = network.downhill[current] {
+                let down = down as usize;
+                assert!(network.flux[down] > network.flux[current]);
+                current = down;
+            }
+            assert_eq!(current, lowest);
+        }
+
+        assert!(!network.rivers.is_empty());
+        assert_eq!(*network.rivers[0].last().unwrap(), lowest);
+        for river in &network.rivers {
+            assert!(river.windows(2).all(|w| heights[w[1]] < heights[w[0]]));
+        }
+    }
+
+    #[test]
+    fn test_flow_network_matches_hydrology_generator() {
+        // 一条河谷：0 号为海洋，其余陆地逐级升高
+        let heights = vec![10, 40, 60, 80, 100];
+        let neighbors = vec![vec![1], vec![0, 2], vec![1, 3], vec![2, 4], vec![3]];
+
+        let network = FlowNetwork::compute(&heights, &neighbors, 3.0, SEA_LEVEL);
+        let generator = HydrologyGenerator::new();
+        let is_land = classify_land_sea(&heights, SEA_LEVEL);
+        let flow = generator.compute_flow_direction(&heights, &is_land, &neighbors);
+
+        assert_eq!(network.downhill, flow);
+        assert_eq!(network.flux, vec![5.0, 4.0, 3.0, 2.0, 1.0]);
+        // 河流从流量达到阈值处开始，流入海洋后结束
+        assert_eq!(network.rivers, vec![vec![2, 1, 0]]);
+    }
 }
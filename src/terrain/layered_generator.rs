@@ -3,6 +3,71 @@
 //! 管理和执行多个地形生成层
 
 use crate::terrain::layers::{LayerOutput, Pos2, TerrainLayer};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+/// 第 `layer_index` 层使用的种子
+///
+/// 用 splitmix64 混合地图种子和层序号，相邻的地图种子也会得到互不相关的层种子，
+/// 而加固定偏移（`seed + 100`）会让种子 1 的第二层与种子 101 的第一层相同。
+pub fn layer_seed(seed: u64, layer_index: usize) -> u64 {
+    splitmix64(splitmix64(seed) ^ layer_index as u64)
+}
+
+/// splitmix64 混合函数
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// 层输出检查点缓存
+///
+/// 按层名称保存最近一次的 `LayerOutput`，并记录生成它时所有输入
+/// （单元格、邻居以及该层及之前各层参数）的哈希值。
+/// 多个生成器可以共享同一个缓存：只修改靠后层的参数时，
+/// 前面昂贵的层（如板块生成）会直接复用缓存结果。
+#[derive(Default)]
+pub struct LayerCheckpoints {
+    entries: Mutex<HashMap<&'static str, (u64, LayerOutput)>>,
+}
+
+impl LayerCheckpoints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 读取与输入哈希匹配的检查点
+    pub fn get(&self, name: &str, key: u64) -> Option<LayerOutput> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(name)
+            .filter(|(cached_key, _)| *cached_key == key)
+            .map(|(_, output)| output.clone())
+    }
+
+    /// 保存检查点，覆盖同名层的旧结果
+    pub fn store(&self, name: &'static str, key: u64, output: &LayerOutput) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(name, (key, output.clone()));
+    }
+
+    /// 清空所有检查点
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// 已缓存的层数量
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
 
 /// 分层地形生成器
 ///
@@ -12,6 +77,8 @@ pub struct LayeredGenerator {
     layers: Vec<Box<dyn TerrainLayer>>,
     /// 随机种子
     seed: u64,
+    /// 可选的检查点缓存
+    checkpoints: Option<Arc<LayerCheckpoints>>,
 }
 
 impl LayeredGenerator {
@@ -20,6 +87,7 @@ impl LayeredGenerator {
         Self {
             layers: Vec::new(),
             seed: 0,
+            checkpoints: None,
         }
     }
 
@@ -29,6 +97,14 @@ impl LayeredGenerator {
         self
     }
 
+    /// 启用检查点缓存
+    ///
+    /// 生成时从最靠后的命中检查点继续执行，并把每个可缓存层的输出写回缓存。
+    pub fn with_checkpoints(mut self, checkpoints: Arc<LayerCheckpoints>) -> Self {
+        self.checkpoints = Some(checkpoints);
+        self
+    }
+
     /// 添加一个生成层
     pub fn add_layer<L: TerrainLayer + 'static>(mut self, layer: L) -> Self {
         self.layers.push(Box::new(layer));
@@ -50,25 +126,113 @@ impl LayeredGenerator {
     /// # 返回
     /// 最终的层输出结果
     pub fn generate(&self, cells: &[eframe::egui::Pos2], neighbors: &[Vec<u32>]) -> LayerOutput {
+        self.generate_with_progress(cells, neighbors, &mut |_| {})
+    }
+
+    /// 执行所有层生成地形，每层完成时以层名称调用 `progress`
+    ///
+    /// 从检查点恢复的层同样会按顺序报告，调用方总能收到每一层的通知。
+    pub fn generate_with_progress(
+        &self,
+        cells: &[eframe::egui::Pos2],
+        neighbors: &[Vec<u32>],
+        progress: &mut dyn FnMut(&'static str),
+    ) -> LayerOutput {
         // Convert eframe Pos2 to our Pos2
         let our_cells: Vec<Pos2> = cells.iter().map(|p| Pos2::new(p.x, p.y)).collect();
 
-        self.generate_internal(&our_cells, neighbors)
+        self.generate_internal(&our_cells, neighbors, progress)
     }
 
     /// Internal generation with our Pos2 type
-    fn generate_internal(&self, cells: &[Pos2], neighbors: &[Vec<u32>]) -> LayerOutput {
+    fn generate_internal(
+        &self,
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+        progress: &mut dyn FnMut(&'static str),
+    ) -> LayerOutput {
+        let Some(checkpoints) = &self.checkpoints else {
+            let mut output = LayerOutput::with_size(cells.len());
+            for layer in &self.layers {
+                #[cfg(debug_assertions)]
+                println!("执行层: {}", layer.name());
+                output = layer.generate(cells, neighbors, &output);
+                progress(layer.name());
+            }
+            return output;
+        };
+
+        let keys = self.checkpoint_keys(cells, neighbors);
+
+        // 从最靠后的命中检查点恢复
+        let mut start = 0;
         let mut output = LayerOutput::with_size(cells.len());
+        for (i, &key) in keys.iter().enumerate().rev() {
+            if let Some(cached) = checkpoints.get(self.layers[i].name(), key) {
+                output = cached;
+                start = i + 1;
+                break;
+            }
+        }
+        for layer in &self.layers[..start] {
+            progress(layer.name());
+        }
 
-        for layer in &self.layers {
+        for (i, layer) in self.layers.iter().enumerate().skip(start) {
             #[cfg(debug_assertions)]
             println!("执行层: {}", layer.name());
             output = layer.generate(cells, neighbors, &output);
+            if let Some(&key) = keys.get(i) {
+                checkpoints.store(layer.name(), key, &output);
+            }
+            progress(layer.name());
         }
 
         output
     }
 
+    /// 计算每层的检查点哈希
+    ///
+    /// 第 i 个哈希覆盖输入几何和第 0..=i 层的参数。遇到第一个不可缓存的层即停止，
+    /// 因此返回的数组长度可能小于层数。
+    fn checkpoint_keys(&self, cells: &[Pos2], neighbors: &[Vec<u32>]) -> Vec<u64> {
+        let mut hasher = DefaultHasher::new();
+        self.seed.hash(&mut hasher);
+        for p in cells {
+            p.x.to_bits().hash(&mut hasher);
+            p.y.to_bits().hash(&mut hasher);
+        }
+        neighbors.hash(&mut hasher);
+
+        let mut keys = Vec::new();
+        for layer in &self.layers {
+            let Some(layer_key) = layer.cache_key() else {
+                break;
+            };
+            layer.name().hash(&mut hasher);
+            layer_key.hash(&mut hasher);
+            keys.push(hasher.finish());
+        }
+        keys
+    }
+
+    /// 只执行第 `index` 层（用于分步生成）
+    ///
+    /// 依次对每一层调用本方法，与 [`LayeredGenerator::generate`] 结果相同。
+    /// 索引越界时返回 None。
+    pub fn generate_layer(
+        &self,
+        index: usize,
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+        previous: &LayerOutput,
+    ) -> Option<LayerOutput> {
+        let layer = self.layers.get(index)?;
+        #[cfg(debug_assertions)]
+        println!("执行层: {}", layer.name());
+        Some(layer.generate(cells, neighbors, previous))
+    }
+
     /// 返回已注册的层数量
     pub fn layer_count(&self) -> usize {
         self.layers.len()
@@ -85,3 +249,89 @@ impl Default for LayeredGenerator {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::layers::{DetailLayer, TectonicConfig, TectonicLayer};
+    use crate::terrain::test_grid::layer_grid;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// 统计 generate 调用次数的包装层
+    struct Counting<L> {
+        inner: L,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl<L: TerrainLayer> TerrainLayer for Counting<L> {
+        fn generate(
+            &self,
+            cells: &[Pos2],
+            neighbors: &[Vec<u32>],
+            previous: &LayerOutput,
+        ) -> LayerOutput {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.inner.generate(cells, neighbors, previous)
+        }
+
+        fn name(&self) -> &'static str {
+            self.inner.name()
+        }
+
+        fn cache_key(&self) -> Option<u64> {
+            self.inner.cache_key()
+        }
+    }
+
+    fn build(
+        checkpoints: &Arc<LayerCheckpoints>,
+        detail_seed: u32,
+        tectonic_calls: &Arc<AtomicUsize>,
+        detail_calls: &Arc<AtomicUsize>,
+    ) -> LayeredGenerator {
+        LayeredGenerator::new()
+            .with_checkpoints(checkpoints.clone())
+            .add_layer(Counting {
+                inner: TectonicLayer::new(TectonicConfig::default()).with_seed(7),
+                calls: tectonic_calls.clone(),
+            })
+            .add_layer(Counting {
+                inner: DetailLayer::new().with_seed(detail_seed),
+                calls: detail_calls.clone(),
+            })
+    }
+
+    #[test]
+    fn test_checkpoint_reuses_tectonic_output() {
+        let (cells, neighbors) = layer_grid(20, 20, 10.0);
+        let checkpoints = Arc::new(LayerCheckpoints::new());
+        let tectonic_calls = Arc::new(AtomicUsize::new(0));
+        let detail_calls = Arc::new(AtomicUsize::new(0));
+
+        let first = build(&checkpoints, 1, &tectonic_calls, &detail_calls);
+        first.generate_internal(&cells, &neighbors, &mut |_| {});
+        assert_eq!(tectonic_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(detail_calls.load(Ordering::SeqCst), 1);
+
+        // 只修改细节层种子：构造层直接从检查点恢复
+        let second = build(&checkpoints, 2, &tectonic_calls, &detail_calls);
+        let mut reported = Vec::new();
+        let resumed = second.generate_internal(&cells, &neighbors, &mut |name| reported.push(name));
+        assert_eq!(tectonic_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(detail_calls.load(Ordering::SeqCst), 2);
+        // 从检查点恢复的层也会报告进度
+        assert_eq!(reported, second.layer_names());
+
+        // 结果与不使用缓存时一致
+        let uncached = LayeredGenerator::new()
+            .add_layer(TectonicLayer::new(TectonicConfig::default()).with_seed(7))
+            .add_layer(DetailLayer::new().with_seed(2))
+            .generate_internal(&cells, &neighbors, &mut |_| {});
+        assert_eq!(resumed.heights, uncached.heights);
+
+        // 完全相同的参数：所有层都命中缓存
+        second.generate_internal(&cells, &neighbors, &mut |_| {});
+        assert_eq!(tectonic_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(detail_calls.load(Ordering::SeqCst), 2);
+    }
+}
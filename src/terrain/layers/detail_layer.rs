@@ -2,8 +2,12 @@
 //!
 //! Adds fine terrain details, only on land to keep oceans smooth.
 
-use super::r#trait::{LayerOutput, LegacyTerrainLayer, Pos2, TerrainContext, TerrainLayer};
+use super::r#trait::{
+    cell_bounds, map_cells, param_hash, LayerOutput, LegacyTerrainLayer, Pos2, TerrainContext,
+    TerrainLayer,
+};
 use crate::terrain::noise::{constrained_noise, smootherstep, NoiseConfig, NoiseGenerator};
+use crate::terrain::topology::Topology;
 
 /// Detail terrain layer for surface texture
 pub struct DetailLayer {
@@ -14,6 +18,10 @@ pub struct DetailLayer {
     /// Noise configuration
     config: NoiseConfig,
     generator: NoiseGenerator,
+    /// Map topology; wrapping topologies sample seamless noise across the edges
+    topology: Topology,
+    /// Sample cells in parallel with rayon (always serial on wasm)
+    parallel: bool,
 }
 
 impl Default for DetailLayer {
@@ -35,6 +43,8 @@ impl DetailLayer {
                 seed: 200,
             },
             generator: NoiseGenerator::new(200),
+            topology: Topology::Plane,
+            parallel: cfg!(not(target_arch = "wasm32")),
         }
     }
 
@@ -44,6 +54,16 @@ impl DetailLayer {
         self
     }
 
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn with_parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
     /// Sample detail at a point (only applies to land)
     fn sample_at(&self, x: f64, y: f64, is_land: bool, coast_distance: f64) -> f64 {
         // No detail in ocean
@@ -83,6 +103,15 @@ impl TerrainLayer for DetailLayer {
         "Detail"
     }
 
+    fn cache_key(&self) -> Option<u64> {
+        Some(param_hash(&(
+            self.amplitude,
+            self.threshold,
+            &self.config,
+            self.topology,
+        )))
+    }
+
     fn generate(
         &self,
         cells: &[Pos2],
@@ -90,18 +119,22 @@ impl TerrainLayer for DetailLayer {
         previous: &LayerOutput,
     ) -> LayerOutput {
         let mut output = previous.clone();
+        let (_, _, width, height) = cell_bounds(cells);
+        let generator =
+            NoiseGenerator::new(self.config.seed).with_topology(self.topology, width, height);
 
-        for (i, cell) in cells.iter().enumerate() {
-            let is_land = previous.heights[i] > 0.0;
-
+        let offsets = map_cells(cells.len(), self.parallel, |i| {
             // Only add detail to land
-            if is_land {
-                let noise = self
-                    .generator
-                    .fbm(cell.x as f64, cell.y as f64, &self.config);
-                let filtered = constrained_noise(noise, self.threshold);
-                output.heights[i] += (filtered * self.amplitude) as f32;
+            if previous.heights[i] <= 0.0 {
+                return 0.0;
             }
+            let cell = cells[i];
+            let noise = generator.fbm_at(eframe::egui::pos2(cell.x, cell.y), &self.config);
+            let filtered = constrained_noise(noise, self.threshold);
+            (filtered * self.amplitude) as f32
+        });
+        for (h, offset) in output.heights.iter_mut().zip(offsets) {
+            *h += offset;
         }
 
         output
@@ -0,0 +1,270 @@
+//! Erosion layer - droplet-based hydraulic erosion
+//!
+//! Simulates rain droplets running downhill over the cell graph, carving
+//! channels on steep slopes and depositing sediment where the flow slows.
+
+use super::r#trait::{param_hash, LayerOutput, Pos2, TerrainLayer};
+use rand::{Rng, SeedableRng};
+
+/// Land cells are never eroded below this height, so erosion alone
+/// cannot turn land into sea
+const MIN_LAND_HEIGHT: f32 = 0.5;
+
+/// Erosion configuration
+#[derive(Debug, Clone)]
+pub struct ErosionConfig {
+    /// Number of droplets to simulate
+    pub droplets: usize,
+    /// How much a droplet keeps its previous direction (0.0-1.0)
+    pub inertia: f32,
+    /// Sediment capacity multiplier
+    pub capacity: f32,
+    /// Fraction of excess sediment deposited per step (0.0-1.0)
+    pub deposition: f32,
+    /// Fraction of free capacity eroded per step (0.0-1.0)
+    pub erosion: f32,
+    /// Fraction of water evaporated per step (0.0-1.0)
+    pub evaporation: f32,
+    /// Maximum steps per droplet
+    pub max_steps: usize,
+    /// Random seed for droplet placement
+    pub seed: u64,
+}
+
+impl Default for ErosionConfig {
+    fn default() -> Self {
+        Self {
+            droplets: 20000,
+            inertia: 0.3,
+            capacity: 1.0,
+            deposition: 0.3,
+            erosion: 0.1,
+            evaporation: 0.02,
+            max_steps: 64,
+            seed: 300,
+        }
+    }
+}
+
+/// Hydraulic erosion layer
+///
+/// Droplets start on random land cells. Sea cells (height <= 0) stop a
+/// droplet; its sediment settles there without raising the cell above sea
+/// level, so the coastline stays put. Intended to run between
+/// `DetailLayer` and `PostprocessLayer`.
+pub struct ErosionLayer {
+    config: ErosionConfig,
+}
+
+impl Default for ErosionLayer {
+    fn default() -> Self {
+        Self::new(ErosionConfig::default())
+    }
+}
+
+impl ErosionLayer {
+    pub fn new(config: ErosionConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.config.seed = seed;
+        self
+    }
+
+    /// Unit vector from cell `a` to cell `b`
+    fn direction(cells: &[Pos2], a: usize, b: usize) -> (f32, f32) {
+        let (dx, dy) = (cells[b].x - cells[a].x, cells[b].y - cells[a].y);
+        let len = (dx * dx + dy * dy).sqrt().max(f32::EPSILON);
+        (dx / len, dy / len)
+    }
+
+    /// Pick the next cell: blend the previous direction with the steepest
+    /// descent direction and follow the neighbor that best matches it
+    fn next_cell(
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+        heights: &[f32],
+        current: usize,
+        dir: (f32, f32),
+        inertia: f32,
+    ) -> Option<(usize, (f32, f32))> {
+        let lowest = neighbors[current]
+            .iter()
+            .map(|&n| n as usize)
+            .min_by(|&a, &b| heights[a].total_cmp(&heights[b]))?;
+
+        let down = Self::direction(cells, current, lowest);
+        let blended = (
+            dir.0 * inertia + down.0 * (1.0 - inertia),
+            dir.1 * inertia + down.1 * (1.0 - inertia),
+        );
+
+        neighbors[current]
+            .iter()
+            .map(|&n| n as usize)
+            .map(|n| {
+                let d = Self::direction(cells, current, n);
+                (n, d, d.0 * blended.0 + d.1 * blended.1)
+            })
+            .max_by(|a, b| a.2.total_cmp(&b.2))
+            .map(|(n, d, _)| (n, d))
+    }
+
+    /// Run all droplets over the height field in place
+    fn erode(&self, cells: &[Pos2], neighbors: &[Vec<u32>], heights: &mut [f32]) {
+        let land: Vec<usize> = (0..heights.len()).filter(|&i| heights[i] > 0.0).collect();
+        if land.is_empty() {
+            return;
+        }
+
+        let cfg = &self.config;
+        let inertia = cfg.inertia.clamp(0.0, 1.0);
+        let deposition = cfg.deposition.clamp(0.0, 1.0);
+        let erosion = cfg.erosion.clamp(0.0, 1.0);
+        let evaporation = cfg.evaporation.clamp(0.0, 1.0);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(cfg.seed);
+
+        for _ in 0..cfg.droplets {
+            let mut current = land[rng.random_range(0..land.len())];
+            let mut dir = (0.0, 0.0);
+            let mut water = 1.0f32;
+            let mut sediment = 0.0f32;
+
+            for _ in 0..cfg.max_steps {
+                let Some((next, next_dir)) =
+                    Self::next_cell(cells, neighbors, heights, current, dir, inertia)
+                else {
+                    break;
+                };
+
+                let drop = heights[current] - heights[next];
+                if drop <= 0.0 {
+                    // Uphill: fill the pit with sediment, the droplet stops
+                    let filled = sediment.min(-drop);
+                    heights[current] += filled;
+                    sediment -= filled;
+                    break;
+                }
+
+                let capacity = drop * water * cfg.capacity;
+                if sediment > capacity {
+                    let deposited = (sediment - capacity) * deposition;
+                    heights[current] += deposited;
+                    sediment -= deposited;
+                } else {
+                    // Never dig below the downstream cell or below sea level
+                    let eroded = ((capacity - sediment) * erosion)
+                        .min(drop)
+                        .min((heights[current] - MIN_LAND_HEIGHT).max(0.0));
+                    heights[current] -= eroded;
+                    sediment += eroded;
+                }
+
+                water *= 1.0 - evaporation;
+                dir = next_dir;
+                current = next;
+
+                if heights[current] <= 0.0 || water <= 0.01 {
+                    break;
+                }
+            }
+
+            // Settle remaining sediment without creating new land
+            if heights[current] <= 0.0 {
+                heights[current] = (heights[current] + sediment).min(0.0);
+            } else {
+                heights[current] += sediment;
+            }
+        }
+    }
+}
+
+impl TerrainLayer for ErosionLayer {
+    fn name(&self) -> &'static str {
+        "Erosion"
+    }
+
+    fn cache_key(&self) -> Option<u64> {
+        Some(param_hash(&self.config))
+    }
+
+    fn generate(
+        &self,
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+        previous: &LayerOutput,
+    ) -> LayerOutput {
+        let mut output = previous.clone();
+        self.erode(cells, neighbors, &mut output.heights);
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Variance of per-cell local relief (height minus neighbor mean)
+    fn relief_variance(heights: &[f32], neighbors: &[Vec<u32>]) -> f32 {
+        let relief: Vec<f32> = neighbors
+            .iter()
+            .enumerate()
+            .map(|(i, ns)| {
+                let mean = ns.iter().map(|&n| heights[n as usize]).sum::<f32>() / ns.len() as f32;
+                heights[i] - mean
+            })
+            .collect();
+        let mean = relief.iter().sum::<f32>() / relief.len() as f32;
+        relief.iter().map(|r| (r - mean).powi(2)).sum::<f32>() / relief.len() as f32
+    }
+
+    #[test]
+    fn test_erosion_keeps_land_and_adds_relief() {
+        let (cols, rows) = (40usize, 40usize);
+        let mut cells = Vec::new();
+        let mut neighbors = Vec::new();
+        for i in 0..cols * rows {
+            let (row, col) = (i / cols, i % cols);
+            cells.push(Pos2::new(col as f32 * 10.0, row as f32 * 10.0));
+            let mut n = Vec::new();
+            for (dc, dr) in [(-1i32, 0i32), (1, 0), (0, -1), (0, 1), (-1, -1), (1, 1)] {
+                let (c, r) = (col as i32 + dc, row as i32 + dr);
+                if (0..cols as i32).contains(&c) && (0..rows as i32).contains(&r) {
+                    n.push((r as usize * cols + c as usize) as u32);
+                }
+            }
+            neighbors.push(n);
+        }
+
+        // Smooth dome surrounded by sea
+        let heights: Vec<f32> = cells
+            .iter()
+            .map(|p| 100.0 - ((p.x - 195.0).powi(2) + (p.y - 195.0).powi(2)).sqrt() * 0.7)
+            .collect();
+        let previous = LayerOutput {
+            heights,
+            ..Default::default()
+        };
+
+        let layer = ErosionLayer::new(ErosionConfig {
+            droplets: 4000,
+            ..Default::default()
+        });
+        let output = layer.generate(&cells, &neighbors, &previous);
+
+        let land = |h: &[f32]| h.iter().filter(|&&h| h > 0.0).count() as f32;
+        let before_land = land(&previous.heights);
+        let after_land = land(&output.heights);
+        assert!(
+            (after_land - before_land).abs() <= before_land * 0.05,
+            "land {} -> {}",
+            before_land,
+            after_land
+        );
+
+        let before = relief_variance(&previous.heights, &neighbors);
+        let after = relief_variance(&output.heights, &neighbors);
+        assert!(after > before, "relief variance {} -> {}", before, after);
+    }
+}
@@ -3,6 +3,7 @@
 //! Each layer adds a specific aspect to the terrain.
 
 mod detail_layer;
+mod erosion_layer;
 mod plate_layer;
 mod postprocess_layer;
 mod regional_layer;
@@ -13,10 +14,14 @@ mod tectonic_layer;
 pub mod r#trait;
 
 pub use detail_layer::DetailLayer;
+pub use erosion_layer::{ErosionConfig, ErosionLayer};
 pub use plate_layer::{BoundaryType, Plate, PlateConfig, PlateLayer, PlateType};
 pub use postprocess_layer::{PostprocessConfig, PostprocessLayer};
 pub use regional_layer::RegionalLayer;
 pub use tectonic_layer::{TectonicConfig, TectonicLayer};
 
 // Re-export the trait and types
-pub use r#trait::{LayerOutput, LegacyTerrainLayer, Pos2, TerrainContext, TerrainLayer};
+pub use r#trait::{
+    cell_bounds, map_cells, param_hash, perlin_at, LayerOutput, LegacyTerrainLayer, Pos2,
+    TerrainContext, TerrainLayer,
+};
@@ -3,7 +3,10 @@
 //! Uses elliptical continent placement with noise-perturbed edges for naturally
 //! broad landmasses, then partitions into tectonic plates via BFS.
 
-use super::r#trait::{LayerOutput, Pos2, TerrainLayer};
+use super::r#trait::{cell_bounds, param_hash, LayerOutput, Pos2, TerrainLayer};
+use crate::terrain::plate::{relative_approach, BoundaryKind};
+use crate::terrain::topology::Topology;
+use eframe::egui;
 use noise::{NoiseFn, Perlin};
 use rand::{Rng, SeedableRng};
 use std::cmp::Ordering;
@@ -31,6 +34,8 @@ pub struct PlateConfig {
     pub continental_ratio: f32,
     pub continental_base: f32,
     pub oceanic_base: f32,
+    /// Exact number of continental plates; overrides the default share when set
+    pub continental_count: Option<usize>,
 }
 
 impl Default for PlateConfig {
@@ -40,6 +45,7 @@ impl Default for PlateConfig {
             continental_ratio: 0.35,
             continental_base: 80.0,
             oceanic_base: -50.0,
+            continental_count: None,
         }
     }
 }
@@ -98,6 +104,8 @@ struct ContinentEllipse {
 pub struct PlateLayer {
     config: PlateConfig,
     seed: u64,
+    /// Map topology; wrapping topologies let continents and edge noise cross the seam
+    topology: Topology,
 }
 
 impl Default for PlateLayer {
@@ -108,7 +116,11 @@ impl Default for PlateLayer {
 
 impl PlateLayer {
     pub fn new(config: PlateConfig) -> Self {
-        Self { config, seed: 0 }
+        Self {
+            config,
+            seed: 0,
+            topology: Topology::Plane,
+        }
     }
 
     pub fn with_seed(mut self, seed: u64) -> Self {
@@ -116,6 +128,23 @@ impl PlateLayer {
         self
     }
 
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// Edge perturbation noise at a normalized position, seamless on wrapping topologies
+    fn edge_noise(&self, perlin: &Perlin, nx: f32, ny: f32, edge_angle: f32) -> f64 {
+        let w = edge_angle as f64 * 0.3;
+        match self.topology {
+            Topology::Plane => perlin.get([nx as f64 * 3.0, ny as f64 * 3.0, w]),
+            topology => {
+                let p = topology.noise_coords(eframe::egui::pos2(nx, ny), 1.0, 1.0);
+                perlin.get([p[0] * 3.0, p[1] * 3.0, p[2] * 3.0, w])
+            }
+        }
+    }
+
     /// Generate plates using elliptical continent placement.
     ///
     /// 1. Place large ellipses as continents (inherently broad shapes)
@@ -131,14 +160,7 @@ impl PlateLayer {
         let n = cells.len();
 
         // Map bounds
-        let (min_x, max_x) = cells.iter().fold((f32::MAX, f32::MIN), |(lo, hi), p| {
-            (lo.min(p.x), hi.max(p.x))
-        });
-        let (min_y, max_y) = cells.iter().fold((f32::MAX, f32::MIN), |(lo, hi), p| {
-            (lo.min(p.y), hi.max(p.y))
-        });
-        let range_x = (max_x - min_x).max(1.0);
-        let range_y = (max_y - min_y).max(1.0);
+        let (min_x, min_y, range_x, range_y) = cell_bounds(cells);
 
         // Step 1: Place continent ellipses
         let perlin_edge = Perlin::new(self.seed as u32);
@@ -216,7 +238,11 @@ impl PlateLayer {
                 let ny = (p.y - min_y) / range_y;
 
                 for ell in &ellipses {
-                    let dx = nx - ell.cx;
+                    let mut dx = nx - ell.cx;
+                    // Wrapping maps measure the shorter way around the seam
+                    if self.topology.wraps_x() {
+                        dx -= dx.round();
+                    }
                     let dy = ny - ell.cy;
                     let cos_a = ell.angle.cos();
                     let sin_a = ell.angle.sin();
@@ -227,11 +253,7 @@ impl PlateLayer {
 
                     // Low-freq noise only — prevents thin tendrils at coast
                     let edge_angle = ly.atan2(lx);
-                    let noise1 = perlin_edge.get([
-                        nx as f64 * 3.0,
-                        ny as f64 * 3.0,
-                        edge_angle as f64 * 0.3,
-                    ]) as f32;
+                    let noise1 = self.edge_noise(&perlin_edge, nx, ny, edge_angle) as f32;
                     // Small perturbation for organic coast, capped to prevent thin tendrils
                     let edge_perturbation = noise1 * 0.12;
 
@@ -244,7 +266,10 @@ impl PlateLayer {
             .collect();
 
         // Step 2: Seed plates
-        let num_continental_plates = ((self.config.num_plates as f32) * 0.45).ceil() as usize;
+        let num_continental_plates = match self.config.continental_count {
+            Some(count) => count.min(self.config.num_plates),
+            None => ((self.config.num_plates as f32) * 0.45).ceil() as usize,
+        };
         let num_oceanic_plates = self
             .config
             .num_plates
@@ -400,23 +425,78 @@ impl PlateLayer {
         seeds
     }
 
-    /// Detect boundary cells between plates
+    /// Detect boundary cells between plates and classify them by relative motion
+    ///
+    /// For every neighbor on another plate, the velocity of this cell's plate
+    /// relative to the neighbor's plate is split into a component along the
+    /// boundary normal (pointing at the neighbor) and a tangential component,
+    /// averaged over all such neighbors. The normal component is the same
+    /// [`relative_approach`] used by the classic generator and is classified with
+    /// [`BoundaryKind::from_approach`]: past its threshold it gives `Convergent`
+    /// (moving together) or `Divergent` (moving apart), unless the tangential
+    /// component dominates, which gives `Transform`. The intensity is the
+    /// magnitude of the chosen component.
     pub fn detect_boundaries(
         &self,
         plate_ids: &[u16],
+        plates: &[Plate],
+        cells: &[Pos2],
         neighbors: &[Vec<u32>],
     ) -> Vec<(usize, BoundaryType)> {
+        let velocity = |pid: u16| {
+            plates
+                .iter()
+                .find(|p| p.id == pid)
+                .map_or(egui::Pos2::ZERO, |p| {
+                    egui::pos2(p.direction.cos() * p.speed, p.direction.sin() * p.speed)
+                })
+        };
+
         let mut boundaries = Vec::new();
 
         for (i, &plate_id) in plate_ids.iter().enumerate() {
+            let own = velocity(plate_id);
+            let mut normal_sum = 0.0f32;
+            let mut tangent_sum = 0.0f32;
+            let mut count = 0;
+
             for &neighbor in &neighbors[i] {
-                let neighbor_plate = plate_ids[neighbor as usize];
-                if neighbor_plate != plate_id {
-                    let boundary_type = BoundaryType::Convergent { intensity: 1.0 };
-                    boundaries.push((i, boundary_type));
-                    break;
+                let neighbor = neighbor as usize;
+                let neighbor_plate = plate_ids[neighbor];
+                if neighbor_plate == plate_id {
+                    continue;
                 }
+
+                let (dx, dy) = (
+                    cells[neighbor].x - cells[i].x,
+                    cells[neighbor].y - cells[i].y,
+                );
+                let len = (dx * dx + dy * dy).sqrt().max(f32::EPSILON);
+                let normal = egui::vec2(dx / len, dy / len);
+
+                let other = velocity(neighbor_plate);
+                let relative = own - other;
+                normal_sum += relative_approach(own, other, normal);
+                tangent_sum += (relative.x * normal.y - relative.y * normal.x).abs();
+                count += 1;
+            }
+
+            if count == 0 {
+                continue;
             }
+
+            let normal = normal_sum / count as f32;
+            let tangent = tangent_sum / count as f32;
+            let boundary_type = match BoundaryKind::from_approach(normal) {
+                BoundaryKind::Convergent if normal >= tangent => {
+                    BoundaryType::Convergent { intensity: normal }
+                }
+                BoundaryKind::Divergent if -normal >= tangent => {
+                    BoundaryType::Divergent { intensity: -normal }
+                }
+                _ => BoundaryType::Transform { intensity: tangent },
+            };
+            boundaries.push((i, boundary_type));
         }
 
         boundaries
@@ -428,6 +508,10 @@ impl TerrainLayer for PlateLayer {
         "Plates"
     }
 
+    fn cache_key(&self) -> Option<u64> {
+        Some(param_hash(&(self.seed, &self.config, self.topology)))
+    }
+
     fn generate(
         &self,
         cells: &[Pos2],
@@ -458,3 +542,92 @@ impl TerrainLayer for PlateLayer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::test_grid::layer_grid;
+
+    #[test]
+    fn test_boundary_classification_from_motion() {
+        let (cols, rows) = (20, 10);
+        let (cells, neighbors) = layer_grid(cols, rows, 10.0);
+        let plate_ids: Vec<u16> = (0..cols * rows)
+            .map(|i| if i % cols < cols / 2 { 1 } else { 2 })
+            .collect();
+        let plate = |id: u16, direction: f32| Plate {
+            id,
+            plate_type: PlateType::Continental,
+            direction,
+            speed: 1.0,
+            cells: Vec::new(),
+        };
+        let layer = PlateLayer::default();
+        let classify =
+            |plates: &[Plate]| layer.detect_boundaries(&plate_ids, plates, &cells, &neighbors);
+
+        // Plate 1 on the left moves right, plate 2 on the right moves left
+        let toward = classify(&[plate(1, 0.0), plate(2, std::f32::consts::PI)]);
+        assert_eq!(toward.len(), rows * 2);
+        for (i, boundary) in &toward {
+            assert!(
+                matches!(boundary, BoundaryType::Convergent { intensity } if (intensity - 2.0).abs() < 1e-4),
+                "cell {}: {:?}",
+                i,
+                boundary
+            );
+        }
+
+        let apart = classify(&[plate(1, std::f32::consts::PI), plate(2, 0.0)]);
+        assert!(apart
+            .iter()
+            .all(|(_, b)| matches!(b, BoundaryType::Divergent { intensity } if *intensity > 1.0)));
+
+        // Sliding past each other along the boundary
+        let sliding = classify(&[
+            plate(1, std::f32::consts::FRAC_PI_2),
+            plate(2, -std::f32::consts::FRAC_PI_2),
+        ]);
+        assert!(sliding
+            .iter()
+            .all(|(_, b)| matches!(b, BoundaryType::Transform { .. })));
+
+        // Approaching slower than the shared convergence threshold
+        let creeping = classify(&[
+            Plate {
+                speed: 0.1,
+                ..plate(1, 0.0)
+            },
+            Plate {
+                speed: 0.1,
+                ..plate(2, std::f32::consts::PI)
+            },
+        ]);
+        assert!(creeping
+            .iter()
+            .all(|(_, b)| matches!(b, BoundaryType::Transform { .. })));
+    }
+
+    #[test]
+    fn test_exact_continental_count() {
+        let (cells, neighbors) = layer_grid(40, 40, 10.0);
+        let config = PlateConfig {
+            num_plates: 10,
+            continental_count: Some(2),
+            ..Default::default()
+        };
+
+        for seed in [1, 2, 3] {
+            let (_, plates) = PlateLayer::new(config.clone())
+                .with_seed(seed)
+                .generate_plates(&cells, &neighbors);
+
+            let continental = plates
+                .iter()
+                .filter(|p| p.plate_type == PlateType::Continental)
+                .count();
+            assert_eq!(continental, 2);
+            assert_eq!(plates.len(), 10);
+        }
+    }
+}
@@ -1,10 +1,14 @@
 //! Postprocess layer - terrain cleanup and smoothing
 //!
-//! Removes small islands, fills small lakes, smooths coastlines.
+//! Removes small islands, fills small lakes, smooths coastlines and
+//! shapes a shallow continental shelf around land.
 
-use super::r#trait::{LayerOutput, Pos2, TerrainLayer};
+use super::r#trait::{param_hash, LayerOutput, Pos2, TerrainLayer};
 use std::collections::VecDeque;
 
+/// Shelf floor drop per hop away from land (layer height units, sea level is 0.0)
+const SHELF_DEPTH_PER_HOP: f32 = 4.0;
+
 /// Postprocessing configuration
 #[derive(Debug, Clone)]
 pub struct PostprocessConfig {
@@ -16,6 +20,8 @@ pub struct PostprocessConfig {
     pub smoothing_iterations: u32,
     /// Target ocean ratio (0.0-1.0), e.g., 0.7 means 70% ocean
     pub ocean_ratio: f32,
+    /// Continental shelf width in graph hops from land (0 disables the shelf)
+    pub shelf_width: u32,
 }
 
 impl Default for PostprocessConfig {
@@ -25,6 +31,7 @@ impl Default for PostprocessConfig {
             min_lake_size: 10,
             smoothing_iterations: 2,
             ocean_ratio: 0.65,
+            shelf_width: 3,
         }
     }
 }
@@ -154,9 +161,19 @@ impl PostprocessLayer {
         }
     }
 
-    /// Continental shelf pass: smooth cells near land-sea boundaries
-    /// Gradually transitions ocean depth near coast and land height near shore
-    fn continental_shelf_pass(heights: &mut [f32], neighbors: &[Vec<u32>], max_hops: usize) {
+    /// Continental shelf pass: gradual transitions near land-sea boundaries
+    ///
+    /// Cells within `shelf_width` hops of the coast are blended towards their
+    /// neighbours, with strength decreasing with distance. Ocean cells `d` hops
+    /// from the nearest land cell (1 = adjacent) are then lifted to at least
+    /// `-SHELF_DEPTH_PER_HOP * d` for `d <= shelf_width`, so depth increases
+    /// gradually away from the coast. The depth clamp never lowers cells and
+    /// keeps them below sea level. A `shelf_width` of 0 disables the pass.
+    fn continental_shelf_pass(heights: &mut [f32], neighbors: &[Vec<u32>], shelf_width: u32) {
+        if shelf_width == 0 {
+            return;
+        }
+
         // Find land-sea boundary cells
         let n = heights.len();
         let mut dist_to_coast = vec![u32::MAX; n];
@@ -176,7 +193,7 @@ impl PostprocessLayer {
         // BFS to compute distance to coast
         while let Some(current) = queue.pop_front() {
             let cd = dist_to_coast[current];
-            if cd as usize >= max_hops {
+            if cd >= shelf_width {
                 continue;
             }
             for &nb in &neighbors[current] {
@@ -188,22 +205,29 @@ impl PostprocessLayer {
             }
         }
 
-        // Smooth cells within max_hops of coast, with strength decreasing with distance
-        for _ in 0..1 {
-            let old = heights.to_vec();
-            for i in 0..n {
-                let d = dist_to_coast[i];
-                if d == u32::MAX || d as usize > max_hops || neighbors[i].is_empty() {
-                    continue;
-                }
-                let blend = 0.5 * (1.0 - d as f32 / (max_hops as f32 + 1.0));
-                if blend <= 0.0 {
-                    continue;
-                }
-                let avg: f32 = neighbors[i].iter().map(|&nb| old[nb as usize]).sum::<f32>()
-                    / neighbors[i].len() as f32;
-                heights[i] = old[i] * (1.0 - blend) + avg * blend;
+        // Smooth cells within shelf_width of coast, with strength decreasing with distance
+        let old = heights.to_vec();
+        for i in 0..n {
+            let d = dist_to_coast[i];
+            if d == u32::MAX || d > shelf_width || neighbors[i].is_empty() {
+                continue;
+            }
+            let blend = 0.5 * (1.0 - d as f32 / (shelf_width as f32 + 1.0));
+            let avg: f32 = neighbors[i].iter().map(|&nb| old[nb as usize]).sum::<f32>()
+                / neighbors[i].len() as f32;
+            heights[i] = old[i] * (1.0 - blend) + avg * blend;
+        }
+
+        // Shallow shelf floor: any path from ocean to land crosses a coastal
+        // ocean cell, so an ocean cell `d` hops from the coast is `d + 1` hops
+        // from land
+        for i in 0..n {
+            let d = dist_to_coast[i];
+            if old[i] > 0.0 || heights[i] > 0.0 || d >= shelf_width {
+                continue;
             }
+            let shelf_floor = -SHELF_DEPTH_PER_HOP * (d + 1) as f32;
+            heights[i] = heights[i].max(shelf_floor);
         }
     }
 
@@ -248,6 +272,10 @@ impl TerrainLayer for PostprocessLayer {
         "Postprocess"
     }
 
+    fn cache_key(&self) -> Option<u64> {
+        Some(param_hash(&self.config))
+    }
+
     fn generate(
         &self,
         _cells: &[Pos2],
@@ -272,9 +300,64 @@ impl TerrainLayer for PostprocessLayer {
             self.config.smoothing_iterations,
         );
 
-        // Continental shelf pass: gradual transitions near coast
-        Self::continental_shelf_pass(&mut output.heights, neighbors, 4);
+        // Continental shelf pass: gradual transitions and a shallow shelf near coast
+        Self::continental_shelf_pass(&mut output.heights, neighbors, self.config.shelf_width);
 
         output
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shelf_makes_coastal_ocean_shallow() {
+        let (cols, rows) = (30usize, 30usize);
+        let (cells, neighbors) = crate::terrain::test_grid::layer_grid(cols, rows, 10.0);
+
+        // Square island dropping straight into deep ocean
+        let island = |i: usize| (10..20).contains(&(i % cols)) && (10..20).contains(&(i / cols));
+        let previous = LayerOutput {
+            heights: (0..cols * rows)
+                .map(|i| if island(i) { 60.0 } else { -50.0 })
+                .collect(),
+            ..Default::default()
+        };
+
+        let layer = PostprocessLayer::new(PostprocessConfig {
+            min_island_size: 1,
+            min_lake_size: 1,
+            smoothing_iterations: 0,
+            ocean_ratio: 0.0,
+            shelf_width: 3,
+        });
+        let heights = layer.generate(&cells, &neighbors, &previous).heights;
+
+        let coastal: Vec<f32> = (0..cols * rows)
+            .filter(|&i| heights[i] <= 0.0)
+            .filter(|&i| neighbors[i].iter().any(|&n| heights[n as usize] > 0.0))
+            .map(|i| heights[i])
+            .collect();
+        let far: Vec<f32> = (0..cols * rows)
+            .filter(|&i| i % cols < 3 || i / cols < 3)
+            .map(|i| heights[i])
+            .collect();
+        assert!(!coastal.is_empty());
+
+        let deepest_coastal = coastal.iter().copied().fold(f32::INFINITY, f32::min);
+        let shallowest_far = far.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        assert!(
+            deepest_coastal >= -SHELF_DEPTH_PER_HOP,
+            "{}",
+            deepest_coastal
+        );
+        assert!(
+            deepest_coastal > shallowest_far,
+            "{} vs {}",
+            deepest_coastal,
+            shallowest_far
+        );
+        assert!(coastal.iter().all(|&h| h <= 0.0));
+    }
+}
@@ -2,8 +2,12 @@
 //!
 //! Adds continental features like highlands, basins, and plains.
 
-use super::r#trait::{LayerOutput, LegacyTerrainLayer, Pos2, TerrainContext, TerrainLayer};
+use super::r#trait::{
+    cell_bounds, map_cells, param_hash, LayerOutput, LegacyTerrainLayer, Pos2, TerrainContext,
+    TerrainLayer,
+};
 use crate::terrain::noise::{smootherstep, NoiseConfig, NoiseGenerator};
+use crate::terrain::topology::Topology;
 
 /// Regional terrain layer for large-scale features
 pub struct RegionalLayer {
@@ -14,6 +18,10 @@ pub struct RegionalLayer {
     /// Noise configuration
     config: NoiseConfig,
     generator: NoiseGenerator,
+    /// Map topology; wrapping topologies sample seamless noise across the edges
+    topology: Topology,
+    /// Sample cells in parallel with rayon (always serial on wasm)
+    parallel: bool,
 }
 
 impl Default for RegionalLayer {
@@ -35,6 +43,8 @@ impl RegionalLayer {
                 seed: 100,
             },
             generator: NoiseGenerator::new(100),
+            topology: Topology::Plane,
+            parallel: cfg!(not(target_arch = "wasm32")),
         }
     }
 
@@ -44,6 +54,16 @@ impl RegionalLayer {
         self
     }
 
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn with_parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
     /// Sample the regional contribution at a point
     fn sample_at(&self, x: f64, y: f64, is_land: bool, coast_distance: f64) -> f64 {
         let noise = self.generator.fbm(x, y, &self.config);
@@ -81,6 +101,15 @@ impl TerrainLayer for RegionalLayer {
         "Regional"
     }
 
+    fn cache_key(&self) -> Option<u64> {
+        Some(param_hash(&(
+            self.land_amplitude,
+            self.ocean_amplitude,
+            &self.config,
+            self.topology,
+        )))
+    }
+
     fn generate(
         &self,
         cells: &[Pos2],
@@ -88,12 +117,14 @@ impl TerrainLayer for RegionalLayer {
         previous: &LayerOutput,
     ) -> LayerOutput {
         let mut output = previous.clone();
+        let (_, _, width, height) = cell_bounds(cells);
+        let generator =
+            NoiseGenerator::new(self.config.seed).with_topology(self.topology, width, height);
 
-        for (i, cell) in cells.iter().enumerate() {
+        let offsets = map_cells(cells.len(), self.parallel, |i| {
             let is_land = previous.heights[i] > 0.0;
-            let noise = self
-                .generator
-                .fbm(cell.x as f64, cell.y as f64, &self.config);
+            let cell = cells[i];
+            let noise = generator.fbm_at(eframe::egui::pos2(cell.x, cell.y), &self.config);
 
             let amplitude = if is_land {
                 self.land_amplitude
@@ -101,9 +132,42 @@ impl TerrainLayer for RegionalLayer {
                 self.ocean_amplitude
             };
 
-            output.heights[i] += (noise * amplitude) as f32;
+            (noise * amplitude) as f32
+        });
+        for (h, offset) in output.heights.iter_mut().zip(offsets) {
+            *h += offset;
         }
 
         output
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrapping_noise_is_seamless() {
+        // 最左列与最右列位于接缝两侧（归一化后经度相同）
+        let (cols, rows) = (41usize, 11usize);
+        let cells: Vec<Pos2> = (0..cols * rows)
+            .map(|i| Pos2::new((i % cols) as f32 * 10.0, (i / cols) as f32 * 10.0))
+            .collect();
+        let neighbors = vec![Vec::new(); cells.len()];
+        let previous = LayerOutput {
+            heights: vec![1.0; cells.len()],
+            ..Default::default()
+        };
+        let seam_gap = |topology| {
+            let layer = RegionalLayer::new().with_seed(5).with_topology(topology);
+            let output = layer.generate(&cells, &neighbors, &previous);
+            (0..rows)
+                .map(|y| (output.heights[y * cols] - output.heights[y * cols + cols - 1]).abs())
+                .fold(0.0f32, f32::max)
+        };
+
+        assert!(seam_gap(Topology::CylinderX) < 1e-4);
+        assert!(seam_gap(Topology::Sphere) < 1e-4);
+        assert!(seam_gap(Topology::Plane) > 1e-3);
+    }
+}
@@ -4,8 +4,11 @@
 //! Based on proven techniques from Red Blob Games / mapgen4 / Brash & Plucky.
 
 use super::plate_layer::{PlateConfig, PlateLayer, PlateType};
-use super::r#trait::{LayerOutput, Pos2, TerrainLayer};
-use noise::{NoiseFn, Perlin};
+use super::r#trait::{
+    cell_bounds, map_cells, param_hash, perlin_at, LayerOutput, Pos2, TerrainLayer,
+};
+use crate::terrain::topology::Topology;
+use noise::Perlin;
 use rand::{Rng, SeedableRng};
 use std::collections::{HashMap, VecDeque};
 
@@ -37,6 +40,10 @@ impl Default for TectonicConfig {
 pub struct TectonicLayer {
     config: TectonicConfig,
     seed: u64,
+    /// Map topology, shared with the plate layer and the noise sampling
+    topology: Topology,
+    /// Compute the final per-cell elevation in parallel with rayon (always serial on wasm)
+    parallel: bool,
 }
 
 impl Default for TectonicLayer {
@@ -47,13 +54,28 @@ impl Default for TectonicLayer {
 
 impl TectonicLayer {
     pub fn new(config: TectonicConfig) -> Self {
-        Self { config, seed: 0 }
+        Self {
+            config,
+            seed: 0,
+            topology: Topology::Plane,
+            parallel: cfg!(not(target_arch = "wasm32")),
+        }
     }
 
     pub fn with_seed(mut self, seed: u64) -> Self {
         self.seed = seed;
         self
     }
+
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn with_parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
 }
 
 impl TerrainLayer for TectonicLayer {
@@ -61,6 +83,10 @@ impl TerrainLayer for TectonicLayer {
         "Tectonic"
     }
 
+    fn cache_key(&self) -> Option<u64> {
+        Some(param_hash(&(self.seed, &self.config, self.topology)))
+    }
+
     fn generate(
         &self,
         cells: &[Pos2],
@@ -71,7 +97,9 @@ impl TerrainLayer for TectonicLayer {
         let n = cells.len();
 
         // Step 1: Generate plates (ellipse-based continental mask)
-        let plate_layer = PlateLayer::new(self.config.plate_config.clone()).with_seed(self.seed);
+        let plate_layer = PlateLayer::new(self.config.plate_config.clone())
+            .with_seed(self.seed)
+            .with_topology(self.topology);
         let (plate_ids, plates) = plate_layer.generate_plates(cells, neighbors);
 
         // Step 2: Compute signed distance from coastline
@@ -269,35 +297,26 @@ impl TerrainLayer for TectonicLayer {
         let perlin_n4 = Perlin::new(self.seed.wrapping_add(400) as u32);
 
         // Map bounds for noise coordinates
-        let (min_x, max_x) = cells.iter().fold((f32::MAX, f32::MIN), |(lo, hi), p| {
-            (lo.min(p.x), hi.max(p.x))
-        });
-        let (min_y, max_y) = cells.iter().fold((f32::MAX, f32::MIN), |(lo, hi), p| {
-            (lo.min(p.y), hi.max(p.y))
-        });
-        let rx = (max_x - min_x).max(1.0);
-        let ry = (max_y - min_y).max(1.0);
+        let (min_x, min_y, rx, ry) = cell_bounds(cells);
 
         // Step 5: Compute final elevation using mapgen4-style blending
         let continental_base = self.config.plate_config.continental_base;
         let oceanic_base = self.config.plate_config.oceanic_base;
 
-        let mut heights = vec![0.0f32; n];
-
-        for i in 0..n {
+        let heights = map_cells(n, self.parallel, |i| {
             if plate_ids[i] == 0 {
-                heights[i] = oceanic_base;
-                continue;
+                return oceanic_base;
             }
 
             let nx = (cells[i].x - min_x) / rx;
             let ny = (cells[i].y - min_y) / ry;
 
             // Noise layers at different frequencies
-            let n0 = perlin_n0.get([nx as f64 * 2.0, ny as f64 * 2.0]) as f32;
-            let n1 = perlin_n1.get([nx as f64 * 4.0, ny as f64 * 4.0]) as f32;
-            let n2 = perlin_n2.get([nx as f64 * 8.0, ny as f64 * 8.0]) as f32;
-            let n4 = perlin_n4.get([nx as f64 * 16.0, ny as f64 * 16.0]) as f32;
+            let topology = self.topology;
+            let n0 = perlin_at(&perlin_n0, topology, nx, ny, 2.0) as f32;
+            let n1 = perlin_at(&perlin_n1, topology, nx, ny, 4.0) as f32;
+            let n2 = perlin_at(&perlin_n2, topology, nx, ny, 8.0) as f32;
+            let n4 = perlin_at(&perlin_n4, topology, nx, ny, 16.0) as f32;
 
             let sd = signed_dist[i];
             let plate = &plates[(plate_ids[i] - 1) as usize];
@@ -334,7 +353,7 @@ impl TerrainLayer for TectonicLayer {
                 // Add fine detail noise
                 let detail = n4 * 5.0 * inland_t; // Detail increases inland
 
-                heights[i] = elevation + detail;
+                elevation + detail
             } else {
                 // --- OCEAN ---
                 let depth_t = (-sd / -min_ocean_dist).clamp(0.0, 1.0);
@@ -344,9 +363,9 @@ impl TerrainLayer for TectonicLayer {
                 let ocean_noise = n1 * 0.3 + n2 * 0.15;
                 let detail = oceanic_base * 0.1 * ocean_noise;
 
-                heights[i] = base + detail;
+                base + detail
             }
-        }
+        });
 
         // Collect boundary cells for metadata
         let boundary_cells: Vec<u32> = coast_cells.iter().map(|&i| i as u32).collect();
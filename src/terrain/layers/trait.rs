@@ -1,6 +1,11 @@
 //! TerrainLayer trait 定义分层地形生成的核心抽象
 
+use crate::terrain::topology::Topology;
+use noise::{NoiseFn, Perlin};
+#[cfg(not(target_arch = "wasm32"))]
+use rayon::prelude::*;
 use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
 
 /// 2D 位置坐标
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -66,6 +71,74 @@ pub trait TerrainLayer: Send + Sync {
 
     /// 返回该层的名称，用于调试和日志
     fn name(&self) -> &'static str;
+
+    /// 影响该层输出的全部参数的哈希值，用于检查点缓存
+    ///
+    /// 返回 None 表示该层不可缓存，其后的所有层都会重新计算。
+    fn cache_key(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// 计算层参数的哈希值（基于 Debug 输出，浮点数按其文本表示参与哈希）
+pub fn param_hash(params: &impl std::fmt::Debug) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    format!("{:?}", params).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 逐单元格计算 `f(0..n)`
+///
+/// `parallel` 为 true 时在非 wasm 平台上用 rayon 并行；每个元素独立计算，
+/// 结果与串行完全一致。
+pub fn map_cells<T, F>(n: usize, parallel: bool, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync + Send,
+{
+    #[cfg(not(target_arch = "wasm32"))]
+    if parallel {
+        return (0..n).into_par_iter().map(f).collect();
+    }
+    #[cfg(target_arch = "wasm32")]
+    let _ = parallel;
+    (0..n).map(f).collect()
+}
+
+/// 单元格包围盒 `(min_x, min_y, 宽, 高)`，宽高至少为 1
+pub fn cell_bounds(cells: &[Pos2]) -> (f32, f32, f32, f32) {
+    let (min_x, max_x, min_y, max_y) = cells.iter().fold(
+        (f32::MAX, f32::MIN, f32::MAX, f32::MIN),
+        |(min_x, max_x, min_y, max_y), p| {
+            (
+                min_x.min(p.x),
+                max_x.max(p.x),
+                min_y.min(p.y),
+                max_y.max(p.y),
+            )
+        },
+    );
+    (
+        min_x,
+        min_y,
+        (max_x - min_x).max(1.0),
+        (max_y - min_y).max(1.0),
+    )
+}
+
+/// 按拓扑在归一化地图坐标上采样 Perlin 噪声
+///
+/// `(nx, ny)` 为 [0, 1] 内的归一化坐标，`frequency` 为横跨地图的噪声周期数。
+/// 平面拓扑直接做 2D 采样；环绕拓扑经 [`Topology::noise_coords`] 映射到 3D 后采样，
+/// 左右边缘（球面还有极点）两侧的噪声保持连续。
+pub fn perlin_at(perlin: &Perlin, topology: Topology, nx: f32, ny: f32, frequency: f64) -> f64 {
+    match topology {
+        Topology::Plane => perlin.get([nx as f64 * frequency, ny as f64 * frequency]),
+        _ => {
+            let p = topology.noise_coords(eframe::egui::pos2(nx, ny), 1.0, 1.0);
+            perlin.get([p[0] * frequency, p[1] * frequency, p[2] * frequency])
+        }
+    }
 }
 
 /// 地形上下文（用于旧版点采样层）
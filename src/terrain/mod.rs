@@ -1,43 +1,57 @@
 // 地形生成模块
 
+pub mod biome;
 pub mod blob;
+pub mod climate;
+pub mod color_map;
 pub mod dsl;
 pub mod features;
+pub mod generation_task;
 pub mod heightmap;
 pub mod hydrology;
 pub mod noise;
 pub mod plate;
 pub mod primitive;
+pub mod relief;
 pub mod template;
 pub mod template_executor;
+pub mod topology;
 
 // 模板测试
 #[cfg(test)]
 mod template_tests;
+#[cfg(test)]
+pub(crate) mod test_grid;
 
 // 新增：分层地形生成系统
 pub mod layered_generator;
 pub mod layers;
 
+pub use biome::{classify_biomes, latitudes, Biome};
 pub use blob::{BlobConfig, BlobGenerator};
-pub use dsl::{parse_template, template_to_dsl};
+pub use climate::{lapse_rate, simulate_moisture, temperature_field, DEFAULT_LAPSE_RATE};
+pub use color_map::{ColorStop, HeightColorMap, RampError, DEFAULT_AMBIENT};
+pub use dsl::{parse_template, parse_template_full, template_to_dsl, ParsedTemplate};
 pub use features::{Feature, FeatureDetector, FeatureType};
+pub use generation_task::TerrainGenerationTask;
 pub use heightmap::*;
 pub use hydrology::*;
 pub use noise::*;
 pub use plate::*;
 pub use primitive::*;
+pub use relief::{contours, hillshade, slope_aspect};
 pub use template::{
     get_suggested_ocean_ratio, get_suggested_plate_count, get_template_by_name,
-    should_use_layered_generation, InvertAxis, MaskMode, StraitDirection, TerrainCommand,
-    TerrainTemplate,
+    should_use_layered_generation, CurveKind, InvertAxis, MaskMode, SmoothTarget, StraitDirection,
+    TerrainCommand, TerrainTemplate,
 };
 pub use template_executor::*;
+pub use topology::Topology;
 
 // 导出分层系统
-pub use layered_generator::LayeredGenerator;
+pub use layered_generator::{layer_seed, LayerCheckpoints, LayeredGenerator};
 pub use layers::{
-    BoundaryType, DetailLayer, LayerOutput, LegacyTerrainLayer, Plate, PlateConfig, PlateLayer,
-    PlateType, Pos2, PostprocessLayer, RegionalLayer, TectonicConfig, TectonicLayer,
-    TerrainContext, TerrainLayer,
+    BoundaryType, DetailLayer, ErosionConfig, ErosionLayer, LayerOutput, LegacyTerrainLayer, Plate,
+    PlateConfig, PlateLayer, PlateType, Pos2, PostprocessLayer, RegionalLayer, TectonicConfig,
+    TectonicLayer, TerrainContext, TerrainLayer,
 };
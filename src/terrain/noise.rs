@@ -1,7 +1,10 @@
 // 噪声生成系统
 
+use eframe::egui::{Pos2, Vec2};
 use noise::{NoiseFn, Perlin};
 
+use super::topology::Topology;
+
 /// 平滑阶梯函数（比 smoothstep 更平滑）
 pub fn smootherstep(edge0: f64, edge1: f64, x: f64) -> f64 {
     let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
@@ -45,6 +48,78 @@ impl Default for NoiseConfig {
 }
 
 impl NoiseConfig {
+    /// 链式设置种子
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// 大陆尺度：极低频，决定大块陆地的起伏
+    pub fn continental() -> Self {
+        Self {
+            octaves: 4,
+            base_frequency: 0.002,
+            persistence: 0.5,
+            lacunarity: 2.0,
+            seed: 0,
+        }
+    }
+
+    /// 区域尺度：低频、振幅衰减较快，给陆地添加中等变化
+    pub fn regional() -> Self {
+        Self {
+            octaves: 3,
+            base_frequency: 0.005,
+            persistence: 0.4,
+            lacunarity: 2.0,
+            seed: 0,
+        }
+    }
+
+    /// 缓丘：少量八度、高振幅保留，形成平缓圆润的大尺度起伏
+    pub fn billowy() -> Self {
+        Self {
+            octaves: 2,
+            base_frequency: 0.003,
+            persistence: 0.6,
+            lacunarity: 2.0,
+            seed: 0,
+        }
+    }
+
+    /// 波状起伏：介于缓丘和细节之间，打破残余的规则图案
+    pub fn undulating() -> Self {
+        Self {
+            octaves: 3,
+            base_frequency: 0.008,
+            persistence: 0.45,
+            lacunarity: 2.2,
+            seed: 0,
+        }
+    }
+
+    /// 山脊：较高频率、多八度且高频层衰减慢，适合锐利的山脊和峰顶
+    pub fn ridged() -> Self {
+        Self {
+            octaves: 6,
+            base_frequency: 0.02,
+            persistence: 0.55,
+            lacunarity: 2.1,
+            seed: 0,
+        }
+    }
+
+    /// 细碎表面：高频，用于模板地形的表面纹理
+    pub fn fine_detail() -> Self {
+        Self {
+            octaves: 4,
+            base_frequency: 0.08,
+            persistence: 0.4,
+            lacunarity: 2.2,
+            seed: 0,
+        }
+    }
+
     /// 中尺度噪声配置（模拟区域构造）
     pub fn medium_scale() -> Self {
         Self {
@@ -73,6 +148,8 @@ pub struct NoiseGenerator {
     perlin: Perlin,
     config: NoiseConfig,
     amplitude: f64,
+    /// 采样域：拓扑与地图尺寸，决定按位置采样时的坐标映射
+    domain: (Topology, f32, f32),
 }
 
 impl NoiseGenerator {
@@ -82,6 +159,7 @@ impl NoiseGenerator {
             perlin: Perlin::new(seed_or_frequency),
             config: NoiseConfig::default(),
             amplitude: 1.0,
+            domain: (Topology::Plane, 0.0, 0.0),
         }
     }
 
@@ -94,6 +172,7 @@ impl NoiseGenerator {
                 ..Default::default()
             },
             amplitude: 1.0,
+            domain: (Topology::Plane, 0.0, 0.0),
         }
     }
 
@@ -116,6 +195,15 @@ impl NoiseGenerator {
         self
     }
 
+    /// 链式设置采样拓扑
+    ///
+    /// 按位置采样（`fbm_at`、`generate_noise_map` 等）时，
+    /// 环绕拓扑会在 3D 空间中采样，使噪声跨越环绕边缘保持连续。
+    pub fn with_topology(mut self, topology: Topology, width: f32, height: f32) -> Self {
+        self.domain = (topology, width, height);
+        self
+    }
+
     /// 采样 fBm 噪声（使用内置配置）
     pub fn sample_fbm(&self, x: f64, y: f64) -> f64 {
         self.fbm(x, y, &self.config) * self.amplitude
@@ -147,26 +235,130 @@ impl NoiseGenerator {
         value / max_value
     }
 
+    /// 3D 分形布朗运动噪声
+    pub fn fbm_3d(&self, p: [f64; 3], config: &NoiseConfig) -> f64 {
+        let mut value = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = config.base_frequency;
+        let mut max_value = 0.0;
+
+        for _ in 0..config.octaves {
+            let q = [p[0] * frequency, p[1] * frequency, p[2] * frequency];
+            value += self.perlin.get(q) * amplitude;
+            max_value += amplitude;
+            amplitude *= config.persistence;
+            frequency *= config.lacunarity;
+        }
+
+        value / max_value
+    }
+
+    /// 脊状多重分形噪声
+    ///
+    /// 每层取 `1 - |noise|`，使零值处折叠成尖锐的脊线，按 fBm 方式叠加并归一化，
+    /// 结果在 [0, 1] 内且偏向高值，适合山脊和峰顶。
+    pub fn ridged(&self, x: f64, y: f64, config: &NoiseConfig) -> f64 {
+        Self::fold_ridged(config, |f| self.perlin.get([x * f, y * f]))
+    }
+
+    /// 3D 脊状多重分形噪声
+    pub fn ridged_3d(&self, p: [f64; 3], config: &NoiseConfig) -> f64 {
+        Self::fold_ridged(config, |f| self.perlin.get([p[0] * f, p[1] * f, p[2] * f]))
+    }
+
+    /// 按地图位置采样脊状噪声，遵循 `with_topology` 设置的采样域
+    pub fn ridged_at(&self, pos: Pos2, config: &NoiseConfig) -> f64 {
+        let (topology, width, height) = self.domain;
+        match topology {
+            Topology::Plane => self.ridged(pos.x as f64, pos.y as f64, config),
+            _ => self.ridged_3d(topology.noise_coords(pos, width, height), config),
+        }
+    }
+
+    /// 按频率采样 `sample` 并叠加脊状变换后的各层
+    fn fold_ridged(config: &NoiseConfig, sample: impl Fn(f64) -> f64) -> f64 {
+        let mut value = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = config.base_frequency;
+        let mut max_value = 0.0;
+
+        for _ in 0..config.octaves {
+            value += (1.0 - sample(frequency).abs()) * amplitude;
+            max_value += amplitude;
+            amplitude *= config.persistence;
+            frequency *= config.lacunarity;
+        }
+
+        if max_value > 0.0 {
+            value / max_value
+        } else {
+            0.0
+        }
+    }
+
+    /// 按地图位置采样 fBm 噪声，遵循 `with_topology` 设置的采样域
+    ///
+    /// 平面拓扑与 `fbm` 结果完全一致。
+    pub fn fbm_at(&self, pos: Pos2, config: &NoiseConfig) -> f64 {
+        let (topology, width, height) = self.domain;
+        match topology {
+            Topology::Plane => self.fbm(pos.x as f64, pos.y as f64, config),
+            _ => self.fbm_3d(topology.noise_coords(pos, width, height), config),
+        }
+    }
+
+    /// 域扭曲 fBm 噪声
+    ///
+    /// 先用两个错开的 fBm 场构造偏移向量，把采样坐标平移 `warp_strength`（地图单位）
+    /// 倍的偏移后再求 fBm，打破直接采样时可辨认的重复纹理。`warp_strength` 为 0 时等同于 [`Self::fbm`]。
+    pub fn warped_fbm(&self, x: f64, y: f64, config: &NoiseConfig, warp_strength: f64) -> f64 {
+        if warp_strength == 0.0 {
+            return self.fbm(x, y, config);
+        }
+        let (ax, ay, bx, by) = Self::warp_offsets(config);
+        let qx = self.fbm(x + ax, y + ay, config);
+        let qy = self.fbm(x + bx, y + by, config);
+        self.fbm(x + warp_strength * qx, y + warp_strength * qy, config)
+    }
+
+    /// 按地图位置采样域扭曲 fBm 噪声，遵循 `with_topology` 设置的采样域
+    pub fn warped_fbm_at(&self, pos: Pos2, config: &NoiseConfig, warp_strength: f64) -> f64 {
+        if warp_strength == 0.0 {
+            return self.fbm_at(pos, config);
+        }
+        let (ax, ay, bx, by) = Self::warp_offsets(config);
+        let qx = self.fbm_at(pos + Vec2::new(ax as f32, ay as f32), config);
+        let qy = self.fbm_at(pos + Vec2::new(bx as f32, by as f32), config);
+        let warped = pos + Vec2::new(qx as f32, qy as f32) * warp_strength as f32;
+        self.fbm_at(warped, config)
+    }
+
+    /// 两个偏移场的采样平移量，按基础频率缩放使两个场互不相关
+    fn warp_offsets(config: &NoiseConfig) -> (f64, f64, f64, f64) {
+        let scale = 1.0 / config.base_frequency.max(f64::EPSILON);
+        (17.3 * scale, 31.7 * scale, 52.1 * scale, 13.9 * scale)
+    }
+
     /// 为多个点生成噪声值
-    pub fn generate_noise_map(
-        &self,
-        positions: &[eframe::egui::Pos2],
-        config: &NoiseConfig,
-    ) -> Vec<f32> {
+    pub fn generate_noise_map(&self, positions: &[Pos2], config: &NoiseConfig) -> Vec<f32> {
         positions
             .iter()
             .map(|pos| {
-                let noise = self.fbm(pos.x as f64, pos.y as f64, config);
+                let noise = self.fbm_at(*pos, config);
                 noise as f32
             })
             .collect()
     }
 
     /// 生成带约束的噪声（用于板块内部）
-    /// strength: 噪声强度因子
+    ///
+    /// 每个点的输出为 `fbm_at(pos) * strength_map[i]`：强度为 0 的点输出恰好为 0，
+    /// 输出随强度线性缩放，相同种子和配置的结果完全确定。
+    /// 噪声本身是连续场，但最高频层的波长小于单元格间距时相邻单元格之间可能不相关，
+    /// 出现孤立尖峰，此时使用 [`Self::generate_constrained_noise_smoothed`]。
     pub fn generate_constrained_noise(
         &self,
-        positions: &[eframe::egui::Pos2],
+        positions: &[Pos2],
         config: &NoiseConfig,
         strength_map: &[f32],
     ) -> Vec<f32> {
@@ -174,18 +366,71 @@ impl NoiseGenerator {
             .iter()
             .enumerate()
             .map(|(i, pos)| {
-                let noise = self.fbm(pos.x as f64, pos.y as f64, config);
+                let noise = self.fbm_at(*pos, config);
                 let strength = strength_map[i];
                 (noise as f32) * strength
             })
             .collect()
     }
+
+    /// 生成带约束的噪声，并在乘以强度前对原始噪声做一次邻域平滑
+    ///
+    /// 每个点的原始噪声与其邻居均值各占一半，消除孤立尖峰；
+    /// 由于平滑发生在乘以强度之前，强度为 0 的点仍输出 0，输出仍随强度线性缩放。
+    pub fn generate_constrained_noise_smoothed(
+        &self,
+        positions: &[Pos2],
+        config: &NoiseConfig,
+        strength_map: &[f32],
+        neighbors: &[Vec<u32>],
+    ) -> Vec<f32> {
+        let raw = self.generate_noise_map(positions, config);
+        smooth_over_neighbors(&raw, neighbors)
+            .into_iter()
+            .zip(strength_map)
+            .map(|(noise, &strength)| noise * strength)
+            .collect()
+    }
+
+    /// 生成带约束的脊状噪声
+    ///
+    /// 脊状噪声从 [0, 1] 映射到 [-1, 1] 后乘以强度，可直接替换 [`Self::generate_constrained_noise`]。
+    pub fn generate_constrained_ridged(
+        &self,
+        positions: &[Pos2],
+        config: &NoiseConfig,
+        strength_map: &[f32],
+    ) -> Vec<f32> {
+        positions
+            .iter()
+            .enumerate()
+            .map(|(i, pos)| {
+                let noise = self.ridged_at(*pos, config) * 2.0 - 1.0;
+                (noise as f32) * strength_map[i]
+            })
+            .collect()
+    }
+}
+
+/// 单次邻域平滑：每个值与其邻居均值各占一半，没有邻居的值保持不变
+fn smooth_over_neighbors(values: &[f32], neighbors: &[Vec<u32>]) -> Vec<f32> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| match neighbors.get(i) {
+            Some(adj) if !adj.is_empty() => {
+                let mean = adj.iter().map(|&j| values[j as usize]).sum::<f32>() / adj.len() as f32;
+                0.5 * v + 0.5 * mean
+            }
+            _ => v,
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use eframe::egui::Pos2;
+    use crate::terrain::test_grid::grid;
 
     #[test]
     fn test_fbm_noise() {
@@ -217,6 +462,118 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_presets_are_finite_and_ridged_is_sharper() {
+        let generator = NoiseGenerator::new(7);
+        let presets = [
+            NoiseConfig::continental(),
+            NoiseConfig::regional(),
+            NoiseConfig::billowy(),
+            NoiseConfig::undulating(),
+            NoiseConfig::ridged(),
+            NoiseConfig::fine_detail(),
+            NoiseConfig::medium_scale(),
+            NoiseConfig::detail_scale(),
+        ];
+        let samples: Vec<(f64, f64)> = (0..40)
+            .flat_map(|y| (0..40).map(move |x| (x as f64 * 12.5, y as f64 * 12.5)))
+            .collect();
+
+        for config in &presets {
+            for &(x, y) in &samples {
+                let v = generator.fbm(x, y, config);
+                assert!(v.is_finite() && (-1.0..=1.0).contains(&v), "{:?}", config);
+            }
+        }
+
+        // 相邻采样点之间的平均变化量
+        let roughness = |config: &NoiseConfig| {
+            samples
+                .iter()
+                .map(|&(x, y)| {
+                    (generator.fbm(x + 1.0, y, config) - generator.fbm(x, y, config)).abs()
+                })
+                .sum::<f64>()
+                / samples.len() as f64
+        };
+        let ridged = roughness(&NoiseConfig::ridged());
+        let continental = roughness(&NoiseConfig::continental());
+        assert!(
+            ridged > continental * 3.0,
+            "ridged {} continental {}",
+            ridged,
+            continental
+        );
+    }
+
+    #[test]
+    fn test_ridged_is_bounded_and_skewed_high() {
+        let generator = NoiseGenerator::new(3);
+        let config = NoiseConfig::ridged();
+        let samples: Vec<(f64, f64)> = (0..50)
+            .flat_map(|y| (0..50).map(move |x| (x as f64 * 7.3, y as f64 * 7.3)))
+            .collect();
+
+        let ridged: Vec<f64> = samples
+            .iter()
+            .map(|&(x, y)| generator.ridged(x, y, &config))
+            .collect();
+        // fBm 映射到同一 [0, 1] 区间再比较
+        let fbm: Vec<f64> = samples
+            .iter()
+            .map(|&(x, y)| (generator.fbm(x, y, &config) + 1.0) / 2.0)
+            .collect();
+
+        assert!(ridged.iter().all(|v| (0.0..=1.0).contains(v)));
+        let high = |values: &[f64]| values.iter().filter(|&&v| v > 0.6).count();
+        let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+        assert!(
+            high(&ridged) > high(&fbm),
+            "{} vs {}",
+            high(&ridged),
+            high(&fbm)
+        );
+        assert!(
+            mean(&ridged) > mean(&fbm) + 0.1,
+            "{} vs {}",
+            mean(&ridged),
+            mean(&fbm)
+        );
+    }
+
+    #[test]
+    fn test_warped_fbm() {
+        let generator = NoiseGenerator::new(11);
+        let config = NoiseConfig::regional();
+        let samples: Vec<(f64, f64)> = (0..20)
+            .flat_map(|y| (0..20).map(move |x| (x as f64 * 13.0, y as f64 * 13.0)))
+            .collect();
+
+        for &(x, y) in &samples {
+            assert_eq!(
+                generator.warped_fbm(x, y, &config, 0.0),
+                generator.fbm(x, y, &config)
+            );
+            let pos = Pos2::new(x as f32, y as f32);
+            assert_eq!(
+                generator.warped_fbm_at(pos, &config, 0.0),
+                generator.fbm_at(pos, &config)
+            );
+        }
+
+        let changed = samples
+            .iter()
+            .filter(|&&(x, y)| {
+                (generator.warped_fbm(x, y, &config, 80.0) - generator.fbm(x, y, &config)).abs()
+                    > 1e-3
+            })
+            .count();
+        assert!(changed > samples.len() * 3 / 4, "{} changed", changed);
+        assert!(samples
+            .iter()
+            .all(|&(x, y)| generator.warped_fbm(x, y, &config, 80.0).abs() <= 1.0));
+    }
+
     #[test]
     fn test_constrained_noise() {
         let generator = NoiseGenerator::new(42);
@@ -234,4 +591,88 @@ mod tests {
 
         assert_eq!(noise_map.len(), 3);
     }
+
+    #[test]
+    fn test_constrained_noise_zero_strength_is_zero() {
+        let generator = NoiseGenerator::new(5);
+        let config = NoiseConfig::fine_detail();
+        let (positions, neighbors) = grid(16, 16, 10.0);
+        let zeros = vec![0.0; positions.len()];
+
+        let noise = generator.generate_constrained_noise(&positions, &config, &zeros);
+        assert!(noise.iter().all(|&v| v == 0.0));
+        let smoothed =
+            generator.generate_constrained_noise_smoothed(&positions, &config, &zeros, &neighbors);
+        assert!(smoothed.iter().all(|&v| v == 0.0));
+    }
+
+    #[test]
+    fn test_constrained_noise_scales_linearly_with_strength() {
+        let generator = NoiseGenerator::new(5);
+        let config = NoiseConfig::regional();
+        let (positions, neighbors) = grid(16, 16, 10.0);
+        let strengths: Vec<f32> = (0..positions.len()).map(|i| (i % 7) as f32 * 0.3).collect();
+        let tripled: Vec<f32> = strengths.iter().map(|s| s * 3.0).collect();
+
+        let base = generator.generate_constrained_noise(&positions, &config, &strengths);
+        let scaled = generator.generate_constrained_noise(&positions, &config, &tripled);
+        let raw = generator.generate_noise_map(&positions, &config);
+        for i in 0..positions.len() {
+            assert!((scaled[i] - base[i] * 3.0).abs() < 1e-5);
+            assert!((base[i] - raw[i] * strengths[i]).abs() < 1e-6);
+        }
+
+        let base = generator
+            .generate_constrained_noise_smoothed(&positions, &config, &strengths, &neighbors);
+        let scaled = generator
+            .generate_constrained_noise_smoothed(&positions, &config, &tripled, &neighbors);
+        for i in 0..positions.len() {
+            assert!((scaled[i] - base[i] * 3.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_constrained_noise_is_deterministic() {
+        let config = NoiseConfig::fine_detail().with_seed(77);
+        let (positions, _) = grid(16, 16, 10.0);
+        let strengths = vec![1.0; positions.len()];
+
+        let a = NoiseGenerator::new(config.seed)
+            .generate_constrained_noise(&positions, &config, &strengths);
+        let b = NoiseGenerator::new(config.seed)
+            .generate_constrained_noise(&positions, &config, &strengths);
+        assert_eq!(a, b);
+
+        let other =
+            NoiseGenerator::new(78).generate_constrained_noise(&positions, &config, &strengths);
+        assert_ne!(a, other);
+    }
+
+    #[test]
+    fn test_smoothing_reduces_isolated_spikes() {
+        let generator = NoiseGenerator::new(9);
+        // 最高频层的波长远小于单元格间距，相邻单元格的噪声几乎不相关
+        let config = NoiseConfig::fine_detail();
+        let (positions, neighbors) = grid(24, 24, 10.0);
+        let strengths = vec![1.0; positions.len()];
+
+        let mean_deviation = |values: &[f32]| {
+            let mut total = 0.0;
+            for (i, adj) in neighbors.iter().enumerate() {
+                let mean = adj.iter().map(|&j| values[j as usize]).sum::<f32>() / adj.len() as f32;
+                total += (values[i] - mean).abs();
+            }
+            total / values.len() as f32
+        };
+
+        let raw = generator.generate_constrained_noise(&positions, &config, &strengths);
+        let smoothed = generator
+            .generate_constrained_noise_smoothed(&positions, &config, &strengths, &neighbors);
+        assert!(
+            mean_deviation(&smoothed) < mean_deviation(&raw) * 0.75,
+            "{} vs {}",
+            mean_deviation(&smoothed),
+            mean_deviation(&raw)
+        );
+    }
 }
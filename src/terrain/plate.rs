@@ -1,6 +1,6 @@
 // 板块构造模拟
 
-use eframe::egui::Pos2;
+use eframe::egui::{Pos2, Vec2};
 use noise::{NoiseFn, Perlin};
 use rand::{Rng, SeedableRng};
 use std::collections::{HashMap, VecDeque};
@@ -44,6 +44,51 @@ pub enum BoundaryType {
     Transform { intensity: f32 },
 }
 
+/// 汇聚/分离判定阈值：法向相对速度超过该值才视为汇聚或分离
+const BOUNDARY_MOTION_THRESHOLD: f32 = 0.3;
+
+/// 边界类别（不含强度信息）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BoundaryKind {
+    Convergent,
+    Divergent,
+    Transform,
+}
+
+impl BoundaryKind {
+    /// 由法向相对接近速度判断边界类别（正值表示两板块相互靠近）
+    pub fn from_approach(approach: f32) -> Self {
+        if approach > BOUNDARY_MOTION_THRESHOLD {
+            BoundaryKind::Convergent
+        } else if approach < -BOUNDARY_MOTION_THRESHOLD {
+            BoundaryKind::Divergent
+        } else {
+            BoundaryKind::Transform
+        }
+    }
+}
+
+impl BoundaryType {
+    /// 边界类别
+    pub fn kind(&self) -> BoundaryKind {
+        match self {
+            BoundaryType::Convergent { .. } => BoundaryKind::Convergent,
+            BoundaryType::Divergent { .. } => BoundaryKind::Divergent,
+            BoundaryType::Transform { .. } => BoundaryKind::Transform,
+        }
+    }
+}
+
+/// 两个板块沿边界法向的相对接近速度
+///
+/// `normal` 为从板块 A 指向板块 B 的单位向量，`vel_a`、`vel_b` 为
+/// [`TectonicPlate::velocity_vector`]。正值表示汇聚，负值表示分离。
+pub fn relative_approach(vel_a: Pos2, vel_b: Pos2, normal: Vec2) -> f32 {
+    let approach_a = vel_a.x * normal.x + vel_a.y * normal.y;
+    let approach_b = -(vel_b.x * normal.x + vel_b.y * normal.y);
+    approach_a + approach_b
+}
+
 /// 板块边界信息
 #[derive(Debug, Clone)]
 pub struct PlateBoundary {
@@ -193,6 +238,25 @@ impl TectonicConfig {
     }
 }
 
+/// 将板块 ID 烘焙为灰度调试场
+///
+/// 每个出现过的板块 ID 按升序映射到一个不同的灰度值（1-255 均匀分布），
+/// 用于在地形上叠加显示板块拼图。板块数超过 255 时灰度值会重复。
+pub fn plate_debug_field(plate_ids: &[u16]) -> Vec<u8> {
+    let mut distinct: Vec<u16> = plate_ids.to_vec();
+    distinct.sort_unstable();
+    distinct.dedup();
+
+    let count = distinct.len().max(1);
+    let gray: HashMap<u16, u8> = distinct
+        .iter()
+        .enumerate()
+        .map(|(rank, &id)| (id, ((rank + 1) * 255 / count) as u8))
+        .collect();
+
+    plate_ids.iter().map(|id| gray[id]).collect()
+}
+
 /// 板块生成器
 pub struct PlateGenerator {
     config: TectonicConfig,
@@ -459,40 +523,36 @@ impl PlateGenerator {
         if dist < 0.001 {
             return BoundaryType::Transform { intensity: 0.0 };
         }
-        let normal_x = dx / dist;
-        let normal_y = dy / dist;
+        let normal = Vec2::new(dx / dist, dy / dist);
 
         // 相对运动在法向的投影
-        let approach_a = vel_a.x * normal_x + vel_a.y * normal_y;
-        let approach_b = vel_b.x * (-normal_x) + vel_b.y * (-normal_y);
-        let relative_approach = approach_a + approach_b;
+        let relative_approach = relative_approach(vel_a, vel_b, normal);
 
         // 判断边界类型
-        let threshold = 0.3;
-        if relative_approach > threshold {
-            // 汇聚边界
-            let subducting = if plate_a.density > plate_b.density {
-                Some(plate_a.id)
-            } else if plate_b.density > plate_a.density {
-                Some(plate_b.id)
-            } else {
-                None
-            };
-
-            BoundaryType::Convergent {
-                intensity: relative_approach,
-                subducting_plate: subducting,
+        match BoundaryKind::from_approach(relative_approach) {
+            BoundaryKind::Convergent => {
+                // 汇聚边界
+                let subducting = if plate_a.density > plate_b.density {
+                    Some(plate_a.id)
+                } else if plate_b.density > plate_a.density {
+                    Some(plate_b.id)
+                } else {
+                    None
+                };
+
+                BoundaryType::Convergent {
+                    intensity: relative_approach,
+                    subducting_plate: subducting,
+                }
             }
-        } else if relative_approach < -threshold {
             // 分离边界
-            BoundaryType::Divergent {
+            BoundaryKind::Divergent => BoundaryType::Divergent {
                 intensity: -relative_approach,
-            }
-        } else {
+            },
             // 转换边界
-            BoundaryType::Transform {
+            BoundaryKind::Transform => BoundaryType::Transform {
                 intensity: relative_approach.abs(),
-            }
+            },
         }
     }
 }
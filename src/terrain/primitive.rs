@@ -127,10 +127,11 @@ pub enum TerrainPrimitive {
     },
 
     /// 山脉链 - 线性山脊
+    /// 多段山脊沿同一轴线首尾相接，构成一条连续山脉
     MountainChain {
-        size: Size, // 影响长度
+        size: Size, // 影响整条山脉的长度
         elevation: Elevation,
-        count: u32, // 山脊数量
+        count: u32, // 山脊段数
         position: PositionConstraint,
     },
 
@@ -142,6 +143,14 @@ pub enum TerrainPrimitive {
         position: PositionConstraint,
     },
 
+    /// 破火山口 - 环形口壁围住下陷的中心
+    /// 与带火山口的 `Volcano` 不同，口壁高于口内和外围
+    Caldera {
+        size: Size,
+        elevation: Elevation,
+        position: PositionConstraint,
+    },
+
     /// 高原 - 平坦的高地
     Plateau {
         size: Size,
@@ -187,6 +196,14 @@ pub enum TerrainPrimitive {
         position: PositionConstraint,
     },
 
+    /// 岛屿 - 单块连通陆地，约 `target_cells` 个单元格
+    /// 与群岛不同，保证生成且只生成一块岛屿
+    Island {
+        target_cells: u32,
+        elevation: Elevation,
+        position: PositionConstraint,
+    },
+
     /// 半岛 - 从陆地延伸出的狭长地带
     Peninsula {
         size: Size,
@@ -235,6 +252,8 @@ impl TerrainPrimitive {
                     x: position.x,
                     y: position.y,
                     radius,
+                    blob_power: None,
+                    jitter: None,
                 }]
             }
 
@@ -246,15 +265,34 @@ impl TerrainPrimitive {
             } => {
                 let length = size.to_length();
                 let height = elevation.to_height();
-                vec![TerrainCommand::Range {
-                    count: *count,
-                    height,
-                    x: position.x,
-                    y: position.y,
-                    length,
-                    width: (0.02, 0.05),
-                    angle: (0.0, 2.0 * PI),
-                }]
+                let count = (*count).max(1);
+
+                // 整条山脉沿约束区域的对角线方向，以区域中心为中点
+                let cx = (position.x.0 + position.x.1) / 2.0;
+                let cy = (position.y.0 + position.y.1) / 2.0;
+                let angle = (position.y.1 - position.y.0).atan2(position.x.1 - position.x.0);
+                let (dir_x, dir_y) = (angle.cos(), angle.sin());
+
+                // 每段山脊首尾相接，略微重叠以保证连续
+                let total = (length.0 + length.1) / 2.0;
+                let segment = total / count as f32;
+
+                (0..count)
+                    .map(|i| {
+                        let offset = (i as f32 - (count - 1) as f32 / 2.0) * segment;
+                        let x = cx + dir_x * offset;
+                        let y = cy + dir_y * offset;
+                        TerrainCommand::Range {
+                            count: 1,
+                            height,
+                            x: (x, x),
+                            y: (y, y),
+                            length: (segment, segment * 1.1),
+                            width: (0.02, 0.05),
+                            angle: (angle, angle),
+                        }
+                    })
+                    .collect()
             }
 
             TerrainPrimitive::Volcano {
@@ -273,6 +311,8 @@ impl TerrainPrimitive {
                         x: position.x,
                         y: position.y,
                         radius,
+                        blob_power: None,
+                        jitter: None,
                     },
                 ];
 
@@ -286,12 +326,27 @@ impl TerrainPrimitive {
                         x: position.x,
                         y: position.y,
                         radius: crater_radius,
+                        blob_power: None,
+                        jitter: None,
                     });
                 }
 
                 commands
             }
 
+            TerrainPrimitive::Caldera {
+                size,
+                elevation,
+                position,
+            } => {
+                vec![TerrainCommand::Caldera {
+                    height: elevation.to_height(),
+                    x: position.x,
+                    y: position.y,
+                    radius: size.to_radius(),
+                }]
+            }
+
             TerrainPrimitive::Plateau {
                 size,
                 elevation,
@@ -306,6 +361,8 @@ impl TerrainPrimitive {
                     x: position.x,
                     y: position.y,
                     radius: (radius.0 * 1.2, radius.1 * 1.5),
+                    blob_power: None,
+                    jitter: None,
                 }]
             }
 
@@ -323,6 +380,8 @@ impl TerrainPrimitive {
                     x: position.x,
                     y: position.y,
                     radius,
+                    blob_power: None,
+                    jitter: None,
                 }]
             }
 
@@ -377,6 +436,8 @@ impl TerrainPrimitive {
                     x: position.x,
                     y: position.y,
                     radius: (radius.0 * 1.5, radius.1 * 2.0),
+                    blob_power: None,
+                    jitter: None,
                 }]
             }
 
@@ -394,6 +455,22 @@ impl TerrainPrimitive {
                     x: position.x,
                     y: position.y,
                     radius,
+                    blob_power: None,
+                    jitter: None,
+                }]
+            }
+
+            TerrainPrimitive::Island {
+                target_cells,
+                elevation,
+                position,
+            } => {
+                // 岛屿：由执行器的专用 BFS 生长路径保证连通
+                vec![TerrainCommand::Island {
+                    target_cells: *target_cells,
+                    height: elevation.to_height(),
+                    x: position.x,
+                    y: position.y,
                 }]
             }
 
@@ -466,6 +543,8 @@ impl TerrainPrimitive {
                     x: position.x,
                     y: position.y,
                     radius,
+                    blob_power: None,
+                    jitter: None,
                 }]
             }
         }
@@ -618,4 +697,54 @@ mod tests {
         let commands = volcano.to_commands();
         assert_eq!(commands.len(), 2); // 主体 + 火山口
     }
+
+    #[test]
+    fn test_mountain_chain_is_linear() {
+        let chain = TerrainPrimitive::MountainChain {
+            size: Size::Large,
+            elevation: Elevation::High,
+            count: 4,
+            position: PositionConstraint::left(),
+        };
+
+        let commands = chain.to_commands();
+        assert_eq!(commands.len(), 4);
+
+        let segments: Vec<(f32, f32, f32)> = commands
+            .iter()
+            .map(|cmd| match cmd {
+                TerrainCommand::Range {
+                    count, x, y, angle, ..
+                } => {
+                    assert_eq!(*count, 1);
+                    assert_eq!(x.0, x.1);
+                    assert_eq!(y.0, y.1);
+                    assert_eq!(angle.0, angle.1);
+                    (x.0, y.0, angle.0)
+                }
+                _ => panic!("Expected Range command"),
+            })
+            .collect();
+
+        // 所有山脊共享同一角度
+        let angle = segments[0].2;
+        assert!(segments.iter().all(|s| (s.2 - angle).abs() < 1e-6));
+
+        // 中心点沿山脉轴线共线且等距
+        let (dir_x, dir_y) = (angle.cos(), angle.sin());
+        let (x0, y0, _) = segments[0];
+        let spacing = {
+            let (x1, y1, _) = segments[1];
+            (x1 - x0).hypot(y1 - y0)
+        };
+        for (i, &(x, y, _)) in segments.iter().enumerate() {
+            let (dx, dy) = (x - x0, y - y0);
+            assert!(
+                (dx * dir_y - dy * dir_x).abs() < 1e-5,
+                "center {} off axis",
+                i
+            );
+            assert!((dx.hypot(dy) - spacing * i as f32).abs() < 1e-5);
+        }
+    }
 }
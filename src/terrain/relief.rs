@@ -0,0 +1,295 @@
+// 地形起伏分析（坡度、坡向、山体阴影、等高线）
+//
+// 单元格不在规则网格上，因此用邻域内的最小二乘平面拟合估计梯度：
+// 对每个邻居 j 有 Δh ≈ gx·Δx + gy·Δy，求使残差平方和最小的 (gx, gy)。
+
+use std::collections::HashMap;
+
+use eframe::egui::Pos2;
+
+use crate::delaunay::DelaunayMesh;
+
+/// 计算每个单元格的坡度和坡向
+///
+/// 返回 `(slope, aspect)`：
+/// - `slope`：梯度大小，即每单位地图距离的高度变化
+/// - `aspect`：下坡方向与 +X 轴的夹角（弧度，-π..=π，Y 轴向下），平地为 0
+///
+/// 邻居不足以确定平面（少于两个或全部共线）的单元格坡度为 0。
+pub fn slope_aspect(
+    heights: &[u8],
+    cells: &[Pos2],
+    neighbors: &[Vec<u32>],
+) -> (Vec<f32>, Vec<f32>) {
+    let mut slope = vec![0.0; cells.len()];
+    let mut aspect = vec![0.0; cells.len()];
+
+    for i in 0..cells.len() {
+        let (gx, gy) = fit_gradient(heights, cells, &neighbors[i], i);
+        let magnitude = (gx * gx + gy * gy).sqrt();
+        if magnitude > f32::EPSILON {
+            slope[i] = magnitude;
+            aspect[i] = (-gy).atan2(-gx);
+        }
+    }
+
+    (slope, aspect)
+}
+
+/// 计算每个单元格的山体阴影 (0..1)
+///
+/// 按坡度和坡向计算方向光照：`light_azimuth` 为光源方向与 +X 轴的夹角
+/// （与坡向同一约定，弧度），`light_altitude` 为光源高度角（弧度，0 为地平线，π/2 为正上方）。
+/// 朝向光源的坡面更亮，背光坡面更暗，平地的亮度为 `sin(light_altitude)`。
+pub fn hillshade(
+    heights: &[u8],
+    cells: &[Pos2],
+    neighbors: &[Vec<u32>],
+    light_azimuth: f32,
+    light_altitude: f32,
+) -> Vec<f32> {
+    let (slope, aspect) = slope_aspect(heights, cells, neighbors);
+    let zenith = std::f32::consts::FRAC_PI_2 - light_altitude;
+
+    slope
+        .iter()
+        .zip(&aspect)
+        .map(|(&s, &a)| {
+            let slope_angle = s.atan();
+            let shade = zenith.cos() * slope_angle.cos()
+                + zenith.sin() * slope_angle.sin() * (light_azimuth - a).cos();
+            shade.clamp(0.0, 1.0)
+        })
+        .collect()
+}
+
+/// 提取等高线
+///
+/// 对每个 `interval` 整数倍的高度等级，在 Delaunay 三角形上执行 marching triangles：
+/// 高度跨越等级的边上按线性插值得到交点，每个被穿过的三角形贡献一条线段，
+/// 再沿共享边把线段连接成折线。`heights` 与 `cells` 按网格顶点索引对应。
+///
+/// 闭合等高线的首尾点相同；在凸包边界处被截断的等高线为开放折线。
+/// `interval` 为 0 时返回空列表。
+pub fn contours(
+    heights: &[u8],
+    cells: &[Pos2],
+    mesh: &DelaunayMesh,
+    interval: u8,
+) -> Vec<Vec<Pos2>> {
+    let mut lines = Vec::new();
+    if interval == 0 {
+        return lines;
+    }
+
+    for level in (interval..=u8::MAX).step_by(interval as usize) {
+        lines.extend(contour_level(heights, cells, mesh, level));
+    }
+    lines
+}
+
+/// 提取单个高度等级的等高线
+fn contour_level(heights: &[u8], cells: &[Pos2], mesh: &DelaunayMesh, level: u8) -> Vec<Vec<Pos2>> {
+    let above = |v: u32| heights[v as usize] >= level;
+    let edge_key = |a: u32, b: u32| (a.min(b), a.max(b));
+
+    // 每条线段连接三角形上两条被穿过的边
+    let mut segments: Vec<[(u32, u32); 2]> = Vec::new();
+    let mut by_edge: HashMap<(u32, u32), Vec<usize>> = HashMap::new();
+    for tri in 0..mesh.triangle_count() as u32 {
+        let [a, b, c] = mesh.triangle_vertices(tri);
+        let crossed: Vec<(u32, u32)> = [(a, b), (b, c), (c, a)]
+            .into_iter()
+            .filter(|&(u, v)| above(u) != above(v))
+            .map(|(u, v)| edge_key(u, v))
+            .collect();
+        if let [e0, e1] = crossed[..] {
+            by_edge.entry(e0).or_default().push(segments.len());
+            by_edge.entry(e1).or_default().push(segments.len());
+            segments.push([e0, e1]);
+        }
+    }
+
+    let crossing = |(a, b): (u32, u32)| {
+        let (ha, hb) = (heights[a as usize] as f32, heights[b as usize] as f32);
+        let t = (level as f32 - ha) / (hb - ha);
+        cells[a as usize] + (cells[b as usize] - cells[a as usize]) * t
+    };
+    let is_open_end = |e: &(u32, u32)| by_edge[e].len() == 1;
+
+    // 先从开放端点出发追踪，剩下的都是闭合环
+    let open_starts = (0..segments.len()).filter(|&s| segments[s].iter().any(is_open_end));
+    let mut visited = vec![false; segments.len()];
+    let mut lines = Vec::new();
+    for start in open_starts.chain(0..segments.len()) {
+        if visited[start] {
+            continue;
+        }
+
+        let mut entry = if is_open_end(&segments[start][1]) {
+            segments[start][1]
+        } else {
+            segments[start][0]
+        };
+        let mut line = vec![crossing(entry)];
+        let mut current = start;
+        loop {
+            visited[current] = true;
+            let [e0, e1] = segments[current];
+            let exit = if e0 == entry { e1 } else { e0 };
+            line.push(crossing(exit));
+
+            match by_edge[&exit]
+                .iter()
+                .find(|&&s| s != current && !visited[s])
+            {
+                Some(&next) => {
+                    current = next;
+                    entry = exit;
+                }
+                None => break,
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// 最小二乘拟合单元格 `i` 邻域的高度平面，返回梯度 (gx, gy)
+fn fit_gradient(heights: &[u8], cells: &[Pos2], neighbors: &[u32], i: usize) -> (f32, f32) {
+    let (mut sxx, mut sxy, mut syy, mut sxh, mut syh) = (0.0f32, 0.0, 0.0, 0.0, 0.0);
+    let h0 = heights[i] as f32;
+    for &j in neighbors {
+        let j = j as usize;
+        let d = cells[j] - cells[i];
+        let dh = heights[j] as f32 - h0;
+        sxx += d.x * d.x;
+        sxy += d.x * d.y;
+        syy += d.y * d.y;
+        sxh += d.x * dh;
+        syh += d.y * dh;
+    }
+
+    let det = sxx * syy - sxy * sxy;
+    if det.abs() <= f32::EPSILON * (sxx * syy).max(1.0) {
+        return (0.0, 0.0);
+    }
+    ((sxh * syy - syh * sxy) / det, (syh * sxx - sxh * sxy) / det)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::test_grid::grid8;
+
+    #[test]
+    fn test_slope_aspect_on_tilted_plane() {
+        let (cells, neighbors) = grid8(20, 20, 2.0);
+        // h = 10 + 0.5x + 0.5y：向右下方升高，下坡指向左上方
+        let heights: Vec<u8> = cells
+            .iter()
+            .map(|p| (10.0 + 0.5 * p.x + 0.5 * p.y) as u8)
+            .collect();
+
+        let (slope, aspect) = slope_aspect(&heights, &cells, &neighbors);
+        let expected_aspect = (-0.5f32).atan2(-0.5);
+        for i in 0..cells.len() {
+            assert!(
+                (slope[i] - 0.5f32.hypot(0.5)).abs() < 1e-4,
+                "cell {}: {}",
+                i,
+                slope[i]
+            );
+            assert!(
+                (aspect[i] - expected_aspect).abs() < 1e-4,
+                "cell {}: {}",
+                i,
+                aspect[i]
+            );
+        }
+
+        // 平地坡度为 0
+        let (flat, _) = slope_aspect(&vec![30; cells.len()], &cells, &neighbors);
+        assert!(flat.iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_hillshade_lit_slope_brighter() {
+        use std::f32::consts::PI;
+
+        let (cells, neighbors) = grid8(10, 10, 2.0);
+        // 向 +X 升高的坡面朝向 -X（坡向 π）
+        let heights: Vec<u8> = cells.iter().map(|p| (10.0 + p.x) as u8).collect();
+        let altitude = PI / 4.0;
+
+        let toward = hillshade(&heights, &cells, &neighbors, PI, altitude);
+        let away = hillshade(&heights, &cells, &neighbors, 0.0, altitude);
+        for i in 0..cells.len() {
+            assert!(toward[i] > away[i] + 0.3, "{} vs {}", toward[i], away[i]);
+            assert!((0.0..=1.0).contains(&toward[i]) && (0.0..=1.0).contains(&away[i]));
+        }
+
+        // 平地只取决于光源高度
+        let flat = hillshade(&vec![30; cells.len()], &cells, &neighbors, 1.0, altitude);
+        assert!(flat.iter().all(|&v| (v - altitude.sin()).abs() < 1e-5));
+    }
+
+    #[test]
+    fn test_contours_ring_a_cone() {
+        use crate::delaunay::triangulate_mesh;
+
+        // 中心 (100, 100) 的圆锥：h = 190 - 2·距离
+        let (cells, _) = grid8(21, 21, 10.0);
+        let center = Pos2::new(100.0, 100.0);
+        let heights: Vec<u8> = cells
+            .iter()
+            .map(|p| (190.0 - 2.0 * p.distance(center)).max(0.0) as u8)
+            .collect();
+        let mesh = triangulate_mesh(cells.clone());
+
+        let lines = contours(&heights, &cells, &mesh, 50);
+        // 等级 50、100、150 各一条
+        assert_eq!(lines.len(), 3);
+
+        for (line, level) in lines.iter().zip([50.0, 100.0, 150.0]) {
+            let radius = (190.0 - level) / 2.0;
+            assert!(line.len() > 8, "level {}: {} points", level, line.len());
+
+            // 闭合
+            assert!(line[0].distance(*line.last().unwrap()) < 1e-3);
+
+            // 每个点都落在对应半径附近
+            for p in line {
+                let d = p.distance(center);
+                assert!(
+                    (d - radius).abs() < 10.0,
+                    "level {}: {} vs {}",
+                    level,
+                    d,
+                    radius
+                );
+            }
+
+            // 环绕峰顶一周
+            let winding: f32 = line
+                .windows(2)
+                .map(|w| {
+                    let (a, b) = (w[0] - center, w[1] - center);
+                    b.y.atan2(b.x) - a.y.atan2(a.x)
+                })
+                .map(|d| {
+                    (d + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU)
+                        - std::f32::consts::PI
+                })
+                .sum();
+            assert!(
+                (winding.abs() - std::f32::consts::TAU).abs() < 1e-2,
+                "level {}: winding {}",
+                level,
+                winding
+            );
+        }
+
+        assert!(contours(&heights, &cells, &mesh, 0).is_empty());
+    }
+}
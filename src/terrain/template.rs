@@ -16,13 +16,33 @@ pub enum TerrainCommand {
         radius: f32, // 半径 (0.0-1.0)
     },
 
+    /// 方山 - 平顶陡壁的台地（区别于平缓的高原和尖顶的火山锥）
+    Mesa {
+        height: f32,       // 顶部高度增量 (0-255)
+        x: f32,            // X 位置 (0.0-1.0)
+        y: f32,            // Y 位置 (0.0-1.0)
+        radius: f32,       // 底部半径 (0.0-1.0)
+        top_fraction: f32, // 平顶半径占底部半径的比例 (0.0-1.0)
+    },
+
+    /// 破火山口 - 环形隆起的火山口壁围住下陷的中心
+    /// 口壁位于约 0.6 倍半径处，口内逐渐下降到中心，口外向四周平缓降到 0
+    Caldera {
+        height: (f32, f32), // 口壁高度范围
+        x: (f32, f32),
+        y: (f32, f32),
+        radius: (f32, f32), // 整体半径范围 (0.0-1.0)
+    },
+
     /// 丘陵 - 圆形隆起
     Hill {
-        count: u32,         // 数量
-        height: (f32, f32), // 高度范围 (min, max)
-        x: (f32, f32),      // X 位置范围 (0.0-1.0)
-        y: (f32, f32),      // Y 位置范围 (0.0-1.0)
-        radius: (f32, f32), // 半径范围 (0.0-1.0)
+        count: u32,              // 数量
+        height: (f32, f32),      // 高度范围 (min, max)
+        x: (f32, f32),           // X 位置范围 (0.0-1.0)
+        y: (f32, f32),           // Y 位置范围 (0.0-1.0)
+        radius: (f32, f32),      // 半径范围 (0.0-1.0)
+        blob_power: Option<f32>, // 覆盖 BFS 衰减因子，None 表示按单元格数量推导
+        jitter: Option<f32>,     // 覆盖 BFS 随机扰动，None 表示使用默认值
     },
 
     /// 有边界的丘陵 - BFS 扩散被限制在指定区域内
@@ -42,6 +62,26 @@ pub enum TerrainCommand {
         x: (f32, f32),
         y: (f32, f32),
         radius: (f32, f32),
+        blob_power: Option<f32>, // 同 Hill
+        jitter: Option<f32>,
+    },
+
+    /// 环礁 - 一圈浅层陆地环绕略低于海面的泻湖
+    /// 每个环礁由沿圆周排列的多个 BFS 小丘组成，中心再挖出泻湖
+    Atolls {
+        count: u32,         // 环礁数量
+        radius: (f32, f32), // 环半径范围 (0.0-1.0)
+        x: (f32, f32),      // 环礁中心的 X 范围
+        y: (f32, f32),      // 环礁中心的 Y 范围
+    },
+
+    /// 岛屿 - 从种子单元格 BFS 生长出的单块连通陆地
+    /// 约 `target_cells` 个单元格全部高于海平面，中心最高，向海岸递减
+    Island {
+        target_cells: u32,
+        height: (f32, f32), // 中心高度范围
+        x: (f32, f32),      // 种子位置的 X 范围
+        y: (f32, f32),      // 种子位置的 Y 范围
     },
 
     /// 山脉 - 细长的隆起区域
@@ -79,12 +119,43 @@ pub enum TerrainCommand {
         value: f32, // 可以是负值以降低高度
     },
 
+    /// 梯度 - 沿指定方向叠加线性高度坡面，模拟大陆整体倾斜
+    Gradient {
+        angle: f32,    // 上升方向（弧度，0 表示向 +X 升高）
+        strength: f32, // 高侧与低侧的高度差，可为负值
+    },
+
+    /// 填充 - 从离起点最近的单元格出发，把连通的低于 `level` 的单元格
+    /// 全部设为 `level`，在盆地中形成平坦水面，遇到不低于 `level` 的边缘即停止
+    FloodFill {
+        start_x: f32, // 起点 X (0.0-1.0)
+        start_y: f32, // 起点 Y (0.0-1.0)
+        level: f32,   // 水面高度 (0-255)
+    },
+
+    /// 扭曲 - 用噪声场偏移每个单元格的取样位置后重新取样高度，
+    /// 打破径向对称（比后生成噪声更可控）
+    Warp {
+        strength: f32,  // 最大偏移距离（地图单位）
+        frequency: f32, // 偏移噪声的基础频率
+    },
+
     /// 乘法 - 将所有高度值乘以系数
     Multiply { factor: f32 },
 
     /// 平滑 - 平均周围单元格的高度
     Smooth { iterations: u32 },
 
+    /// 海岸平滑 - 只平滑海陆边界附近 `band` 步图距离内的单元格，
+    /// 内陆山峰和深海保持不变
+    SmoothCoast { iterations: u32, band: u32 },
+
+    /// 阶梯 - 将陆地高度吸附到海平面以上等距的台阶上，形成梯田式等高带
+    Terrace {
+        steps: u32,    // 海平面到 255 之间等分的台阶数
+        strength: f32, // 0.0 无效果，1.0 完全吸附
+    },
+
     /// 侵蚀 - 基于坡度搬运沉积物，模拟水蚀对地形的重塑
     Erode {
         iterations: u32, // 迭代轮数
@@ -93,10 +164,35 @@ pub enum TerrainCommand {
         deposition: f32, // 沉积比例（0.0-1.0）
     },
 
+    /// 风蚀 - 沿盛行风方向削低迎风坡、在背风侧堆积，塑造雅丹等干旱地貌
+    WindErosion {
+        direction: f32,  // 风吹向的方向（弧度，0 表示吹向 +X）
+        strength: f32,   // 每轮迎风坡被削去的比例 (0.0-1.0)
+        iterations: u32, // 迭代轮数
+    },
+
+    /// 河流 - 从高处的源头沿最陡下降方向流向海洋，沿途下切出河道
+    River {
+        count: u32,         // 河流数量
+        source_height: f32, // 源头最低高度
+        meander: f32,       // 蜿蜒程度 (0.0-1.0)，0 表示严格沿最陡方向
+    },
+
+    /// 减去模板 - 生成指定名称的模板作为模板印，缩放到当前高度范围后按权重减去
+    /// 可用于挖出形如另一预设的海域或谷地
+    Subtract {
+        template: String, // 模板名称（见 get_template_by_name）
+        weight: f32,      // 权重 (0.0-1.0)
+    },
+
     /// 遮罩 - 应用边缘或中心渐变效果
+    /// 默认以地图中心为圆心、按拓扑计算边缘距离；指定 `center` 或 `radius` 时
+    /// 改为到该圆心的距离除以半径，可叠加多个不同圆心的遮罩生成不对称的世界
     Mask {
         mode: MaskMode,
-        strength: f32, // 强度 (0.0-1.0)
+        strength: f32,              // 强度 (0.0-1.0)
+        center: Option<(f32, f32)>, // 圆心 (0.0-1.0)，None 表示地图中心
+        radius: Option<f32>,        // 半径 (0.0-1.0，相对地图长边)，None 表示中心到角点的距离
     },
 
     /// 反转 - 沿 X、Y 或两个轴镜像高度图
@@ -118,6 +214,55 @@ pub enum TerrainCommand {
     AdjustSeaRatio {
         ocean_ratio: f32, // 目标海洋比例 (0.0-1.0)，例如 0.7 表示 70% 海洋
     },
+
+    /// 重映射曲线 - 对归一化高度应用传递函数，重塑高程分布
+    /// 最低点和最高点保持不变
+    RemapCurve { kind: CurveKind },
+
+    /// 散布 - 按每单元格概率在随机单元格上放置小型特征（小岛、礁石、绿洲等）
+    /// 内部特征的位置会被替换为采样到的单元格位置
+    Scatter {
+        feature: Box<TerrainCommand>, // 要放置的特征
+        density: f32,                 // 每个候选单元格被选中的概率 (0.0-1.0)
+        mask: Option<SmoothTarget>,   // 限制在陆地或海洋上，None 表示不限制
+    },
+}
+
+impl TerrainCommand {
+    /// 将命令的位置固定到指定坐标 (0.0-1.0)
+    ///
+    /// 对不带位置的命令（Add、Smooth 等）返回原命令的副本
+    pub fn at_position(&self, px: f32, py: f32) -> TerrainCommand {
+        let mut cmd = self.clone();
+        match &mut cmd {
+            TerrainCommand::Mountain { x, y, .. } | TerrainCommand::Mesa { x, y, .. } => {
+                *x = px;
+                *y = py;
+            }
+            TerrainCommand::FloodFill {
+                start_x, start_y, ..
+            } => {
+                *start_x = px;
+                *start_y = py;
+            }
+            TerrainCommand::Hill { x, y, .. }
+            | TerrainCommand::BoundedHill { x, y, .. }
+            | TerrainCommand::Pit { x, y, .. }
+            | TerrainCommand::Atolls { x, y, .. }
+            | TerrainCommand::Island { x, y, .. }
+            | TerrainCommand::Caldera { x, y, .. }
+            | TerrainCommand::Range { x, y, .. }
+            | TerrainCommand::Trough { x, y, .. } => {
+                *x = (px, px);
+                *y = (py, py);
+            }
+            TerrainCommand::Scatter { feature, .. } => {
+                **feature = feature.at_position(px, py);
+            }
+            _ => {}
+        }
+        cmd
+    }
 }
 
 /// 海峡方向
@@ -134,8 +279,18 @@ pub enum MaskMode {
     EdgeFade,
     /// 中心增强（中心升高，边缘降低）
     CenterBoost,
-    /// 径向渐变（从中心到边缘线性变化）
-    RadialGradient,
+    /// 径向渐变（从中心的 `inner` 倍线性过渡到边缘的 `outer` 倍）
+    RadialGradient { inner: f32, outer: f32 },
+}
+
+impl MaskMode {
+    /// 默认径向渐变：中心保持原高度，边缘降为 0
+    pub const fn radial() -> Self {
+        MaskMode::RadialGradient {
+            inner: 1.0,
+            outer: 0.0,
+        }
+    }
 }
 
 /// 反转轴
@@ -146,6 +301,46 @@ pub enum InvertAxis {
     Both,
 }
 
+/// 高度重映射曲线类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CurveKind {
+    /// 平滑阶梯 3t² - 2t³（压平两端，拉伸中段）
+    Smoothstep,
+    /// 伽马曲线 t^gamma（gamma > 1 压低中段，gamma < 1 抬高中段）
+    Gamma(f32),
+    /// S 曲线 t^k / (t^k + (1-t)^k)（k > 1 锐化两端，k < 1 压平两端）
+    SCurve(f32),
+}
+
+impl CurveKind {
+    /// 对 0.0-1.0 的归一化值应用曲线
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            CurveKind::Smoothstep => t * t * (3.0 - 2.0 * t),
+            CurveKind::Gamma(gamma) => t.powf(gamma.max(0.0)),
+            CurveKind::SCurve(k) => {
+                let a = t.powf(k);
+                let b = (1.0 - t).powf(k);
+                if a + b > 0.0 {
+                    a / (a + b)
+                } else {
+                    t
+                }
+            }
+        }
+    }
+}
+
+/// 作用目标 - 将命令限制在陆地或海洋单元格上
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SmoothTarget {
+    /// 仅陆地（高于海平面）
+    Land,
+    /// 仅海洋（低于海平面）
+    Ocean,
+}
+
 /// 地形模板
 #[derive(Debug, Clone)]
 pub struct TerrainTemplate {
@@ -237,6 +432,8 @@ impl TerrainTemplate {
                 x: (0.2, 0.8),
                 y: (0.25, 0.75),
                 radius: (0.25, 0.35),
+                blob_power: None,
+                jitter: None,
             },
             // 次级大陆
             TerrainCommand::Hill {
@@ -245,6 +442,8 @@ impl TerrainTemplate {
                 x: (0.1, 0.9),
                 y: (0.15, 0.85),
                 radius: (0.15, 0.22),
+                blob_power: None,
+                jitter: None,
             },
             // 主要山脉 - 少量长山脉
             TerrainCommand::Range {
@@ -263,6 +462,8 @@ impl TerrainTemplate {
                 x: (0.05, 0.95),
                 y: (0.1, 0.9),
                 radius: (0.04, 0.08),
+                blob_power: None,
+                jitter: None,
             },
             // 海沟
             TerrainCommand::Trough {
@@ -297,6 +498,8 @@ impl TerrainTemplate {
                 x: (0.1, 0.9),
                 y: (0.1, 0.9),
                 radius: (0.03, 0.08),
+                blob_power: None,
+                jitter: None,
             },
             // 几个稍大的岛屿
             TerrainCommand::Hill {
@@ -305,6 +508,8 @@ impl TerrainTemplate {
                 x: (0.2, 0.8),
                 y: (0.2, 0.8),
                 radius: (0.08, 0.12),
+                blob_power: None,
+                jitter: None,
             },
             // 海沟分隔岛屿群
             TerrainCommand::Trough {
@@ -323,6 +528,8 @@ impl TerrainTemplate {
                 x: (0.0, 1.0),
                 y: (0.0, 1.0),
                 radius: (0.08, 0.15),
+                blob_power: None,
+                jitter: None,
             },
             // 归一化
             TerrainCommand::Normalize,
@@ -348,6 +555,8 @@ impl TerrainTemplate {
                 x: (0.2, 0.8),
                 y: (0.2, 0.8),
                 radius: (0.12, 0.22),
+                blob_power: None,
+                jitter: None,
             },
             // 山脉
             TerrainCommand::Range {
@@ -401,6 +610,8 @@ impl TerrainTemplate {
                 x: (0.35, 0.65),
                 y: (0.35, 0.65),
                 radius: (0.05, 0.1),
+                blob_power: None,
+                jitter: None,
             },
             // 小岛屿
             TerrainCommand::Hill {
@@ -409,6 +620,8 @@ impl TerrainTemplate {
                 x: (0.2, 0.8),
                 y: (0.2, 0.8),
                 radius: (0.03, 0.06),
+                blob_power: None,
+                jitter: None,
             },
             // 归一化
             TerrainCommand::Normalize,
@@ -427,6 +640,8 @@ impl TerrainTemplate {
                 x: (0.45, 0.55),
                 y: (0.45, 0.55),
                 radius: (0.15, 0.2),
+                blob_power: None,
+                jitter: None,
             },
             // 环形岛屿
             TerrainCommand::Hill {
@@ -435,6 +650,8 @@ impl TerrainTemplate {
                 x: (0.3, 0.7),
                 y: (0.3, 0.7),
                 radius: (0.04, 0.07),
+                blob_power: None,
+                jitter: None,
             },
             // 一些突出点
             TerrainCommand::Hill {
@@ -443,11 +660,15 @@ impl TerrainTemplate {
                 x: (0.35, 0.65),
                 y: (0.35, 0.65),
                 radius: (0.05, 0.08),
+                blob_power: None,
+                jitter: None,
             },
             // 遮罩使边缘更深
             TerrainCommand::Mask {
                 mode: MaskMode::CenterBoost,
                 strength: 0.3,
+                center: None,
+                radius: None,
             },
             // 归一化
             TerrainCommand::Normalize,
@@ -466,6 +687,8 @@ impl TerrainTemplate {
                 x: (0.0, 0.4),
                 y: (0.1, 0.9),
                 radius: (0.15, 0.25),
+                blob_power: None,
+                jitter: None,
             },
             // 延伸半岛
             TerrainCommand::Range {
@@ -484,6 +707,8 @@ impl TerrainTemplate {
                 x: (0.5, 1.0),
                 y: (0.0, 1.0),
                 radius: (0.06, 0.12),
+                blob_power: None,
+                jitter: None,
             },
             // 海沟
             TerrainCommand::Trough {
@@ -514,6 +739,8 @@ impl TerrainTemplate {
                 x: (0.0, 1.0),
                 y: (0.0, 1.0),
                 radius: (0.1, 0.2),
+                blob_power: None,
+                jitter: None,
             },
             // 山脉
             TerrainCommand::Range {
@@ -532,6 +759,8 @@ impl TerrainTemplate {
                 x: (0.1, 0.9),
                 y: (0.1, 0.9),
                 radius: (0.08, 0.15),
+                blob_power: None,
+                jitter: None,
             },
             // 平滑
             TerrainCommand::Smooth { iterations: 2 },
@@ -552,6 +781,8 @@ impl TerrainTemplate {
                 x: (0.1, 0.9),
                 y: (0.1, 0.9),
                 radius: (0.04, 0.08),
+                blob_power: None,
+                jitter: None,
             },
             // 海底山脉
             TerrainCommand::Range {
@@ -580,6 +811,8 @@ impl TerrainTemplate {
                 x: (0.0, 1.0),
                 y: (0.0, 1.0),
                 radius: (0.1, 0.2),
+                blob_power: None,
+                jitter: None,
             },
             // 归一化
             TerrainCommand::Normalize,
@@ -624,6 +857,8 @@ impl TerrainTemplate {
                 x: (0.35, 0.45),
                 y: (0.25, 0.30),
                 radius: (0.06, 0.1),
+                blob_power: None,
+                jitter: None,
             },
             TerrainCommand::Hill {
                 count: 1,
@@ -631,6 +866,8 @@ impl TerrainTemplate {
                 x: (0.75, 0.80),
                 y: (0.25, 0.75),
                 radius: (0.04, 0.08),
+                blob_power: None,
+                jitter: None,
             },
             TerrainCommand::Hill {
                 count: 1,
@@ -638,11 +875,15 @@ impl TerrainTemplate {
                 x: (0.10, 0.15),
                 y: (0.20, 0.25),
                 radius: (0.03, 0.06),
+                blob_power: None,
+                jitter: None,
             },
             // 遮罩 - 边缘降低
             TerrainCommand::Mask {
                 mode: MaskMode::EdgeFade,
                 strength: 0.6,
+                center: None,
+                radius: None,
             },
             TerrainCommand::Normalize,
             TerrainCommand::AdjustSeaRatio { ocean_ratio: 0.88 },
@@ -668,6 +909,8 @@ impl TerrainTemplate {
                 x: (0.25, 0.55),
                 y: (0.45, 0.55),
                 radius: (0.08, 0.12),
+                blob_power: None,
+                jitter: None,
             },
             // 山脉
             TerrainCommand::Range {
@@ -685,6 +928,8 @@ impl TerrainTemplate {
             TerrainCommand::Mask {
                 mode: MaskMode::EdgeFade,
                 strength: 0.5,
+                center: None,
+                radius: None,
             },
             // 平滑
             TerrainCommand::Smooth { iterations: 2 },
@@ -714,6 +959,8 @@ impl TerrainTemplate {
                 x: (0.60, 0.60),
                 y: (0.50, 0.50),
                 radius: (0.06, 0.09),
+                blob_power: None,
+                jitter: None,
             },
             TerrainCommand::Hill {
                 count: 1,
@@ -721,6 +968,8 @@ impl TerrainTemplate {
                 x: (0.15, 0.20),
                 y: (0.20, 0.75),
                 radius: (0.05, 0.08),
+                blob_power: None,
+                jitter: None,
             },
             // 次级山脉
             TerrainCommand::Range {
@@ -748,6 +997,8 @@ impl TerrainTemplate {
                 x: (0.15, 0.85),
                 y: (0.20, 0.80),
                 radius: (0.04, 0.08),
+                blob_power: None,
+                jitter: None,
             },
             TerrainCommand::Normalize,
             TerrainCommand::AdjustSeaRatio { ocean_ratio: 0.65 },
@@ -771,6 +1022,8 @@ impl TerrainTemplate {
                 x: (0.10, 0.30),
                 y: (0.10, 0.90),
                 radius: (0.08, 0.12),
+                blob_power: None,
+                jitter: None,
             },
             TerrainCommand::Smooth { iterations: 2 },
             // 主体丘陵
@@ -780,6 +1033,8 @@ impl TerrainTemplate {
                 x: (0.20, 0.70),
                 y: (0.30, 0.70),
                 radius: (0.08, 0.12),
+                blob_power: None,
+                jitter: None,
             },
             // 山脉
             TerrainCommand::Range {
@@ -817,6 +1072,8 @@ impl TerrainTemplate {
                 x: (0.05, 0.15),
                 y: (0.20, 0.80),
                 radius: (0.05, 0.08),
+                blob_power: None,
+                jitter: None,
             },
             TerrainCommand::Hill {
                 count: 1,
@@ -824,6 +1081,8 @@ impl TerrainTemplate {
                 x: (0.85, 0.95),
                 y: (0.70, 0.80),
                 radius: (0.05, 0.08),
+                blob_power: None,
+                jitter: None,
             },
             // 坑洞/湖泊
             TerrainCommand::Pit {
@@ -832,12 +1091,16 @@ impl TerrainTemplate {
                 x: (0.15, 0.85),
                 y: (0.20, 0.80),
                 radius: (0.04, 0.08),
+                blob_power: None,
+                jitter: None,
             },
             // 大幅降低高度使其变得平坦
             TerrainCommand::Multiply { factor: 0.4 },
             TerrainCommand::Mask {
                 mode: MaskMode::EdgeFade,
                 strength: 0.6,
+                center: None,
+                radius: None,
             },
             TerrainCommand::Normalize,
             TerrainCommand::AdjustSeaRatio { ocean_ratio: 0.70 },
@@ -925,6 +1188,8 @@ impl TerrainTemplate {
             TerrainCommand::Mask {
                 mode: MaskMode::EdgeFade,
                 strength: 0.5,
+                center: None,
+                radius: None,
             },
             TerrainCommand::Normalize,
             TerrainCommand::AdjustSeaRatio { ocean_ratio: 0.58 },
@@ -953,6 +1218,8 @@ impl TerrainTemplate {
                 x: (0.10, 0.90),
                 y: (0.30, 0.70),
                 radius: (0.06, 0.1),
+                blob_power: None,
+                jitter: None,
             },
             // 左侧丘陵
             TerrainCommand::Hill {
@@ -961,6 +1228,8 @@ impl TerrainTemplate {
                 x: (0.10, 0.30),
                 y: (0.20, 0.80),
                 radius: (0.05, 0.08),
+                blob_power: None,
+                jitter: None,
             },
             // 右侧丘陵
             TerrainCommand::Hill {
@@ -969,6 +1238,8 @@ impl TerrainTemplate {
                 x: (0.60, 0.90),
                 y: (0.20, 0.80),
                 radius: (0.05, 0.08),
+                blob_power: None,
+                jitter: None,
             },
             // 平滑
             TerrainCommand::Smooth { iterations: 3 },
@@ -1018,6 +1289,8 @@ impl TerrainTemplate {
                 x: (0.25, 0.75),
                 y: (0.30, 0.70),
                 radius: (0.12, 0.18),
+                blob_power: None,
+                jitter: None,
             },
             // 西侧延伸
             TerrainCommand::Hill {
@@ -1026,6 +1299,8 @@ impl TerrainTemplate {
                 x: (0.25, 0.35),
                 y: (0.30, 0.70),
                 radius: (0.1, 0.15),
+                blob_power: None,
+                jitter: None,
             },
             // 平滑
             TerrainCommand::Smooth { iterations: 1 },
@@ -1038,10 +1313,14 @@ impl TerrainTemplate {
                 x: (0.50, 0.55),
                 y: (0.48, 0.52),
                 radius: (0.04, 0.07),
+                blob_power: None,
+                jitter: None,
             },
             TerrainCommand::Mask {
                 mode: MaskMode::CenterBoost,
                 strength: 0.4,
+                center: None,
+                radius: None,
             },
             TerrainCommand::Normalize,
             TerrainCommand::AdjustSeaRatio { ocean_ratio: 0.92 },
@@ -1078,6 +1357,8 @@ impl TerrainTemplate {
                 x: (0.10, 0.90),
                 y: (0.0, 0.05),
                 radius: (0.1, 0.15),
+                blob_power: None,
+                jitter: None,
             },
             // 南部丘陵
             TerrainCommand::Hill {
@@ -1086,13 +1367,17 @@ impl TerrainTemplate {
                 x: (0.10, 0.90),
                 y: (0.95, 1.0),
                 radius: (0.1, 0.15),
+                blob_power: None,
+                jitter: None,
             },
             // 降低陆地
             TerrainCommand::Multiply { factor: 0.9 },
             // 边缘遮罩（反向 - 中央凹陷）
             TerrainCommand::Mask {
-                mode: MaskMode::RadialGradient,
+                mode: MaskMode::radial(),
                 strength: -0.4,
+                center: None,
+                radius: None,
             },
             // 平滑
             TerrainCommand::Smooth { iterations: 1 },
@@ -1103,6 +1388,8 @@ impl TerrainTemplate {
                 x: (0.0, 0.05),
                 y: (0.20, 0.80),
                 radius: (0.1, 0.15),
+                blob_power: None,
+                jitter: None,
             },
             // 东部延伸
             TerrainCommand::Hill {
@@ -1111,6 +1398,8 @@ impl TerrainTemplate {
                 x: (0.95, 1.0),
                 y: (0.20, 0.80),
                 radius: (0.1, 0.15),
+                blob_power: None,
+                jitter: None,
             },
             // 内海海沟（加深中央区域）
             TerrainCommand::Trough {
@@ -1167,6 +1456,8 @@ impl TerrainTemplate {
                 x: (0.05, 0.95),
                 y: (0.80, 1.0),
                 radius: (0.04, 0.07),
+                blob_power: None,
+                jitter: None,
             },
             // 中部连接丘陵
             TerrainCommand::Hill {
@@ -1175,6 +1466,8 @@ impl TerrainTemplate {
                 x: (0.05, 0.95),
                 y: (0.40, 0.60),
                 radius: (0.04, 0.07),
+                blob_power: None,
+                jitter: None,
             },
             // 海沟分隔
             TerrainCommand::Trough {
@@ -1208,6 +1501,8 @@ impl TerrainTemplate {
                 x: (0.15, 0.50),
                 y: (0.0, 0.10),
                 radius: (0.1, 0.16),
+                blob_power: None,
+                jitter: None,
             },
             // 东北角大陆
             TerrainCommand::Hill {
@@ -1216,6 +1511,8 @@ impl TerrainTemplate {
                 x: (0.50, 0.85),
                 y: (0.0, 0.10),
                 radius: (0.08, 0.14),
+                blob_power: None,
+                jitter: None,
             },
             // 东南角大陆
             TerrainCommand::Hill {
@@ -1224,6 +1521,8 @@ impl TerrainTemplate {
                 x: (0.50, 0.85),
                 y: (0.90, 1.0),
                 radius: (0.1, 0.16),
+                blob_power: None,
+                jitter: None,
             },
             // 西南角大陆
             TerrainCommand::Hill {
@@ -1232,6 +1531,8 @@ impl TerrainTemplate {
                 x: (0.15, 0.50),
                 y: (0.90, 1.0),
                 radius: (0.08, 0.14),
+                blob_power: None,
+                jitter: None,
             },
             // 中央大陆核心
             TerrainCommand::Hill {
@@ -1240,6 +1541,8 @@ impl TerrainTemplate {
                 x: (0.20, 0.80),
                 y: (0.48, 0.52),
                 radius: (0.1, 0.16),
+                blob_power: None,
+                jitter: None,
             },
             // 平滑
             TerrainCommand::Smooth { iterations: 2 },
@@ -1290,6 +1593,8 @@ impl TerrainTemplate {
                 x: (0.0, 0.30),
                 y: (0.0, 0.20),
                 radius: (0.08, 0.14),
+                blob_power: None,
+                jitter: None,
             },
             // 西侧大陆延伸
             TerrainCommand::Hill {
@@ -1298,6 +1603,8 @@ impl TerrainTemplate {
                 x: (0.10, 0.50),
                 y: (0.20, 0.40),
                 radius: (0.08, 0.14),
+                blob_power: None,
+                jitter: None,
             },
             // 中央连接带
             TerrainCommand::Hill {
@@ -1306,6 +1613,8 @@ impl TerrainTemplate {
                 x: (0.30, 0.70),
                 y: (0.40, 0.60),
                 radius: (0.08, 0.14),
+                blob_power: None,
+                jitter: None,
             },
             // 东侧大陆延伸
             TerrainCommand::Hill {
@@ -1314,6 +1623,8 @@ impl TerrainTemplate {
                 x: (0.50, 0.90),
                 y: (0.60, 0.80),
                 radius: (0.08, 0.14),
+                blob_power: None,
+                jitter: None,
             },
             // 东南大陆块
             TerrainCommand::Hill {
@@ -1322,6 +1633,8 @@ impl TerrainTemplate {
                 x: (0.70, 1.0),
                 y: (0.80, 1.0),
                 radius: (0.08, 0.14),
+                blob_power: None,
+                jitter: None,
             },
             // 平滑
             TerrainCommand::Smooth { iterations: 2 },
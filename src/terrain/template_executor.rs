@@ -3,9 +3,22 @@
 // 执行地形模板命令，修改高度图数据
 
 use super::blob::{BlobConfig, BlobGenerator};
-use super::template::{InvertAxis, MaskMode, StraitDirection, TerrainCommand, TerrainTemplate};
-use eframe::egui::Pos2;
+use super::heightmap::SEA_LEVEL;
+use super::noise::{NoiseConfig, NoiseGenerator};
+use super::template::{
+    get_template_by_name, CurveKind, InvertAxis, MaskMode, SmoothTarget, StraitDirection,
+    TerrainCommand, TerrainTemplate,
+};
+use super::topology::Topology;
+use crate::spatial::GridIndex;
+use eframe::egui::{Pos2, Rect, Vec2};
 use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+
+/// 破火山口口壁所在位置占整体半径的比例
+const CALDERA_RIM_FRACTION: f32 = 0.6;
+/// 破火山口中心高度占口壁高度的比例
+const CALDERA_FLOOR_FRACTION: f32 = 0.35;
 
 /// 生成模式
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -17,12 +30,47 @@ pub enum GenerationMode {
     BfsBlob,
 }
 
+/// 模板执行错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateExecError {
+    /// Subtract 引用的模板不存在
+    UnknownTemplate(String),
+    /// Subtract 直接或间接引用了正在执行的模板
+    RecursiveSubtract(String),
+}
+
+impl std::fmt::Display for TemplateExecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownTemplate(name) => write!(f, "unknown template '{}' in Subtract", name),
+            Self::RecursiveSubtract(name) => {
+                write!(f, "template '{}' subtracts itself recursively", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TemplateExecError {}
+
+/// 一次执行过程中的 Subtract 状态
+#[derive(Default)]
+struct SubtractContext {
+    /// 正在执行的 Subtract 模板名称（小写），用于检测递归
+    active: Vec<String>,
+    /// 被跳过的命令对应的错误
+    errors: Vec<TemplateExecError>,
+}
+
 /// 模板执行器
 pub struct TemplateExecutor {
     width: u32,
     height: u32,
     seed: u64,
     mode: GenerationMode,
+    topology: Topology,
+    sea_level: u8,
+    /// Subtract 可引用的额外模板（按小写名称索引），优先于内置模板
+    stencils: HashMap<String, TerrainTemplate>,
 }
 
 impl TemplateExecutor {
@@ -32,6 +80,9 @@ impl TemplateExecutor {
             height,
             seed,
             mode: GenerationMode::BfsBlob, // 默认使用 BFS 模式
+            topology: Topology::Plane,
+            sea_level: SEA_LEVEL,
+            stencils: HashMap::new(),
         }
     }
 
@@ -42,20 +93,70 @@ impl TemplateExecutor {
             height,
             seed,
             mode,
+            topology: Topology::Plane,
+            sea_level: SEA_LEVEL,
+            stencils: HashMap::new(),
         }
     }
 
+    /// 链式注册可被 Subtract 按名称引用的模板（如从文件加载的自定义模板）
+    pub fn with_stencil(mut self, template: TerrainTemplate) -> Self {
+        self.stencils.insert(template.name.to_lowercase(), template);
+        self
+    }
+
+    /// 链式设置海平面高度（影响 SeaRatio、Scatter、River 等按海陆判断的命令）
+    pub fn with_sea_level(mut self, sea_level: u8) -> Self {
+        self.sea_level = sea_level;
+        self
+    }
+
+    /// 链式设置地图拓扑（影响遮罩）
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
     /// 设置生成模式
     pub fn set_mode(&mut self, mode: GenerationMode) {
         self.mode = mode;
     }
 
     /// 执行模板，生成高度图
+    ///
+    /// 引用未知模板或递归引用的 Subtract 命令会被跳过，
+    /// 需要得到这些错误时使用 [`Self::try_execute`]。
     pub fn execute(
         &self,
         template: &TerrainTemplate,
         cells: &[Pos2],
         neighbors: &[Vec<u32>],
+    ) -> Vec<f32> {
+        self.run(template, cells, neighbors, &mut SubtractContext::default())
+    }
+
+    /// 执行模板，任一 Subtract 命令引用未知模板或递归引用时返回第一个错误
+    pub fn try_execute(
+        &self,
+        template: &TerrainTemplate,
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+    ) -> Result<Vec<f32>, TemplateExecError> {
+        let mut ctx = SubtractContext::default();
+        let heights = self.run(template, cells, neighbors, &mut ctx);
+        match ctx.errors.into_iter().next() {
+            Some(err) => Err(err),
+            None => Ok(heights),
+        }
+    }
+
+    /// 从零高度开始依次执行模板命令
+    fn run(
+        &self,
+        template: &TerrainTemplate,
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+        ctx: &mut SubtractContext,
     ) -> Vec<f32> {
         let mut heights = vec![0.0; cells.len()];
         let mut rng = rand::rngs::StdRng::seed_from_u64(self.seed);
@@ -67,7 +168,7 @@ impl TemplateExecutor {
             #[cfg(debug_assertions)]
             println!("  [{}] 执行命令: {:?}", _idx + 1, command);
 
-            self.execute_command(command, &mut heights, cells, neighbors, &mut rng);
+            self.execute_command(command, &mut heights, cells, neighbors, &mut rng, ctx);
         }
 
         heights
@@ -81,6 +182,7 @@ impl TemplateExecutor {
         cells: &[Pos2],
         neighbors: &[Vec<u32>],
         rng: &mut rand::rngs::StdRng,
+        ctx: &mut SubtractContext,
     ) {
         match command {
             TerrainCommand::Mountain {
@@ -92,13 +194,40 @@ impl TemplateExecutor {
                 self.apply_mountain(heights, cells, *height, *x, *y, *radius);
             }
 
+            TerrainCommand::Mesa {
+                height,
+                x,
+                y,
+                radius,
+                top_fraction,
+            } => {
+                self.apply_mesa(heights, cells, *height, *x, *y, *radius, *top_fraction);
+            }
+
+            TerrainCommand::Caldera {
+                height,
+                x,
+                y,
+                radius,
+            } => {
+                let h = rng.random_range(height.0..=height.1);
+                let px = rng.random_range(x.0..=x.1);
+                let py = rng.random_range(y.0..=y.1);
+                let r = rng.random_range(radius.0..=radius.1);
+                self.apply_caldera(heights, cells, h, px, py, r);
+            }
+
             TerrainCommand::Hill {
                 count,
                 height,
                 x,
                 y,
                 radius,
+                blob_power,
+                jitter,
             } => {
+                let blob_config =
+                    BlobConfig::from_cell_count(cells.len()).with_overrides(*blob_power, *jitter);
                 for _ in 0..*count {
                     let h = rng.random_range(height.0..=height.1);
                     let px = rng.random_range(x.0..=x.1);
@@ -110,7 +239,16 @@ impl TemplateExecutor {
                             self.apply_hill(heights, cells, h, px, py, r);
                         }
                         GenerationMode::BfsBlob => {
-                            self.apply_hill_bfs(heights, cells, neighbors, h, px, py, rng);
+                            self.apply_hill_bfs(
+                                heights,
+                                cells,
+                                neighbors,
+                                h,
+                                px,
+                                py,
+                                blob_config.clone(),
+                                rng,
+                            );
                         }
                     }
                 }
@@ -128,7 +266,16 @@ impl TemplateExecutor {
                     let px = rng.random_range(x.0..=x.1);
                     let py = rng.random_range(y.0..=y.1);
 
-                    self.apply_hill_bfs_bounded(heights, cells, neighbors, h, px, py, *bounds, rng);
+                    match self.mode {
+                        GenerationMode::Classic => {
+                            self.apply_hill_bounded(heights, cells, h, px, py, *bounds);
+                        }
+                        GenerationMode::BfsBlob => {
+                            self.apply_hill_bfs_bounded(
+                                heights, cells, neighbors, h, px, py, *bounds, rng,
+                            );
+                        }
+                    }
                 }
             }
 
@@ -138,7 +285,11 @@ impl TemplateExecutor {
                 x,
                 y,
                 radius,
+                blob_power,
+                jitter,
             } => {
+                let blob_config =
+                    BlobConfig::from_cell_count(cells.len()).with_overrides(*blob_power, *jitter);
                 for _ in 0..*count {
                     let d = rng.random_range(depth.0..=depth.1);
                     let px = rng.random_range(x.0..=x.1);
@@ -150,12 +301,56 @@ impl TemplateExecutor {
                             self.apply_pit(heights, cells, d, px, py, r);
                         }
                         GenerationMode::BfsBlob => {
-                            self.apply_pit_bfs(heights, cells, neighbors, d, px, py, rng);
+                            self.apply_pit_bfs(
+                                heights,
+                                cells,
+                                neighbors,
+                                d,
+                                px,
+                                py,
+                                blob_config.clone(),
+                                rng,
+                            );
                         }
                     }
                 }
             }
 
+            TerrainCommand::Atolls {
+                count,
+                radius,
+                x,
+                y,
+            } => {
+                for _ in 0..*count {
+                    let px = rng.random_range(x.0..=x.1);
+                    let py = rng.random_range(y.0..=y.1);
+                    let r = rng.random_range(radius.0..=radius.1);
+                    self.apply_atoll(heights, cells, neighbors, px, py, r, rng);
+                }
+            }
+
+            TerrainCommand::Island {
+                target_cells,
+                height,
+                x,
+                y,
+            } => {
+                let h = rng.random_range(height.0..=height.1);
+                let px = rng.random_range(x.0..=x.1);
+                let py = rng.random_range(y.0..=y.1);
+                self.grow_island(
+                    heights,
+                    cells,
+                    neighbors,
+                    *target_cells as usize,
+                    h,
+                    px,
+                    py,
+                    rng,
+                );
+            }
+
             TerrainCommand::Range {
                 count,
                 height,
@@ -229,6 +424,26 @@ impl TemplateExecutor {
                 }
             }
 
+            TerrainCommand::Gradient { angle, strength } => {
+                self.apply_gradient(heights, cells, *angle, *strength);
+            }
+
+            TerrainCommand::FloodFill {
+                start_x,
+                start_y,
+                level,
+            } => {
+                self.flood_fill(heights, cells, neighbors, *start_x, *start_y, *level);
+            }
+
+            TerrainCommand::Warp {
+                strength,
+                frequency,
+            } => {
+                let seed = rng.random::<u32>();
+                self.apply_warp(heights, cells, *strength, *frequency, seed);
+            }
+
             TerrainCommand::Multiply { factor } => {
                 for h in heights.iter_mut() {
                     *h *= factor;
@@ -239,13 +454,21 @@ impl TemplateExecutor {
                 self.smooth_heights(heights, neighbors, *iterations);
             }
 
+            TerrainCommand::SmoothCoast { iterations, band } => {
+                self.smooth_coast(heights, neighbors, *iterations, *band);
+            }
+
+            TerrainCommand::Terrace { steps, strength } => {
+                self.apply_terrace(heights, *steps, *strength);
+            }
+
             TerrainCommand::Erode {
                 iterations,
                 rain,
                 capacity,
                 deposition,
             } => {
-                self.erode_heights(
+                self.apply_erosion(
                     heights,
                     neighbors,
                     *iterations,
@@ -255,8 +478,58 @@ impl TemplateExecutor {
                 );
             }
 
-            TerrainCommand::Mask { mode, strength } => {
-                self.apply_mask(heights, cells, *mode, *strength);
+            TerrainCommand::WindErosion {
+                direction,
+                strength,
+                iterations,
+            } => {
+                self.wind_erode_heights(
+                    heights,
+                    cells,
+                    neighbors,
+                    *direction,
+                    *strength,
+                    *iterations,
+                );
+            }
+
+            TerrainCommand::River {
+                count,
+                source_height,
+                meander,
+            } => {
+                self.apply_rivers(heights, neighbors, *count, *source_height, *meander, rng);
+            }
+
+            TerrainCommand::Subtract { template, weight } => {
+                let key = template.to_lowercase();
+                let stencil = self
+                    .stencils
+                    .get(&key)
+                    .cloned()
+                    .or_else(|| get_template_by_name(template));
+                match stencil {
+                    None => ctx
+                        .errors
+                        .push(TemplateExecError::UnknownTemplate(template.clone())),
+                    Some(_) if ctx.active.contains(&key) => ctx
+                        .errors
+                        .push(TemplateExecError::RecursiveSubtract(template.clone())),
+                    Some(stencil) => {
+                        ctx.active.push(key);
+                        self.subtract_template(heights, cells, neighbors, &stencil, *weight, ctx);
+                        ctx.active.pop();
+                    }
+                }
+            }
+
+            TerrainCommand::Mask {
+                mode,
+                strength,
+                center,
+                radius,
+            } => {
+                self.apply_mask(heights, cells, *mode, *strength, *center, *radius);
             }
 
             TerrainCommand::Invert { axis, probability } => {
@@ -282,14 +555,75 @@ impl TemplateExecutor {
             TerrainCommand::AdjustSeaRatio { ocean_ratio } => {
                 self.adjust_sea_ratio(heights, *ocean_ratio);
             }
+
+            TerrainCommand::RemapCurve { kind } => {
+                self.remap_heights(heights, *kind);
+            }
+
+            TerrainCommand::Scatter {
+                feature,
+                density,
+                mask,
+            } => {
+                // 先在当前高度图上选出所有位置，避免放置过程改变候选集合
+                let sites = self.scatter_sites(heights, *density, *mask, rng);
+                for idx in sites {
+                    let px = (cells[idx].x / self.width as f32).clamp(0.0, 1.0);
+                    let py = (cells[idx].y / self.height as f32).clamp(0.0, 1.0);
+                    let placed = feature.at_position(px, py);
+                    self.execute_command(&placed, heights, cells, neighbors, rng, ctx);
+                }
+            }
+        }
+    }
+
+    /// 重映射高度曲线
+    /// 将高度归一化到 0-1 后应用曲线，再映射回原范围
+    fn remap_heights(&self, heights: &mut [f32], kind: CurveKind) {
+        if heights.is_empty() {
+            return;
+        }
+
+        let min = heights.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = heights.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let range = max - min;
+        if range.abs() < 0.001 {
+            return;
+        }
+
+        for h in heights.iter_mut() {
+            let t = (*h - min) / range;
+            *h = min + kind.apply(t) * range;
         }
     }
 
+    /// 按概率采样散布位置
+    /// 每个满足遮罩条件的单元格以 density 的概率被选中
+    fn scatter_sites(
+        &self,
+        heights: &[f32],
+        density: f32,
+        mask: Option<SmoothTarget>,
+        rng: &mut rand::rngs::StdRng,
+    ) -> Vec<usize> {
+        let density = density.clamp(0.0, 1.0);
+        let sea_level = self.sea_level as f32;
+
+        heights
+            .iter()
+            .enumerate()
+            .filter(|(_, &h)| match mask {
+                Some(SmoothTarget::Land) => h >= sea_level,
+                Some(SmoothTarget::Ocean) => h < sea_level,
+                None => true,
+            })
+            .filter_map(|(i, _)| (rng.random::<f32>() < density).then_some(i))
+            .collect()
+    }
+
     /// 调整海陆比例
     /// 通过重新映射高度值，使得指定比例的区域落在海平面以下
     fn adjust_sea_ratio(&self, heights: &mut [f32], ocean_ratio: f32) {
-        use super::heightmap::SEA_LEVEL;
-
         if heights.is_empty() {
             return;
         }
@@ -304,22 +638,22 @@ impl TemplateExecutor {
         let threshold = sorted[percentile_idx];
 
         // 重新映射高度值
-        // 低于 threshold 的映射到 0 ~ SEA_LEVEL
-        // 高于 threshold 的映射到 SEA_LEVEL ~ 255
-        let sea_level = SEA_LEVEL as f32;
+        // 低于 threshold 的映射到 0 ~ sea_level
+        // 高于 threshold 的映射到 sea_level ~ 255
+        let sea_level = self.sea_level as f32;
         let min_h = sorted[0];
         let max_h = sorted[sorted.len() - 1];
 
         for h in heights.iter_mut() {
             if *h <= threshold {
-                // 海洋区域：映射到 0 ~ SEA_LEVEL
+                // 海洋区域：映射到 0 ~ sea_level
                 if (threshold - min_h).abs() > 0.001 {
                     *h = (*h - min_h) / (threshold - min_h) * sea_level;
                 } else {
                     *h = sea_level * 0.5;
                 }
             } else {
-                // 陆地区域：映射到 SEA_LEVEL ~ 255
+                // 陆地区域：映射到 sea_level ~ 255
                 if (max_h - threshold).abs() > 0.001 {
                     *h = sea_level + (*h - threshold) / (max_h - threshold) * (255.0 - sea_level);
                 } else {
@@ -352,6 +686,194 @@ impl TemplateExecutor {
         }
     }
 
+    /// 应用破火山口效果
+    ///
+    /// 口壁（半径的 [`CALDERA_RIM_FRACTION`] 处）抬升 `height`；口内按距离平方
+    /// 下降到中心的 [`CALDERA_FLOOR_FRACTION`] 倍口壁高度，口外以 smoothstep 降到 0。
+    fn apply_caldera(
+        &self,
+        heights: &mut [f32],
+        cells: &[Pos2],
+        height: f32,
+        center_x: f32,
+        center_y: f32,
+        radius: f32,
+    ) {
+        let center = Pos2::new(center_x * self.width as f32, center_y * self.height as f32);
+        let radius_pixels = radius * self.width.max(self.height) as f32;
+        let rim_pixels = (radius_pixels * CALDERA_RIM_FRACTION).max(f32::EPSILON);
+        let flank = (radius_pixels - rim_pixels).max(f32::EPSILON);
+
+        for (i, pos) in cells.iter().enumerate() {
+            let dist = pos.distance(center);
+            if dist <= rim_pixels {
+                let t = (dist / rim_pixels).powi(2);
+                heights[i] +=
+                    height * (CALDERA_FLOOR_FRACTION + (1.0 - CALDERA_FLOOR_FRACTION) * t);
+            } else if dist < radius_pixels {
+                let t = 1.0 - (dist - rim_pixels) / flank;
+                heights[i] += height * t * t * (3.0 - 2.0 * t);
+            }
+        }
+    }
+
+    /// 应用方山效果
+    ///
+    /// 平顶区域内高度恒定，平顶以外到底部边缘之间以陡峭的
+    /// smoothstep 曲线下降到 0。
+    fn apply_mesa(
+        &self,
+        heights: &mut [f32],
+        cells: &[Pos2],
+        height: f32,
+        center_x: f32,
+        center_y: f32,
+        radius: f32,
+        top_fraction: f32,
+    ) {
+        let center = Pos2::new(center_x * self.width as f32, center_y * self.height as f32);
+        let radius_pixels = radius * self.width.max(self.height) as f32;
+        let top_pixels = radius_pixels * top_fraction.clamp(0.0, 1.0);
+        let wall = (radius_pixels - top_pixels).max(f32::EPSILON);
+
+        for (i, pos) in cells.iter().enumerate() {
+            let dist = pos.distance(center);
+            if dist <= top_pixels {
+                heights[i] += height;
+            } else if dist < radius_pixels {
+                let t = 1.0 - (dist - top_pixels) / wall;
+                heights[i] += height * t * t * (3.0 - 2.0 * t);
+            }
+        }
+    }
+
+    /// 应用环礁效果
+    ///
+    /// 沿半径为 `radius` 的圆周放置一圈低矮小丘形成礁环，小丘数量随周长增加，
+    /// 保持相邻小丘约两个单元格间距。再把内侧区域压到比最低礁环点更低、
+    /// 且在海平面以下的高度，形成泻湖。
+    #[allow(clippy::too_many_arguments)]
+    fn apply_atoll(
+        &self,
+        heights: &mut [f32],
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+        center_x: f32,
+        center_y: f32,
+        radius: f32,
+        rng: &mut rand::rngs::StdRng,
+    ) {
+        if cells.is_empty() {
+            return;
+        }
+
+        let center = Pos2::new(center_x * self.width as f32, center_y * self.height as f32);
+        let scale = self.width.max(self.height) as f32;
+        let radius_pixels = radius * scale;
+
+        // 每两个单元格间距放一个小丘
+        let cell_spacing = (self.width as f32 * self.height as f32 / cells.len() as f32).sqrt();
+        let ring_sites = (std::f32::consts::TAU * radius_pixels / (cell_spacing * 2.0))
+            .round()
+            .clamp(6.0, 64.0) as usize;
+
+        // 礁环：圆周上略带抖动的浅层小丘
+        let jitter = std::f32::consts::PI / ring_sites as f32 * 0.5;
+        let mut ring_cells = Vec::with_capacity(ring_sites);
+        for i in 0..ring_sites {
+            let angle = i as f32 / ring_sites as f32 * std::f32::consts::TAU
+                + rng.random_range(-jitter..=jitter);
+            let r = radius_pixels * rng.random_range(0.9..=1.1);
+            let site = center + eframe::egui::vec2(angle.cos(), angle.sin()) * r;
+            let (sx, sy) = (site.x / self.width as f32, site.y / self.height as f32);
+            let h = rng.random_range(20.0..=30.0);
+
+            match self.mode {
+                GenerationMode::Classic => {
+                    self.apply_hill(heights, cells, h, sx, sy, radius * 0.3);
+                }
+                GenerationMode::BfsBlob => {
+                    let blob_config = BlobConfig::from_cell_count(cells.len());
+                    self.apply_hill_bfs(heights, cells, neighbors, h, sx, sy, blob_config, rng);
+                }
+            }
+            ring_cells.push(BlobGenerator::find_nearest_cell(cells, site.x, site.y));
+        }
+
+        // 泻湖：内侧压到最低礁环点和海平面以下，中心一半为平坦湖底，向礁环渐浅
+        let ring_min = ring_cells
+            .iter()
+            .map(|&i| heights[i])
+            .fold(f32::INFINITY, f32::min);
+        let lagoon_floor =
+            (ring_min - (ring_min.abs() * 0.3 + 2.0)).min(self.sea_level as f32 - 2.0);
+        let inner = radius_pixels * 0.7;
+
+        for (i, pos) in cells.iter().enumerate() {
+            let dist = pos.distance(center);
+            if dist < inner {
+                let t = ((dist / inner - 0.5) * 2.0).max(0.0).powi(2);
+                let target = lagoon_floor + (heights[i] - lagoon_floor) * t;
+                heights[i] = heights[i].min(target);
+            }
+        }
+    }
+
+    /// 生长单块岛屿
+    ///
+    /// 从离 (center_x, center_y) 最近的单元格出发，每次从边界随机取一个单元格扩展，
+    /// 直到收集 `target_cells` 个连通单元格（种子所在连通区域不足时取全部）。
+    /// 岛屿单元格至少抬升到海平面以上，按到种子的图距离从 `height` 线性降到海岸。
+    #[allow(clippy::too_many_arguments)]
+    fn grow_island(
+        &self,
+        heights: &mut [f32],
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+        target_cells: usize,
+        height: f32,
+        center_x: f32,
+        center_y: f32,
+        rng: &mut rand::rngs::StdRng,
+    ) {
+        if cells.is_empty() || target_cells == 0 {
+            return;
+        }
+
+        let start = BlobGenerator::find_nearest_cell(
+            cells,
+            center_x * self.width as f32,
+            center_y * self.height as f32,
+        );
+
+        // 随机边界生长：形状不规则但始终连通
+        let mut depth = vec![u32::MAX; cells.len()];
+        depth[start] = 0;
+        let mut island = Vec::with_capacity(target_cells);
+        let mut frontier = vec![start];
+        while island.len() < target_cells && !frontier.is_empty() {
+            let current = frontier.swap_remove(rng.random_range(0..frontier.len()));
+            island.push(current);
+            for &n in &neighbors[current] {
+                let n = n as usize;
+                if n < cells.len() && depth[n] == u32::MAX {
+                    depth[n] = depth[current] + 1;
+                    frontier.push(n);
+                }
+            }
+        }
+
+        let sea_level = self.sea_level as f32;
+        let coast = sea_level + 1.0;
+        let peak = height.max(coast);
+        let max_depth = island.iter().map(|&i| depth[i]).max().unwrap_or(0).max(1) as f32;
+        for &i in &island {
+            let t = depth[i] as f32 / max_depth;
+            let h = peak + (coast - peak) * t;
+            heights[i] = heights[i].max(h);
+        }
+    }
+
     /// 应用丘陵效果
     fn apply_hill(
         &self,
@@ -365,6 +887,39 @@ impl TemplateExecutor {
         self.apply_mountain(heights, cells, height, center_x, center_y, radius);
     }
 
+    /// 应用限定范围的山丘（经典模式）
+    ///
+    /// 与山丘相同的距离衰减，半径取边界框较短边的一半，
+    /// 边界框（归一化坐标）以外的单元格不受影响。
+    fn apply_hill_bounded(
+        &self,
+        heights: &mut [f32],
+        cells: &[Pos2],
+        height: f32,
+        center_x: f32,
+        center_y: f32,
+        bounds: (f32, f32, f32, f32),
+    ) {
+        let (min_x, max_x, min_y, max_y) = bounds;
+        let (w, h) = (self.width as f32, self.height as f32);
+        let center = Pos2::new(center_x * w, center_y * h);
+        let radius_pixels = ((max_x - min_x) * w).min((max_y - min_y) * h) * 0.5;
+        if radius_pixels <= 0.0 {
+            return;
+        }
+
+        for (i, pos) in cells.iter().enumerate() {
+            let (nx, ny) = (pos.x / w, pos.y / h);
+            if nx < min_x || nx > max_x || ny < min_y || ny > max_y {
+                continue;
+            }
+            let dist = pos.distance(center);
+            if dist < radius_pixels {
+                heights[i] += height * (1.0 - (dist / radius_pixels).powi(2));
+            }
+        }
+    }
+
     /// 应用坑洞效果
     fn apply_pit(
         &self,
@@ -460,13 +1015,48 @@ impl TemplateExecutor {
     }
 
     /// 应用遮罩效果
-    fn apply_mask(&self, heights: &mut [f32], cells: &[Pos2], mode: MaskMode, strength: f32) {
-        let center = Pos2::new(self.width as f32 / 2.0, self.height as f32 / 2.0);
-        let max_dist = (self.width as f32 / 2.0).hypot(self.height as f32 / 2.0);
+    ///
+    /// 设 d 为归一化的中心距离（0 = 中心，1 = 边缘）：
+    /// - EdgeFade: `1 - strength * d`，从中心到边缘线性降低
+    /// - CenterBoost: `1 + strength * (1 - 2d)`，中心升高、边缘降低
+    /// - RadialGradient: `1 + strength * (lerp(inner, outer, d) - 1)`，在整个半径上
+    ///   线性插值；strength 为负时反转（中心凹陷、边缘抬升）
+    ///
+    /// 指定 `center` 或 `radius` 时归一化距离改为到该圆心的距离除以半径（环绕拓扑下
+    /// X 方向取最短距离），都为 None 时按拓扑计算到地图中心的边缘距离。
+    fn apply_mask(
+        &self,
+        heights: &mut [f32],
+        cells: &[Pos2],
+        mode: MaskMode,
+        strength: f32,
+        center: Option<(f32, f32)>,
+        radius: Option<f32>,
+    ) {
+        let (width, height) = (self.width as f32, self.height as f32);
+        let custom = (center.is_some() || radius.is_some()).then(|| {
+            let (cx, cy) = center.unwrap_or((0.5, 0.5));
+            let radius_pixels = match radius {
+                Some(r) => r * width.max(height),
+                None => (width / 2.0).hypot(height / 2.0),
+            };
+            (
+                Pos2::new(cx * width, cy * height),
+                radius_pixels.max(f32::EPSILON),
+            )
+        });
 
         for (i, pos) in cells.iter().enumerate() {
-            let dist = pos.distance(center);
-            let normalized_dist = (dist / max_dist).clamp(0.0, 1.0);
+            let normalized_dist = match custom {
+                Some((center, radius_pixels)) => {
+                    let mut dx = (pos.x - center.x).abs();
+                    if self.topology.wraps_x() {
+                        dx = dx.min(width - dx);
+                    }
+                    (dx.hypot(pos.y - center.y) / radius_pixels).clamp(0.0, 1.0)
+                }
+                None => self.topology.mask_distance(*pos, width, height),
+            };
 
             let factor = match mode {
                 MaskMode::EdgeFade => {
@@ -477,9 +1067,10 @@ impl TemplateExecutor {
                     // 中心升高，边缘降低
                     1.0 + (1.0 - normalized_dist) * strength - normalized_dist * strength
                 }
-                MaskMode::RadialGradient => {
-                    // 径向渐变
-                    1.0 - normalized_dist * strength
+                MaskMode::RadialGradient { inner, outer } => {
+                    // 从中心到边缘线性插值
+                    let multiplier = inner + (outer - inner) * normalized_dist;
+                    1.0 + (multiplier - 1.0) * strength
                 }
             };
 
@@ -487,6 +1078,114 @@ impl TemplateExecutor {
         }
     }
 
+    /// 线性梯度
+    ///
+    /// 把归一化坐标（以地图中心为原点）投影到方向向量上，再缩放到 [-1, 1]，
+    /// 使低侧角点降低 `strength / 2`、高侧角点升高 `strength / 2`。
+    fn apply_gradient(&self, heights: &mut [f32], cells: &[Pos2], angle: f32, strength: f32) {
+        let (dx, dy) = (angle.cos(), angle.sin());
+        // 投影在单位正方形上的最大值
+        let extent = ((dx.abs() + dy.abs()) * 0.5).max(f32::EPSILON);
+
+        for (h, pos) in heights.iter_mut().zip(cells) {
+            let nx = pos.x / self.width as f32 - 0.5;
+            let ny = pos.y / self.height as f32 - 0.5;
+            let t = ((nx * dx + ny * dy) / extent).clamp(-1.0, 1.0);
+            *h += t * strength * 0.5;
+        }
+    }
+
+    /// 从离 (`start_x`, `start_y`) 最近的单元格出发填充盆地
+    ///
+    /// 沿邻接关系扩散到所有连通且低于 `level` 的单元格并设为 `level`；
+    /// 起点本身不低于 `level` 时不做任何修改。
+    fn flood_fill(
+        &self,
+        heights: &mut [f32],
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+        start_x: f32,
+        start_y: f32,
+        level: f32,
+    ) {
+        if cells.is_empty() {
+            return;
+        }
+
+        let start = BlobGenerator::find_nearest_cell(
+            cells,
+            start_x * self.width as f32,
+            start_y * self.height as f32,
+        );
+        if heights[start] >= level {
+            return;
+        }
+
+        heights[start] = level;
+        let mut stack = vec![start];
+        while let Some(i) = stack.pop() {
+            for &n in &neighbors[i] {
+                let n = n as usize;
+                if heights[n] < level {
+                    heights[n] = level;
+                    stack.push(n);
+                }
+            }
+        }
+    }
+
+    /// 扭曲高度图
+    ///
+    /// 每个单元格沿两个独立噪声场给出的偏移（最大 `strength` 地图单位）移动取样位置，
+    /// 再取离该位置最近的单元格在扭曲前的高度。环绕拓扑下偏移后的 X 坐标回绕，
+    /// 其余越界坐标夹到地图范围内。
+    fn apply_warp(
+        &self,
+        heights: &mut [f32],
+        cells: &[Pos2],
+        strength: f32,
+        frequency: f32,
+        seed: u32,
+    ) {
+        if cells.is_empty() || strength == 0.0 {
+            return;
+        }
+
+        let (width, height) = (self.width as f32, self.height as f32);
+        let config = NoiseConfig {
+            octaves: 3,
+            base_frequency: frequency as f64,
+            persistence: 0.5,
+            lacunarity: 2.0,
+            seed,
+        };
+        let gen_x = NoiseGenerator::new(seed).with_topology(self.topology, width, height);
+        let gen_y =
+            NoiseGenerator::new(seed.wrapping_add(1)).with_topology(self.topology, width, height);
+
+        let bounds = Rect::from_min_size(Pos2::ZERO, Vec2::new(width, height));
+        let index = GridIndex::build_auto(cells, bounds);
+        let original = heights.to_vec();
+
+        for (i, pos) in cells.iter().enumerate() {
+            let offset = Vec2::new(
+                gen_x.fbm_at(*pos, &config) as f32,
+                gen_y.fbm_at(*pos, &config) as f32,
+            ) * strength;
+            let mut target = *pos + offset;
+            target.x = if self.topology.wraps_x() {
+                target.x.rem_euclid(width)
+            } else {
+                target.x.clamp(0.0, width)
+            };
+            target.y = target.y.clamp(0.0, height);
+
+            if let Some(source) = index.find_nearest(cells, target) {
+                heights[i] = original[source as usize];
+            }
+        }
+    }
+
     /// 反转高度图
     fn invert_heights(&self, heights: &mut [f32], cells: &[Pos2], axis: InvertAxis) {
         let center_x = self.width as f32 / 2.0;
@@ -517,9 +1216,13 @@ impl TemplateExecutor {
         }
     }
 
-    /// 侵蚀地形（简化水蚀模型）
-    /// 核心思想：陡坡区域更易被侵蚀，低坡区域更容易沉积。
-    fn erode_heights(
+    /// 水力侵蚀
+    ///
+    /// 每轮迭代在每个单元格降下 `rain` 单位的水，按高度从高到低处理：
+    /// 水流向最低的更低邻居，携沙能力 = 水量 × 坡度 × `capacity`。
+    /// 未达到携沙能力时从当前单元格剥蚀（最多半个坡度），超出时按
+    /// `deposition` 比例沉积多余泥沙；流入洼地的泥沙全部沉积。
+    fn apply_erosion(
         &self,
         heights: &mut [f32],
         neighbors: &[Vec<u32>],
@@ -535,62 +1238,232 @@ impl TemplateExecutor {
         let rain = rain.clamp(0.0, 1.0);
         let capacity = capacity.max(0.0);
         let deposition = deposition.clamp(0.0, 1.0);
+        let mut order: Vec<usize> = (0..heights.len()).collect();
 
         for _ in 0..iterations {
-            let original = heights.to_vec();
-            let mut delta = vec![0.0f32; heights.len()];
+            let mut water = vec![rain; heights.len()];
+            let mut sediment = vec![0.0f32; heights.len()];
+            order.sort_by(|&a, &b| heights[b].total_cmp(&heights[a]));
 
-            for (i, ns) in neighbors.iter().enumerate() {
-                if ns.is_empty() {
+            for &i in &order {
+                let lowest = neighbors[i]
+                    .iter()
+                    .map(|&n| n as usize)
+                    .filter(|&n| n < heights.len())
+                    .min_by(|&a, &b| heights[a].total_cmp(&heights[b]));
+                let Some(target) = lowest.filter(|&n| heights[n] < heights[i]) else {
+                    // 洼地：泥沙全部沉积
+                    heights[i] += sediment[i];
                     continue;
+                };
+
+                let slope = heights[i] - heights[target];
+                let carry = water[i] * slope * capacity;
+                if sediment[i] < carry {
+                    let eroded = (carry - sediment[i]).min(slope * 0.5).min(heights[i]);
+                    heights[i] -= eroded;
+                    sediment[i] += eroded;
+                } else {
+                    let dropped = (sediment[i] - carry) * deposition;
+                    heights[i] += dropped;
+                    sediment[i] -= dropped;
                 }
 
-                let h = original[i];
-                let mut lower_neighbors = Vec::new();
-                let mut total_slope = 0.0f32;
+                water[target] += water[i];
+                sediment[target] += sediment[i];
+            }
+        }
+    }
+
+    /// 生成河流
+    ///
+    /// 从高度不低于 `source_height` 的单元格中随机选取源头（没有足够高的
+    /// 单元格时退而选取最高的单元格），依次调用 [`Self::carve_river`]。
+    fn apply_rivers(
+        &self,
+        heights: &mut [f32],
+        neighbors: &[Vec<u32>],
+        count: u32,
+        source_height: f32,
+        meander: f32,
+        rng: &mut rand::rngs::StdRng,
+    ) {
+        if heights.is_empty() {
+            return;
+        }
+
+        let mut sources: Vec<usize> = (0..heights.len())
+            .filter(|&i| heights[i] >= source_height)
+            .collect();
+        if sources.is_empty() {
+            let highest = (0..heights.len())
+                .max_by(|&a, &b| heights[a].total_cmp(&heights[b]))
+                .unwrap_or(0);
+            sources.push(highest);
+        }
+
+        for _ in 0..count {
+            let source = sources[rng.random_range(0..sources.len())];
+            self.carve_river(heights, neighbors, source, meander, rng);
+        }
+    }
+
+    /// 从源头下切一条河流，返回河道经过的单元格（从源头到河口）
+    ///
+    /// 每一步在更低的邻居中选择落差最大者，`meander` 对落差施加随机扰动，
+    /// 使河道偏离严格的最陡路径。到达海平面以下或无处可流时停止。
+    /// 沿途单元格下切 `RIVER_CARVE_DEPTH`，并保证河道高度单调不增。
+    pub fn carve_river(
+        &self,
+        heights: &mut [f32],
+        neighbors: &[Vec<u32>],
+        source: usize,
+        meander: f32,
+        rng: &mut rand::rngs::StdRng,
+    ) -> Vec<usize> {
+        const RIVER_CARVE_DEPTH: f32 = 2.0;
+
+        let sea_level = self.sea_level as f32;
+        let meander = meander.clamp(0.0, 1.0);
+        let mut path = vec![source];
+        let mut visited = std::collections::HashSet::from([source]);
+        let mut current = source;
+
+        while heights[current] >= sea_level && path.len() < heights.len() {
+            let next = neighbors[current]
+                .iter()
+                .map(|&n| n as usize)
+                .filter(|&n| n < heights.len() && heights[n] < heights[current])
+                .filter(|n| !visited.contains(n))
+                .map(|n| {
+                    let drop = heights[current] - heights[n];
+                    (n, drop * (1.0 + meander * rng.random_range(-1.0..1.0)))
+                })
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            let Some((next, _)) = next else {
+                break;
+            };
+            visited.insert(next);
+            path.push(next);
+            current = next;
+        }
+
+        // 下切河道，保持从源头到河口单调不增
+        let mut upstream = f32::INFINITY;
+        for &cell in &path {
+            let carved = (heights[cell] - RIVER_CARVE_DEPTH).min(upstream).max(0.0);
+            heights[cell] = carved;
+            upstream = carved;
+        }
+
+        path
+    }
+
+    /// 风蚀
+    ///
+    /// 每个单元格找到最接近上风向和下风向的邻居。高于上风邻居的部分视为
+    /// 迎风坡，按强度削去一部分并搬运到下风邻居，使地形沿风向拉长。
+    fn wind_erode_heights(
+        &self,
+        heights: &mut [f32],
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+        direction: f32,
+        strength: f32,
+        iterations: u32,
+    ) {
+        if heights.is_empty() || iterations == 0 {
+            return;
+        }
+
+        let strength = strength.clamp(0.0, 1.0);
+        let wind = eframe::egui::vec2(direction.cos(), direction.sin());
+
+        for _ in 0..iterations {
+            let original = heights.to_vec();
+            let mut delta = vec![0.0f32; heights.len()];
+
+            for (i, ns) in neighbors.iter().enumerate() {
+                // (邻居索引, 与风向夹角的余弦)
+                let mut upwind: Option<(usize, f32)> = None;
+                let mut downwind: Option<(usize, f32)> = None;
 
                 for &n in ns {
                     let ni = n as usize;
                     if ni >= original.len() {
                         continue;
                     }
-                    let slope = h - original[ni];
-                    if slope > 0.0 {
-                        lower_neighbors.push((ni, slope));
-                        total_slope += slope;
+                    let offset = cells[ni] - cells[i];
+                    let len = offset.length();
+                    if len <= f32::EPSILON {
+                        continue;
+                    }
+                    let alignment = offset.dot(wind) / len;
+                    if alignment < 0.0 && upwind.is_none_or(|(_, a)| alignment < a) {
+                        upwind = Some((ni, alignment));
+                    }
+                    if alignment > 0.0 && downwind.is_none_or(|(_, a)| alignment > a) {
+                        downwind = Some((ni, alignment));
                     }
                 }
 
-                if lower_neighbors.is_empty() {
+                let (Some((up, up_alignment)), Some((down, _))) = (upwind, downwind) else {
                     continue;
-                }
+                };
 
-                let potential = total_slope * rain * capacity;
-                let removed = potential.min(h.max(0.0));
-                if removed <= 0.0 {
+                let exposure = original[i] - original[up];
+                if exposure <= 0.0 {
                     continue;
                 }
 
+                // 只削去一半暴露高度以内，避免迎风坡翻转
+                let removed = exposure * 0.5 * strength * -up_alignment;
                 delta[i] -= removed;
-
-                for (ni, slope) in lower_neighbors {
-                    let weight = if total_slope > 0.0 {
-                        slope / total_slope
-                    } else {
-                        0.0
-                    };
-                    let transported = removed * weight;
-                    let deposit = transported * deposition;
-                    delta[ni] += deposit;
-                }
+                delta[down] += removed;
             }
 
             for (h, d) in heights.iter_mut().zip(delta.iter()) {
-                *h = (*h + *d).max(0.0);
+                *h += *d;
             }
         }
     }
 
+    /// 减去模板印
+    ///
+    /// 使用相同的种子和模式生成模板，把结果线性映射到当前高度的
+    /// 最小值-最大值范围后，乘以权重从当前高度中减去。
+    fn subtract_template(
+        &self,
+        heights: &mut [f32],
+        cells: &[Pos2],
+        neighbors: &[Vec<u32>],
+        stencil: &TerrainTemplate,
+        weight: f32,
+        ctx: &mut SubtractContext,
+    ) {
+        if heights.is_empty() {
+            return;
+        }
+
+        let stencil_heights = self.run(stencil, cells, neighbors, ctx);
+
+        let range = |values: &[f32]| {
+            let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+            let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            (min, max)
+        };
+        let (s_min, s_max) = range(&stencil_heights);
+        let (h_min, h_max) = range(heights);
+        if s_max - s_min < 0.001 {
+            return;
+        }
+
+        for (h, s) in heights.iter_mut().zip(stencil_heights) {
+            let scaled = (s - s_min) / (s_max - s_min) * (h_max - h_min);
+            *h -= scaled * weight;
+        }
+    }
+
     /// 平滑高度
     fn smooth_heights(&self, heights: &mut [f32], neighbors: &[Vec<u32>], iterations: u32) {
         for _ in 0..iterations {
@@ -623,6 +1496,92 @@ impl TemplateExecutor {
         }
     }
 
+    /// 海岸平滑
+    /// 从海陆边界两侧的单元格出发做 BFS，只对图距离不超过 band 的单元格做局部平均
+    fn smooth_coast(
+        &self,
+        heights: &mut [f32],
+        neighbors: &[Vec<u32>],
+        iterations: u32,
+        band: u32,
+    ) {
+        use std::collections::VecDeque;
+
+        if band == 0 || heights.is_empty() {
+            return;
+        }
+
+        let sea_level = self.sea_level as f32;
+        let is_land = |h: f32| h >= sea_level;
+
+        // 边界单元格距离为 1，向外逐层扩展到 band
+        let mut distance = vec![u32::MAX; heights.len()];
+        let mut queue = VecDeque::new();
+        for i in 0..heights.len() {
+            let on_boundary = neighbors[i].iter().any(|&n| {
+                let n = n as usize;
+                n < heights.len() && is_land(heights[n]) != is_land(heights[i])
+            });
+            if on_boundary {
+                distance[i] = 1;
+                queue.push_back(i);
+            }
+        }
+        while let Some(i) = queue.pop_front() {
+            if distance[i] >= band {
+                continue;
+            }
+            for &n in &neighbors[i] {
+                let n = n as usize;
+                if n < heights.len() && distance[n] == u32::MAX {
+                    distance[n] = distance[i] + 1;
+                    queue.push_back(n);
+                }
+            }
+        }
+
+        let coastal: Vec<usize> = (0..heights.len())
+            .filter(|&i| distance[i] <= band)
+            .collect();
+
+        for _ in 0..iterations {
+            let original = heights.to_vec();
+
+            for &i in &coastal {
+                let valid_neighbors: Vec<f32> = neighbors[i]
+                    .iter()
+                    .filter_map(|&n| original.get(n as usize).copied())
+                    .collect();
+                if valid_neighbors.is_empty() {
+                    continue;
+                }
+                let neighbor_avg: f32 =
+                    valid_neighbors.iter().sum::<f32>() / valid_neighbors.len() as f32;
+
+                heights[i] = original[i] * 0.5 + neighbor_avg * 0.5;
+            }
+        }
+    }
+
+    /// 阶梯化陆地高度
+    /// 台阶高度为 `sea_level + k * (255 - sea_level) / steps`（k = 0..=steps），
+    /// 每个陆地单元格按 strength 向最近的台阶插值
+    fn apply_terrace(&self, heights: &mut [f32], steps: u32, strength: f32) {
+        if steps == 0 {
+            return;
+        }
+
+        let sea_level = self.sea_level as f32;
+        let step = (255.0 - sea_level) / steps as f32;
+        let strength = strength.clamp(0.0, 1.0);
+
+        for h in heights.iter_mut().filter(|h| **h >= sea_level) {
+            let k = ((*h - sea_level) / step).round().min(steps as f32);
+            let level = sea_level + k * step;
+            *h = *h * (1.0 - strength) + level * strength;
+        }
+    }
+
     /// 归一化高度
     fn normalize_heights(&self, heights: &mut [f32]) {
         if heights.is_empty() {
@@ -655,9 +1614,9 @@ impl TemplateExecutor {
         height: f32,
         center_x: f32,
         center_y: f32,
+        blob_config: BlobConfig,
         rng: &mut rand::rngs::StdRng,
     ) {
-        let blob_config = BlobConfig::from_cell_count(cells.len());
         let blob_gen = BlobGenerator::new(blob_config);
 
         let x = center_x * self.width as f32;
@@ -710,9 +1669,9 @@ impl TemplateExecutor {
         depth: f32,
         center_x: f32,
         center_y: f32,
+        blob_config: BlobConfig,
         rng: &mut rand::rngs::StdRng,
     ) {
-        let blob_config = BlobConfig::from_cell_count(cells.len());
         let blob_gen = BlobGenerator::new(blob_config);
 
         let x = center_x * self.width as f32;
@@ -752,7 +1711,16 @@ impl TemplateExecutor {
         let start_idx = BlobGenerator::find_nearest_cell(cells, start_x, start_y);
         let end_idx = BlobGenerator::find_nearest_cell(cells, end_x, end_y);
 
-        blob_gen.add_range(heights, cells, neighbors, start_idx, end_idx, height, rng);
+        blob_gen.add_range(
+            heights,
+            cells,
+            neighbors,
+            start_idx,
+            end_idx,
+            height,
+            Some(angle),
+            rng,
+        );
     }
 
     /// BFS 扩散式海沟
@@ -785,6 +1753,15 @@ impl TemplateExecutor {
         let start_idx = BlobGenerator::find_nearest_cell(cells, start_x, start_y);
         let end_idx = BlobGenerator::find_nearest_cell(cells, end_x, end_y);
 
-        blob_gen.add_trough(heights, cells, neighbors, start_idx, end_idx, depth, rng);
+        blob_gen.add_trough(
+            heights,
+            cells,
+            neighbors,
+            start_idx,
+            end_idx,
+            depth,
+            Some(angle),
+            rng,
+        );
     }
 }
@@ -7,10 +7,12 @@ mod tests {
     use crate::terrain::dsl::{load_template_from_file, load_templates_from_dir, parse_template};
     use crate::terrain::heightmap::SEA_LEVEL;
     use crate::terrain::plate::TectonicConfig;
-    use crate::terrain::template::{TerrainCommand, TerrainTemplate};
-    use crate::terrain::template_executor::{GenerationMode, TemplateExecutor};
+    use crate::terrain::template::{
+        CurveKind, MaskMode, SmoothTarget, TerrainCommand, TerrainTemplate,
+    };
+    use crate::terrain::template_executor::{GenerationMode, TemplateExecError, TemplateExecutor};
     use crate::terrain::{TerrainConfig, TerrainGenerator};
-    use eframe::egui::Pos2;
+    use eframe::egui::{Pos2, Vec2};
     use std::path::Path;
 
     // ============================================================================
@@ -141,6 +143,7 @@ mod tests {
                 x,
                 y,
                 radius,
+                ..
             } => {
                 assert!(
                     *count > 0,
@@ -235,6 +238,8 @@ mod tests {
                 x: _,
                 y: _,
                 radius: _,
+                blob_power: _,
+                jitter: _,
             } => {
                 assert!(
                     *count > 0,
@@ -336,7 +341,7 @@ mod tests {
             if row > 0 {
                 cell_neighbors.push(i as u32 - cols);
             }
-            if row < rows - 1 && i + cols as usize <= cell_count {
+            if row < rows - 1 && i + (cols as usize) < cell_count {
                 cell_neighbors.push(i as u32 + cols);
             }
             neighbors.push(cell_neighbors);
@@ -474,6 +479,1014 @@ mod tests {
         );
     }
 
+    // ============================================================================
+    // 散布命令测试
+    // ============================================================================
+
+    /// 只影响单个单元格的微小山峰，便于统计散布数量
+    fn scatter_template(density: f32, mask: Option<SmoothTarget>) -> TerrainTemplate {
+        TerrainTemplate::new("Scatter", "Scatter test").with_command(TerrainCommand::Scatter {
+            feature: Box::new(TerrainCommand::Mountain {
+                height: 50.0,
+                x: 0.5,
+                y: 0.5,
+                radius: 0.001,
+            }),
+            density,
+            mask,
+        })
+    }
+
+    #[test]
+    fn test_scatter_density_and_determinism() {
+        let width = 128;
+        let height = 128;
+        let cell_count = 2500;
+        let (cells, neighbors) = create_test_grid(width, height, cell_count);
+        let density = 0.1;
+        let template = scatter_template(density, None);
+
+        let heights =
+            TemplateExecutor::new(width, height, 7).execute(&template, &cells, &neighbors);
+        let placed = heights.iter().filter(|h| **h > 0.0).count() as f32;
+
+        // 二项分布：期望 250，标准差 15，允许 4 个标准差的误差
+        let expected = density * cell_count as f32;
+        let sigma = (expected * (1.0 - density)).sqrt();
+        assert!(
+            (placed - expected).abs() < 4.0 * sigma,
+            "Scatter placed {} features, expected about {}",
+            placed,
+            expected
+        );
+
+        let again = TemplateExecutor::new(width, height, 7).execute(&template, &cells, &neighbors);
+        assert_eq!(heights, again, "Scatter should be seed-deterministic");
+
+        let other = TemplateExecutor::new(width, height, 8).execute(&template, &cells, &neighbors);
+        assert_ne!(heights, other);
+    }
+
+    #[test]
+    fn test_scatter_respects_mask() {
+        let width = 64;
+        let height = 64;
+        let cell_count = 400;
+        let (cells, neighbors) = create_test_grid(width, height, cell_count);
+
+        // 初始高度全为 0，即全部是海洋
+        let land_only = scatter_template(0.5, Some(SmoothTarget::Land));
+        let heights =
+            TemplateExecutor::new(width, height, 1).execute(&land_only, &cells, &neighbors);
+        assert!(heights.iter().all(|h| *h == 0.0));
+
+        let ocean_only = scatter_template(0.5, Some(SmoothTarget::Ocean));
+        let heights =
+            TemplateExecutor::new(width, height, 1).execute(&ocean_only, &cells, &neighbors);
+        assert!(heights.iter().any(|h| *h > 0.0));
+    }
+
+    #[test]
+    fn test_remap_gamma_lowers_midlands() {
+        let width = 64;
+        let height = 64;
+        let cell_count = 400;
+        let (cells, neighbors) = create_test_grid(width, height, cell_count);
+
+        let base = TerrainTemplate::new("Base", "Base")
+            .with_command(TerrainCommand::Mountain {
+                height: 200.0,
+                x: 0.5,
+                y: 0.5,
+                radius: 0.6,
+            })
+            .with_command(TerrainCommand::Normalize);
+        let remapped = base.clone().with_command(TerrainCommand::RemapCurve {
+            kind: CurveKind::Gamma(2.0),
+        });
+
+        let before = TemplateExecutor::new(width, height, 1).execute(&base, &cells, &neighbors);
+        let after = TemplateExecutor::new(width, height, 1).execute(&remapped, &cells, &neighbors);
+
+        let max = before.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        for (b, a) in before.iter().zip(after.iter()) {
+            if *b <= 0.001 {
+                // 最低点保持不变
+                assert!(a.abs() < 0.001);
+            } else if (*b - max).abs() < 0.001 {
+                // 最高点保持不变
+                assert!((a - max).abs() < 0.01);
+            } else {
+                // 中段被压低
+                assert!(a < b, "gamma > 1 should lower {} but got {}", b, a);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mesa_flat_top_and_steep_walls() {
+        let width = 64;
+        let height = 64;
+        let cell_count = 900;
+        let (cells, neighbors) = create_test_grid(width, height, cell_count);
+
+        let template = TerrainTemplate::new("Mesa", "Mesa").with_command(TerrainCommand::Mesa {
+            height: 100.0,
+            x: 0.5,
+            y: 0.5,
+            radius: 0.3,
+            top_fraction: 0.5,
+        });
+        let heights =
+            TemplateExecutor::new(width, height, 1).execute(&template, &cells, &neighbors);
+
+        let center = Pos2::new(32.0, 32.0);
+        let radius = 0.3 * 64.0;
+        let top = radius * 0.5;
+
+        let mut top_count = 0;
+        for (pos, h) in cells.iter().zip(&heights) {
+            let dist = pos.distance(center);
+            if dist <= top {
+                // 平顶高度一致
+                assert!((h - 100.0).abs() < 1e-3, "top cell height {}", h);
+                top_count += 1;
+            } else if dist >= radius {
+                assert_eq!(*h, 0.0);
+            }
+        }
+        assert!(top_count > 0);
+
+        // 陡壁：刚离开平顶边缘仍然很高，接近底部边缘时迅速下降
+        let wall = radius - top;
+        let near_rim: Vec<f32> = cells
+            .iter()
+            .zip(&heights)
+            .filter(|(p, _)| {
+                let d = p.distance(center);
+                d > top && d < top + wall * 0.2
+            })
+            .map(|(_, h)| *h)
+            .collect();
+        let near_base: Vec<f32> = cells
+            .iter()
+            .zip(&heights)
+            .filter(|(p, _)| {
+                let d = p.distance(center);
+                d > radius - wall * 0.2 && d < radius
+            })
+            .map(|(_, h)| *h)
+            .collect();
+        assert!(!near_rim.is_empty() && !near_base.is_empty());
+        assert!(near_rim.iter().all(|h| *h > 85.0));
+        assert!(near_base.iter().all(|h| *h < 15.0));
+    }
+
+    #[test]
+    fn test_wind_erosion_elongates_downwind() {
+        let width = 64;
+        let height = 64;
+        let cell_count = 1024;
+        let (cells, neighbors) = create_test_grid(width, height, cell_count);
+
+        let hill = TerrainTemplate::new("Hill", "Hill").with_command(TerrainCommand::Mountain {
+            height: 100.0,
+            x: 0.5,
+            y: 0.5,
+            radius: 0.2,
+        });
+        let eroded = hill.clone().with_command(TerrainCommand::WindErosion {
+            direction: 0.0, // 吹向 +X
+            strength: 0.5,
+            iterations: 10,
+        });
+
+        let before = TemplateExecutor::new(width, height, 1).execute(&hill, &cells, &neighbors);
+        let after = TemplateExecutor::new(width, height, 1).execute(&eroded, &cells, &neighbors);
+
+        let centroid_x = |heights: &[f32]| {
+            let total: f32 = heights.iter().sum();
+            cells.iter().zip(heights).map(|(p, h)| p.x * h).sum::<f32>() / total
+        };
+        // 质心沿风向移动，且总量守恒
+        assert!(centroid_x(&after) > centroid_x(&before) + 0.5);
+        let sum_before: f32 = before.iter().sum();
+        let sum_after: f32 = after.iter().sum();
+        assert!((sum_before - sum_after).abs() < sum_before * 1e-3);
+
+        // 高于阈值的区域在下风侧延伸得比上风侧更远
+        let extent = |heights: &[f32]| {
+            let xs: Vec<f32> = cells
+                .iter()
+                .zip(heights)
+                .filter(|(p, h)| **h > 5.0 && (p.y - 32.0).abs() < 2.0)
+                .map(|(p, _)| p.x - 32.0)
+                .collect();
+            let upwind = xs.iter().copied().fold(0.0f32, f32::min).abs();
+            let downwind = xs.iter().copied().fold(0.0f32, f32::max);
+            (upwind, downwind)
+        };
+        let (up_before, down_before) = extent(&before);
+        let (up_after, down_after) = extent(&after);
+        assert!(
+            (up_before - down_before).abs() <= 2.0,
+            "hill should start symmetric"
+        );
+        assert!(down_after > up_after, "{} vs {}", down_after, up_after);
+    }
+
+    #[test]
+    fn test_smooth_coast_keeps_inland_peak() {
+        let width = 64;
+        let height = 64;
+        let cell_count = 1024;
+        let (cells, neighbors) = create_test_grid(width, height, cell_count);
+
+        // 西侧低、东侧高的坡面，海岸附近散布小山使海岸线参差，东部内陆一座高峰
+        let base = "Add 15\nGradient 0 30\nHill 25 5-15 10-30 0-100\nMountain 200 80 50 10";
+        let run = |text: &str| {
+            let template = parse_template("Coast", "SmoothCoast", text).unwrap();
+            TemplateExecutor::with_mode(width, height, 7, GenerationMode::Classic)
+                .execute(&template, &cells, &neighbors)
+        };
+        let before = run(base);
+        let after = run(&format!("{}\nSmoothCoast 2 3", base));
+
+        // 内陆高峰完全不变
+        let peak = (0..cell_count)
+            .max_by(|&a, &b| before[a].total_cmp(&before[b]))
+            .unwrap();
+        assert!(before[peak] > 150.0);
+        assert_eq!(before[peak], after[peak]);
+
+        // 只有海岸带内的单元格被修改
+        let changed = (0..cell_count).filter(|&i| before[i] != after[i]).count();
+        assert!(
+            changed > 0 && changed < cell_count / 2,
+            "changed {}",
+            changed
+        );
+
+        // 海岸附近更平滑：相邻单元格高度差总和下降
+        let roughness = |h: &[f32]| -> f32 {
+            (0..cell_count)
+                .flat_map(|i| neighbors[i].iter().map(move |&n| (i, n as usize)))
+                .map(|(i, n)| (h[i] - h[n]).abs())
+                .sum()
+        };
+        assert!(
+            roughness(&after) < roughness(&before),
+            "{} vs {}",
+            roughness(&after),
+            roughness(&before)
+        );
+    }
+
+    #[test]
+    fn test_terrace_snaps_land_to_steps() {
+        let width = 64;
+        let height = 64;
+        let cell_count = 1024;
+        let (cells, neighbors) = create_test_grid(width, height, cell_count);
+
+        let template =
+            parse_template("Steps", "Terrace", "Add 10\nGradient 0 200\nTerrace 6 1.0").unwrap();
+        let heights =
+            TemplateExecutor::new(width, height, 1).execute(&template, &cells, &neighbors);
+
+        let sea_level = SEA_LEVEL as f32;
+        let step = (255.0 - sea_level) / 6.0;
+        let levels: Vec<f32> = (0..=6).map(|k| sea_level + k as f32 * step).collect();
+
+        let land: Vec<f32> = heights
+            .iter()
+            .copied()
+            .filter(|&h| h >= sea_level)
+            .collect();
+        assert!(land.len() > cell_count / 4);
+        for h in &land {
+            assert!(
+                levels.iter().any(|l| (h - l).abs() < 1e-4),
+                "{} not on a step",
+                h
+            );
+        }
+        // 坡面跨越多个台阶
+        let distinct = levels
+            .iter()
+            .filter(|l| land.iter().any(|h| (h - *l).abs() < 1e-4))
+            .count();
+        assert!(distinct >= 3, "{}", distinct);
+    }
+
+    #[test]
+    fn test_hill_blob_power_override_spreads_further() {
+        let width = 64;
+        let height = 64;
+        let cell_count = 1024;
+        let (cells, neighbors) = create_test_grid(width, height, cell_count);
+
+        let raised = |text: &str| {
+            let template = parse_template("Blob", "Override", text).unwrap();
+            TemplateExecutor::with_mode(width, height, 9, GenerationMode::BfsBlob)
+                .execute(&template, &cells, &neighbors)
+                .iter()
+                .filter(|&&h| h > 1.0)
+                .count()
+        };
+
+        // 1024 单元格默认 blob_power 为 0.95
+        let default = raised("Hill 1 80 50 50");
+        let wide = raised("Hill 1 80 50 50 10 0.99 0.0");
+        assert!(wide > default, "{} vs {}", wide, default);
+    }
+
+    #[test]
+    fn test_flood_fill_flattens_pit_to_shoreline() {
+        let width = 100;
+        let height = 100;
+        let (cells, neighbors) = create_test_grid(width, height, 2500);
+
+        // 中心的抛物面坑：h = 100 - 80·(1 - (d/R)²)，R = 30；右下角另有一个不相连的坑
+        let template = parse_template(
+            "Basin",
+            "Flooded pit",
+            "Add 100\nMountain -80 50 50 30\nMountain -80 90 90 8\nFloodFill 50 50 60",
+        )
+        .unwrap();
+        let heights =
+            TemplateExecutor::new(width, height, 1).execute(&template, &cells, &neighbors);
+
+        // 水位 60 对应的岸线在 d = R/√2 处
+        let center = Pos2::new(50.0, 50.0);
+        let shoreline = 30.0 / 2f32.sqrt();
+        for (p, &h) in cells.iter().zip(&heights) {
+            let d = p.distance(center);
+            if d < shoreline - 0.5 {
+                assert_eq!(h, 60.0, "cell at distance {} not flat: {}", d, h);
+            } else if d > shoreline + 0.5 && d < 40.0 {
+                assert!(h > 60.0, "cell at distance {} changed: {}", d, h);
+            }
+        }
+
+        // 不相连的坑不会被填充
+        let other = cells
+            .iter()
+            .position(|p| p.distance(Pos2::new(90.0, 90.0)) < 1.5)
+            .unwrap();
+        assert!(heights[other] < 60.0, "{}", heights[other]);
+    }
+
+    #[test]
+    fn test_warp_breaks_rotational_symmetry() {
+        let width = 100;
+        let height = 100;
+        let (cells, neighbors) = create_test_grid(width, height, 4900);
+        let center = Pos2::new(50.0, 50.0);
+
+        // 半径 15~35 圆环内按角度分成 16 个扇区，取各扇区平均高度的方差
+        let angular_variance = |heights: &[f32]| -> f32 {
+            let mut sums = [0.0f32; 16];
+            let mut counts = [0u32; 16];
+            for (p, h) in cells.iter().zip(heights) {
+                let d = *p - center;
+                if !(15.0..35.0).contains(&d.length()) {
+                    continue;
+                }
+                let bin = (((d.y.atan2(d.x) + std::f32::consts::PI) / std::f32::consts::TAU * 16.0)
+                    as usize)
+                    .min(15);
+                sums[bin] += h;
+                counts[bin] += 1;
+            }
+            let means: Vec<f32> = sums
+                .iter()
+                .zip(&counts)
+                .map(|(s, &c)| s / c as f32)
+                .collect();
+            let mean = means.iter().sum::<f32>() / means.len() as f32;
+            means.iter().map(|m| (m - mean).powi(2)).sum::<f32>() / means.len() as f32
+        };
+
+        let rings = parse_template("Rings", "Cone", "Mountain 200 50 50 45").unwrap();
+        let executor = TemplateExecutor::new(width, height, 5);
+        let before = executor.execute(&rings, &cells, &neighbors);
+
+        let warped = parse_template(
+            "Rings",
+            "Warped cone",
+            "Mountain 200 50 50 45\nWarp 12 0.03",
+        )
+        .unwrap();
+        let after = executor.execute(&warped, &cells, &neighbors);
+
+        assert!(after.iter().all(|h| (0.0..=200.0).contains(h)));
+        assert!(
+            angular_variance(&after) > angular_variance(&before) * 4.0 + 1.0,
+            "{} vs {}",
+            angular_variance(&after),
+            angular_variance(&before)
+        );
+
+        // DSL 解析出的参数
+        match &warped.commands[1] {
+            TerrainCommand::Warp {
+                strength,
+                frequency,
+            } => {
+                assert_eq!(*strength, 12.0);
+                assert_eq!(*frequency, 0.03);
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_gradient_raises_high_side() {
+        let width = 64;
+        let height = 64;
+        let cell_count = 1024;
+        let (cells, neighbors) = create_test_grid(width, height, cell_count);
+
+        let template = parse_template("Tilt", "Gradient", "Add 50\nGradient 0.785 40").unwrap();
+        let heights =
+            TemplateExecutor::new(width, height, 1).execute(&template, &cells, &neighbors);
+
+        // 方向向右下：右下角一侧高于左上角一侧
+        let side = |high: bool| -> Vec<f32> {
+            cells
+                .iter()
+                .zip(&heights)
+                .filter(|(p, _)| {
+                    let s = p.x + p.y;
+                    if high {
+                        s > 96.0
+                    } else {
+                        s < 32.0
+                    }
+                })
+                .map(|(_, h)| *h)
+                .collect()
+        };
+        let low_max = side(false).into_iter().fold(f32::MIN, f32::max);
+        let high_min = side(true).into_iter().fold(f32::MAX, f32::min);
+        assert!(high_min > low_max, "{} vs {}", high_min, low_max);
+
+        let span = heights.iter().copied().fold(f32::MIN, f32::max)
+            - heights.iter().copied().fold(f32::MAX, f32::min);
+        assert!(span <= 40.0 + 1e-3, "span {}", span);
+    }
+
+    #[test]
+    fn test_off_center_mask_boosts_around_its_center() {
+        let width = 64;
+        let height = 64;
+        let (cells, neighbors) = create_test_grid(width, height, 1024);
+
+        let template = parse_template(
+            "Offset",
+            "Off-center boost",
+            "Add 100\nMask center 0.5 25 25 30",
+        )
+        .unwrap();
+        let heights =
+            TemplateExecutor::new(width, height, 1).execute(&template, &cells, &neighbors);
+
+        let nearest = |x: f32, y: f32| {
+            let target = Pos2::new(x, y);
+            (0..cells.len())
+                .min_by(|&a, &b| {
+                    cells[a]
+                        .distance(target)
+                        .total_cmp(&cells[b].distance(target))
+                })
+                .unwrap()
+        };
+        let at_center = heights[nearest(16.0, 16.0)];
+        let at_middle = heights[nearest(32.0, 32.0)];
+        assert!(at_center > 140.0, "{}", at_center);
+        // 地图中部在遮罩半径之外，被压低而不是抬升
+        assert!(at_middle < 100.0, "{}", at_middle);
+
+        let radial = parse_template("Radial", "Radial", "Mask radial 0.5 30 40 60").unwrap();
+        match radial.commands[0] {
+            TerrainCommand::Mask {
+                mode,
+                strength,
+                center,
+                radius,
+            } => {
+                assert_eq!(mode, MaskMode::radial());
+                assert_eq!(strength, 0.5);
+                assert_eq!(center, Some((0.3, 0.4)));
+                assert_eq!(radius, Some(0.6));
+            }
+            ref other => panic!("unexpected command {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_mask_radial_differs_from_edge_fade() {
+        let width = 64;
+        let height = 64;
+        let cell_count = 1024;
+        let (cells, neighbors) = create_test_grid(width, height, cell_count);
+
+        let masked = |mode: MaskMode, strength: f32| {
+            let template = TerrainTemplate::new("Mask", "Mask")
+                .with_command(TerrainCommand::Add { value: 100.0 })
+                .with_command(TerrainCommand::Mask {
+                    mode,
+                    strength,
+                    center: None,
+                    radius: None,
+                });
+            TemplateExecutor::new(width, height, 1).execute(&template, &cells, &neighbors)
+        };
+        let edge = masked(MaskMode::EdgeFade, 0.5);
+        let radial = masked(
+            MaskMode::RadialGradient {
+                inner: 1.5,
+                outer: 1.0,
+            },
+            1.0,
+        );
+        let inverted = masked(MaskMode::radial(), -0.5);
+
+        // 半径一半处：EdgeFade 线性降低，自定义 inner/outer 的 RadialGradient 仍高于原高度
+        let half_diag = (32.0f32 * 32.0 * 2.0).sqrt();
+        let mid: Vec<usize> = (0..cells.len())
+            .filter(|&i| {
+                let d = cells[i].distance(Pos2::new(32.0, 32.0)) / half_diag;
+                (0.45..0.55).contains(&d)
+            })
+            .collect();
+        assert!(!mid.is_empty());
+        for &i in &mid {
+            assert!(
+                (70.0..80.0).contains(&edge[i]),
+                "edge fade at mid {}",
+                edge[i]
+            );
+            assert!(radial[i] > 120.0, "radial at mid {}", radial[i]);
+            assert!(
+                inverted[i] > 120.0,
+                "inverted radial at mid {}",
+                inverted[i]
+            );
+        }
+    }
+
+    #[test]
+    fn test_erode_cone_lowers_peak_and_builds_apron() {
+        let width = 64;
+        let height = 64;
+        let cell_count = 1024;
+        let (cells, neighbors) = create_test_grid(width, height, cell_count);
+
+        // 经典模式下的单座山为径向衰减的锥体
+        let cone = TerrainTemplate::new("Cone", "Cone").with_command(TerrainCommand::Mountain {
+            height: 100.0,
+            x: 0.5,
+            y: 0.5,
+            radius: 0.2,
+        });
+        let eroded = cone.clone().with_command(TerrainCommand::Erode {
+            iterations: 5,
+            rain: 0.3,
+            capacity: 0.8,
+            deposition: 0.4,
+        });
+
+        let executor = TemplateExecutor::with_mode(width, height, 1, GenerationMode::Classic);
+        let before = executor.execute(&cone, &cells, &neighbors);
+        let after = executor.execute(&eroded, &cells, &neighbors);
+
+        let peak = |heights: &[f32]| heights.iter().copied().fold(0.0f32, f32::max);
+        assert!(peak(&after) < peak(&before), "peak should be lowered");
+
+        // 被抬高的单元格位于山脚：原高度远低于峰值
+        let raised: Vec<f32> = before
+            .iter()
+            .zip(&after)
+            .filter(|(b, a)| **a > **b + 1e-3)
+            .map(|(b, _)| *b)
+            .collect();
+        assert!(!raised.is_empty(), "sediment apron should form");
+        assert!(raised.iter().all(|h| *h < peak(&before) * 0.5));
+    }
+
+    #[test]
+    fn test_bounded_hill_stays_in_bounds() {
+        let width = 64;
+        let height = 64;
+        let cell_count = 1024;
+        let (cells, neighbors) = create_test_grid(width, height, cell_count);
+
+        let template =
+            TerrainTemplate::new("Bounded", "Bounded").with_command(TerrainCommand::BoundedHill {
+                count: 3,
+                height: (60.0, 80.0),
+                x: (0.1, 0.35),
+                y: (0.2, 0.8),
+                bounds: (0.0, 0.45, 0.0, 1.0),
+            });
+
+        for mode in [GenerationMode::BfsBlob, GenerationMode::Classic] {
+            let heights = TemplateExecutor::with_mode(width, height, 7, mode)
+                .execute(&template, &cells, &neighbors);
+            assert!(
+                heights.iter().any(|h| *h > 0.0),
+                "{:?}: hill was dropped",
+                mode
+            );
+            for (pos, h) in cells.iter().zip(&heights) {
+                if pos.x / width as f32 > 0.45 {
+                    assert_eq!(
+                        *h, 0.0,
+                        "{:?}: cell at {:?} raised outside bounds",
+                        mode, pos
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_river_path_descends_to_sea() {
+        use rand::SeedableRng;
+
+        let width = 64;
+        let height = 64;
+        let cell_count = 1024;
+        let (cells, neighbors) = create_test_grid(width, height, cell_count);
+
+        let cone = TerrainTemplate::new("Cone", "Cone").with_command(TerrainCommand::Mountain {
+            height: 200.0,
+            x: 0.5,
+            y: 0.5,
+            radius: 0.3,
+        });
+        let executor = TemplateExecutor::with_mode(width, height, 1, GenerationMode::Classic);
+        let mut heights = executor.execute(&cone, &cells, &neighbors);
+        let source = (0..heights.len())
+            .max_by(|&a, &b| heights[a].total_cmp(&heights[b]))
+            .unwrap();
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        let path = executor.carve_river(&mut heights, &neighbors, source, 0.3, &mut rng);
+
+        assert!(path.len() > 2);
+        assert_eq!(path[0], source);
+        assert!(heights[*path.last().unwrap()] < SEA_LEVEL as f32);
+        for pair in path.windows(2) {
+            assert!(
+                heights[pair[1]] <= heights[pair[0]],
+                "river must not flow uphill"
+            );
+        }
+
+        let template = parse_template("River", "DSL river parse", "River 3 180 0.3").unwrap();
+        assert!(matches!(
+            template.commands[0],
+            TerrainCommand::River { count: 3, meander, .. } if (meander - 0.3).abs() < 1e-6
+        ));
+    }
+
+    #[test]
+    fn test_subtract_self_flattens() {
+        let width = 64;
+        let height = 64;
+        let cell_count = 400;
+        let (cells, neighbors) = create_test_grid(width, height, cell_count);
+
+        let base = TerrainTemplate::archipelago();
+        let mut carved = base.clone();
+        carved.commands.push(TerrainCommand::Subtract {
+            template: "archipelago".to_string(),
+            weight: 1.0,
+        });
+
+        let before = TemplateExecutor::new(width, height, 9).execute(&base, &cells, &neighbors);
+        let after = TemplateExecutor::new(width, height, 9).execute(&carved, &cells, &neighbors);
+
+        let spread = |values: &[f32]| {
+            let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+            let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            max - min
+        };
+        assert!(spread(&before) > 10.0);
+        assert!(
+            spread(&after) < 0.01,
+            "spread after subtract: {}",
+            spread(&after)
+        );
+
+        // 未知模板名称不改变高度
+        let unknown = base.clone().with_command(TerrainCommand::Subtract {
+            template: "no_such_template".to_string(),
+            weight: 1.0,
+        });
+        let executor = TemplateExecutor::new(width, height, 9);
+        let unchanged = executor.execute(&unknown, &cells, &neighbors);
+        assert_eq!(unchanged, before);
+        assert_eq!(
+            executor.try_execute(&unknown, &cells, &neighbors),
+            Err(TemplateExecError::UnknownTemplate(
+                "no_such_template".to_string()
+            ))
+        );
+        assert_eq!(executor.try_execute(&carved, &cells, &neighbors), Ok(after));
+    }
+
+    #[test]
+    fn test_subtract_recursion_is_rejected() {
+        let (width, height) = (64, 64);
+        let (cells, neighbors) = create_test_grid(width, height, 400);
+        let subtract = |name: &str| TerrainCommand::Subtract {
+            template: name.to_string(),
+            weight: 0.5,
+        };
+
+        // 直接递归：模板减去自身
+        let selfish = TerrainTemplate::new("Selfish", "")
+            .with_command(TerrainCommand::Mountain {
+                height: 80.0,
+                x: 0.5,
+                y: 0.5,
+                radius: 0.3,
+            })
+            .with_command(subtract("selfish"));
+        let executor = TemplateExecutor::new(width, height, 3).with_stencil(selfish.clone());
+        assert_eq!(
+            executor.try_execute(&selfish, &cells, &neighbors),
+            Err(TemplateExecError::RecursiveSubtract("selfish".to_string()))
+        );
+
+        // 间接递归：A 减去 B，B 又减去 A；宽松执行仍然返回结果
+        let ta = TerrainTemplate::new("A", "").with_command(subtract("b"));
+        let tb = TerrainTemplate::new("B", "").with_command(subtract("a"));
+        let executor = TemplateExecutor::new(width, height, 3)
+            .with_stencil(ta.clone())
+            .with_stencil(tb);
+        assert_eq!(
+            executor.try_execute(&ta, &cells, &neighbors),
+            Err(TemplateExecError::RecursiveSubtract("b".to_string()))
+        );
+        assert_eq!(executor.execute(&ta, &cells, &neighbors).len(), cells.len());
+    }
+
+    #[test]
+    fn test_try_generate_rejects_mismatched_inputs() {
+        use crate::terrain::TerrainInputError;
+
+        let (cells, neighbors) = create_test_grid(64, 64, 100);
+        let generator = TerrainGenerator::new(TerrainConfig::with_template("earth-like"));
+
+        // 邻居列表缺失
+        let err = generator
+            .try_generate(&cells, &neighbors[..90])
+            .unwrap_err();
+        assert_eq!(
+            err,
+            TerrainInputError::LengthMismatch {
+                cells: 100,
+                neighbors: 90
+            }
+        );
+        assert!(err.to_string().contains("100 cells"));
+
+        // 邻居索引越界
+        let mut bad_neighbors = neighbors.clone();
+        bad_neighbors[42].push(1000);
+        let err = generator.try_generate(&cells, &bad_neighbors).unwrap_err();
+        assert_eq!(
+            err,
+            TerrainInputError::NeighborOutOfRange {
+                cell: 42,
+                neighbor: 1000,
+                cell_count: 100
+            }
+        );
+
+        // 一致的输入正常生成
+        let (heights, _, _) = generator.try_generate(&cells, &neighbors).unwrap();
+        assert_eq!(heights.len(), cells.len());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid terrain input")]
+    fn test_generate_panics_with_description() {
+        let (cells, neighbors) = create_test_grid(64, 64, 100);
+        let generator = TerrainGenerator::new(TerrainConfig::with_template("earth-like"));
+        generator.generate(&cells[..50], &neighbors);
+    }
+
+    #[test]
+    fn test_atolls_have_lower_lagoon() {
+        let width = 100;
+        let height = 100;
+        let cell_count = 2500;
+        let (cells, neighbors) = create_test_grid(width, height, cell_count);
+
+        for mode in [GenerationMode::BfsBlob, GenerationMode::Classic] {
+            for seed in [1, 2, 3] {
+                let template = TerrainTemplate::new("Atoll", "Atoll")
+                    .with_command(TerrainCommand::Add { value: -10.0 })
+                    .with_command(TerrainCommand::Atolls {
+                        count: 1,
+                        radius: (0.2, 0.2),
+                        x: (0.5, 0.5),
+                        y: (0.5, 0.5),
+                    });
+                let heights = TemplateExecutor::with_mode(width, height, seed, mode)
+                    .execute(&template, &cells, &neighbors);
+
+                let center = Pos2::new(50.0, 50.0);
+                let center_height = heights[cells
+                    .iter()
+                    .enumerate()
+                    .min_by(|a, b| a.1.distance(center).total_cmp(&b.1.distance(center)))
+                    .unwrap()
+                    .0];
+
+                // 礁环附近（半径 18-22）的最高点都高于泻湖中心
+                let ring_max = cells
+                    .iter()
+                    .zip(&heights)
+                    .filter(|(p, _)| (p.distance(center) - 20.0).abs() < 2.0)
+                    .map(|(_, h)| *h)
+                    .fold(f32::NEG_INFINITY, f32::max);
+                assert!(
+                    center_height < ring_max,
+                    "{:?} seed {}: lagoon {} vs ring {}",
+                    mode,
+                    seed,
+                    center_height,
+                    ring_max
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_atoll_lagoon_below_sea_level() {
+        let (width, height) = (100, 100);
+        let (cells, neighbors) = create_test_grid(width, height, 2500);
+        let center = Pos2::new(50.0, 50.0);
+        let center_cell = cells
+            .iter()
+            .enumerate()
+            .min_by(|a, b| a.1.distance(center).total_cmp(&b.1.distance(center)))
+            .unwrap()
+            .0;
+
+        // 整片区域抬到海平面以上，泻湖仍然要低于配置的海平面
+        for sea_level in [SEA_LEVEL, 60] {
+            for radius in [0.08, 0.2, 0.35] {
+                let template = TerrainTemplate::new("Atoll", "Atoll")
+                    .with_command(TerrainCommand::Add { value: 90.0 })
+                    .with_command(TerrainCommand::Atolls {
+                        count: 1,
+                        radius: (radius, radius),
+                        x: (0.5, 0.5),
+                        y: (0.5, 0.5),
+                    });
+                let heights = TemplateExecutor::new(width, height, 4)
+                    .with_sea_level(sea_level)
+                    .execute(&template, &cells, &neighbors);
+                assert!(
+                    heights[center_cell] < sea_level as f32,
+                    "sea level {} radius {}: lagoon {}",
+                    sea_level,
+                    radius,
+                    heights[center_cell]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_atolls() {
+        let template =
+            parse_template("Atoll", "DSL atolls parse", "Atolls 3 5-8 20-80 20-80").unwrap();
+        let TerrainCommand::Atolls {
+            count,
+            radius,
+            x,
+            y,
+        } = &template.commands[0]
+        else {
+            panic!("expected Atolls, got {:?}", template.commands[0]);
+        };
+        assert_eq!(*count, 3);
+        assert!((radius.0 - 0.05).abs() < 1e-6 && (radius.1 - 0.08).abs() < 1e-6);
+        assert!((x.0 - 0.2).abs() < 1e-6 && (x.1 - 0.8).abs() < 1e-6);
+        assert!((y.0 - 0.2).abs() < 1e-6 && (y.1 - 0.8).abs() < 1e-6);
+
+        assert!(parse_template("Atoll", "", "Atolls 3 5-8 20-80").is_err());
+    }
+
+    #[test]
+    fn test_caldera_rim_above_center_and_base() {
+        use crate::terrain::primitive::{Elevation, PositionConstraint, Size, TerrainPrimitive};
+
+        let (cells, neighbors) = create_test_grid(100, 100, 2500);
+        let template = TerrainTemplate::new("Caldera", "Caldera")
+            .with_command(TerrainCommand::Add { value: 10.0 })
+            .with_primitive(TerrainPrimitive::Caldera {
+                size: Size::Large,
+                elevation: Elevation::Medium,
+                position: PositionConstraint {
+                    x: (0.5, 0.5),
+                    y: (0.5, 0.5),
+                },
+            });
+        let heights = TemplateExecutor::new(100, 100, 5).execute(&template, &cells, &neighbors);
+
+        let center = Pos2::new(50.0, 50.0);
+        let (center_idx, _) = cells
+            .iter()
+            .enumerate()
+            .min_by(|a, b| a.1.distance(center).total_cmp(&b.1.distance(center)))
+            .unwrap();
+        let base = heights
+            .iter()
+            .zip(&cells)
+            .filter(|(_, p)| p.distance(center) > 45.0)
+            .map(|(h, _)| *h)
+            .fold(f32::NEG_INFINITY, f32::max);
+
+        // Large 半径 18%-28%，口壁在 0.6 倍半径处：10.8-16.8
+        let rim_min = heights
+            .iter()
+            .zip(&cells)
+            .filter(|(_, p)| (p.distance(center) - 13.8).abs() < 3.0)
+            .map(|(h, _)| *h)
+            .fold(f32::INFINITY, f32::min);
+        let rim_max = heights
+            .iter()
+            .zip(&cells)
+            .filter(|(_, p)| (p.distance(center) - 13.8).abs() < 3.0)
+            .map(|(h, _)| *h)
+            .fold(f32::NEG_INFINITY, f32::max);
+
+        assert!(
+            rim_max > heights[center_idx] + 10.0,
+            "rim {} center {}",
+            rim_max,
+            heights[center_idx]
+        );
+        assert!(rim_min > base, "rim {} base {}", rim_min, base);
+        assert!(heights[center_idx] > base);
+    }
+
+    #[test]
+    fn test_island_is_single_connected_landmass() {
+        use crate::terrain::primitive::{Elevation, PositionConstraint, TerrainPrimitive};
+
+        let (cells, neighbors) = create_test_grid(100, 100, 2500);
+        let target = 300;
+        let template =
+            TerrainTemplate::new("Island", "Island").with_primitive(TerrainPrimitive::Island {
+                target_cells: target,
+                elevation: Elevation::Medium,
+                position: PositionConstraint::center(),
+            });
+
+        for seed in [1, 2, 3] {
+            let heights =
+                TemplateExecutor::new(100, 100, seed).execute(&template, &cells, &neighbors);
+            let land: Vec<usize> = (0..cells.len())
+                .filter(|&i| heights[i] >= SEA_LEVEL as f32)
+                .collect();
+            let count = land.len() as f32;
+            assert!(
+                (count - target as f32).abs() <= target as f32 * 0.1,
+                "seed {}: {} land cells",
+                seed,
+                count
+            );
+
+            // 从任一陆地单元格出发只经过陆地即可到达全部陆地
+            let mut seen = vec![false; cells.len()];
+            let mut stack = vec![land[0]];
+            seen[land[0]] = true;
+            let mut reached = 0;
+            while let Some(i) = stack.pop() {
+                reached += 1;
+                for &n in &neighbors[i] {
+                    let n = n as usize;
+                    if !seen[n] && heights[n] >= SEA_LEVEL as f32 {
+                        seen[n] = true;
+                        stack.push(n);
+                    }
+                }
+            }
+            assert_eq!(reached, land.len(), "seed {}", seed);
+        }
+    }
+
     // ============================================================================
     // 内置模板测试
     // ============================================================================
@@ -639,6 +1652,301 @@ SeaRatio 0.7";
         assert!(!heights[0].is_nan());
     }
 
+    #[test]
+    fn test_parallel_generation_matches_serial() {
+        let (cells, neighbors) = create_test_grid(512, 512, 3000);
+
+        let mut tectonic = TectonicConfig::earth_like();
+        tectonic.seed = 7;
+        let generate = |parallel: bool| {
+            let config = TerrainConfig {
+                medium_noise_strength: 0.05,
+                detail_noise_strength: 0.02,
+                parallel,
+                ..TerrainConfig::with_tectonic_simulation(tectonic.clone())
+            };
+            TerrainGenerator::new(config).generate(&cells, &neighbors)
+        };
+
+        let (serial, _, serial_plates) = generate(false);
+        let (parallel, _, parallel_plates) = generate(true);
+        assert_eq!(serial, parallel);
+        assert_eq!(serial_plates, parallel_plates);
+
+        // 分层生成的各层同样按配置并行
+        let layered = |parallel: bool| {
+            let config = TerrainConfig {
+                parallel,
+                ..TerrainConfig::with_layered(7, 6)
+            };
+            let (heights, _, plate_ids) =
+                TerrainGenerator::new(config).generate(&cells, &neighbors);
+            (heights, plate_ids)
+        };
+        assert_eq!(layered(false), layered(true));
+    }
+
+    #[test]
+    fn test_parallel_enabled_by_default_on_native() {
+        assert_eq!(
+            TerrainConfig::default().parallel,
+            cfg!(not(target_arch = "wasm32"))
+        );
+    }
+
+    #[test]
+    fn test_sea_level_config_moves_waterline() {
+        use crate::terrain::features::FeatureDetector;
+
+        let (cells, neighbors) = create_test_grid(256, 256, 1600);
+        let mut tectonic = TectonicConfig::earth_like();
+        tectonic.seed = 5;
+        let generate = |sea_level: u8| {
+            let config = TerrainConfig {
+                sea_level,
+                ..TerrainConfig::with_tectonic_simulation(tectonic.clone())
+            };
+            TerrainGenerator::new(config).generate(&cells, &neighbors).0
+        };
+
+        // 同一高度图，海平面抬高到 60 后海洋单元格更多
+        let heights = generate(SEA_LEVEL);
+        let border: Vec<bool> = neighbors.iter().map(|n| n.len() < 4).collect();
+        let ocean = |detector: FeatureDetector| {
+            detector
+                .ocean_mask(&heights, &neighbors, &border)
+                .into_iter()
+                .filter(|&o| o)
+                .count()
+        };
+        let low = ocean(FeatureDetector::default());
+        let high = ocean(FeatureDetector::default().with_sea_level(60));
+        assert!(high > low, "{} vs {}", high, low);
+
+        // 生成时的海平面随配置移动：海陆比例保持，水线位于 60
+        let raised = generate(60);
+        let below = |h: &[u8], level: u8| h.iter().filter(|&&h| h < level).count();
+        let diff = below(&raised, 60).abs_diff(below(&heights, SEA_LEVEL));
+        assert!(diff < cells.len() / 20, "ocean count drifted by {}", diff);
+        assert!(below(&raised, SEA_LEVEL) < below(&raised, 60));
+    }
+
+    #[test]
+    fn test_transform_boundary_effect_is_small() {
+        use crate::terrain::plate::{BoundaryType, PlateBoundary};
+
+        let (cells, neighbors) = create_test_grid(200, 200, 400);
+        let plate_id: Vec<u16> = cells
+            .iter()
+            .map(|p| if p.x < 100.0 { 1 } else { 2 })
+            .collect();
+        let boundary_cells: Vec<u32> = (0..cells.len())
+            .filter(|&i| {
+                plate_id[i] == 1 && neighbors[i].iter().any(|&n| plate_id[n as usize] == 2)
+            })
+            .map(|i| i as u32)
+            .collect();
+
+        let generator = TerrainGenerator::new(TerrainConfig::with_tectonic_simulation(
+            TectonicConfig::earth_like(),
+        ));
+        let perturbation = |boundary_type: BoundaryType| {
+            let boundary = PlateBoundary {
+                plate_a: 1,
+                plate_b: 2,
+                boundary_type,
+                cells: boundary_cells.clone(),
+            };
+            let mut heights = vec![0.0f32; cells.len()];
+            generator.apply_boundary_effects(&mut heights, &[boundary], &plate_id, &neighbors);
+            boundary_cells
+                .iter()
+                .map(|&i| heights[i as usize].abs())
+                .sum::<f32>()
+                / boundary_cells.len() as f32
+        };
+
+        let transform = perturbation(BoundaryType::Transform { intensity: 1.0 });
+        let convergent = perturbation(BoundaryType::Convergent {
+            intensity: 1.0,
+            subducting_plate: None,
+        });
+        assert!(transform > 0.0);
+        assert!(
+            transform < convergent * 0.5,
+            "transform {} vs convergent {}",
+            transform,
+            convergent
+        );
+    }
+
+    #[test]
+    fn test_preview_skips_erosion_and_detail_noise() {
+        let (cells, neighbors) = create_test_grid(256, 256, 800);
+        let generate = |enable_erosion: bool, detail_noise_strength: f32| {
+            TerrainGenerator::new(TerrainConfig {
+                enable_erosion,
+                detail_noise_strength,
+                preview: true,
+                ..TerrainConfig::with_tectonic_simulation(TectonicConfig::earth_like())
+            })
+            .generate(&cells, &neighbors)
+        };
+
+        let (plain, _, plain_plates) = generate(false, 0.0);
+        let (rough, _, rough_plates) = generate(true, 0.8);
+        assert_eq!(plain, rough);
+        assert_eq!(plain_plates, rough_plates);
+
+        // 板块数量减半
+        let plates = |preview: bool| {
+            TerrainGenerator::new(TerrainConfig {
+                preview,
+                ..TerrainConfig::with_tectonic_simulation(TectonicConfig::earth_like())
+            })
+            .generate(&cells, &neighbors)
+            .1
+            .len()
+        };
+        assert_eq!(plates(true), (plates(false) / 2).max(2));
+    }
+
+    #[test]
+    fn test_adjacent_seeds_give_uncorrelated_layers() {
+        use crate::terrain::layer_seed;
+
+        let seeds_1: Vec<u64> = (0..4).map(|i| layer_seed(1, i)).collect();
+        let seeds_2: Vec<u64> = (0..4).map(|i| layer_seed(2, i)).collect();
+        assert!(seeds_1.iter().all(|s| !seeds_2.contains(s)));
+        // 层种子截断到 u32 使用时也不重叠
+        assert!(seeds_1
+            .iter()
+            .all(|&a| seeds_2.iter().all(|&b| a as u32 != b as u32)));
+
+        let (cells, neighbors) = create_test_grid(256, 256, 1000);
+        let generate = |seed| {
+            TerrainGenerator::new(TerrainConfig::with_layered(seed, 6))
+                .generate(&cells, &neighbors)
+                .0
+        };
+        let (a, b) = (generate(1), generate(2));
+        let differing = a.iter().zip(&b).filter(|(x, y)| x != y).count();
+        assert!(
+            differing > cells.len() / 2,
+            "only {} of {} cells differ",
+            differing,
+            cells.len()
+        );
+    }
+
+    #[test]
+    fn test_generate_with_progress_reports_stages_in_order() {
+        use crate::terrain::GenStage;
+
+        let (cells, neighbors) = create_test_grid(256, 256, 800);
+        let configs = [
+            TerrainConfig::with_tectonic_simulation(TectonicConfig::earth_like()),
+            TerrainConfig::with_layered(7, 6),
+            TerrainConfig::with_template("volcano"),
+        ];
+
+        for config in configs {
+            let generator = TerrainGenerator::new(config);
+            let mut events = Vec::new();
+            let (heights, _, _) =
+                generator
+                    .generate_with_progress(&cells, &neighbors, |stage, t| events.push((stage, t)));
+
+            assert_eq!(heights, generator.generate(&cells, &neighbors).0);
+            assert!(events.windows(2).all(|w| w[0].0 <= w[1].0), "{:?}", events);
+            assert!(events.iter().all(|&(_, t)| (0.0..=1.0).contains(&t)));
+            for stage in [
+                GenStage::Plates,
+                GenStage::Tectonics,
+                GenStage::Regional,
+                GenStage::Detail,
+                GenStage::Postprocess,
+            ] {
+                assert!(events.contains(&(stage, 1.0)), "{:?} missing", stage);
+            }
+            assert_eq!(events.last(), Some(&(GenStage::Postprocess, 1.0)));
+        }
+    }
+
+    #[test]
+    fn test_layered_progress_through_checkpoints() {
+        use crate::terrain::LayerCheckpoints;
+        use std::sync::Arc;
+
+        let (cells, neighbors) = create_test_grid(256, 256, 800);
+        let checkpoints = Arc::new(LayerCheckpoints::new());
+        let generator = TerrainGenerator::new(TerrainConfig::with_layered(7, 6))
+            .with_checkpoints(checkpoints.clone());
+
+        let run = || {
+            let mut events = Vec::new();
+            let (heights, _, _) =
+                generator
+                    .generate_with_progress(&cells, &neighbors, |stage, t| events.push((stage, t)));
+            (heights, events)
+        };
+        let (first, first_events) = run();
+        assert!(!checkpoints.is_empty());
+
+        // 全部命中检查点时仍然报告每个阶段
+        let (second, second_events) = run();
+        assert_eq!(first, second);
+        assert_eq!(first_events, second_events);
+
+        let plain = TerrainGenerator::new(TerrainConfig::with_layered(7, 6));
+        assert_eq!(plain.generate(&cells, &neighbors).0, first);
+    }
+
+    #[test]
+    fn test_generate_cached_reuses_plates() {
+        use crate::terrain::GenCache;
+
+        let (cells, neighbors) = create_test_grid(256, 256, 1600);
+        let mut tectonic = TectonicConfig::earth_like();
+        tectonic.seed = 11;
+        let generator = |seed: u64, ocean_ratio: f32| {
+            let mut tectonic = tectonic.clone();
+            tectonic.seed = seed;
+            TerrainGenerator::new(TerrainConfig {
+                ocean_ratio: Some(ocean_ratio),
+                ..TerrainConfig::with_tectonic_simulation(tectonic)
+            })
+        };
+
+        let mut cache = GenCache::new();
+        let (wet, _, wet_plates) =
+            generator(11, 0.8).generate_cached(&cells, &neighbors, &mut cache);
+        let cached_ids = cache.plate_ids().unwrap().to_vec();
+        let (dry, _, dry_plates) =
+            generator(11, 0.4).generate_cached(&cells, &neighbors, &mut cache);
+
+        // 只改海洋比例：板块只生成一次，板块 ID 完全相同
+        assert_eq!(cache.plate_generations(), 1);
+        assert_eq!(wet_plates, dry_plates);
+        assert_eq!(cache.plate_ids().unwrap(), cached_ids.as_slice());
+        let ocean = |h: &[u8]| h.iter().filter(|&&h| h < SEA_LEVEL).count();
+        assert!(ocean(&wet) > ocean(&dry));
+
+        // 缓存结果与不使用缓存的生成一致
+        assert_eq!(dry, generator(11, 0.4).generate(&cells, &neighbors).0);
+
+        // 种子变化使缓存失效
+        generator(12, 0.4).generate_cached(&cells, &neighbors, &mut cache);
+        assert_eq!(cache.plate_generations(), 2);
+
+        // 单元格数量不变但位置变化，同样使缓存失效
+        let moved: Vec<Pos2> = cells.iter().map(|p| *p + Vec2::new(0.5, 0.0)).collect();
+        generator(12, 0.4).generate_cached(&moved, &neighbors, &mut cache);
+        assert_eq!(cache.plate_generations(), 3);
+        generator(12, 0.4).generate_cached(&moved, &neighbors, &mut cache);
+        assert_eq!(cache.plate_generations(), 3);
+    }
+
     #[test]
     fn test_tectonic_simulation_realistic_distribution() {
         let width = 256;
@@ -711,6 +2019,99 @@ SeaRatio 0.7";
         );
     }
 
+    #[test]
+    fn test_template_modifier_strength() {
+        use crate::terrain::template::StraitDirection;
+        use std::f32::consts::PI;
+
+        let (cells, neighbors) = create_test_grid(128, 128, 400);
+        let template = TerrainTemplate::new("Modifiers", "Modifier strength test")
+            .with_command(TerrainCommand::Strait {
+                width: 0.2,
+                direction: StraitDirection::Vertical,
+                position: 0.5,
+                depth: 50.0,
+            })
+            .with_command(TerrainCommand::Range {
+                count: 1,
+                height: (100.0, 100.0),
+                x: (0.0, 0.3),
+                y: (0.0, 1.0),
+                length: (0.5, 0.5),
+                width: (0.05, 0.05),
+                angle: (0.0, PI),
+            });
+
+        let total_change = |strength: f32| -> u32 {
+            let config = TerrainConfig {
+                template_modifier_strength: strength,
+                ..Default::default()
+            };
+            let generator = TerrainGenerator::new(config);
+            let baseline = vec![128u8; cells.len()];
+            let mut heights = baseline.clone();
+            generator.apply_template_modifiers(&mut heights, &template, &cells, &neighbors);
+            heights
+                .iter()
+                .zip(baseline.iter())
+                .map(|(&a, &b)| (a as i32 - b as i32).unsigned_abs())
+                .sum()
+        };
+
+        let weak = total_change(0.3);
+        let strong = total_change(1.0);
+        assert!(weak > 0);
+        assert!(
+            strong > weak,
+            "Stronger modifiers should change more terrain: weak={}, strong={}",
+            weak,
+            strong
+        );
+    }
+
+    #[test]
+    fn test_generate_async_matches_sync() {
+        let (cells, neighbors) = create_test_grid(256, 256, 900);
+
+        let configs = vec![
+            TerrainConfig::with_template("earth-like"),
+            TerrainConfig::with_template_and_seed(TerrainTemplate::archipelago(), 99),
+            TerrainConfig::with_layered(7, 8),
+            // 覆盖模板建议值：分步任务与同步生成共用同一个执行计划
+            TerrainConfig {
+                num_plates: Some(4),
+                ocean_ratio: Some(0.5),
+                ..TerrainConfig::with_template("earth-like")
+            },
+            TerrainConfig::with_tectonic_simulation(TectonicConfig {
+                seed: 3,
+                iterations: 5,
+                ..Default::default()
+            }),
+        ];
+
+        for config in configs {
+            let generator = TerrainGenerator::new(config);
+            let expected = generator.generate(&cells, &neighbors);
+
+            for chunk_size in [1, 7, 10_000] {
+                let mut task = generator.generate_async(&cells, &neighbors, chunk_size);
+                let mut steps = 0;
+                while !task.step() {
+                    steps += 1;
+                    assert!(task.progress() < 1.0);
+                }
+                // 分层和构造模拟模式都拆分为多个步骤
+                assert!(steps > 3, "chunk_size={}: {} steps", chunk_size, steps);
+                assert_eq!(task.progress(), 1.0);
+                let (heights, _, plate_ids) = task.into_result().unwrap();
+
+                assert_eq!(heights, expected.0, "chunk_size={}", chunk_size);
+                assert_eq!(plate_ids, expected.2, "chunk_size={}", chunk_size);
+            }
+        }
+    }
+
     #[test]
     fn test_large_grid() {
         let template = TerrainTemplate::archipelago();
@@ -0,0 +1,81 @@
+// 测试用规则网格
+
+use eframe::egui::Pos2;
+
+use super::layers;
+
+/// 规则网格的四邻接关系（单元格按行优先编号，顺序为左、右、上、下）
+pub fn grid_neighbors(cols: usize, rows: usize) -> Vec<Vec<u32>> {
+    (0..cols * rows)
+        .map(|i| {
+            let (x, y) = (i % cols, i / cols);
+            let mut list = Vec::new();
+            if x > 0 {
+                list.push(i as u32 - 1);
+            }
+            if x + 1 < cols {
+                list.push(i as u32 + 1);
+            }
+            if y > 0 {
+                list.push((i - cols) as u32);
+            }
+            if y + 1 < rows {
+                list.push((i + cols) as u32);
+            }
+            list
+        })
+        .collect()
+}
+
+/// 规则网格的八邻接关系（包含对角线）
+pub fn grid_neighbors8(cols: usize, rows: usize) -> Vec<Vec<u32>> {
+    (0..cols * rows)
+        .map(|i| {
+            let (x, y) = ((i % cols) as i32, (i / cols) as i32);
+            [
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ]
+            .into_iter()
+            .map(|(dx, dy)| (x + dx, y + dy))
+            .filter(|&(nx, ny)| nx >= 0 && ny >= 0 && (nx as usize) < cols && (ny as usize) < rows)
+            .map(|(nx, ny)| (ny as usize * cols + nx as usize) as u32)
+            .collect()
+        })
+        .collect()
+}
+
+/// 规则网格上的点（行优先，间距为 `spacing`，从原点开始）
+pub fn grid_points(cols: usize, rows: usize, spacing: f32) -> Vec<Pos2> {
+    (0..cols * rows)
+        .map(|i| Pos2::new((i % cols) as f32 * spacing, (i / cols) as f32 * spacing))
+        .collect()
+}
+
+/// 规则网格上的点和四邻接关系
+pub fn grid(cols: usize, rows: usize, spacing: f32) -> (Vec<Pos2>, Vec<Vec<u32>>) {
+    (grid_points(cols, rows, spacing), grid_neighbors(cols, rows))
+}
+
+/// 规则网格上的点和八邻接关系
+pub fn grid8(cols: usize, rows: usize, spacing: f32) -> (Vec<Pos2>, Vec<Vec<u32>>) {
+    (
+        grid_points(cols, rows, spacing),
+        grid_neighbors8(cols, rows),
+    )
+}
+
+/// 与 [`grid`] 相同，点使用分层生成系统的 [`layers::Pos2`]
+pub fn layer_grid(cols: usize, rows: usize, spacing: f32) -> (Vec<layers::Pos2>, Vec<Vec<u32>>) {
+    let cells = grid_points(cols, rows, spacing)
+        .into_iter()
+        .map(|p| layers::Pos2::new(p.x, p.y))
+        .collect();
+    (cells, grid_neighbors(cols, rows))
+}
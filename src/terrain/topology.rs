@@ -0,0 +1,139 @@
+// 地图拓扑
+//
+// 决定地图边缘如何衔接：平面（四边都是边界）、东西向环绕的圆柱面、
+// 以及东西环绕且南北越过极点相接的球面。邻接构建、噪声采样和遮罩都会参考它。
+
+use eframe::egui::Pos2;
+use std::f64::consts::{PI, TAU};
+
+/// 地图拓扑
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Default, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Topology {
+    /// 平面：四条边都是地图边界
+    #[default]
+    Plane,
+    /// 圆柱面：左右边缘相接，上下仍是边界
+    CylinderX,
+    /// 球面：左右边缘相接，上下边缘在极点处相接，没有边界
+    Sphere,
+}
+
+impl Topology {
+    /// 左右边缘是否相接
+    pub fn wraps_x(self) -> bool {
+        !matches!(self, Topology::Plane)
+    }
+
+    /// 遮罩使用的归一化边缘距离（0 = 地图中心，1 = 边界）
+    ///
+    /// 平面按到中心的径向距离计算；圆柱面只有上下边界，只看纵向距离；
+    /// 球面没有边界，恒为 0。
+    pub fn mask_distance(self, pos: Pos2, width: f32, height: f32) -> f32 {
+        let half_w = width / 2.0;
+        let half_h = height / 2.0;
+        match self {
+            Topology::Plane => {
+                let dist = pos.distance(Pos2::new(half_w, half_h));
+                (dist / half_w.hypot(half_h)).clamp(0.0, 1.0)
+            }
+            Topology::CylinderX => ((pos.y - half_h).abs() / half_h.max(1.0)).clamp(0.0, 1.0),
+            Topology::Sphere => 0.0,
+        }
+    }
+
+    /// 噪声采样坐标
+    ///
+    /// 平面直接使用 (x, y, 0)。圆柱面把 x 映射到周长为 `width` 的圆上，
+    /// 球面把 (x, y) 视为经纬度映射到赤道周长为 `width` 的球面上，
+    /// 这样噪声在环绕边缘处连续，且特征尺度与平面一致。
+    pub fn noise_coords(self, pos: Pos2, width: f32, height: f32) -> [f64; 3] {
+        let (x, y) = (pos.x as f64, pos.y as f64);
+        let (w, h) = ((width as f64).max(1.0), (height as f64).max(1.0));
+        let radius = w / TAU;
+        match self {
+            Topology::Plane => [x, y, 0.0],
+            Topology::CylinderX => {
+                let angle = x / w * TAU;
+                [radius * angle.cos(), y, radius * angle.sin()]
+            }
+            Topology::Sphere => {
+                let lon = x / w * TAU;
+                let lat = (y / h - 0.5) * PI;
+                [
+                    radius * lat.cos() * lon.cos(),
+                    radius * lat.sin(),
+                    radius * lat.cos() * lon.sin(),
+                ]
+            }
+        }
+    }
+
+    /// 三角剖分时附加的影子点
+    ///
+    /// 返回 (影子点位置, 原始点索引)。圆柱面在左右各复制一份点集；
+    /// 球面还把点集经过极点翻折到上下两侧（经度偏移半圈），
+    /// 使靠近极点的单元格与对侧单元格相邻。
+    pub fn ghost_points(self, points: &[Pos2], width: f32, height: f32) -> Vec<(Pos2, u32)> {
+        let mut ghosts = Vec::new();
+        if !self.wraps_x() {
+            return ghosts;
+        }
+
+        for (i, p) in points.iter().enumerate() {
+            ghosts.push((Pos2::new(p.x - width, p.y), i as u32));
+            ghosts.push((Pos2::new(p.x + width, p.y), i as u32));
+        }
+
+        if self == Topology::Sphere {
+            for (i, p) in points.iter().enumerate() {
+                let x = (p.x + width / 2.0).rem_euclid(width);
+                for y in [-p.y, 2.0 * height - p.y] {
+                    for dx in [-width, 0.0, width] {
+                        ghosts.push((Pos2::new(x + dx, y), i as u32));
+                    }
+                }
+            }
+        }
+
+        ghosts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_noise_coords_wrap_horizontally() {
+        for topology in [Topology::CylinderX, Topology::Sphere] {
+            let left = topology.noise_coords(Pos2::new(0.0, 30.0), 200.0, 100.0);
+            let right = topology.noise_coords(Pos2::new(200.0, 30.0), 200.0, 100.0);
+            for (a, b) in left.iter().zip(&right) {
+                assert!((a - b).abs() < 1e-9, "{:?}", topology);
+            }
+        }
+
+        let left = Topology::Plane.noise_coords(Pos2::new(0.0, 30.0), 200.0, 100.0);
+        let right = Topology::Plane.noise_coords(Pos2::new(200.0, 30.0), 200.0, 100.0);
+        assert_ne!(left, right);
+    }
+
+    #[test]
+    fn test_mask_distance() {
+        let center = Pos2::new(100.0, 50.0);
+        let left_edge = Pos2::new(0.0, 50.0);
+        assert_eq!(Topology::Plane.mask_distance(center, 200.0, 100.0), 0.0);
+        assert!(Topology::Plane.mask_distance(left_edge, 200.0, 100.0) > 0.5);
+        assert_eq!(
+            Topology::CylinderX.mask_distance(left_edge, 200.0, 100.0),
+            0.0
+        );
+        assert_eq!(
+            Topology::Sphere.mask_distance(Pos2::new(0.0, 0.0), 200.0, 100.0),
+            0.0
+        );
+    }
+}
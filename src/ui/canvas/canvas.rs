@@ -6,6 +6,8 @@ pub struct Canvas {
     pub canvas_state_resource: CanvasStateResource,
     pub map_system_resource: MapSystemResource,
     pub input_state_manager: InputStateManager,
+    /// 下一帧绘制时是否将视图适配到整张地图
+    fit_pending: bool,
 }
 
 impl Canvas {
@@ -17,6 +19,17 @@ impl Canvas {
             canvas_state_resource: canvas_state_resource.clone(),
             map_system_resource,
             input_state_manager: InputStateManager::new(canvas_state_resource),
+            fit_pending: false,
         }
     }
+
+    /// 请求在下一帧绘制时将视图适配到整张地图（例如重新生成地形之后）
+    pub fn request_fit(&mut self) {
+        self.fit_pending = true;
+    }
+
+    /// 取出并清除适配请求
+    pub(super) fn take_fit_request(&mut self) -> bool {
+        std::mem::take(&mut self.fit_pending)
+    }
 }
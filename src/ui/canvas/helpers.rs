@@ -1,5 +1,6 @@
 use super::state::CanvasState;
 
+#[allow(clippy::needless_late_init)]
 pub fn draw_grid(ui: &mut egui::Ui, canvas_state: &CanvasState, screen_rect: egui::Rect) {
     // println!("draw_grid");
     let painter = ui.painter_at(screen_rect);
@@ -1,5 +1,8 @@
 use egui::emath::TSTransform;
 
+/// 适配视口时每侧保留的边距（占视口尺寸的比例）
+const FIT_MARGIN: f32 = 0.05;
+
 #[derive(serde::Deserialize, serde::Serialize, Debug)]
 pub struct CanvasState {
     pub offset: egui::Vec2,
@@ -57,6 +60,34 @@ impl CanvasState {
         egui::Vec2::new(screen_pos.x, screen_pos.y)
     }
 
+    /// 将屏幕坐标转换为世界（地图）坐标，是 [`Self::world_to_screen`] 的逆变换
+    ///
+    /// 地图坐标即画布坐标，等同于 [`Self::to_canvas`]。
+    pub fn screen_to_world(&self, p: egui::Pos2) -> egui::Pos2 {
+        self.to_canvas(p)
+    }
+
+    /// 按当前平移和缩放将世界（地图）坐标转换为屏幕坐标，等同于 [`Self::to_screen`]
+    #[allow(dead_code)]
+    pub fn world_to_screen(&self, p: egui::Pos2) -> egui::Pos2 {
+        self.to_screen(p)
+    }
+
+    /// 计算平移和缩放，使画布上的 `bounds` 居中并完整显示在屏幕上的 `viewport` 内
+    ///
+    /// 四周保留少量边距，缩放范围与滚轮缩放一致（0.1..=100）。
+    pub fn fit_to_bounds(&mut self, bounds: egui::Rect, viewport: egui::Rect) {
+        let available = viewport.size() * (1.0 - 2.0 * FIT_MARGIN);
+        let scale = (available.x / bounds.width())
+            .min(available.y / bounds.height())
+            .clamp(0.1, 100.0);
+        let translation = viewport.center().to_vec2() - bounds.center().to_vec2() * scale;
+
+        self.transform = TSTransform::new(translation, scale);
+        self.offset = translation;
+        self.scale = scale;
+    }
+
     pub fn to_canvas_vec2(&self, screen_pos: egui::Vec2) -> egui::Vec2 {
         // canvas_pos * self.scale + self.offset
         let screen_pos = egui::Pos2::new(screen_pos.x, screen_pos.y);
@@ -65,3 +96,65 @@ impl CanvasState {
         egui::Vec2::new(canvas_pos.x, canvas_pos.y)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::{Pos2, Rect};
+
+    #[test]
+    fn test_fit_to_bounds_maps_corners_into_viewport() {
+        let viewport = Rect::from_min_max(Pos2::new(200.0, 50.0), Pos2::new(1000.0, 650.0));
+
+        for bounds in [
+            Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(1024.0, 768.0)),
+            Rect::from_min_max(Pos2::new(-500.0, 300.0), Pos2::new(-400.0, 900.0)),
+            Rect::from_min_max(Pos2::new(10.0, 10.0), Pos2::new(20.0, 12.0)),
+        ] {
+            let mut state = CanvasState::default();
+            state.fit_to_bounds(bounds, viewport);
+
+            for corner in [
+                bounds.left_top(),
+                bounds.right_top(),
+                bounds.left_bottom(),
+                bounds.right_bottom(),
+            ] {
+                let screen = state.to_screen(corner);
+                assert!(viewport.contains(screen), "{:?} -> {:?}", corner, screen);
+            }
+
+            // 居中，且至少一个方向贴近边距
+            let center = state.to_screen(bounds.center());
+            assert!((center - viewport.center()).length() < 1e-3);
+            let fitted = state.to_screen_rect(bounds);
+            let fill = (fitted.width() / viewport.width()).max(fitted.height() / viewport.height());
+            assert!((fill - (1.0 - 2.0 * FIT_MARGIN)).abs() < 1e-4, "{}", fill);
+        }
+    }
+
+    #[test]
+    fn test_screen_world_round_trip() {
+        let state = CanvasState {
+            transform: TSTransform::new(egui::vec2(-123.5, 48.25), 3.7),
+            ..Default::default()
+        };
+
+        for p in [
+            Pos2::new(0.0, 0.0),
+            Pos2::new(512.0, 384.0),
+            Pos2::new(-40.5, 1999.0),
+        ] {
+            let back = state.screen_to_world(state.world_to_screen(p));
+            assert!((back - p).length() < 1e-3, "{:?} -> {:?}", p, back);
+            let back = state.world_to_screen(state.screen_to_world(p));
+            assert!((back - p).length() < 1e-3, "{:?} -> {:?}", p, back);
+        }
+
+        // 世界坐标先缩放再平移
+        assert_eq!(
+            state.world_to_screen(Pos2::new(10.0, 0.0)),
+            Pos2::new(10.0 * 3.7 - 123.5, 48.25)
+        );
+    }
+}
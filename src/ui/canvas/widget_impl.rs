@@ -17,6 +17,18 @@ impl Widget for &mut Canvas {
 
         self.input_state_manager.update(ui);
 
+        if self.take_fit_request() {
+            let (width, height) = self
+                .map_system_resource
+                .read_resource(|map_system| (map_system.grid.width, map_system.grid.height));
+            let bounds = egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(width as f32, height as f32),
+            );
+            self.canvas_state_resource
+                .with_resource(|canvas_state| canvas_state.fit_to_bounds(bounds, screen_rect));
+        }
+
         self.canvas_state_resource.read_resource(|canvas_state| {
             draw_grid(ui, canvas_state, screen_rect);
             // println!("canvas rect: {}", screen_rect);